@@ -0,0 +1,50 @@
+use solana_vntr_sniper::common::config_file;
+use solana_vntr_sniper::services::control::{get_control_addr, load_control_token, send_command};
+
+fn main() {
+    // Same layered config as the bot (profile .env, .env, config.toml), so CONTROL_ADDR and
+    // CONTROL_TOKEN match the running instance. It writes the environment, so it runs before
+    // the tokio runtime starts its worker threads.
+    if let Err(e) = config_file::load_config_file() {
+        eprintln!("Failed to load config file: {}", e);
+        std::process::exit(1);
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start the tokio runtime: {}", e);
+            std::process::exit(1);
+        }
+    };
+    runtime.block_on(run());
+}
+
+async fn run() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        eprintln!("Usage: solana-mm <pause|resume|reset-drawdown|stop|kill|approve <id>|reject <id>|status|cache [flush]|params|set <key> <value>...|audit [n]>");
+        std::process::exit(2);
+    }
+    let command = args.join(" ");
+    let token = match load_control_token() {
+        Ok(token) => token,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match send_command(&get_control_addr(), &token, &command).await {
+        Ok(response) => {
+            println!("{}", response);
+            if response.starts_with("error") {
+                std::process::exit(1);
+            }
+        },
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
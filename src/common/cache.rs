@@ -1,212 +1,263 @@
 use std::collections::{HashMap, HashSet};
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 use anchor_client::solana_sdk::pubkey::Pubkey;
 use spl_token_2022::state::{Account, Mint};
 use spl_token_2022::extension::StateWithExtensionsOwned;
 use lazy_static::lazy_static;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use moka::notification::RemovalCause;
+use moka::sync::Cache;
+use moka::Expiry;
+use crate::common::disk_cache::DISK_CACHE;
 use crate::dex::raydium_cpmm::RaydiumCPMM;
 
-/// TTL Cache entry that stores a value with an expiration time
-pub struct CacheEntry<T> {
-    pub value: T,
-    pub expires_at: Instant,
+/// Value stored with the TTL it was inserted with
+#[derive(Clone)]
+struct TtlValue<T> {
+    value: T,
+    ttl: Duration,
 }
 
-impl<T> CacheEntry<T> {
-    pub fn new(value: T, ttl_seconds: u64) -> Self {
-        Self {
-            value,
-            expires_at: Instant::now() + Duration::from_secs(ttl_seconds),
-        }
+/// Expires each entry after its own TTL rather than one cache-wide value
+struct PerEntryTtl;
+
+impl<T> Expiry<Pubkey, TtlValue<T>> for PerEntryTtl {
+    fn expire_after_create(&self, _key: &Pubkey, value: &TtlValue<T>, _created_at: Instant) -> Option<Duration> {
+        Some(value.ttl)
     }
-    
-    pub fn is_expired(&self) -> bool {
-        Instant::now() > self.expires_at
+}
+
+/// Lookup and eviction counters of one cache
+#[derive(Default)]
+struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    expired: AtomicU64,
+    evicted: AtomicU64,
+}
+
+/// Point-in-time counters of one cache, for the maintenance log and the `cache` control command
+#[derive(Debug, Clone)]
+pub struct CacheStats {
+    pub name: &'static str,
+    pub hits: u64,
+    /// Lookups that found nothing, including keys whose entry had already expired
+    pub misses: u64,
+    /// Entries dropped because their TTL ran out
+    pub expired: u64,
+    /// Entries dropped to stay within max capacity
+    pub evicted: u64,
+    pub size: usize,
+}
+
+impl CacheStats {
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups > 0 { self.hits as f64 / lookups as f64 } else { 0.0 }
+    }
+
+    pub fn to_line(&self) -> String {
+        format!(
+            "{}: {} hits / {} misses ({:.1}% hit rate), {} expired, {} evicted, {} entries",
+            self.name, self.hits, self.misses, self.hit_rate() * 100.0, self.expired, self.evicted, self.size
+        )
     }
 }
 
-/// Token account cache
-pub struct TokenAccountCache {
-    accounts: RwLock<HashMap<Pubkey, CacheEntry<StateWithExtensionsOwned<Account>>>>,
+/// Bounded TTL cache keyed by pubkey. Entries expire after their TTL and the least recently
+/// used ones are evicted once `max_capacity` is reached; reads never block on writers.
+pub struct TtlCache<T: Clone + Send + Sync + 'static> {
+    name: &'static str,
+    entries: Cache<Pubkey, TtlValue<T>>,
     default_ttl: u64,
+    counters: Arc<CacheCounters>,
 }
 
-impl TokenAccountCache {
-    pub fn new(default_ttl: u64) -> Self {
+impl<T: Clone + Send + Sync + 'static> TtlCache<T> {
+    pub fn new(name: &'static str, default_ttl: u64, max_capacity: u64) -> Self {
+        let counters = Arc::new(CacheCounters::default());
+        let listener_counters = counters.clone();
         Self {
-            accounts: RwLock::new(HashMap::new()),
+            name,
+            entries: Cache::builder()
+                .max_capacity(max_capacity)
+                .expire_after(PerEntryTtl)
+                .eviction_listener(move |_key, _value, cause| match cause {
+                    RemovalCause::Expired => { listener_counters.expired.fetch_add(1, Ordering::Relaxed); },
+                    RemovalCause::Size => { listener_counters.evicted.fetch_add(1, Ordering::Relaxed); },
+                    _ => {},
+                })
+                .build(),
             default_ttl,
+            counters,
         }
     }
     
-    pub fn get(&self, key: &Pubkey) -> Option<StateWithExtensionsOwned<Account>> {
-        let accounts = self.accounts.read().unwrap();
-        if let Some(entry) = accounts.get(key) {
-            if !entry.is_expired() {
-                return Some(entry.value.clone());
-            }
-        }
-        None
+    pub fn get(&self, key: &Pubkey) -> Option<T> {
+        let value = self.entries.get(key).map(|entry| entry.value);
+        let counter = if value.is_some() { &self.counters.hits } else { &self.counters.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
     }
     
-    pub fn insert(&self, key: Pubkey, value: StateWithExtensionsOwned<Account>, ttl: Option<u64>) {
-        let ttl = ttl.unwrap_or(self.default_ttl);
-        let mut accounts = self.accounts.write().unwrap();
-        accounts.insert(key, CacheEntry::new(value, ttl));
+    pub fn insert(&self, key: Pubkey, value: T, ttl: Option<u64>) {
+        let ttl = Duration::from_secs(ttl.unwrap_or(self.default_ttl));
+        self.entries.insert(key, TtlValue { value, ttl });
     }
     
     pub fn remove(&self, key: &Pubkey) {
-        let mut accounts = self.accounts.write().unwrap();
-        accounts.remove(key);
+        self.entries.invalidate(key);
     }
     
+    /// Presence check that doesn't count as a lookup
+    pub fn contains_key(&self, key: &Pubkey) -> bool {
+        self.entries.contains_key(key)
+    }
+    
+    pub fn keys(&self) -> Vec<Pubkey> {
+        self.entries.iter().map(|(key, _)| *key).collect()
+    }
+    
+    pub fn clear(&self) {
+        self.entries.invalidate_all();
+    }
+    
+    /// Run pending evictions now; expired entries are never returned either way
     pub fn clear_expired(&self) {
-        let mut accounts = self.accounts.write().unwrap();
-        accounts.retain(|_, entry| !entry.is_expired());
+        self.entries.run_pending_tasks();
     }
     
     // Get the current size of the cache
     pub fn size(&self) -> usize {
-        let accounts = self.accounts.read().unwrap();
-        accounts.len()
+        self.entries.run_pending_tasks();
+        self.entries.entry_count() as usize
+    }
+    
+    pub fn stats(&self) -> CacheStats {
+        let size = self.size();
+        CacheStats {
+            name: self.name,
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            expired: self.counters.expired.load(Ordering::Relaxed),
+            evicted: self.counters.evicted.load(Ordering::Relaxed),
+            size,
+        }
     }
 }
 
-/// Token mint cache
+/// Token account cache
+pub type TokenAccountCache = TtlCache<StateWithExtensionsOwned<Account>>;
+
+/// PumpSwap pool cache
+pub type PoolCache = TtlCache<RaydiumCPMM>;
+
+/// Raw pool-side accounts (the CPMM pool state and AMM config), keyed by address
+pub type PoolAccountCache = TtlCache<Vec<u8>>;
+
+/// Accounts an RPC lookup found missing, so repeated existence checks (e.g. ATAs of wallets
+/// rotating in) don't query again within a few seconds
+pub type MissingAccountCache = TtlCache<()>;
+
+/// Token mint cache, written through to the disk cache when one is configured
 pub struct TokenMintCache {
-    mints: RwLock<HashMap<Pubkey, CacheEntry<StateWithExtensionsOwned<Mint>>>>,
-    default_ttl: u64,
+    mints: TtlCache<StateWithExtensionsOwned<Mint>>,
 }
 
 impl TokenMintCache {
-    pub fn new(default_ttl: u64) -> Self {
+    pub fn new(default_ttl: u64, max_capacity: u64) -> Self {
         Self {
-            mints: RwLock::new(HashMap::new()),
-            default_ttl,
+            mints: TtlCache::new("token mints", default_ttl, max_capacity),
         }
     }
     
     pub fn get(&self, key: &Pubkey) -> Option<StateWithExtensionsOwned<Mint>> {
-        let mints = self.mints.read().unwrap();
-        if let Some(entry) = mints.get(key) {
-            if !entry.is_expired() {
-                return Some(entry.value.clone());
-            }
-        }
-        None
+        self.mints.get(key)
     }
     
     pub fn insert(&self, key: Pubkey, value: StateWithExtensionsOwned<Mint>, ttl: Option<u64>) {
-        let ttl = ttl.unwrap_or(self.default_ttl);
-        let mut mints = self.mints.write().unwrap();
-        mints.insert(key, CacheEntry::new(value, ttl));
+        DISK_CACHE.put_mint(&key, &value);
+        self.mints.insert(key, value, ttl);
     }
     
     pub fn remove(&self, key: &Pubkey) {
-        let mut mints = self.mints.write().unwrap();
-        mints.remove(key);
+        self.mints.remove(key);
     }
     
     pub fn clear_expired(&self) {
-        let mut mints = self.mints.write().unwrap();
-        mints.retain(|_, entry| !entry.is_expired());
-    }
-    
-    // Get the current size of the cache
-    pub fn size(&self) -> usize {
-        let mints = self.mints.read().unwrap();
-        mints.len()
-    }
-}
-
-/// PumpSwap pool cache
-pub struct PoolCache {
-    pools: RwLock<HashMap<Pubkey, CacheEntry<RaydiumCPMM>>>,
-    default_ttl: u64,
-}
-
-impl PoolCache {
-    pub fn new(default_ttl: u64) -> Self {
-        Self {
-            pools: RwLock::new(HashMap::new()),
-            default_ttl,
-        }
+        self.mints.clear_expired();
     }
     
-    pub fn get(&self, mint: &Pubkey) -> Option<RaydiumCPMM> {
-        let pools = self.pools.read().unwrap();
-        if let Some(entry) = pools.get(mint) {
-            if !entry.is_expired() {
-                return Some(entry.value.clone());
-            }
-        }
-        None
-    }
-    
-    pub fn insert(&self, mint: Pubkey, pool: RaydiumCPMM, ttl: Option<u64>) {
-        let ttl = ttl.unwrap_or(self.default_ttl);
-        let mut pools = self.pools.write().unwrap();
-        pools.insert(mint, CacheEntry::new(pool, ttl));
-    }
-    
-    pub fn remove(&self, mint: &Pubkey) {
-        let mut pools = self.pools.write().unwrap();
-        pools.remove(mint);
+    pub fn clear(&self) {
+        self.mints.clear();
     }
     
-    pub fn clear_expired(&self) {
-        let mut pools = self.pools.write().unwrap();
-        pools.retain(|_, entry| !entry.is_expired());
+    pub fn size(&self) -> usize {
+        self.mints.size()
     }
     
-    // Get the current size of the cache
-    pub fn size(&self) -> usize {
-        let pools = self.pools.read().unwrap();
-        pools.len()
+    pub fn stats(&self) -> CacheStats {
+        self.mints.stats()
     }
 }
 
-/// Simple wallet token account tracker
+/// Token accounts known to exist for our wallets: the one existence cache the DEX modules
+/// consult before adding an idempotent create instruction. Bounded like the other caches;
+/// an entry that ages out or is evicted only costs a redundant create instruction.
 pub struct WalletTokenAccounts {
-    accounts: RwLock<HashSet<Pubkey>>,
+    accounts: TtlCache<()>,
 }
 
 impl WalletTokenAccounts {
-    pub fn new() -> Self {
+    pub fn new(ttl_seconds: u64, max_capacity: u64) -> Self {
         Self {
-            accounts: RwLock::new(HashSet::new()),
+            accounts: TtlCache::new("wallet token accounts", ttl_seconds, max_capacity),
         }
     }
     
     pub fn contains(&self, account: &Pubkey) -> bool {
-        let accounts = self.accounts.read().unwrap();
-        accounts.contains(account)
+        self.accounts.get(account).is_some()
     }
     
     pub fn insert(&self, account: Pubkey) -> bool {
-        let mut accounts = self.accounts.write().unwrap();
-        accounts.insert(account)
+        let inserted = !self.accounts.contains_key(&account);
+        self.accounts.insert(account, (), None);
+        MISSING_ACCOUNT_CACHE.remove(&account);
+        if inserted {
+            DISK_CACHE.put_wallet_token_account(&account);
+        }
+        inserted
     }
     
     pub fn remove(&self, account: &Pubkey) -> bool {
-        let mut accounts = self.accounts.write().unwrap();
-        accounts.remove(account)
+        let removed = self.accounts.contains_key(account);
+        self.accounts.remove(account);
+        if removed {
+            DISK_CACHE.remove_wallet_token_account(account);
+        }
+        removed
     }
     
     pub fn get_all(&self) -> HashSet<Pubkey> {
-        let accounts = self.accounts.read().unwrap();
-        accounts.clone()
+        self.accounts.keys().into_iter().collect()
     }
     
     pub fn clear(&self) {
-        let mut accounts = self.accounts.write().unwrap();
-        accounts.clear();
+        self.accounts.clear();
     }
     
     pub fn size(&self) -> usize {
-        let accounts = self.accounts.read().unwrap();
-        accounts.len()
+        self.accounts.size()
+    }
+    
+    pub fn clear_expired(&self) {
+        self.accounts.clear_expired();
+    }
+    
+    pub fn stats(&self) -> CacheStats {
+        self.accounts.stats()
     }
 }
 
@@ -328,10 +379,56 @@ impl BoughtTokensTracker {
 
 // Global cache instances with reasonable TTL values
 lazy_static! {
-    pub static ref TOKEN_ACCOUNT_CACHE: TokenAccountCache = TokenAccountCache::new(60); // 60 seconds TTL
-    pub static ref TOKEN_MINT_CACHE: TokenMintCache = TokenMintCache::new(300); // 5 minutes TTL
-    pub static ref POOL_CACHE: PoolCache = PoolCache::new(30); // 30 seconds TTL
-    pub static ref WALLET_TOKEN_ACCOUNTS: WalletTokenAccounts = WalletTokenAccounts::new();
+    pub static ref TOKEN_ACCOUNT_CACHE: TokenAccountCache = TokenAccountCache::new("token accounts", 60, 10_000); // 60 seconds TTL
+    pub static ref TOKEN_MINT_CACHE: TokenMintCache = TokenMintCache::new(300, 1_000); // 5 minutes TTL
+    pub static ref POOL_CACHE: PoolCache = PoolCache::new("pools", 30, 1_000); // 30 seconds TTL
+    pub static ref MISSING_ACCOUNT_CACHE: MissingAccountCache = MissingAccountCache::new("missing accounts", 10, 10_000); // 10 seconds TTL
+    pub static ref POOL_ACCOUNT_CACHE: PoolAccountCache = PoolAccountCache::new("pool accounts", 30, 1_000); // 30 seconds TTL
+    pub static ref WALLET_TOKEN_ACCOUNTS: WalletTokenAccounts = WalletTokenAccounts::new(86_400, 50_000); // 24 hours TTL
     pub static ref TARGET_WALLET_TOKENS: TargetWalletTokens = TargetWalletTokens::new();
     pub static ref BOUGHT_TOKENS: BoughtTokensTracker = BoughtTokensTracker::new();
-} 
\ No newline at end of file
+}
+
+/// Counters of every bounded cache
+pub fn cache_stats() -> Vec<CacheStats> {
+    vec![TOKEN_ACCOUNT_CACHE.stats(), TOKEN_MINT_CACHE.stats(), POOL_CACHE.stats(), POOL_ACCOUNT_CACHE.stats(), MISSING_ACCOUNT_CACHE.stats(), WALLET_TOKEN_ACCOUNTS.stats()]
+}
+
+/// Drop what a confirmed swap by `wallet` in `mint` made stale: the wallet's token and WSOL
+/// account balances (either token program), "not found" results for those accounts, which
+/// the swap may have created, and the cached pool, so the next trade decision
+/// reads fresh state instead of balances up to a TTL old
+pub fn invalidate_after_swap(wallet: &Pubkey, mint: &Pubkey) {
+    for token_mint in [mint, &spl_token::native_mint::ID] {
+        for program in [spl_token::ID, spl_token_2022::ID] {
+            let account = get_associated_token_address_with_program_id(wallet, token_mint, &program);
+            TOKEN_ACCOUNT_CACHE.remove(&account);
+            MISSING_ACCOUNT_CACHE.remove(&account);
+        }
+    }
+    POOL_CACHE.remove(mint);
+}
+
+/// Run pending evictions in every bounded cache
+pub fn clear_expired_caches() {
+    TOKEN_ACCOUNT_CACHE.clear_expired();
+    TOKEN_MINT_CACHE.clear_expired();
+    POOL_CACHE.clear_expired();
+    POOL_ACCOUNT_CACHE.clear_expired();
+    MISSING_ACCOUNT_CACHE.clear_expired();
+    WALLET_TOKEN_ACCOUNTS.clear_expired();
+}
+
+/// Drop every token account, mint, pool, pool account and "not found" entry so the next
+/// reads go to RPC, for debugging stale state. The wallet token-account set is kept, since
+/// it is rebuilt only by the startup scan. Returns the number of entries dropped.
+pub fn flush_caches() -> usize {
+    let dropped = TOKEN_ACCOUNT_CACHE.size() + TOKEN_MINT_CACHE.size() + POOL_CACHE.size()
+        + POOL_ACCOUNT_CACHE.size() + MISSING_ACCOUNT_CACHE.size();
+    TOKEN_ACCOUNT_CACHE.clear();
+    TOKEN_MINT_CACHE.clear();
+    POOL_CACHE.clear();
+    POOL_ACCOUNT_CACHE.clear();
+    MISSING_ACCOUNT_CACHE.clear();
+    dropped
+}
@@ -0,0 +1,374 @@
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Result};
+use colored::{ColoredString, Colorize};
+use dotenv::dotenv;
+use once_cell::sync::OnceCell;
+use crate::common::logger::Logger;
+
+/// Variables that were set externally (environment / .env) when the config file was first loaded
+static EXTERNAL_OVERRIDES: OnceCell<HashSet<&'static str>> = OnceCell::new();
+
+/// True if `env_name` was set outside config.toml, in which case file changes must not replace it
+pub fn is_externally_overridden(env_name: &str) -> bool {
+    EXTERNAL_OVERRIDES
+        .get()
+        .map(|set| set.contains(env_name))
+        .unwrap_or(false)
+}
+
+/// Mapping from `section.key` in config.toml to the environment variable read by `Config`
+pub const CONFIG_KEY_MAP: &[(&str, &str)] = &[
+    // RPC / streaming
+    ("rpc.http", "RPC_HTTP"),
+    ("yellowstone.grpc_http", "YELLOWSTONE_GRPC_HTTP"),
+    ("yellowstone.grpc_token", "YELLOWSTONE_GRPC_TOKEN"),
+    // Target token
+    ("token.target_token_mint", "TARGET_TOKEN_MINT"),
+    ("token.coin_creator", "COIN_CREATOR"),
+    // DEX / pool
+    ("dex.dex", "DEX"),
+    ("dex.pool_id", "POOL_ID"),
+    ("dex.pool_base_account", "POOL_BASE_ACCOUNT"),
+    ("dex.pool_quote_account", "POOL_QUOTE_ACCOUNT"),
+    ("dex.pool_state_stream", "POOL_STATE_STREAM"),
+    ("dex.pool_state_max_age_seconds", "POOL_STATE_MAX_AGE_SECONDS"),
+    // Trading amounts and limits
+    ("trading.min_buy_amount", "MIN_BUY_AMOUNT"),
+    ("trading.max_buy_amount", "MAX_BUY_AMOUNT"),
+    ("trading.min_sol", "MIN_SOL"),
+    ("trading.minimal_balance_for_fee", "MINIMAL_BALANCE_FOR_FEE"),
+    ("trading.minimal_wsol_balance_for_trading", "MINIMAL_WSOL_BALANCE_FOR_TRADING"),
+    ("trading.selling_time_after_buying", "SELLING_TIME_AFTER_BUYING"),
+    ("trading.interval", "INTERVAL"),
+    ("trading.slippage", "SLIPPAGE"),
+    ("trading.token_amount", "TOKEN_AMOUNT"),
+    ("trading.counter_limit", "COUNTER_LIMIT"),
+    ("trading.is_progressive_sell", "IS_PROGRESSIVE_SELL"),
+    ("trading.unit_price", "UNIT_PRICE"),
+    ("trading.unit_limit", "UNIT_LIMIT"),
+    // Advanced randomization & safety
+    ("advanced.min_sell_delay_hours", "MIN_SELL_DELAY_HOURS"),
+    ("advanced.max_sell_delay_hours", "MAX_SELL_DELAY_HOURS"),
+    ("advanced.price_change_threshold", "PRICE_CHANGE_THRESHOLD"),
+    ("advanced.min_buy_ratio", "MIN_BUY_RATIO"),
+    ("advanced.max_buy_ratio", "MAX_BUY_RATIO"),
+    ("volume_waves.active_hours", "VOLUME_WAVE_ACTIVE_HOURS"),
+    ("volume_waves.slow_hours", "VOLUME_WAVE_SLOW_HOURS"),
+    ("volume_waves.burst_minutes", "VOLUME_WAVE_BURST_MINUTES"),
+    ("volume_waves.dormant_minutes", "VOLUME_WAVE_DORMANT_MINUTES"),
+    ("volume_waves.burst_probability", "VOLUME_WAVE_BURST_PROBABILITY"),
+    ("volume_waves.dormant_probability", "VOLUME_WAVE_DORMANT_PROBABILITY"),
+    ("indicators.candle_history_size", "CANDLE_HISTORY_SIZE"),
+    ("indicators.ma_type", "MA_TYPE"),
+    ("indicators.ma_fast_period", "MA_FAST_PERIOD"),
+    ("indicators.ma_slow_period", "MA_SLOW_PERIOD"),
+    ("indicators.ma_interval", "MA_INTERVAL"),
+    ("indicators.bollinger_enabled", "BOLLINGER_ENABLED"),
+    ("indicators.bollinger_period", "BOLLINGER_PERIOD"),
+    ("indicators.bollinger_std_dev", "BOLLINGER_STD_DEV"),
+    ("indicators.bollinger_interval", "BOLLINGER_INTERVAL"),
+    ("indicators.bollinger_confirm_drops", "BOLLINGER_CONFIRM_DROPS"),
+    ("indicators.order_flow_window_minutes", "ORDER_FLOW_WINDOW_MINUTES"),
+    ("indicators.order_flow_min_volume_sol", "ORDER_FLOW_MIN_VOLUME_SOL"),
+    ("indicators.vwap_alert_pct", "VWAP_ALERT_PCT"),
+    ("indicators.vwap_alert_cooldown_minutes", "VWAP_ALERT_COOLDOWN_MINUTES"),
+    ("guardian.enabled", "GUARDIAN_MODE_ENABLED"),
+    ("guardian.drop_threshold", "GUARDIAN_DROP_THRESHOLD"),
+    ("guardian.duration_minutes", "GUARDIAN_DURATION_MINUTES"),
+    ("guardian.cooldown_hours", "GUARDIAN_COOLDOWN_HOURS"),
+    ("guardian.max_interventions_per_day", "GUARDIAN_MAX_INTERVENTIONS_PER_DAY"),
+    ("guardian.tier_light", "GUARDIAN_TIER_LIGHT"),
+    ("guardian.tier_medium", "GUARDIAN_TIER_MEDIUM"),
+    ("guardian.tier_strong", "GUARDIAN_TIER_STRONG"),
+    ("guardian.pump_enabled", "PUMP_GUARDIAN_ENABLED"),
+    ("guardian.pump_rise_threshold", "PUMP_GUARDIAN_RISE_THRESHOLD"),
+    ("guardian.pump_duration_minutes", "PUMP_GUARDIAN_DURATION_MINUTES"),
+    ("guardian.pump_cooldown_hours", "PUMP_GUARDIAN_COOLDOWN_HOURS"),
+    ("guardian.pump_sell_bias", "PUMP_GUARDIAN_SELL_BIAS"),
+    ("guardian.pump_size_multiplier", "PUMP_GUARDIAN_SIZE_MULTIPLIER"),
+    // Subsystem toggles
+    ("features.volume_waves", "ENABLE_VOLUME_WAVES"),
+    ("features.dynamic_ratios", "ENABLE_DYNAMIC_RATIOS"),
+    ("features.price_monitor", "ENABLE_PRICE_MONITOR"),
+    ("features.price_throttle_mode", "PRICE_THROTTLE_MODE"),
+    ("features.price_throttle_size_factor", "PRICE_THROTTLE_SIZE_FACTOR"),
+    ("features.telegram", "ENABLE_TELEGRAM"),
+    // Risk limits
+    ("risk.daily_sol_spend_cap", "DAILY_SOL_SPEND_CAP"),
+    ("risk.kill_switch_file", "KILL_SWITCH_FILE"),
+    ("risk.approval_threshold_sol", "APPROVAL_THRESHOLD_SOL"),
+    ("risk.approval_timeout_seconds", "APPROVAL_TIMEOUT_SECONDS"),
+    ("risk.max_trades_per_hour", "MAX_TRADES_PER_HOUR"),
+    ("risk.max_trades_per_wallet_per_day", "MAX_TRADES_PER_WALLET_PER_DAY"),
+    ("risk.kill_switch_liquidate", "KILL_SWITCH_LIQUIDATE"),
+    ("risk.max_drawdown_percent", "MAX_DRAWDOWN_PERCENT"),
+    ("risk.drawdown_check_seconds", "DRAWDOWN_CHECK_SECONDS"),
+    ("risk.min_pool_liquidity_sol", "MIN_POOL_LIQUIDITY_SOL"),
+    ("risk.liquidity_resume_sol", "LIQUIDITY_RESUME_SOL"),
+    ("risk.rug_detection", "RUG_DETECTION_ENABLED"),
+    ("risk.rug_lp_mint", "RUG_LP_MINT"),
+    ("risk.rug_lp_drop_percent", "RUG_LP_DROP_PERCENT"),
+    ("risk.rug_auto_exit", "RUG_AUTO_EXIT"),
+    ("risk.creator_monitor", "CREATOR_MONITOR_ENABLED"),
+    ("risk.creator_auto_pause_buys", "CREATOR_AUTO_PAUSE_BUYS"),
+    ("risk.creator_pause_minutes", "CREATOR_PAUSE_MINUTES"),
+    ("risk.creator_alert_min_tokens", "CREATOR_ALERT_MIN_TOKENS"),
+    ("risk.sandwich_detection", "SANDWICH_DETECTION_ENABLED"),
+    ("risk.sandwich_sample_size", "SANDWICH_SAMPLE_SIZE"),
+    ("risk.sandwich_rate_threshold", "SANDWICH_RATE_THRESHOLD"),
+    ("risk.sandwich_slippage_step_bps", "SANDWICH_SLIPPAGE_STEP_BPS"),
+    ("risk.sandwich_min_slippage_bps", "SANDWICH_MIN_SLIPPAGE_BPS"),
+    // Position rules
+    ("positions.take_profit_levels", "TAKE_PROFIT_LEVELS"),
+    ("positions.stop_loss_percent", "STOP_LOSS_PERCENT"),
+    ("positions.stop_loss_trailing", "STOP_LOSS_TRAILING"),
+    ("positions.stop_loss_stagger_seconds", "STOP_LOSS_STAGGER_SECONDS"),
+    ("positions.retracement_levels", "RETRACEMENT_LEVELS"),
+    ("positions.retracement_stagger_seconds", "RETRACEMENT_STAGGER_SECONDS"),
+    ("positions.milestone_interval_percent", "MILESTONE_INTERVAL_PERCENT"),
+    ("positions.milestone_sell_percent", "MILESTONE_SELL_PERCENT"),
+    ("positions.milestone_max_intervals", "MILESTONE_MAX_INTERVALS"),
+    // Inventory-aware ratio skew
+    ("inventory.refresh_seconds", "INVENTORY_REFRESH_SECONDS"),
+    ("inventory.max_tokens", "MAX_INVENTORY_TOKENS"),
+    ("inventory.max_pool_percent", "MAX_INVENTORY_POOL_PERCENT"),
+    // TWAP accumulation mode
+    ("twap.enabled", "TWAP_ENABLED"),
+    ("twap.target_tokens", "TWAP_TARGET_TOKENS"),
+    ("twap.duration_hours", "TWAP_DURATION_HOURS"),
+    // Random trader engine (MODE=random_trader)
+    ("random_trader.mode", "MODE"),
+    ("random_trader.min_buy_amount", "RANDOM_TRADER_MIN_BUY_AMOUNT"),
+    ("random_trader.max_buy_amount", "RANDOM_TRADER_MAX_BUY_AMOUNT"),
+    ("random_trader.min_sell_percent", "RANDOM_TRADER_MIN_SELL_PERCENT"),
+    ("random_trader.max_sell_percent", "RANDOM_TRADER_MAX_SELL_PERCENT"),
+    ("random_trader.min_interval_seconds", "RANDOM_TRADER_MIN_INTERVAL_SECONDS"),
+    ("random_trader.max_interval_seconds", "RANDOM_TRADER_MAX_INTERVAL_SECONDS"),
+    ("random_trader.seed", "RANDOM_TRADER_SEED"),
+    // Wallet pool / helper commands
+    ("wallet_pool.wallet_count", "WALLET_COUNT"),
+    ("wallet_pool.wrap_amount", "WRAP_AMOUNT"),
+    ("wallet_pool.dust_threshold_sol", "DUST_THRESHOLD_SOL"),
+    ("wallet_pool.is_check_target_wallet_token_account", "IS_CHECK_TARGET_WALLET_TOKEN_ACCOUNT"),
+    // Telegram (the bot token stays in the environment)
+    ("telegram.chat_id", "TELEGRAM_CHAT_ID"),
+    ("copy_trading.target_wallets", "COPY_TARGET_WALLETS"),
+    ("copy_trading.size_scale", "COPY_SIZE_SCALE"),
+    ("copy_trading.max_buy_sol", "COPY_MAX_BUY_SOL"),
+    ("copy_trading.min_delay_ms", "COPY_MIN_DELAY_MS"),
+    ("copy_trading.max_delay_ms", "COPY_MAX_DELAY_MS"),
+    ("copy_trading.copy_sells", "COPY_SELLS"),
+    ("copy_trading.monitor_wallets", "MONITOR_WALLETS"),
+    ("copy_trading.monitor_alert_min_sol", "MONITOR_ALERT_MIN_SOL"),
+    ("copy_trading.monitor_alert_mints", "MONITOR_ALERT_MINTS"),
+    ("telegram.pnl_report_minutes", "PNL_REPORT_MINUTES"),
+    ("telegram.activity_report_minutes", "ACTIVITY_REPORT_MINUTES"),
+    ("telegram.whale_alert_sol", "WHALE_ALERT_SOL"),
+    ("telegram.usd_price_source", "USD_PRICE_SOURCE"),
+    ("telegram.usd_price_refresh_seconds", "USD_PRICE_REFRESH_SECONDS"),
+    ("telegram.pyth_token_feed_id", "PYTH_TOKEN_FEED_ID"),
+    // Runtime / operations
+    ("runtime.state_dir", "STATE_DIR"),
+    ("runtime.state_checkpoint_seconds", "STATE_CHECKPOINT_SECONDS"),
+    ("runtime.cache_backend", "CACHE_BACKEND"),
+    ("runtime.cache_dir", "CACHE_DIR"),
+    ("runtime.cache_maintenance_seconds", "CACHE_MAINTENANCE_SECONDS"),
+    ("runtime.shutdown_timeout_seconds", "SHUTDOWN_TIMEOUT_SECONDS"),
+    ("runtime.control_addr", "CONTROL_ADDR"),
+    ("runtime.control_token", "CONTROL_TOKEN"),
+    ("runtime.log_filter", "LOG_FILTER"),
+    ("runtime.log_format", "LOG_FORMAT"),
+    ("runtime.webhook_addr", "WEBHOOK_ADDR"),
+];
+
+/// Path of the TOML configuration file (CONFIG_FILE, defaults to ./config.toml)
+pub fn get_config_file_path() -> PathBuf {
+    env::var("CONFIG_FILE")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("config.toml"))
+}
+
+/// Look up the environment variable a `section.key` path maps to
+pub fn env_name_for(path: &str) -> Option<&'static str> {
+    CONFIG_KEY_MAP.iter().find(|(key, _)| *key == path).map(|(_, env)| *env)
+}
+
+/// Flatten a parsed config file into `(env var, value)` pairs.
+/// Unknown keys are returned separately so they can be reported.
+pub fn flatten_config(table: &toml::Table) -> Result<(Vec<(&'static str, String)>, Vec<String>)> {
+    let mut values = Vec::new();
+    let mut unknown = Vec::new();
+
+    for (section, section_value) in table {
+        let section_table = section_value
+            .as_table()
+            .ok_or_else(|| anyhow!("Top-level key '{}' must be a [section]", section))?;
+
+        for (key, value) in section_table {
+            let path = format!("{}.{}", section, key);
+            let rendered = match value {
+                toml::Value::String(s) => s.clone(),
+                toml::Value::Integer(i) => i.to_string(),
+                toml::Value::Float(f) => f.to_string(),
+                toml::Value::Boolean(b) => b.to_string(),
+                _ => return Err(anyhow!("Unsupported value type for '{}' (expected string, number or bool)", path)),
+            };
+
+            match env_name_for(&path) {
+                Some(env_name) => values.push((env_name, rendered)),
+                None => unknown.push(path),
+            }
+        }
+    }
+
+    Ok((values, unknown))
+}
+
+fn read_table(path: &Path) -> Result<toml::Table> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    content
+        .parse()
+        .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Merge `overlay` into `base` section by section (overlay keys win)
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (section, value) in overlay {
+        match (base.get_mut(&section).and_then(|v| v.as_table_mut()), value) {
+            (Some(base_section), toml::Value::Table(overlay_section)) => {
+                for (key, v) in overlay_section {
+                    base_section.insert(key, v);
+                }
+            },
+            (_, value) => {
+                base.insert(section, value);
+            },
+        }
+    }
+}
+
+/// Active environment profile (`--profile <name>` argument, or PROFILE in the environment / .env)
+pub fn get_profile() -> Option<String> {
+    let args: Vec<String> = env::args().collect();
+    if let Some(pos) = args.iter().position(|a| a == "--profile") {
+        if let Some(profile) = args.get(pos + 1) {
+            return Some(profile.trim().to_lowercase());
+        }
+    }
+
+    if let Ok(profile) = env::var("PROFILE") {
+        if !profile.trim().is_empty() {
+            return Some(profile.trim().to_lowercase());
+        }
+    }
+
+    // PROFILE may live in .env, which has not been loaded yet
+    fs::read_to_string(".env")
+        .ok()?
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("PROFILE="))
+        .map(|value| value.trim().trim_matches(|c| c == '"' || c == '\'').to_lowercase())
+        .find(|value| !value.is_empty())
+}
+
+/// `config.toml` -> `config.<profile>.toml`
+pub fn profile_config_path(base: &Path, profile: &str) -> PathBuf {
+    let stem = base.file_stem().and_then(|s| s.to_str()).unwrap_or("config");
+    let extension = base.extension().and_then(|s| s.to_str()).unwrap_or("toml");
+    base.with_file_name(format!("{}.{}.{}", stem, profile, extension))
+}
+
+/// Existing config files in layering order: base first, then the profile overlay
+pub fn config_file_paths() -> Vec<PathBuf> {
+    let base = get_config_file_path();
+    let mut paths = vec![base.clone()];
+    if let Some(profile) = get_profile() {
+        paths.push(profile_config_path(&base, &profile));
+    }
+    paths.into_iter().filter(|p| p.exists()).collect()
+}
+
+/// Read and flatten the layered config files without touching the environment
+pub fn read_config_values() -> Result<(Vec<(&'static str, String)>, Vec<String>)> {
+    let mut merged = toml::Table::new();
+    for path in config_file_paths() {
+        merge_tables(&mut merged, read_table(&path)?);
+    }
+    flatten_config(&merged)
+}
+
+/// Result of `load_config_file`. Logging is set up from the loaded values (LOG_FILTER,
+/// LOG_FORMAT), so its messages are held here until `log` is called after `init_logging`.
+pub struct ConfigFileLoad {
+    /// Config files that were loaded (empty when none exist)
+    pub paths: Vec<PathBuf>,
+    // Rendered in `log`, so LOG_FORMAT=json has switched colors off by then
+    messages: Vec<ColoredString>,
+}
+
+impl ConfigFileLoad {
+    /// Write the messages collected while loading
+    pub fn log(&self) {
+        let logger = Logger::new("[CONFIG-FILE] => ".cyan().bold().to_string());
+        for message in &self.messages {
+            logger.log(message.to_string());
+        }
+    }
+}
+
+/// Load the layered configuration into the process environment. Precedence, highest first:
+/// real environment, `.env.<profile>`, `.env`, `config.<profile>.toml`, `config.toml`.
+pub fn load_config_file() -> Result<ConfigFileLoad> {
+    let profile = get_profile();
+    let mut messages = Vec::new();
+
+    // dotenv never overrides, so the more specific file is loaded first
+    if let Some(profile) = profile.as_deref() {
+        env::set_var("PROFILE", profile);
+        let profile_env = format!(".env.{}", profile);
+        if dotenv::from_filename(&profile_env).is_ok() {
+            messages.push(format!("📄 Loaded {}", profile_env).cyan());
+        }
+    }
+    dotenv().ok();
+
+    let paths = config_file_paths();
+    if let Some(profile) = profile.as_deref() {
+        messages.push(format!("🏷️ Active profile: {}", profile).cyan().bold());
+    }
+    if paths.is_empty() {
+        return Ok(ConfigFileLoad { paths, messages });
+    }
+
+    let (values, unknown) = read_config_values()?;
+    for key in &unknown {
+        messages.push(format!("⚠️ Ignoring unknown config key '{}'", key).yellow());
+    }
+
+    let mut applied = 0;
+    let mut overridden = HashSet::new();
+    for (env_name, value) in values {
+        if env::var(env_name).is_ok() {
+            overridden.insert(env_name);
+            continue;
+        }
+        env::set_var(env_name, value);
+        applied += 1;
+    }
+
+    let _ = EXTERNAL_OVERRIDES.set(overridden.clone());
+
+    let names: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+    messages.push(format!(
+        "📄 Loaded {} ({} values applied, {} overridden by environment)",
+        names.join(" + "), applied, overridden.len()
+    ).cyan());
+
+    Ok(ConfigFileLoad { paths, messages })
+}
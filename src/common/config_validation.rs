@@ -0,0 +1,532 @@
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+/// Expected format of an environment variable
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueKind {
+    /// http(s) or ws(s) URL
+    Url,
+    /// Base58 public key
+    Pubkey,
+    /// Base58 keypair (long form private key)
+    Keypair,
+    /// Non-empty string
+    Text,
+    /// Comma-separated base58 public keys
+    PubkeyList,
+    /// Floating point number within [min, max]
+    Float { min: f64, max: f64 },
+    /// Unsigned integer within [min, max]
+    Integer { min: u64, max: u64 },
+    /// true / false
+    Bool,
+}
+
+impl ValueKind {
+    fn describe(&self) -> String {
+        match self {
+            ValueKind::Url => "an http(s):// or ws(s):// URL".to_string(),
+            ValueKind::Pubkey => "a base58 public key".to_string(),
+            ValueKind::Keypair => "a base58 private key (long form, 85+ chars)".to_string(),
+            ValueKind::Text => "a non-empty string".to_string(),
+            ValueKind::PubkeyList => "comma-separated base58 public keys".to_string(),
+            ValueKind::Float { min, max } => format!("a number between {} and {}", min, max),
+            ValueKind::Integer { min, max } => format!("an integer between {} and {}", min, max),
+            ValueKind::Bool => "true or false".to_string(),
+        }
+    }
+
+    fn check(&self, value: &str) -> bool {
+        let value = value.trim();
+        match self {
+            ValueKind::Url => ["http://", "https://", "ws://", "wss://"].iter().any(|p| value.starts_with(p)),
+            ValueKind::Pubkey => Pubkey::from_str(value).is_ok(),
+            ValueKind::Keypair => value.len() >= 85 && bs58::decode(value).into_vec().map(|b| b.len() == 64).unwrap_or(false),
+            ValueKind::Text => !value.is_empty(),
+            ValueKind::PubkeyList => value.split(',').all(|key| Pubkey::from_str(key.trim()).is_ok()),
+            ValueKind::Float { min, max } => value.parse::<f64>().map(|v| v.is_finite() && v >= *min && v <= *max).unwrap_or(false),
+            ValueKind::Integer { min, max } => value.parse::<u64>().map(|v| v >= *min && v <= *max).unwrap_or(false),
+            ValueKind::Bool => matches!(value.to_lowercase().as_str(), "true" | "false"),
+        }
+    }
+}
+
+/// Specification of a single environment variable
+#[derive(Debug, Clone, Copy)]
+pub struct EnvSpec {
+    pub name: &'static str,
+    pub kind: ValueKind,
+    pub required: bool,
+    pub example: &'static str,
+}
+
+const fn required(name: &'static str, kind: ValueKind, example: &'static str) -> EnvSpec {
+    EnvSpec { name, kind, required: true, example }
+}
+
+const fn optional(name: &'static str, kind: ValueKind, example: &'static str) -> EnvSpec {
+    EnvSpec { name, kind, required: false, example }
+}
+
+const SOL_AMOUNT: ValueKind = ValueKind::Float { min: 0.0, max: 1_000_000.0 };
+const RATIO: ValueKind = ValueKind::Float { min: 0.0, max: 1.0 };
+
+/// Every variable read by `Config::new` and the trading engines
+pub const ENV_SPECS: &[EnvSpec] = &[
+    required("RPC_HTTP", ValueKind::Url, "https://your-solana-rpc.example.com"),
+    required("PRIVATE_KEY", ValueKind::Keypair, "YourBase58PrivateKeyString"),
+    required("YELLOWSTONE_GRPC_HTTP", ValueKind::Url, "https://grpc.yellowstone.example.com"),
+    required("YELLOWSTONE_GRPC_TOKEN", ValueKind::Text, "your-grpc-api-token"),
+    required("TARGET_TOKEN_MINT", ValueKind::Pubkey, "CGrptxv4hSiNSCTufJzBMzarfrfjNhD9vMmhYQ8eVPsA"),
+    required("DEX", ValueKind::Integer { min: 0, max: 2 }, "2"),
+    required("MIN_BUY_AMOUNT", SOL_AMOUNT, "0.02"),
+    required("MAX_BUY_AMOUNT", SOL_AMOUNT, "0.10"),
+    optional("COIN_CREATOR", ValueKind::Pubkey, "CreatorPubkeyIfUsingPumpFun"),
+    optional("POOL_ID", ValueKind::Pubkey, "51WkKvB7zGPvPd8Hr57xv2rWevVa5CDwVhYQAfFMjTKG"),
+    optional("POOL_BASE_ACCOUNT", ValueKind::Pubkey, "Gb3z5zsk3LPNYhXSBLdDjx6kpdxMMT6q6WsU1eKPqtCZ"),
+    optional("POOL_QUOTE_ACCOUNT", ValueKind::Pubkey, "H2FkTkXdqjjLMPaAzcmF5FFVAVL1n41QHUUyWmHdmQRN"),
+    optional("POOL_STATE_STREAM", ValueKind::Bool, "true"),
+    optional("POOL_STATE_MAX_AGE_SECONDS", ValueKind::Integer { min: 1, max: 3_600 }, "30"),
+    optional("MIN_SOL", SOL_AMOUNT, "0.005"),
+    optional("MINIMAL_BALANCE_FOR_FEE", SOL_AMOUNT, "0.01"),
+    optional("MINIMAL_WSOL_BALANCE_FOR_TRADING", SOL_AMOUNT, "0.001"),
+    optional("SELLING_TIME_AFTER_BUYING", ValueKind::Integer { min: 0, max: 86_400 }, "1"),
+    optional("INTERVAL", ValueKind::Integer { min: 1, max: 86_400 }, "10"),
+    optional("SLIPPAGE", ValueKind::Integer { min: 0, max: 25_000 }, "10000"),
+    optional("TOKEN_AMOUNT", SOL_AMOUNT, "0.001"),
+    optional("COUNTER_LIMIT", ValueKind::Integer { min: 0, max: u32::MAX as u64 }, "0"),
+    optional("IS_PROGRESSIVE_SELL", ValueKind::Bool, "false"),
+    optional("UNIT_PRICE", ValueKind::Integer { min: 0, max: 100_000_000 }, "20000"),
+    optional("UNIT_LIMIT", ValueKind::Integer { min: 1, max: 1_400_000 }, "200000"),
+    optional("MIN_SELL_DELAY_HOURS", ValueKind::Integer { min: 0, max: 8_760 }, "24"),
+    optional("MAX_SELL_DELAY_HOURS", ValueKind::Integer { min: 0, max: 8_760 }, "72"),
+    optional("PRICE_CHANGE_THRESHOLD", RATIO, "0.15"),
+    optional("MIN_BUY_RATIO", RATIO, "0.67"),
+    optional("MAX_BUY_RATIO", RATIO, "0.73"),
+    optional("VOLUME_WAVE_ACTIVE_HOURS", ValueKind::Integer { min: 1, max: 168 }, "2"),
+    optional("VOLUME_WAVE_SLOW_HOURS", ValueKind::Integer { min: 1, max: 168 }, "6"),
+    optional("VOLUME_WAVE_BURST_MINUTES", ValueKind::Integer { min: 1, max: 1_440 }, "15"),
+    optional("VOLUME_WAVE_DORMANT_MINUTES", ValueKind::Integer { min: 1, max: 1_440 }, "60"),
+    optional("VOLUME_WAVE_BURST_PROBABILITY", RATIO, "0.15"),
+    optional("VOLUME_WAVE_DORMANT_PROBABILITY", RATIO, "0.1"),
+    optional("GUARDIAN_MODE_ENABLED", ValueKind::Bool, "true"),
+    optional("ENABLE_VOLUME_WAVES", ValueKind::Bool, "true"),
+    optional("ENABLE_DYNAMIC_RATIOS", ValueKind::Bool, "true"),
+    optional("ENABLE_PRICE_MONITOR", ValueKind::Bool, "true"),
+    optional("PRICE_THROTTLE_MODE", ValueKind::Text, "shrink"),
+    optional("PRICE_THROTTLE_SIZE_FACTOR", ValueKind::Float { min: 0.0, max: 1.0 }, "0.5"),
+    optional("ENABLE_TELEGRAM", ValueKind::Bool, "true"),
+    optional("CANDLE_HISTORY_SIZE", ValueKind::Integer { min: 10, max: 100_000 }, "500"),
+    optional("MA_TYPE", ValueKind::Text, "ema"),
+    optional("MA_FAST_PERIOD", ValueKind::Integer { min: 1, max: 1_000 }, "9"),
+    optional("MA_SLOW_PERIOD", ValueKind::Integer { min: 2, max: 1_000 }, "21"),
+    optional("MA_INTERVAL", ValueKind::Text, "5m"),
+    optional("BOLLINGER_ENABLED", ValueKind::Bool, "false"),
+    optional("BOLLINGER_PERIOD", ValueKind::Integer { min: 2, max: 500 }, "20"),
+    optional("BOLLINGER_STD_DEV", ValueKind::Float { min: 0.5, max: 5.0 }, "2.0"),
+    optional("BOLLINGER_INTERVAL", ValueKind::Text, "5m"),
+    optional("BOLLINGER_CONFIRM_DROPS", ValueKind::Bool, "false"),
+    optional("ORDER_FLOW_WINDOW_MINUTES", ValueKind::Integer { min: 1, max: 1_440 }, "15"),
+    optional("ORDER_FLOW_MIN_VOLUME_SOL", SOL_AMOUNT, "1.0"),
+    optional("VWAP_ALERT_PCT", ValueKind::Float { min: 0.0, max: 100.0 }, "5"),
+    optional("VWAP_ALERT_COOLDOWN_MINUTES", ValueKind::Integer { min: 0, max: 1_440 }, "15"),
+    optional("GUARDIAN_DROP_THRESHOLD", ValueKind::Float { min: 0.01, max: 0.99 }, "0.10"),
+    optional("GUARDIAN_DURATION_MINUTES", ValueKind::Integer { min: 1, max: 1_440 }, "30"),
+    optional("GUARDIAN_COOLDOWN_HOURS", ValueKind::Integer { min: 0, max: 168 }, "2"),
+    optional("GUARDIAN_MAX_INTERVENTIONS_PER_DAY", ValueKind::Integer { min: 0, max: 1_000 }, "6"),
+    optional("GUARDIAN_TIER_LIGHT", ValueKind::Text, "0.7,0.1,1.2"),
+    optional("GUARDIAN_TIER_MEDIUM", ValueKind::Text, "0.5,0.2,1.5"),
+    optional("GUARDIAN_TIER_STRONG", ValueKind::Text, "0.3,0.3,2.0"),
+    optional("PUMP_GUARDIAN_ENABLED", ValueKind::Bool, "false"),
+    optional("PUMP_GUARDIAN_RISE_THRESHOLD", ValueKind::Float { min: 0.01, max: 10.0 }, "0.15"),
+    optional("PUMP_GUARDIAN_DURATION_MINUTES", ValueKind::Integer { min: 1, max: 1_440 }, "20"),
+    optional("PUMP_GUARDIAN_COOLDOWN_HOURS", ValueKind::Integer { min: 0, max: 168 }, "2"),
+    optional("PUMP_GUARDIAN_SELL_BIAS", ValueKind::Float { min: 0.0, max: 0.5 }, "0.2"),
+    optional("PUMP_GUARDIAN_SIZE_MULTIPLIER", ValueKind::Float { min: 1.0, max: 5.0 }, "1.5"),
+    optional("DAILY_SOL_SPEND_CAP", SOL_AMOUNT, "5.0"),
+    optional("KILL_SWITCH_LIQUIDATE", ValueKind::Bool, "false"),
+    optional("APPROVAL_THRESHOLD_SOL", SOL_AMOUNT, "1.0"),
+    optional("APPROVAL_TIMEOUT_SECONDS", ValueKind::Integer { min: 1, max: 86_400 }, "120"),
+    optional("MAX_TRADES_PER_HOUR", ValueKind::Integer { min: 0, max: 10_000 }, "20"),
+    optional("MAX_TRADES_PER_WALLET_PER_DAY", ValueKind::Integer { min: 0, max: 10_000 }, "10"),
+    optional("MAX_DRAWDOWN_PERCENT", ValueKind::Float { min: 0.0, max: 99.0 }, "15"),
+    optional("DRAWDOWN_CHECK_SECONDS", ValueKind::Integer { min: 10, max: 86_400 }, "60"),
+    optional("MIN_POOL_LIQUIDITY_SOL", SOL_AMOUNT, "20.0"),
+    optional("LIQUIDITY_RESUME_SOL", SOL_AMOUNT, "25.0"),
+    optional("RUG_DETECTION_ENABLED", ValueKind::Bool, "false"),
+    optional("RUG_LP_MINT", ValueKind::Pubkey, "PoolLpMintPubkey"),
+    optional("RUG_LP_DROP_PERCENT", ValueKind::Float { min: 1.0, max: 100.0 }, "30"),
+    optional("RUG_AUTO_EXIT", ValueKind::Bool, "true"),
+    optional("CREATOR_MONITOR_ENABLED", ValueKind::Bool, "true"),
+    optional("CREATOR_AUTO_PAUSE_BUYS", ValueKind::Bool, "false"),
+    optional("CREATOR_PAUSE_MINUTES", ValueKind::Integer { min: 1, max: 10_080 }, "60"),
+    optional("CREATOR_ALERT_MIN_TOKENS", ValueKind::Float { min: 0.0, max: 1e15 }, "0"),
+    optional("SANDWICH_DETECTION_ENABLED", ValueKind::Bool, "true"),
+    optional("SANDWICH_SAMPLE_SIZE", ValueKind::Integer { min: 1, max: 1_000 }, "20"),
+    optional("SANDWICH_RATE_THRESHOLD", ValueKind::Float { min: 0.0, max: 100.0 }, "20"),
+    optional("SANDWICH_SLIPPAGE_STEP_BPS", ValueKind::Integer { min: 0, max: 10_000 }, "200"),
+    optional("SANDWICH_MIN_SLIPPAGE_BPS", ValueKind::Integer { min: 0, max: 10_000 }, "100"),
+    optional("COPY_TARGET_WALLETS", ValueKind::PubkeyList, "Wallet1Pubkey,Wallet2Pubkey"),
+    optional("COPY_SIZE_SCALE", ValueKind::Float { min: 0.0, max: 100.0 }, "0.5"),
+    optional("COPY_MAX_BUY_SOL", SOL_AMOUNT, "1.0"),
+    optional("COPY_MIN_DELAY_MS", ValueKind::Integer { min: 0, max: 600_000 }, "500"),
+    optional("COPY_MAX_DELAY_MS", ValueKind::Integer { min: 0, max: 600_000 }, "3000"),
+    optional("COPY_SELLS", ValueKind::Bool, "true"),
+    optional("MONITOR_WALLETS", ValueKind::Text, "wallet1,wallet2"),
+    optional("MONITOR_ALERT_MIN_SOL", ValueKind::Float { min: 0.0, max: 1_000_000.0 }, "5"),
+    optional("MONITOR_ALERT_MINTS", ValueKind::Bool, "true"),
+    optional("CONTROL_TOKEN", ValueKind::Text, "long-random-string"),
+    optional("WEBHOOK_ADDR", ValueKind::Text, "127.0.0.1:8787"),
+    optional("WEBHOOK_SECRET", ValueKind::Text, "long-random-string"),
+    optional("DUST_THRESHOLD_SOL", SOL_AMOUNT, "0.001"),
+    optional("WHALE_ALERT_SOL", SOL_AMOUNT, "5.0"),
+    optional("USD_PRICE_SOURCE", ValueKind::Text, "jupiter"),
+    optional("USD_PRICE_REFRESH_SECONDS", ValueKind::Integer { min: 5, max: 86_400 }, "60"),
+    optional("PYTH_TOKEN_FEED_ID", ValueKind::Text, "0xPythFeedId"),
+    optional("PNL_REPORT_MINUTES", ValueKind::Integer { min: 0, max: 10_080 }, "60"),
+    optional("ACTIVITY_REPORT_MINUTES", ValueKind::Integer { min: 0, max: 1_440 }, "30"),
+    optional("TAKE_PROFIT_LEVELS", ValueKind::Text, "20:25,50:25,100:50"),
+    optional("STOP_LOSS_PERCENT", ValueKind::Float { min: 0.0, max: 99.0 }, "30"),
+    optional("STOP_LOSS_TRAILING", ValueKind::Bool, "false"),
+    optional("STOP_LOSS_STAGGER_SECONDS", ValueKind::Integer { min: 0, max: 86_400 }, "120"),
+    optional("RETRACEMENT_LEVELS", ValueKind::Text, "50:20:30,100:15:50"),
+    optional("RETRACEMENT_STAGGER_SECONDS", ValueKind::Integer { min: 0, max: 86_400 }, "30"),
+    optional("MILESTONE_INTERVAL_PERCENT", ValueKind::Float { min: 0.0, max: 10_000.0 }, "50"),
+    optional("MILESTONE_SELL_PERCENT", ValueKind::Float { min: 0.0, max: 100.0 }, "10"),
+    optional("MILESTONE_MAX_INTERVALS", ValueKind::Integer { min: 1, max: 1_000 }, "10"),
+    optional("INVENTORY_REFRESH_SECONDS", ValueKind::Integer { min: 10, max: 86_400 }, "60"),
+    optional("MAX_INVENTORY_TOKENS", ValueKind::Float { min: 0.0, max: 1e15 }, "2000000"),
+    optional("MAX_INVENTORY_POOL_PERCENT", ValueKind::Float { min: 0.0, max: 100.0 }, "5"),
+    optional("TWAP_ENABLED", ValueKind::Bool, "false"),
+    optional("TWAP_TARGET_TOKENS", ValueKind::Float { min: 0.0, max: 1e15 }, "1000000"),
+    optional("TWAP_DURATION_HOURS", ValueKind::Float { min: 0.1, max: 8_760.0 }, "24"),
+    optional("WALLET_COUNT", ValueKind::Integer { min: 1, max: 10_000 }, "100"),
+    optional("WRAP_AMOUNT", SOL_AMOUNT, "0.5"),
+    optional("RANDOM_TRADER_SEED", ValueKind::Integer { min: 0, max: u64::MAX }, "42"),
+    optional("MODE", ValueKind::Text, "market_maker"),
+    optional("RANDOM_TRADER_MIN_BUY_AMOUNT", SOL_AMOUNT, "0.001"),
+    optional("RANDOM_TRADER_MAX_BUY_AMOUNT", SOL_AMOUNT, "0.01"),
+    optional("RANDOM_TRADER_MIN_SELL_PERCENT", ValueKind::Float { min: 0.0, max: 100.0 }, "10"),
+    optional("RANDOM_TRADER_MAX_SELL_PERCENT", ValueKind::Float { min: 0.0, max: 100.0 }, "50"),
+    optional("RANDOM_TRADER_MIN_INTERVAL_SECONDS", ValueKind::Integer { min: 1, max: 86_400 }, "30"),
+    optional("RANDOM_TRADER_MAX_INTERVAL_SECONDS", ValueKind::Integer { min: 1, max: 86_400 }, "300"),
+    optional("CACHE_BACKEND", ValueKind::Text, "memory"),
+    optional("CACHE_DIR", ValueKind::Text, "state/cache"),
+    optional("CACHE_MAINTENANCE_SECONDS", ValueKind::Integer { min: 1, max: 86_400 }, "60"),
+    optional("STATE_DIR", ValueKind::Text, "state"),
+    optional("STATE_CHECKPOINT_SECONDS", ValueKind::Integer { min: 1, max: 86_400 }, "30"),
+    optional("CONFIG_HOT_RELOAD", ValueKind::Bool, "true"),
+    optional("CONFIG_RELOAD_INTERVAL_SECONDS", ValueKind::Integer { min: 1, max: 3_600 }, "5"),
+    optional("SHUTDOWN_TIMEOUT_SECONDS", ValueKind::Integer { min: 0, max: 3_600 }, "60"),
+    optional("SECRETS_BACKEND", ValueKind::Text, "vault"),
+    optional("SECRETS_PRIVATE_KEY_ID", ValueKind::Text, "secret/data/market-maker#private_key"),
+    optional("SECRETS_GRPC_TOKEN_ID", ValueKind::Text, "secret/data/market-maker#grpc_token"),
+    optional("VAULT_ADDR", ValueKind::Url, "https://vault.example.com"),
+    optional("LOG_FILTER", ValueKind::Text, "info,market_maker=debug"),
+    optional("LOG_FORMAT", ValueKind::Text, "console"),
+];
+
+/// A single problem found during validation
+#[derive(Debug, Clone)]
+pub struct ConfigIssue {
+    pub name: String,
+    pub message: String,
+}
+
+/// All problems found in the configuration, reported together
+#[derive(Debug, Clone)]
+pub struct ConfigError {
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Invalid configuration ({} problem(s)):", self.issues.len())?;
+        for issue in &self.issues {
+            writeln!(f, "  - {}: {}", issue.name, issue.message)?;
+        }
+        write!(f, "Fix these in .env / config.toml and restart.")
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+fn get_value(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+fn parse<T: FromStr>(name: &str) -> Option<T> {
+    get_value(name).and_then(|v| v.trim().parse::<T>().ok())
+}
+
+/// `min <= max` check for a pair of optional numeric variables
+fn check_ordered<T: FromStr + PartialOrd + fmt::Display>(issues: &mut Vec<ConfigIssue>, min_name: &str, max_name: &str) {
+    if let (Some(min), Some(max)) = (parse::<T>(min_name), parse::<T>(max_name)) {
+        if min > max {
+            issues.push(ConfigIssue {
+                name: format!("{} / {}", min_name, max_name),
+                message: format!("{} ({}) must not exceed {} ({})", min_name, min, max_name, max),
+            });
+        }
+    }
+}
+
+/// Validate the environment against `ENV_SPECS` plus cross-field rules.
+/// Every problem is collected so they can be fixed in one pass.
+pub fn validate_env() -> Result<(), ConfigError> {
+    let mut issues = Vec::new();
+
+    for spec in ENV_SPECS {
+        match get_value(spec.name) {
+            None if spec.required => issues.push(ConfigIssue {
+                name: spec.name.to_string(),
+                message: format!("missing; expected {} (e.g. {}={})", spec.kind.describe(), spec.name, spec.example),
+            }),
+            None => {},
+            Some(value) if !spec.kind.check(&value) => issues.push(ConfigIssue {
+                name: spec.name.to_string(),
+                message: format!(
+                    "invalid value '{}'; expected {} (e.g. {}={})",
+                    if spec.kind == ValueKind::Keypair || spec.name.ends_with("_TOKEN") { "<redacted>".to_string() } else { value },
+                    spec.kind.describe(),
+                    spec.name,
+                    spec.example
+                ),
+            }),
+            Some(_) => {},
+        }
+    }
+
+    check_ordered::<f64>(&mut issues, "MIN_BUY_AMOUNT", "MAX_BUY_AMOUNT");
+    check_ordered::<f64>(&mut issues, "MIN_BUY_RATIO", "MAX_BUY_RATIO");
+    check_ordered::<u64>(&mut issues, "MIN_SELL_DELAY_HOURS", "MAX_SELL_DELAY_HOURS");
+    check_ordered::<f64>(&mut issues, "MIN_POOL_LIQUIDITY_SOL", "LIQUIDITY_RESUME_SOL");
+    check_ordered::<u64>(&mut issues, "COPY_MIN_DELAY_MS", "COPY_MAX_DELAY_MS");
+    check_ordered::<u64>(&mut issues, "MA_FAST_PERIOD", "MA_SLOW_PERIOD");
+    check_ordered::<f64>(&mut issues, "RANDOM_TRADER_MIN_BUY_AMOUNT", "RANDOM_TRADER_MAX_BUY_AMOUNT");
+    check_ordered::<f64>(&mut issues, "RANDOM_TRADER_MIN_SELL_PERCENT", "RANDOM_TRADER_MAX_SELL_PERCENT");
+    check_ordered::<u64>(&mut issues, "RANDOM_TRADER_MIN_INTERVAL_SECONDS", "RANDOM_TRADER_MAX_INTERVAL_SECONDS");
+
+    if let Some(levels) = get_value("TAKE_PROFIT_LEVELS") {
+        if let Err(e) = crate::engine::positions::parse_take_profit_levels(&levels) {
+            issues.push(ConfigIssue { name: "TAKE_PROFIT_LEVELS".to_string(), message: e });
+        }
+    }
+    if let Some(levels) = get_value("RETRACEMENT_LEVELS") {
+        if let Err(e) = crate::engine::positions::parse_retracement_levels(&levels) {
+            issues.push(ConfigIssue { name: "RETRACEMENT_LEVELS".to_string(), message: e });
+        }
+    }
+
+    if get_value("TWAP_ENABLED").map(|v| v.trim().to_lowercase() == "true").unwrap_or(false)
+        && parse::<f64>("TWAP_TARGET_TOKENS").map(|v| v <= 0.0).unwrap_or(true)
+    {
+        issues.push(ConfigIssue {
+            name: "TWAP_TARGET_TOKENS".to_string(),
+            message: "required when TWAP_ENABLED=true; expected a positive token amount".to_string(),
+        });
+    }
+
+    for name in ["GUARDIAN_TIER_LIGHT", "GUARDIAN_TIER_MEDIUM", "GUARDIAN_TIER_STRONG"] {
+        if let Some(tier) = get_value(name) {
+            if let Err(e) = crate::common::guardian_mode::parse_intervention_tier(&tier) {
+                issues.push(ConfigIssue { name: name.to_string(), message: e });
+            }
+        }
+    }
+
+    if let Some(mode) = get_value("PRICE_THROTTLE_MODE") {
+        if !["shrink", "skip"].contains(&mode.trim().to_lowercase().as_str()) {
+            issues.push(ConfigIssue {
+                name: "PRICE_THROTTLE_MODE".to_string(),
+                message: format!("invalid value '{}'; expected shrink or skip", mode),
+            });
+        }
+    }
+    if let Some(kind) = get_value("MA_TYPE") {
+        if !["ema", "sma"].contains(&kind.trim().to_lowercase().as_str()) {
+            issues.push(ConfigIssue {
+                name: "MA_TYPE".to_string(),
+                message: format!("invalid value '{}'; expected ema or sma", kind),
+            });
+        }
+    }
+    for name in ["MA_INTERVAL", "BOLLINGER_INTERVAL"] {
+        if let Some(interval) = get_value(name) {
+            if crate::common::price_monitor::CandleInterval::parse(&interval).is_none() {
+                issues.push(ConfigIssue {
+                    name: name.to_string(),
+                    message: format!("invalid value '{}'; expected 1m, 5m or 1h", interval),
+                });
+            }
+        }
+    }
+
+    if let Some(mode) = get_value("MODE") {
+        if !["market_maker", "random_trader", "random-trader"].contains(&mode.trim().to_lowercase().as_str()) {
+            issues.push(ConfigIssue {
+                name: "MODE".to_string(),
+                message: format!("invalid value '{}'; expected market_maker or random_trader", mode),
+            });
+        }
+    }
+
+    if let Some(filter) = get_value("LOG_FILTER") {
+        if let Err(e) = tracing_subscriber::EnvFilter::try_new(&filter) {
+            issues.push(ConfigIssue {
+                name: "LOG_FILTER".to_string(),
+                message: format!("invalid filter '{}': {}", filter, e),
+            });
+        }
+    }
+
+    if let Some(format) = get_value("LOG_FORMAT") {
+        if !["console", "json"].contains(&format.trim().to_lowercase().as_str()) {
+            issues.push(ConfigIssue {
+                name: "LOG_FORMAT".to_string(),
+                message: format!("invalid value '{}'; expected console or json", format),
+            });
+        }
+    }
+
+    if let Some(backend) = get_value("CACHE_BACKEND") {
+        if !["memory", "sled"].contains(&backend.trim().to_lowercase().as_str()) {
+            issues.push(ConfigIssue {
+                name: "CACHE_BACKEND".to_string(),
+                message: format!("invalid value '{}'; expected memory or sled", backend),
+            });
+        }
+    }
+
+    if get_value("SECRETS_BACKEND").is_some() {
+        if let Err(e) = crate::common::secrets::SecretsBackend::from_env() {
+            issues.push(ConfigIssue { name: "SECRETS_BACKEND".to_string(), message: e.to_string() });
+        }
+    }
+
+    if let Some(source) = get_value("USD_PRICE_SOURCE") {
+        if !["off", "jupiter", "jup", "pyth"].contains(&source.trim().to_lowercase().as_str()) {
+            issues.push(ConfigIssue {
+                name: "USD_PRICE_SOURCE".to_string(),
+                message: format!("invalid value '{}'; expected off, jupiter or pyth", source),
+            });
+        }
+    }
+
+    // Raydium venues need explicit pool accounts; Pump.fun (DEX=2) derives them
+    if let Some(dex) = parse::<u64>("DEX") {
+        if dex != 2 {
+            for name in ["POOL_ID", "POOL_BASE_ACCOUNT", "POOL_QUOTE_ACCOUNT"] {
+                if get_value(name).is_none() {
+                    issues.push(ConfigIssue {
+                        name: name.to_string(),
+                        message: format!("required when DEX={} (Raydium); expected a base58 public key", dex),
+                    });
+                }
+            }
+        }
+    }
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigError { issues })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_kinds_accept_their_format() {
+        assert!(ValueKind::Url.check("https://rpc.example.com"));
+        assert!(ValueKind::Url.check("wss://rpc.example.com"));
+        assert!(!ValueKind::Url.check("rpc.example.com"));
+
+        assert!(ValueKind::Pubkey.check("So11111111111111111111111111111111111111112"));
+        assert!(!ValueKind::Pubkey.check("not-a-pubkey"));
+
+        assert!(ValueKind::Text.check(" value "));
+        assert!(!ValueKind::Text.check("   "));
+
+        let keys = "So11111111111111111111111111111111111111112, 11111111111111111111111111111111";
+        assert!(ValueKind::PubkeyList.check(keys));
+        assert!(!ValueKind::PubkeyList.check("So11111111111111111111111111111111111111112,typo"));
+
+        let float = ValueKind::Float { min: 0.0, max: 1.0 };
+        assert!(float.check("0.5") && float.check("0") && float.check("1"));
+        assert!(!float.check("1.5") && !float.check("-0.1") && !float.check("NaN") && !float.check("abc"));
+
+        let integer = ValueKind::Integer { min: 1, max: 10 };
+        assert!(integer.check("1") && integer.check(" 10 "));
+        assert!(!integer.check("0") && !integer.check("11") && !integer.check("2.5") && !integer.check("-1"));
+
+        assert!(ValueKind::Bool.check("TRUE") && ValueKind::Bool.check("false"));
+        assert!(!ValueKind::Bool.check("yes"));
+    }
+
+    #[test]
+    fn keypair_kind_needs_a_64_byte_base58_key() {
+        let keypair = anchor_client::solana_sdk::signature::Keypair::new();
+        assert!(ValueKind::Keypair.check(&keypair.to_base58_string()));
+        assert!(!ValueKind::Keypair.check("So11111111111111111111111111111111111111112"));
+    }
+
+    #[test]
+    fn env_specs_are_unique() {
+        let mut names: Vec<&str> = ENV_SPECS.iter().map(|s| s.name).collect();
+        names.sort();
+        let before = names.len();
+        names.dedup();
+        assert_eq!(before, names.len());
+    }
+
+    /// Sets variables for one test and restores their previous values when dropped
+    struct EnvGuard {
+        saved: Vec<(&'static str, Option<String>)>,
+    }
+
+    impl EnvGuard {
+        fn set(vars: &[(&'static str, &str)]) -> Self {
+            let saved = vars.iter().map(|(name, _)| (*name, env::var(name).ok())).collect();
+            for (name, value) in vars {
+                env::set_var(name, value);
+            }
+            Self { saved }
+        }
+    }
+
+    impl Drop for EnvGuard {
+        fn drop(&mut self) {
+            for (name, value) in &self.saved {
+                match value {
+                    Some(value) => env::set_var(name, value),
+                    None => env::remove_var(name),
+                }
+            }
+        }
+    }
+
+    // The only test that touches the process environment, so parallel tests can't race on it
+    #[test]
+    fn validate_env_reports_every_problem() {
+        let _env = EnvGuard::set(&[
+            ("MIN_BUY_AMOUNT", "0.5"),
+            ("MAX_BUY_AMOUNT", "0.1"),
+            ("TAKE_PROFIT_LEVELS", "20:60,50:60"),
+            ("PRICE_THROTTLE_MODE", "pause"),
+            ("DEX", "7"),
+            ("WALLET_COUNT", "0"),
+            ("SECRETS_BACKEND", "keychain"),
+        ]);
+
+        let issues = validate_env().unwrap_err().issues;
+        let names: Vec<&str> = issues.iter().map(|i| i.name.as_str()).collect();
+        for expected in ["MIN_BUY_AMOUNT / MAX_BUY_AMOUNT", "TAKE_PROFIT_LEVELS", "PRICE_THROTTLE_MODE", "DEX", "WALLET_COUNT", "SECRETS_BACKEND"] {
+            assert!(names.contains(&expected), "missing issue for {} in {:?}", expected, names);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use yellowstone_grpc_proto::prelude::{TokenBalance, TransactionStatusMeta};
+
+/// Coin creator monitor settings
+#[derive(Debug, Clone, Copy)]
+pub struct CreatorMonitorConfig {
+    pub enabled: bool,
+    /// Pause new buys after the creator moves tokens out
+    pub auto_pause_buys: bool,
+    /// How long buys stay paused after a creator outflow
+    pub pause_minutes: u64,
+    /// Ignore outflows smaller than this many tokens
+    pub min_tokens: f64,
+}
+
+impl CreatorMonitorConfig {
+    /// Read CREATOR_MONITOR_ENABLED, CREATOR_AUTO_PAUSE_BUYS, CREATOR_PAUSE_MINUTES and CREATOR_ALERT_MIN_TOKENS
+    pub fn from_env() -> Self {
+        let flag = |name: &str, default: bool| {
+            std::env::var(name).ok().map(|v| v.trim().to_lowercase() == "true").unwrap_or(default)
+        };
+        Self {
+            enabled: flag("CREATOR_MONITOR_ENABLED", true),
+            auto_pause_buys: flag("CREATOR_AUTO_PAUSE_BUYS", false),
+            pause_minutes: std::env::var("CREATOR_PAUSE_MINUTES").ok().and_then(|v| v.parse().ok()).unwrap_or(60),
+            min_tokens: std::env::var("CREATOR_ALERT_MIN_TOKENS").ok().and_then(|v| v.parse().ok()).unwrap_or(0.0),
+        }
+    }
+}
+
+fn owned_balance(balances: &[TokenBalance], owner: &str, mint: &str) -> f64 {
+    balances
+        .iter()
+        .filter(|b| b.owner == owner && b.mint == mint)
+        .filter_map(|b| b.ui_token_amount.as_ref())
+        .map(|amount| amount.ui_amount)
+        .sum()
+}
+
+/// Tokens of `mint` that left accounts owned by `owner` in this transaction (0 if none)
+pub fn token_outflow(meta: &TransactionStatusMeta, owner: &str, mint: &str) -> f64 {
+    let before = owned_balance(&meta.pre_token_balances, owner, mint);
+    let after = owned_balance(&meta.post_token_balances, owner, mint);
+    (before - after).max(0.0)
+}
@@ -0,0 +1,168 @@
+use std::path::PathBuf;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use colored::Colorize;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use solana_program_pack::Pack;
+use spl_token_2022::extension::{AccountType, BaseStateWithExtensions, StateWithExtensionsOwned};
+use spl_token_2022::state::{Account, Mint};
+use crate::common::cache::{TOKEN_MINT_CACHE, WALLET_TOKEN_ACCOUNTS};
+use crate::common::logger::Logger;
+use crate::common::persistence::{state_dir, unix_now};
+use crate::common::pool_state::POOL_STATE;
+use crate::engine::monitor::PoolInfo;
+
+const MINTS_TREE: &str = "mints";
+const WALLET_TOKEN_ACCOUNTS_TREE: &str = "wallet_token_accounts";
+const POOLS_TREE: &str = "pools";
+
+/// Pool identity as stored on disk; reserves come back stale and are ignored until streamed again
+#[derive(Serialize, Deserialize)]
+struct StoredPool {
+    pool_id: Pubkey,
+    base_mint: Pubkey,
+    quote_mint: Pubkey,
+    base_reserve: u64,
+    quote_reserve: u64,
+    coin_creator: Pubkey,
+    saved_at: i64,
+}
+
+/// Entries loaded by `warm_up`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WarmUpCounts {
+    pub mints: usize,
+    pub wallet_token_accounts: usize,
+    pub pools: usize,
+}
+
+/// Optional sled store behind the in-memory caches (CACHE_BACKEND=sled). Mint info, the wallet
+/// token-account set and pool identities are written through on insert and loaded back at
+/// startup, so a restart doesn't refetch them for every pool wallet.
+pub struct DiskCache {
+    db: Option<sled::Db>,
+    logger: Logger,
+}
+
+impl DiskCache {
+    /// Open CACHE_DIR (default `<state dir>/cache`) when CACHE_BACKEND=sled; memory-only otherwise
+    fn from_env() -> Self {
+        let logger = Logger::new("[DISK-CACHE] => ".cyan().bold().to_string());
+        let backend = std::env::var("CACHE_BACKEND").unwrap_or_default().trim().to_lowercase();
+        if backend != "sled" {
+            return Self { db: None, logger };
+        }
+        let dir = std::env::var("CACHE_DIR")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| state_dir().join("cache"));
+        let db = match sled::open(&dir) {
+            Ok(db) => Some(db),
+            Err(e) => {
+                logger.log(format!("Failed to open cache store {}: {}, using memory only", dir.display(), e).red().to_string());
+                None
+            }
+        };
+        Self { db, logger }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.db.is_some()
+    }
+
+    fn write(&self, tree: &str, key: &[u8], value: Option<Vec<u8>>) {
+        let Some(db) = &self.db else { return };
+        let result = db.open_tree(tree).and_then(|tree| match value {
+            Some(value) => tree.insert(key, value).map(|_| ()),
+            None => tree.remove(key).map(|_| ()),
+        });
+        if let Err(e) = result {
+            self.logger.log(format!("Failed to write {} cache entry: {}", tree, e).red().to_string());
+        }
+    }
+
+    pub fn put_mint(&self, mint: &Pubkey, state: &StateWithExtensionsOwned<Mint>) {
+        self.write(MINTS_TREE, mint.as_ref(), Some(encode_mint(state)));
+    }
+
+    pub fn put_wallet_token_account(&self, account: &Pubkey) {
+        self.write(WALLET_TOKEN_ACCOUNTS_TREE, account.as_ref(), Some(Vec::new()));
+    }
+
+    pub fn remove_wallet_token_account(&self, account: &Pubkey) {
+        self.write(WALLET_TOKEN_ACCOUNTS_TREE, account.as_ref(), None);
+    }
+
+    pub fn put_pool(&self, info: &PoolInfo) {
+        let stored = StoredPool {
+            pool_id: info.pool_id,
+            base_mint: info.base_mint,
+            quote_mint: info.quote_mint,
+            base_reserve: info.base_reserve,
+            quote_reserve: info.quote_reserve,
+            coin_creator: info.coin_creator,
+            saved_at: unix_now(),
+        };
+        match serde_json::to_vec(&stored) {
+            Ok(data) => self.write(POOLS_TREE, info.pool_id.as_ref(), Some(data)),
+            Err(e) => {
+                self.logger.log(format!("Failed to encode pool {}: {}", info.pool_id, e).red().to_string());
+            },
+        }
+    }
+
+    /// Load everything stored into the in-memory caches
+    pub fn warm_up(&self) -> WarmUpCounts {
+        let mut counts = WarmUpCounts::default();
+        let Some(db) = &self.db else { return counts };
+        let entries = |name: &str| db.open_tree(name).map(|tree| tree.iter().flatten().collect::<Vec<_>>()).unwrap_or_default();
+
+        for (key, value) in entries(MINTS_TREE) {
+            let (Ok(mint), Ok(state)) = (Pubkey::try_from(key.as_ref()), StateWithExtensionsOwned::<Mint>::unpack(value.to_vec())) else { continue };
+            TOKEN_MINT_CACHE.insert(mint, state, None);
+            counts.mints += 1;
+        }
+        for (key, _) in entries(WALLET_TOKEN_ACCOUNTS_TREE) {
+            let Ok(account) = Pubkey::try_from(key.as_ref()) else { continue };
+            WALLET_TOKEN_ACCOUNTS.insert(account);
+            counts.wallet_token_accounts += 1;
+        }
+        for (_, value) in entries(POOLS_TREE) {
+            let Ok(stored) = serde_json::from_slice::<StoredPool>(&value) else { continue };
+            POOL_STATE.restore(PoolInfo {
+                pool_id: stored.pool_id,
+                base_mint: stored.base_mint,
+                quote_mint: stored.quote_mint,
+                base_reserve: stored.base_reserve,
+                quote_reserve: stored.quote_reserve,
+                coin_creator: stored.coin_creator,
+            }, stored.saved_at);
+            counts.pools += 1;
+        }
+
+        self.logger.log(format!(
+            "💾 Cache warmed from disk: {} mints, {} wallet token accounts, {} pools",
+            counts.mints, counts.wallet_token_accounts, counts.pools
+        ).green().to_string());
+        counts
+    }
+}
+
+/// Account bytes `StateWithExtensionsOwned::unpack` accepts: the packed base, plus the
+/// account-type byte and TLV data after the base account length when extensions are present
+fn encode_mint(state: &StateWithExtensionsOwned<Mint>) -> Vec<u8> {
+    let mut data = vec![0u8; Mint::LEN];
+    Mint::pack_into_slice(&state.base, &mut data);
+    let tlv = state.get_tlv_data();
+    if !tlv.is_empty() {
+        data.resize(Account::LEN, 0);
+        data.push(AccountType::Mint as u8);
+        data.extend_from_slice(tlv);
+    }
+    data
+}
+
+lazy_static! {
+    pub static ref DISK_CACHE: DiskCache = DiskCache::from_env();
+}
@@ -0,0 +1,141 @@
+use colored::Colorize;
+use crate::common::logger::Logger;
+
+/// Drawdown guard settings (MAX_DRAWDOWN_PERCENT, 0 = disabled)
+#[derive(Debug, Clone, Copy)]
+pub struct DrawdownConfig {
+    pub max_drawdown_percent: f64,
+    pub check_seconds: u64,
+}
+
+impl DrawdownConfig {
+    /// Read MAX_DRAWDOWN_PERCENT and DRAWDOWN_CHECK_SECONDS
+    pub fn from_env() -> Self {
+        Self {
+            max_drawdown_percent: std::env::var("MAX_DRAWDOWN_PERCENT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.0),
+            check_seconds: std::env::var("DRAWDOWN_CHECK_SECONDS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(60),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_drawdown_percent > 0.0
+    }
+}
+
+/// Tracks portfolio value (SOL + tokens marked to market) against the session high
+pub struct DrawdownMonitor {
+    config: DrawdownConfig,
+    session_high: f64,
+    tripped: bool,
+    logger: Logger,
+}
+
+impl DrawdownMonitor {
+    pub fn new(config: DrawdownConfig) -> Self {
+        let logger = Logger::new("[DRAWDOWN] => ".red().bold().to_string());
+        if config.is_enabled() {
+            logger.log(format!("📉 Auto-pause at {}% drawdown from session high", config.max_drawdown_percent).yellow().to_string());
+        }
+        Self {
+            config,
+            session_high: 0.0,
+            tripped: false,
+            logger,
+        }
+    }
+
+    /// Current drawdown from the session high, in percent
+    pub fn drawdown_percent(&self, value_sol: f64) -> f64 {
+        if self.session_high <= 0.0 {
+            return 0.0;
+        }
+        ((self.session_high - value_sol) / self.session_high * 100.0).max(0.0)
+    }
+
+    /// Feed a new portfolio valuation. Returns the drawdown when this update trips the guard.
+    pub fn update(&mut self, value_sol: f64) -> Option<f64> {
+        if !self.config.is_enabled() || self.tripped {
+            return None;
+        }
+        if value_sol > self.session_high {
+            self.session_high = value_sol;
+            return None;
+        }
+
+        let drawdown = self.drawdown_percent(value_sol);
+        if drawdown < self.config.max_drawdown_percent {
+            return None;
+        }
+        self.tripped = true;
+        self.logger.log(format!(
+            "📉 Portfolio {:.4} SOL is {:.2}% below session high {:.4} SOL (limit {}%)",
+            value_sol, drawdown, self.session_high, self.config.max_drawdown_percent
+        ).red().bold().to_string());
+        Some(drawdown)
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.tripped
+    }
+
+    /// Re-arm after an explicit drawdown reset, measuring drawdown from the current value
+    pub fn rearm(&mut self, value_sol: f64) {
+        self.tripped = false;
+        self.session_high = value_sol;
+        self.logger.log(format!("🔁 Drawdown guard re-armed at {:.4} SOL", value_sol).green().to_string());
+    }
+
+    pub fn session_high(&self) -> f64 {
+        self.session_high
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(max_drawdown_percent: f64) -> DrawdownMonitor {
+        DrawdownMonitor::new(DrawdownConfig { max_drawdown_percent, check_seconds: 60 })
+    }
+
+    #[test]
+    fn trips_once_at_the_limit_from_the_session_high() {
+        let mut monitor = monitor(10.0);
+        assert!(monitor.update(8.0).is_none());
+        assert!(monitor.update(10.0).is_none());
+        assert_eq!(monitor.session_high(), 10.0);
+        assert!(monitor.update(9.5).is_none());
+
+        let drawdown = monitor.update(9.0).unwrap();
+        assert!((drawdown - 10.0).abs() < 1e-9);
+        assert!(monitor.is_tripped());
+        assert!(monitor.update(5.0).is_none(), "reports the trip once");
+    }
+
+    #[test]
+    fn rearm_measures_from_the_current_value() {
+        let mut monitor = monitor(10.0);
+        monitor.update(10.0);
+        assert!(monitor.update(8.0).is_some());
+
+        monitor.rearm(8.0);
+        assert!(!monitor.is_tripped());
+        assert_eq!(monitor.session_high(), 8.0);
+        assert!(monitor.update(7.5).is_none());
+        assert!(monitor.update(7.0).is_some());
+    }
+
+    #[test]
+    fn disabled_guard_never_trips() {
+        let mut monitor = monitor(0.0);
+        monitor.update(10.0);
+        assert!(monitor.update(0.1).is_none());
+        assert_eq!(monitor.drawdown_percent(5.0), 0.0);
+    }
+}
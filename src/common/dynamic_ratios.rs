@@ -1,10 +1,40 @@
+use std::collections::VecDeque;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::time::{Duration, Instant};
 use rand::Rng;
 use colored::Colorize;
 use chrono::Datelike;
+use serde::{Deserialize, Serialize};
 use crate::common::logger::Logger;
+use crate::common::persistence::unix_now;
+use crate::common::price_monitor::{instant_from_unix, unix_from_instant};
+
+/// State store key for the dynamic ratio manager
+pub const DYNAMIC_RATIO_STATE_KEY: &str = "dynamic_ratios";
+/// Ratio changes kept in the history
+const RATIO_HISTORY_SIZE: usize = 50;
+
+/// One change of the rotating buy ratio
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatioChange {
+    pub at: i64,
+    pub old_ratio: f64,
+    pub new_ratio: f64,
+    pub reason: String,
+}
+
+/// Buy ratio and rotation schedule persisted across restarts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DynamicRatioState {
+    pub current_buy_ratio: f64,
+    pub last_change_at: i64,
+    pub history: Vec<RatioChange>,
+    /// Week start (Sunday, YYYY-MM-DD) of the weekly manager
+    #[serde(default)]
+    pub week_start: Option<String>,
+    pub saved_at: i64,
+}
 
 /// Dynamic ratio manager that changes buy/sell ratios weekly
 pub struct DynamicRatioManager {
@@ -13,6 +43,11 @@ pub struct DynamicRatioManager {
     max_buy_ratio: f64,
     last_change_time: Instant,
     change_interval: Duration,
+    enabled: bool,
+    // Latest market trend signal; replaced, never accumulated
+    trend_bias: TrendBias,
+    // Most recent buy ratio changes, oldest first
+    history: VecDeque<RatioChange>,
     logger: Logger,
 }
 
@@ -32,20 +67,43 @@ impl DynamicRatioManager {
             max_buy_ratio,
             last_change_time: Instant::now(),
             change_interval: Duration::from_secs(change_interval_hours * 3600),
+            enabled: true,
+            trend_bias: TrendBias::Neutral,
+            history: VecDeque::with_capacity(RATIO_HISTORY_SIZE),
             logger,
         }
     }
     
-    /// Get the current buy ratio, updating it if needed
+    fn record_change(&mut self, old_ratio: f64, reason: &str) {
+        if (old_ratio - self.current_buy_ratio).abs() < f64::EPSILON {
+            return;
+        }
+        if self.history.len() >= RATIO_HISTORY_SIZE {
+            self.history.pop_front();
+        }
+        self.history.push_back(RatioChange {
+            at: unix_now(),
+            old_ratio,
+            new_ratio: self.current_buy_ratio,
+            reason: reason.to_string(),
+        });
+    }
+    
+    /// Get the current buy ratio (including the trend bias), updating it if needed
     pub fn get_current_buy_ratio(&mut self) -> f64 {
         let now = Instant::now();
         
         // Check if it's time to change the ratio
-        if now.duration_since(self.last_change_time) >= self.change_interval {
+        if self.enabled && now.duration_since(self.last_change_time) >= self.change_interval {
             self.update_ratio();
         }
         
-        self.current_buy_ratio
+        // Trend bias stays within the configured bounds
+        if self.enabled {
+            (self.current_buy_ratio + self.trend_bias.factor()).max(self.min_buy_ratio).min(self.max_buy_ratio)
+        } else {
+            self.current_buy_ratio
+        }
     }
     
     /// Force update the ratio (for testing or manual changes)
@@ -58,6 +116,7 @@ impl DynamicRatioManager {
             (self.max_buy_ratio - self.min_buy_ratio) * rng.gen::<f64>();
         
         self.last_change_time = Instant::now();
+        self.record_change(old_ratio, "rotation");
         
         self.logger.log(format!(
             "🔄 Buy ratio changed from {:.1}% to {:.1}% (Sell ratio: {:.1}%)",
@@ -100,11 +159,13 @@ impl DynamicRatioManager {
         }
         
         // Update current ratio if it's outside new bounds
+        let old_ratio = self.current_buy_ratio;
         if self.current_buy_ratio < self.min_buy_ratio {
             self.current_buy_ratio = self.min_buy_ratio;
         } else if self.current_buy_ratio > self.max_buy_ratio {
             self.current_buy_ratio = self.max_buy_ratio;
         }
+        self.record_change(old_ratio, "bounds changed");
         
         self.logger.log(format!(
             "⚙️ Ratio bounds updated: {:.1}% - {:.1}% (Current: {:.1}%)",
@@ -114,37 +175,105 @@ impl DynamicRatioManager {
         ).yellow().to_string());
     }
     
-    /// Apply trend bias to ratios (for market conditions)
+    /// Enable or disable ratio rotation. While disabled the buy ratio is pinned to `fixed_buy_ratio`.
+    pub fn set_enabled(&mut self, enabled: bool, fixed_buy_ratio: f64) {
+        self.enabled = enabled;
+        if !enabled {
+            let old_ratio = self.current_buy_ratio;
+            self.current_buy_ratio = fixed_buy_ratio.max(0.0).min(1.0);
+            self.record_change(old_ratio, "pinned");
+        }
+
+        self.logger.log(format!(
+            "⚙️ Dynamic ratios {} (Current buy ratio: {:.1}%)",
+            if enabled { "enabled" } else { "disabled" },
+            self.current_buy_ratio * 100.0
+        ).yellow().to_string());
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    
+    /// Set the market trend bias applied on top of the rotating ratio. Each call replaces the
+    /// previous bias, so repeated signals do not compound; `Neutral` clears it.
     pub fn apply_trend_bias(&mut self, bias: TrendBias) {
-        let bias_factor = match bias {
-            TrendBias::BullishStrong => 0.1,   // +10% towards buying
-            TrendBias::BullishMild => 0.05,    // +5% towards buying
-            TrendBias::Neutral => 0.0,         // No bias
-            TrendBias::BearishMild => -0.05,   // +5% towards selling
-            TrendBias::BearishStrong => -0.1,  // +10% towards selling
-        };
+        if !self.enabled || bias == self.trend_bias {
+            return;
+        }
         
-        // Apply bias but keep within bounds
-        let biased_ratio = (self.current_buy_ratio + bias_factor)
+        let old_bias = self.trend_bias;
+        self.trend_bias = bias;
+        let biased_ratio = (self.current_buy_ratio + bias.factor())
             .max(self.min_buy_ratio)
             .min(self.max_buy_ratio);
         
-        if biased_ratio != self.current_buy_ratio {
-            let old_ratio = self.current_buy_ratio;
-            self.current_buy_ratio = biased_ratio;
-            
-            self.logger.log(format!(
-                "📈 Trend bias applied ({:?}): {:.1}% -> {:.1}%",
-                bias,
-                old_ratio * 100.0,
-                self.current_buy_ratio * 100.0
-            ).blue().to_string());
+        self.logger.log(format!(
+            "📈 Trend bias {:?} -> {:?}: buy ratio {:.1}% -> {:.1}%",
+            old_bias,
+            bias,
+            self.current_buy_ratio * 100.0,
+            biased_ratio * 100.0
+        ).blue().to_string());
+    }
+    
+    /// Most recent `limit` buy ratio changes, oldest first
+    pub fn ratio_history(&self, limit: usize) -> Vec<RatioChange> {
+        let skip = self.history.len().saturating_sub(limit);
+        self.history.iter().skip(skip).cloned().collect()
+    }
+    
+    /// Current ratio, next change and the last `limit` changes, for reports
+    pub fn history_report(&self, limit: usize) -> String {
+        let mut report = format!(
+            "🎲 Buy ratio {:.1}% (next change in {}h {}m)",
+            self.current_buy_ratio * 100.0,
+            self.time_until_next_change().as_secs() / 3600,
+            self.time_until_next_change().as_secs() % 3600 / 60
+        );
+        for change in self.ratio_history(limit).iter().rev() {
+            let when = chrono::DateTime::from_timestamp(change.at, 0)
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            report.push_str(&format!(
+                "\n  {} {:.1}% -> {:.1}% ({})",
+                when, change.old_ratio * 100.0, change.new_ratio * 100.0, change.reason
+            ));
         }
+        report
+    }
+    
+    /// Ratio, rotation schedule and history for the state store
+    pub fn state(&self) -> DynamicRatioState {
+        DynamicRatioState {
+            current_buy_ratio: self.current_buy_ratio,
+            last_change_at: unix_from_instant(self.last_change_time),
+            history: self.history.iter().cloned().collect(),
+            week_start: None,
+            saved_at: unix_now(),
+        }
+    }
+    
+    /// Reload the ratio and change time from a previous run so the rotation keeps its cadence.
+    /// A ratio outside the current bounds is clamped; a change that came due while offline
+    /// happens on the next lookup.
+    pub fn restore_state(&mut self, state: &DynamicRatioState) {
+        self.history = state.history.iter().cloned().collect();
+        if self.enabled {
+            self.current_buy_ratio = state.current_buy_ratio.max(self.min_buy_ratio).min(self.max_buy_ratio);
+            self.last_change_time = instant_from_unix(state.last_change_at);
+        }
+        self.logger.log(format!(
+            "♻️ Restored buy ratio {:.1}% (next change in {} min, {} changes in history)",
+            self.current_buy_ratio * 100.0,
+            self.time_until_next_change().as_secs() / 60,
+            self.history.len()
+        ).purple().to_string());
     }
 }
 
 /// Trend bias for adjusting ratios based on market conditions
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TrendBias {
     BullishStrong,
     BullishMild,
@@ -153,6 +282,19 @@ pub enum TrendBias {
     BearishStrong,
 }
 
+impl TrendBias {
+    /// Shift applied to the buy ratio
+    pub fn factor(&self) -> f64 {
+        match self {
+            TrendBias::BullishStrong => 0.1,   // +10% towards buying
+            TrendBias::BullishMild => 0.05,    // +5% towards buying
+            TrendBias::Neutral => 0.0,         // No bias
+            TrendBias::BearishMild => -0.05,   // +5% towards selling
+            TrendBias::BearishStrong => -0.1,  // +10% towards selling
+        }
+    }
+}
+
 /// Statistics about current ratio state
 #[derive(Debug, Clone)]
 pub struct RatioStats {
@@ -225,4 +367,20 @@ impl WeeklyRatioManager {
     pub fn get_stats(&self) -> RatioStats {
         self.dynamic_manager.get_ratio_stats()
     }
+    
+    /// Ratio, history and week start for the state store
+    pub fn state(&self) -> DynamicRatioState {
+        DynamicRatioState {
+            week_start: self.last_sunday.map(|d| d.format("%Y-%m-%d").to_string()),
+            ..self.dynamic_manager.state()
+        }
+    }
+    
+    /// Reload a previous run's state; the ratio only changes again when a new week starts
+    pub fn restore_state(&mut self, state: &DynamicRatioState) {
+        self.dynamic_manager.restore_state(state);
+        self.last_sunday = state.week_start
+            .as_deref()
+            .and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+    }
 } 
\ No newline at end of file
@@ -3,7 +3,10 @@ use tokio::sync::Mutex;
 use tokio::time::{Duration, Instant};
 use std::collections::VecDeque;
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
 use crate::common::logger::Logger;
+use crate::common::persistence::unix_now;
+use crate::common::price_monitor::{instant_from_unix, unix_from_instant, PersistedPricePoint};
 
 /// Guardian mode manager that protects against rapid price drops
 pub struct GuardianMode {
@@ -17,31 +20,56 @@ pub struct GuardianMode {
     intervention_strength: InterventionStrength,
     cooldown_period: Duration,
     last_intervention: Option<Instant>,
+    // Frequency / bias / size multipliers per intervention strength
+    tiers: InterventionTiers,
+    // Interventions started since the state was first created, survives restarts
+    intervention_count: u32,
+    // Start times of interventions in the last 24h, for max_interventions_per_day (0 = unlimited)
+    recent_interventions: VecDeque<Instant>,
+    max_interventions_per_day: u32,
+    // Activations refused by the daily limit, and when that was last logged
+    refused_activations: u32,
+    last_limit_warning: Option<Instant>,
+    // Most recent candle close below the lower Bollinger band
+    last_band_breach: Option<Instant>,
+    // Percentage drops only trigger with a recent band breach (BOLLINGER_CONFIRM_DROPS)
+    require_band_confirmation: bool,
+    // Latest external order-flow imbalance (-1 = all sells, 1 = all buys)
+    order_flow_imbalance: f64,
 }
 
 impl GuardianMode {
     /// Create a new guardian mode instance
-    pub fn new(enabled: bool, drop_threshold: f64) -> Self {
+    pub fn new(config: GuardianConfig) -> Self {
         let logger = Logger::new("[GUARDIAN-MODE] => ".red().bold().to_string());
         
-        if enabled {
-            logger.log(format!("🛡️ Guardian mode initialized (Drop threshold: {:.1}%)", 
-                drop_threshold * 100.0).green().to_string());
+        if config.enabled {
+            logger.log(format!("🛡️ Guardian mode initialized (Drop threshold: {:.1}%, {} min active, {}h cooldown)", 
+                config.drop_threshold * 100.0, config.guardian_duration_minutes, config.cooldown_hours).green().to_string());
         } else {
             logger.log("🛡️ Guardian mode disabled".yellow().to_string());
         }
         
         Self {
-            enabled,
-            drop_threshold,
+            enabled: config.enabled,
+            drop_threshold: config.drop_threshold,
             price_history: VecDeque::with_capacity(50),
             guardian_active: false,
             activation_time: None,
-            guardian_duration: Duration::from_secs(30 * 60), // Active for 30 minutes
+            guardian_duration: Duration::from_secs(config.guardian_duration_minutes * 60),
             logger,
             intervention_strength: InterventionStrength::Medium,
-            cooldown_period: Duration::from_secs(2 * 60 * 60), // 2 hour cooldown between interventions
+            cooldown_period: Duration::from_secs(config.cooldown_hours * 60 * 60),
             last_intervention: None,
+            tiers: config.tiers,
+            intervention_count: 0,
+            recent_interventions: VecDeque::new(),
+            max_interventions_per_day: config.max_interventions_per_day,
+            refused_activations: 0,
+            last_limit_warning: None,
+            last_band_breach: None,
+            require_band_confirmation: false,
+            order_flow_imbalance: 0.0,
         }
     }
     
@@ -92,11 +120,101 @@ impl GuardianMode {
         // Analyze price drop over different time windows
         let drop_detected = self.detect_rapid_drop();
         
-        if drop_detected {
+        if drop_detected && self.require_band_confirmation && !self.has_recent_band_breach() {
+            self.logger.log("📉 Drop ignored: no close below the lower Bollinger band in the last 15 minutes".yellow().to_string());
+            return;
+        }
+        
+        if drop_detected && !self.daily_limit_reached() {
             self.activate_guardian();
         }
     }
     
+    /// Interventions started in the last 24 hours
+    fn interventions_last_24h(&self) -> u32 {
+        let window = Duration::from_secs(24 * 60 * 60);
+        self.recent_interventions.iter().filter(|at| at.elapsed() < window).count() as u32
+    }
+    
+    /// True when max_interventions_per_day is used up; refusals are logged at most every 15 minutes
+    fn daily_limit_reached(&mut self) -> bool {
+        let window = Duration::from_secs(24 * 60 * 60);
+        while self.recent_interventions.front().map(|at| at.elapsed() >= window).unwrap_or(false) {
+            self.recent_interventions.pop_front();
+        }
+        if self.max_interventions_per_day == 0 || (self.recent_interventions.len() as u32) < self.max_interventions_per_day {
+            return false;
+        }
+        
+        let warn = self.last_limit_warning
+            .map(|at| at.elapsed() >= Duration::from_secs(15 * 60))
+            .unwrap_or(true);
+        if warn {
+            self.last_limit_warning = Some(Instant::now());
+            self.refused_activations += 1;
+            let next = self.recent_interventions
+                .front()
+                .map(|at| window.saturating_sub(at.elapsed()))
+                .unwrap_or_default();
+            self.logger.log(format!(
+                "🚫 Guardian activation refused: {}/{} interventions in the last 24h (next slot in {} min)",
+                self.recent_interventions.len(), self.max_interventions_per_day, next.as_secs() / 60
+            ).red().to_string());
+        }
+        true
+    }
+    
+    fn has_recent_band_breach(&self) -> bool {
+        self.last_band_breach
+            .map(|at| Instant::now().duration_since(at) <= Duration::from_secs(15 * 60))
+            .unwrap_or(false)
+    }
+    
+    fn in_cooldown(&self) -> bool {
+        self.last_intervention
+            .map(|last| Instant::now().duration_since(last) < self.cooldown_period)
+            .unwrap_or(false)
+    }
+    
+    /// Latest external order-flow imbalance, reported with activations and in the status
+    pub fn set_order_flow(&mut self, imbalance: f64) {
+        self.order_flow_imbalance = imbalance.max(-1.0).min(1.0);
+    }
+    
+    /// Percentage drops only activate guardian mode when confirmed by a recent lower band breach
+    pub fn set_band_confirmation(&mut self, required: bool) {
+        self.require_band_confirmation = required;
+    }
+    
+    /// A closed candle finished below the lower Bollinger band. Unless band breaches only
+    /// confirm drops, this activates guardian mode, stronger the further below the band it closed.
+    pub fn on_lower_band_breach(&mut self, close: f64, lower: f64, middle: f64) {
+        if !self.enabled {
+            return;
+        }
+        self.last_band_breach = Some(Instant::now());
+        if self.require_band_confirmation || self.guardian_active || self.in_cooldown() || self.daily_limit_reached() {
+            return;
+        }
+        
+        // Distance below the lower band in units of the middle-to-lower band width
+        let band_width = (middle - lower).max(f64::EPSILON);
+        let depth = (lower - close) / band_width;
+        self.logger.log(format!(
+            "📉 Close {:.10} below lower Bollinger band {:.10} ({:.2} band widths)",
+            close, lower, depth
+        ).red().bold().to_string());
+        
+        self.intervention_strength = if depth > 0.5 {
+            InterventionStrength::Strong
+        } else if depth > 0.2 {
+            InterventionStrength::Medium
+        } else {
+            InterventionStrength::Light
+        };
+        self.start_intervention("lower band breach");
+    }
+    
     /// Detect rapid price drops using multiple time windows
     fn detect_rapid_drop(&self) -> bool {
         let now = Instant::now();
@@ -150,11 +268,26 @@ impl GuardianMode {
         }
     }
     
+    fn start_intervention(&mut self, trigger: &str) {
+        self.guardian_active = true;
+        self.activation_time = Some(Instant::now());
+        self.last_intervention = Some(Instant::now());
+        self.intervention_count += 1;
+        self.recent_interventions.push_back(Instant::now());
+        
+        self.logger.log(format!(
+            "🚨 GUARDIAN MODE ACTIVATED ({})! Strength: {:?} | Order flow: {:+.0}% | {} today",
+            trigger, self.intervention_strength, self.order_flow_imbalance * 100.0, self.daily_usage()
+        ).red().bold().to_string());
+    }
+    
     /// Activate guardian mode
     fn activate_guardian(&mut self) {
         self.guardian_active = true;
         self.activation_time = Some(Instant::now());
         self.last_intervention = Some(Instant::now());
+        self.intervention_count += 1;
+        self.recent_interventions.push_back(Instant::now());
         
         // Determine intervention strength based on drop severity
         let recent_drop = self.calculate_price_drop(Duration::from_secs(5 * 60));
@@ -167,12 +300,23 @@ impl GuardianMode {
         };
         
         self.logger.log(format!(
-            "🚨 GUARDIAN MODE ACTIVATED! Strength: {:?} | Drop: {:.1}%",
+            "🚨 GUARDIAN MODE ACTIVATED! Strength: {:?} | Drop: {:.1}% | Order flow: {:+.0}% | {} today",
             self.intervention_strength,
-            recent_drop * 100.0
+            recent_drop * 100.0,
+            self.order_flow_imbalance * 100.0,
+            self.daily_usage()
         ).red().bold().to_string());
     }
     
+    /// `3/6` interventions in the last 24h, or just the count when unlimited
+    fn daily_usage(&self) -> String {
+        if self.max_interventions_per_day == 0 {
+            self.interventions_last_24h().to_string()
+        } else {
+            format!("{}/{}", self.interventions_last_24h(), self.max_interventions_per_day)
+        }
+    }
+    
     /// Update guardian mode status (deactivate if duration exceeded)
     fn update_guardian_status(&mut self) {
         if !self.guardian_active {
@@ -208,48 +352,44 @@ impl GuardianMode {
         }
     }
     
-    /// Get frequency multiplier (faster trading when guardian is active)
-    pub fn get_frequency_multiplier(&self) -> f64 {
+    /// Multipliers of the active tier, or None while guardian mode is inactive
+    fn active_tier(&self) -> Option<InterventionTier> {
         if !self.guardian_active {
-            return 1.0;
-        }
-        
-        match self.intervention_strength {
-            InterventionStrength::None => 1.0,
-            InterventionStrength::Light => 0.7,   // 30% faster
-            InterventionStrength::Medium => 0.5,  // 50% faster
-            InterventionStrength::Strong => 0.3,  // 70% faster
+            return None;
         }
+        self.tiers.get(self.intervention_strength)
+    }
+    
+    /// Get frequency multiplier (faster trading when guardian is active)
+    pub fn get_frequency_multiplier(&self) -> f64 {
+        self.active_tier().map(|t| t.frequency_multiplier).unwrap_or(1.0)
     }
     
     /// Get buy bias (increased probability of buying when guardian is active)
     pub fn get_buy_bias(&self) -> f64 {
-        if !self.guardian_active {
-            return 0.0;
-        }
-        
-        match self.intervention_strength {
-            InterventionStrength::None => 0.0,
-            InterventionStrength::Light => 0.1,   // +10% buy probability
-            InterventionStrength::Medium => 0.2,  // +20% buy probability  
-            InterventionStrength::Strong => 0.3,  // +30% buy probability
-        }
+        self.active_tier().map(|t| t.buy_bias).unwrap_or(0.0)
     }
     
     /// Get amount multiplier (larger trades when guardian is active)
     pub fn get_amount_multiplier(&self) -> f64 {
-        if !self.guardian_active {
-            return 1.0;
-        }
-        
-        match self.intervention_strength {
-            InterventionStrength::None => 1.0,
-            InterventionStrength::Light => 1.2,   // 20% larger trades
-            InterventionStrength::Medium => 1.5,  // 50% larger trades
-            InterventionStrength::Strong => 2.0,  // 100% larger trades
+        self.active_tier().map(|t| t.amount_multiplier).unwrap_or(1.0)
+    }
+
+    /// Size factor for a regular trade during an intervention, or None to hold it back.
+    /// Buys grow by the size multiplier and, since trades are queued rather than scheduled,
+    /// by the inverse of the interval multiplier (0.5 = twice the volume). A sell is held
+    /// back when `roll` (uniform in 0..1) falls under the added buy probability.
+    pub fn trade_factor(&self, is_buy: bool, roll: f64) -> Option<f64> {
+        let Some(tier) = self.active_tier() else {
+            return Some(1.0);
+        };
+        if is_buy {
+            Some(tier.amount_multiplier / tier.frequency_multiplier.max(0.01))
+        } else {
+            (roll >= tier.buy_bias).then_some(1.0)
         }
     }
-    
+
     /// Get guardian status information
     pub fn get_status(&self) -> GuardianStatus {
         let time_remaining = if let Some(activation_time) = self.activation_time {
@@ -271,6 +411,72 @@ impl GuardianMode {
             time_remaining,
             cooldown_remaining,
             recent_price_drop: self.calculate_price_drop(Duration::from_secs(5 * 60)),
+            order_flow_imbalance: self.order_flow_imbalance,
+            interventions_last_24h: self.interventions_last_24h(),
+            max_interventions_per_day: self.max_interventions_per_day,
+            refused_activations: self.refused_activations,
+        }
+    }
+    
+    /// Recent price window for the state store
+    pub fn price_points(&self) -> Vec<PersistedPricePoint> {
+        self.price_history
+            .iter()
+            .map(|p| PersistedPricePoint { price: p.price, volume_sol: p.volume, at: unix_from_instant(p.timestamp) })
+            .collect()
+    }
+    
+    /// Reload the price window from a previous run, dropping points older than 30 minutes
+    pub fn restore_price_points(&mut self, points: &[PersistedPricePoint]) {
+        let cutoff = unix_now() - 30 * 60;
+        self.price_history = points
+            .iter()
+            .filter(|p| p.at >= cutoff)
+            .map(|p| PriceSnapshot { price: p.price, volume: p.volume_sol, timestamp: instant_from_unix(p.at) })
+            .collect();
+    }
+    
+    /// Activation, cooldown and intervention count for the state store
+    pub fn state(&self) -> GuardianState {
+        GuardianState {
+            active: self.guardian_active,
+            activated_at: self.activation_time.map(unix_from_instant),
+            last_intervention: self.last_intervention.map(unix_from_instant),
+            intervention_strength: self.intervention_strength,
+            intervention_count: self.intervention_count,
+            recent_interventions: self.recent_interventions.iter().map(|at| unix_from_instant(*at)).collect(),
+            saved_at: unix_now(),
+        }
+    }
+    
+    /// Reload state from a previous run so a restart neither resets the cooldown nor cuts an
+    /// active intervention short
+    pub fn restore_state(&mut self, state: &GuardianState) {
+        self.last_intervention = state.last_intervention.map(instant_from_unix);
+        self.intervention_count = state.intervention_count;
+        self.intervention_strength = state.intervention_strength;
+        let day_ago = unix_now() - 24 * 60 * 60;
+        self.recent_interventions = state.recent_interventions
+            .iter()
+            .filter(|at| **at > day_ago)
+            .map(|at| instant_from_unix(*at))
+            .collect();
+        
+        let remaining = state.activated_at
+            .filter(|_| state.active)
+            .map(|at| self.guardian_duration.saturating_sub(Duration::from_secs((unix_now() - at).max(0) as u64)))
+            .unwrap_or_default();
+        if remaining > Duration::ZERO {
+            self.guardian_active = true;
+            self.activation_time = state.activated_at.map(instant_from_unix);
+            self.logger.log(format!(
+                "♻️ Resuming guardian intervention ({:?}, {} min left)", self.intervention_strength, remaining.as_secs() / 60
+            ).yellow().to_string());
+        }
+        
+        if self.in_cooldown() {
+            let left = self.cooldown_period.saturating_sub(self.last_intervention.map(|at| at.elapsed()).unwrap_or_default());
+            self.logger.log(format!("♻️ Guardian cooldown restored ({} min left)", left.as_secs() / 60).yellow().to_string());
         }
     }
     
@@ -285,6 +491,14 @@ impl GuardianMode {
         self.deactivate_guardian();
     }
     
+    /// Enable or disable guardian mode, keeping the current drop threshold
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if !enabled && self.guardian_active {
+            self.deactivate_guardian();
+        }
+        self.update_settings(enabled, self.drop_threshold);
+    }
+    
     /// Update settings
     pub fn update_settings(&mut self, enabled: bool, drop_threshold: f64) {
         self.enabled = enabled;
@@ -306,7 +520,7 @@ struct PriceSnapshot {
 }
 
 /// Intervention strength levels
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum InterventionStrength {
     None,
     Light,
@@ -323,14 +537,100 @@ pub struct GuardianStatus {
     pub time_remaining: Duration,
     pub cooldown_remaining: Duration,
     pub recent_price_drop: f64,
+    pub order_flow_imbalance: f64,
+    pub interventions_last_24h: u32,
+    /// 0 = unlimited
+    pub max_interventions_per_day: u32,
+    pub refused_activations: u32,
+}
+
+/// State store key for guardian mode
+pub const GUARDIAN_STATE_KEY: &str = "guardian";
+
+/// Guardian activation and cooldown persisted across restarts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardianState {
+    pub active: bool,
+    pub activated_at: Option<i64>,
+    pub last_intervention: Option<i64>,
+    pub intervention_strength: InterventionStrength,
+    pub intervention_count: u32,
+    /// Unix start times of interventions in the last 24h
+    #[serde(default)]
+    pub recent_interventions: Vec<i64>,
+    pub saved_at: i64,
 }
 
 /// Global guardian mode instance
 pub type GlobalGuardianMode = Arc<Mutex<GuardianMode>>;
 
 /// Create a global guardian mode instance
-pub fn create_global_guardian_mode(enabled: bool, drop_threshold: f64) -> GlobalGuardianMode {
-    Arc::new(Mutex::new(GuardianMode::new(enabled, drop_threshold)))
+pub fn create_global_guardian_mode(config: GuardianConfig) -> GlobalGuardianMode {
+    Arc::new(Mutex::new(GuardianMode::new(config)))
+}
+
+/// Trading adjustments applied while an intervention of one strength is active
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InterventionTier {
+    /// Interval multiplier (0.5 = trade twice as often)
+    pub frequency_multiplier: f64,
+    /// Added buy probability
+    pub buy_bias: f64,
+    /// Trade size multiplier
+    pub amount_multiplier: f64,
+}
+
+/// Parse a `frequency,buy_bias,amount` triple such as `0.5,0.2,1.5`
+pub fn parse_intervention_tier(value: &str) -> Result<InterventionTier, String> {
+    let parts: Vec<f64> = value
+        .split(',')
+        .map(|p| p.trim().parse::<f64>().map_err(|_| format!("'{}' is not a number", p.trim())))
+        .collect::<Result<_, _>>()?;
+    let &[frequency_multiplier, buy_bias, amount_multiplier] = parts.as_slice() else {
+        return Err(format!("expected frequency,buy_bias,amount (got '{}')", value));
+    };
+    if frequency_multiplier <= 0.0 || frequency_multiplier > 1.0 {
+        return Err(format!("frequency multiplier must be in (0, 1] (got {})", frequency_multiplier));
+    }
+    if !(0.0..=0.5).contains(&buy_bias) {
+        return Err(format!("buy bias must be between 0 and 0.5 (got {})", buy_bias));
+    }
+    if amount_multiplier < 1.0 || amount_multiplier > 5.0 {
+        return Err(format!("amount multiplier must be between 1 and 5 (got {})", amount_multiplier));
+    }
+    Ok(InterventionTier { frequency_multiplier, buy_bias, amount_multiplier })
+}
+
+/// Multipliers for the light, medium and strong intervention tiers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InterventionTiers {
+    pub light: InterventionTier,
+    pub medium: InterventionTier,
+    pub strong: InterventionTier,
+}
+
+impl InterventionTiers {
+    pub fn get(&self, strength: InterventionStrength) -> Option<InterventionTier> {
+        match strength {
+            InterventionStrength::None => None,
+            InterventionStrength::Light => Some(self.light),
+            InterventionStrength::Medium => Some(self.medium),
+            InterventionStrength::Strong => Some(self.strong),
+        }
+    }
+}
+
+impl Default for InterventionTiers {
+    fn default() -> Self {
+        Self {
+            // 30% faster, +10% buy probability, 20% larger trades
+            light: InterventionTier { frequency_multiplier: 0.7, buy_bias: 0.1, amount_multiplier: 1.2 },
+            // 50% faster, +20% buy probability, 50% larger trades
+            medium: InterventionTier { frequency_multiplier: 0.5, buy_bias: 0.2, amount_multiplier: 1.5 },
+            // 70% faster, +30% buy probability, 100% larger trades
+            strong: InterventionTier { frequency_multiplier: 0.3, buy_bias: 0.3, amount_multiplier: 2.0 },
+        }
+    }
 }
 
 /// Guardian mode configuration
@@ -341,6 +641,7 @@ pub struct GuardianConfig {
     pub guardian_duration_minutes: u64,
     pub cooldown_hours: u64,
     pub max_interventions_per_day: u32,
+    pub tiers: InterventionTiers,
 }
 
 impl Default for GuardianConfig {
@@ -351,6 +652,127 @@ impl Default for GuardianConfig {
             guardian_duration_minutes: 30,
             cooldown_hours: 2,
             max_interventions_per_day: 6,
+            tiers: InterventionTiers::default(),
+        }
+    }
+}
+
+impl GuardianConfig {
+    /// Read GUARDIAN_MODE_ENABLED, GUARDIAN_DROP_THRESHOLD, GUARDIAN_DURATION_MINUTES,
+    /// GUARDIAN_COOLDOWN_HOURS, GUARDIAN_MAX_INTERVENTIONS_PER_DAY and GUARDIAN_TIER_LIGHT /
+    /// _MEDIUM / _STRONG, falling back to the defaults
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let get = |name: &str| std::env::var(name).ok();
+        let tier = |name: &str, default: InterventionTier| {
+            get(name).and_then(|v| parse_intervention_tier(&v).ok()).unwrap_or(default)
+        };
+        Self {
+            enabled: get("GUARDIAN_MODE_ENABLED").map(|v| v.trim().to_lowercase() != "false").unwrap_or(defaults.enabled),
+            drop_threshold: get("GUARDIAN_DROP_THRESHOLD").and_then(|v| v.parse().ok()).unwrap_or(defaults.drop_threshold),
+            guardian_duration_minutes: get("GUARDIAN_DURATION_MINUTES").and_then(|v| v.parse().ok()).unwrap_or(defaults.guardian_duration_minutes),
+            cooldown_hours: get("GUARDIAN_COOLDOWN_HOURS").and_then(|v| v.parse().ok()).unwrap_or(defaults.cooldown_hours),
+            max_interventions_per_day: get("GUARDIAN_MAX_INTERVENTIONS_PER_DAY").and_then(|v| v.parse().ok()).unwrap_or(defaults.max_interventions_per_day),
+            tiers: InterventionTiers {
+                light: tier("GUARDIAN_TIER_LIGHT", defaults.tiers.light),
+                medium: tier("GUARDIAN_TIER_MEDIUM", defaults.tiers.medium),
+                strong: tier("GUARDIAN_TIER_STRONG", defaults.tiers.strong),
+            },
+        }
+    }
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(guardian: &mut GuardianMode, prices: &[f64]) {
+        for price in prices {
+            guardian.add_price_point(*price, 1.0);
         }
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn a_sharp_drop_sizes_up_buys_and_holds_back_sells() {
+        let mut guardian = GuardianMode::new(GuardianConfig::default());
+        assert_eq!(guardian.trade_factor(true, 0.0), Some(1.0));
+        assert_eq!(guardian.trade_factor(false, 0.0), Some(1.0));
+
+        // 20% inside the 5-minute window, over 1.5x the 10% threshold
+        feed(&mut guardian, &[1.0, 0.95, 0.9, 0.85, 0.8]);
+        assert!(guardian.is_active());
+        assert_eq!(guardian.get_intervention_strength(), InterventionStrength::Strong);
+
+        // Strong tier: 2.0x size over a 0.3 interval multiplier
+        let buy = 0.1 * guardian.trade_factor(true, 0.5).unwrap();
+        assert!((buy - 0.1 * 2.0 / 0.3).abs() < 1e-9);
+        assert_eq!(guardian.trade_factor(false, 0.29), None);
+        assert_eq!(guardian.trade_factor(false, 0.3), Some(1.0));
+    }
+
+    #[test]
+    fn the_daily_limit_refuses_further_activations_from_the_price_feed() {
+        // No duration and no cooldown, so every new low would start another intervention
+        let config = GuardianConfig { guardian_duration_minutes: 0, cooldown_hours: 0, max_interventions_per_day: 2, ..GuardianConfig::default() };
+        let mut guardian = GuardianMode::new(config);
+        feed(&mut guardian, &[1.0, 0.95, 0.9, 0.85, 0.8, 0.75]);
+        let status = guardian.get_status();
+        assert_eq!(status.interventions_last_24h, 2);
+        assert_eq!(status.refused_activations, 0);
+
+        feed(&mut guardian, &[0.7]);
+        let status = guardian.get_status();
+        assert_eq!(status.interventions_last_24h, 2);
+        assert_eq!(status.refused_activations, 1);
+        assert!(!guardian.is_active());
+        assert_eq!(guardian.trade_factor(true, 0.0), Some(1.0));
+    }
+
+    #[test]
+    fn an_activation_from_the_price_feed_survives_a_restart() {
+        let mut guardian = GuardianMode::new(GuardianConfig::default());
+        feed(&mut guardian, &[1.0, 0.95, 0.9, 0.85, 0.8]);
+        let state = guardian.state();
+        assert!(state.active);
+        assert_eq!(state.intervention_count, 1);
+        assert_eq!(state.recent_interventions.len(), 1);
+
+        let mut restarted = GuardianMode::new(GuardianConfig::default());
+        restarted.restore_state(&state);
+        assert!(restarted.is_active());
+        assert_eq!(restarted.get_intervention_strength(), InterventionStrength::Strong);
+
+        // Still inside the cooldown after the intervention: a new drop doesn't start another
+        restarted.force_deactivate();
+        feed(&mut restarted, &[1.0, 0.9, 0.8, 0.7, 0.6]);
+        assert!(!restarted.is_active());
+        assert_eq!(restarted.state().intervention_count, 1);
+    }
+
+    #[test]
+    fn a_band_breach_sizes_trades_and_confirms_drops() {
+        // 0.6 band widths below the lower band is a strong intervention
+        let mut guardian = GuardianMode::new(GuardianConfig::default());
+        guardian.on_lower_band_breach(0.94, 1.0, 1.1);
+        assert_eq!(guardian.get_intervention_strength(), InterventionStrength::Strong);
+        assert!(guardian.trade_factor(true, 0.0).unwrap() > 1.0);
+        assert_eq!(guardian.trade_factor(false, 0.0), None);
+
+        // With confirmation required a drop alone is ignored, and a breach lets the next one through
+        let mut guardian = GuardianMode::new(GuardianConfig::default());
+        guardian.set_band_confirmation(true);
+        feed(&mut guardian, &[1.0, 0.95, 0.9, 0.85, 0.8]);
+        assert!(!guardian.is_active());
+        guardian.on_lower_band_breach(0.79, 0.8, 0.9);
+        assert!(!guardian.is_active());
+        feed(&mut guardian, &[0.78]);
+        assert!(guardian.is_active());
+    }
+
+    #[test]
+    fn a_small_drop_leaves_trades_alone() {
+        let mut guardian = GuardianMode::new(GuardianConfig::default());
+        feed(&mut guardian, &[1.0, 0.99, 0.98, 0.97, 0.96]);
+        assert!(!guardian.is_active());
+        assert_eq!(guardian.trade_factor(true, 0.0), Some(1.0));
+    }
+}
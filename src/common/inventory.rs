@@ -0,0 +1,141 @@
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use colored::Colorize;
+use crate::common::logger::Logger;
+
+/// Cap on aggregate token inventory across the wallet pool (disabled when no limit is set)
+#[derive(Debug, Clone, Copy)]
+pub struct InventoryConfig {
+    pub refresh_seconds: u64,
+    /// Hard cap on aggregate inventory in tokens (0 = no absolute cap)
+    pub max_tokens: f64,
+    /// Hard cap as a percentage of the pool's token reserve (0 = no pool-relative cap)
+    pub max_pool_percent: f64,
+}
+
+impl InventoryConfig {
+    /// Read INVENTORY_REFRESH_SECONDS, MAX_INVENTORY_TOKENS and MAX_INVENTORY_POOL_PERCENT
+    pub fn from_env() -> Self {
+        let get = |name: &str, default: f64| std::env::var(name).ok().and_then(|v| v.parse::<f64>().ok()).unwrap_or(default);
+        Self {
+            refresh_seconds: std::env::var("INVENTORY_REFRESH_SECONDS").ok().and_then(|v| v.parse().ok()).unwrap_or(60),
+            max_tokens: get("MAX_INVENTORY_TOKENS", 0.0),
+            max_pool_percent: get("MAX_INVENTORY_POOL_PERCENT", 0.0),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_tokens > 0.0 || self.max_pool_percent > 0.0
+    }
+
+    /// Effective cap in tokens: the tighter of the absolute and pool-relative limits
+    pub fn cap_tokens(&self, pool_reserve_tokens: Option<f64>) -> Option<f64> {
+        let absolute = (self.max_tokens > 0.0).then_some(self.max_tokens);
+        let relative = match pool_reserve_tokens {
+            Some(reserve) if self.max_pool_percent > 0.0 => Some(reserve * self.max_pool_percent / 100.0),
+            _ => None,
+        };
+        match (absolute, relative) {
+            (Some(a), Some(r)) => Some(a.min(r)),
+            (a, r) => a.or(r),
+        }
+    }
+}
+
+/// State change reported by the inventory guard
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InventoryEvent {
+    OverCap { tokens: f64, cap: f64 },
+    BackUnderCap { tokens: f64, cap: f64 },
+}
+
+/// Refuses new buys while aggregate inventory is over its cap; sells keep running
+pub struct InventoryGuard {
+    config: InventoryConfig,
+    over_cap: bool,
+    logger: Logger,
+}
+
+impl InventoryGuard {
+    pub fn new(config: InventoryConfig) -> Self {
+        Self {
+            config,
+            over_cap: false,
+            logger: Logger::new("[INVENTORY] => ".blue().bold().to_string()),
+        }
+    }
+
+    /// Feed the latest aggregate inventory and pool token reserve; reports crossing the cap
+    /// in either direction. Without a usable cap the last state is kept.
+    pub fn update(&mut self, tokens: f64, pool_reserve_tokens: Option<f64>) -> Option<InventoryEvent> {
+        let cap = self.config.cap_tokens(pool_reserve_tokens)?;
+        let over_cap = tokens > cap;
+        if over_cap == self.over_cap {
+            return None;
+        }
+
+        self.over_cap = over_cap;
+        if over_cap {
+            self.logger.log(format!(
+                "🚧 Inventory {:.0} over cap {:.0}, buys paused until it is back under", tokens, cap
+            ).red().bold().to_string());
+            Some(InventoryEvent::OverCap { tokens, cap })
+        } else {
+            self.logger.log(format!(
+                "✅ Inventory {:.0} back under cap {:.0}, buys resumed", tokens, cap
+            ).green().to_string());
+            Some(InventoryEvent::BackUnderCap { tokens, cap })
+        }
+    }
+
+    /// False while inventory is over the cap
+    pub fn allows_buys(&self) -> bool {
+        !self.over_cap
+    }
+}
+
+/// Global inventory guard instance
+pub type GlobalInventoryGuard = Arc<Mutex<InventoryGuard>>;
+
+/// Create a global inventory guard
+pub fn create_global_inventory_guard(config: InventoryConfig) -> GlobalInventoryGuard {
+    Arc::new(Mutex::new(InventoryGuard::new(config)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capped(max_tokens: f64, max_pool_percent: f64) -> InventoryGuard {
+        InventoryGuard::new(InventoryConfig { refresh_seconds: 60, max_tokens, max_pool_percent })
+    }
+
+    #[test]
+    fn buys_stop_over_the_cap_and_resume_under_it() {
+        let mut guard = capped(1_000.0, 0.0);
+        assert_eq!(guard.update(900.0, None), None);
+        assert!(guard.allows_buys());
+
+        assert_eq!(guard.update(1_200.0, None), Some(InventoryEvent::OverCap { tokens: 1_200.0, cap: 1_000.0 }));
+        assert!(!guard.allows_buys());
+        assert_eq!(guard.update(1_100.0, None), None, "still over, no repeat event");
+        assert!(!guard.allows_buys());
+
+        assert_eq!(guard.update(1_000.0, None), Some(InventoryEvent::BackUnderCap { tokens: 1_000.0, cap: 1_000.0 }));
+        assert!(guard.allows_buys());
+    }
+
+    #[test]
+    fn the_tighter_cap_applies_and_a_missing_reserve_keeps_the_state() {
+        // 5% of a 10,000 reserve is 500, tighter than the absolute 1,000
+        let mut guard = capped(1_000.0, 5.0);
+        assert!(guard.update(600.0, Some(10_000.0)).is_some());
+        assert!(!guard.allows_buys());
+
+        // Pool-only cap and no reserve reading: nothing to compare against
+        let mut guard = capped(0.0, 5.0);
+        assert!(guard.update(600.0, Some(10_000.0)).is_some());
+        assert_eq!(guard.update(0.0, None), None);
+        assert!(!guard.allows_buys());
+    }
+}
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use colored::Colorize;
+use crate::common::logger::Logger;
+
+/// Quote-side liquidity thresholds (MIN_POOL_LIQUIDITY_SOL, 0 = disabled)
+#[derive(Debug, Clone, Copy)]
+pub struct LiquidityConfig {
+    /// Buys pause when the pool's SOL reserve drops below this
+    pub min_quote_sol: f64,
+    /// Buys resume once the reserve is back above this (defaults to 120% of the minimum)
+    pub resume_quote_sol: f64,
+}
+
+impl LiquidityConfig {
+    /// Read MIN_POOL_LIQUIDITY_SOL and LIQUIDITY_RESUME_SOL
+    pub fn from_env() -> Self {
+        let get = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<f64>().ok());
+        let min_quote_sol = get("MIN_POOL_LIQUIDITY_SOL").unwrap_or(0.0);
+        let resume_quote_sol = get("LIQUIDITY_RESUME_SOL")
+            .filter(|v| *v >= min_quote_sol)
+            .unwrap_or(min_quote_sol * 1.2);
+        Self {
+            min_quote_sol,
+            resume_quote_sol,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.min_quote_sol > 0.0
+    }
+}
+
+/// State change reported by the liquidity guard
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LiquidityEvent {
+    Dropped { reserve_sol: f64 },
+    Recovered { reserve_sol: f64 },
+}
+
+/// Pauses buying while the pool's quote reserve is too thin (LP pulls, big dumps)
+pub struct LiquidityGuard {
+    config: LiquidityConfig,
+    reserve_sol: Option<f64>,
+    low: bool,
+    logger: Logger,
+}
+
+impl LiquidityGuard {
+    pub fn new(config: LiquidityConfig) -> Self {
+        let logger = Logger::new("[LIQUIDITY] => ".blue().bold().to_string());
+        if config.is_enabled() {
+            logger.log(format!(
+                "💧 Buys pause below {} SOL pool liquidity, resume above {} SOL",
+                config.min_quote_sol, config.resume_quote_sol
+            ).yellow().to_string());
+        }
+        Self {
+            config,
+            reserve_sol: None,
+            low: false,
+            logger,
+        }
+    }
+
+    /// Feed the latest quote reserve and report a pause or recovery
+    pub fn update(&mut self, reserve_sol: f64) -> Option<LiquidityEvent> {
+        self.reserve_sol = Some(reserve_sol);
+        if !self.config.is_enabled() {
+            return None;
+        }
+
+        if !self.low && reserve_sol < self.config.min_quote_sol {
+            self.low = true;
+            self.logger.log(format!(
+                "💧 Pool liquidity {:.4} SOL below {} SOL, buys paused",
+                reserve_sol, self.config.min_quote_sol
+            ).red().bold().to_string());
+            return Some(LiquidityEvent::Dropped { reserve_sol });
+        }
+        if self.low && reserve_sol >= self.config.resume_quote_sol {
+            self.low = false;
+            self.logger.log(format!(
+                "💧 Pool liquidity recovered to {:.4} SOL, buys resumed", reserve_sol
+            ).green().bold().to_string());
+            return Some(LiquidityEvent::Recovered { reserve_sol });
+        }
+        None
+    }
+
+    /// False while liquidity is below the threshold
+    pub fn allows_buys(&self) -> bool {
+        !self.low
+    }
+
+    /// Last observed quote reserve in SOL
+    pub fn reserve_sol(&self) -> Option<f64> {
+        self.reserve_sol
+    }
+}
+
+/// Global liquidity guard instance
+pub type GlobalLiquidityGuard = Arc<Mutex<LiquidityGuard>>;
+
+/// Create a global liquidity guard
+pub fn create_global_liquidity_guard(config: LiquidityConfig) -> GlobalLiquidityGuard {
+    Arc::new(Mutex::new(LiquidityGuard::new(config)))
+}
@@ -1,11 +1,106 @@
+use std::fmt;
 use chrono::Local;
 use colored::*;
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::{format, FmtContext, FormatEvent, FormatFields, FormattedFields};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
 
-const LOG_LEVEL: &str = "LOG";
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Filter directives for log output (LOG_FILTER, e.g. `info,market_maker=debug,cache=warn`).
+/// Logger targets are the prefix names in snake case: `[MARKET-MAKER]` is `market_maker`.
+pub fn get_log_filter() -> String {
+    std::env::var("LOG_FILTER")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| "info".to_string())
+}
+
+/// Output format of log lines (LOG_FORMAT)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Colored `[date] message` lines (default)
+    Console,
+    /// One JSON object per line with level, target, message and the trade span fields
+    Json,
+}
+
+impl LogFormat {
+    /// Read LOG_FORMAT: `json`, otherwise console
+    pub fn from_env() -> Self {
+        match std::env::var("LOG_FORMAT").unwrap_or_default().trim().to_lowercase().as_str() {
+            "json" => LogFormat::Json,
+            _ => LogFormat::Console,
+        }
+    }
+}
+
+/// Install the tracing subscriber: LOG_FILTER levels per target, and either the colored
+/// console layer or JSON lines (LOG_FORMAT=json). `Logger` output and `log` records are routed
+/// through it, so every line shares the same filter and carries the span (e.g. the trade and
+/// its `trade_id`) it was logged in.
+pub fn init_logging() {
+    let filter = EnvFilter::try_new(get_log_filter()).unwrap_or_else(|e| {
+        eprintln!("Invalid LOG_FILTER ({}), using info", e);
+        EnvFilter::new("info")
+    });
+    let result = match LogFormat::from_env() {
+        LogFormat::Console => {
+            let console = tracing_subscriber::fmt::layer().event_format(ConsoleFormat);
+            tracing_subscriber::registry().with(filter).with(console).try_init()
+        },
+        LogFormat::Json => {
+            // Messages are built with colored strings; escape codes have no place in JSON
+            colored::control::set_override(false);
+            let json = tracing_subscriber::fmt::layer().json().with_current_span(true).with_span_list(true);
+            tracing_subscriber::registry().with(filter).with(json).try_init()
+        },
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to initialize logging: {}", e);
+    }
+}
+
+/// `[date] message`, the way `Logger` has always printed, with the level for anything other
+/// than info and the active spans (e.g. `trade{side=Buy ...}`) in front of the message
+struct ConsoleFormat;
+
+impl<S, N> FormatEvent<S, N> for ConsoleFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: format::Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        write!(writer, "[{}] ", Local::now().format(DATE_FORMAT).to_string().blue().bold())?;
+        match *event.metadata().level() {
+            Level::ERROR => write!(writer, "[{}] ", "ERROR".red().bold())?,
+            Level::WARN => write!(writer, "[{}] ", "WARN".yellow().bold())?,
+            Level::DEBUG | Level::TRACE => write!(writer, "[DEBUG] ")?,
+            Level::INFO => {},
+        }
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                write!(writer, "{}", span.name().dimmed())?;
+                if let Some(fields) = span.extensions().get::<FormattedFields<N>>() {
+                    if !fields.is_empty() {
+                        write!(writer, "{}", format!("{{{}}}", fields).dimmed())?;
+                    }
+                }
+                write!(writer, " ")?;
+            }
+        }
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
 
 #[derive(Clone)]
 pub struct Logger {
     prefix: String,
+    target: String,
     date_format: String,
 }
 
@@ -13,45 +108,43 @@ impl Logger {
     // Constructor function to create a new Logger instance
     pub fn new(prefix: String) -> Self {
         Logger {
+            target: target_from_prefix(&prefix),
             prefix,
-            date_format: String::from("%Y-%m-%d %H:%M:%S"),
+            date_format: String::from(DATE_FORMAT),
         }
     }
 
     // Method to log a message with a prefix
     pub fn log(&self, message: String) -> String {
-        let log = format!("{} {}", self.prefix_with_date(), message);
-        println!("{}", log);
-        log
+        log::info!(target: &self.target, "{} {}", self.prefix, message);
+        self.with_date(&message)
     }
 
     pub fn debug(&self, message: String) -> String {
-        let log = format!("{} [{}] {}", self.prefix_with_date(), "DEBUG", message);
-        if LogLevel::new().is_debug() {
-            println!("{}", log);
-        }
-        log
+        log::debug!(target: &self.target, "{} {}", self.prefix, message);
+        self.with_date(&message)
     }
-    pub fn error(&self, message: String) -> String {
-        let log = format!("{} [{}] {}", self.prefix_with_date(), "ERROR", message);
-        println!("{}", log);
 
-        log
+    pub fn error(&self, message: String) -> String {
+        log::error!(target: &self.target, "{} {}", self.prefix, message);
+        self.with_date(&message)
     }
 
     // Add success method to fix compilation errors in monitor.rs
     pub fn success(&self, message: String) -> String {
-        let log = format!("{} [{}] {}", self.prefix_with_date(), "SUCCESS".green().bold(), message);
-        println!("{}", log);
-        log
+        log::info!(target: &self.target, "{} [{}] {}", self.prefix, "SUCCESS".green().bold(), message);
+        self.with_date(&message)
     }
 
     // Add a new method for performance-critical paths
     pub fn log_critical(&self, message: String) -> String {
         // Only log if not in a performance-critical section
-        let log = format!("{} {}", self.prefix_with_date(), message);
-        // Skip println for critical paths
-        log
+        // Skip output for critical paths
+        self.with_date(&message)
+    }
+
+    fn with_date(&self, message: &str) -> String {
+        format!("{} {}", self.prefix_with_date(), message)
     }
 
     fn prefix_with_date(&self) -> String {
@@ -64,15 +157,35 @@ impl Logger {
     }
 }
 
-struct LogLevel<'a> {
-    level: &'a str,
+/// `"[MARKET-MAKER] => "` (colored or not) becomes the filter target `market_maker`
+fn target_from_prefix(prefix: &str) -> String {
+    let plain = strip_ansi(prefix);
+    let name = plain
+        .split(|c| c == '[' || c == ']')
+        .find(|part| !part.trim().is_empty())
+        .unwrap_or("app");
+    let target: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    if target.is_empty() { "app".to_string() } else { target }
 }
-impl LogLevel<'_> {
-    fn new() -> Self {
-        let level = LOG_LEVEL;
-        LogLevel { level }
-    }
-    fn is_debug(&self) -> bool {
-        self.level.to_lowercase().eq("debug")
+
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip the escape sequence up to its final letter
+            for next in chars.by_ref() {
+                if next.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            plain.push(c);
+        }
     }
+    plain
 }
@@ -2,8 +2,26 @@ pub mod config;
 pub mod constants;
 pub mod logger;
 pub mod cache;
+pub mod disk_cache;
 pub mod wallet_pool;
 pub mod price_monitor;
 pub mod dynamic_ratios;
 pub mod volume_waves;
 pub mod guardian_mode;
+pub mod pump_guardian;
+pub mod twap;
+pub mod spend_limit;
+pub mod rate_limit;
+pub mod drawdown;
+pub mod liquidity;
+pub mod inventory;
+pub mod pool_state;
+pub mod rug_detection;
+pub mod creator_monitor;
+pub mod order_flow;
+pub mod unique_counter;
+pub mod usd_price;
+pub mod persistence;
+pub mod config_file;
+pub mod secrets;
+pub mod config_validation;
@@ -0,0 +1,90 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// Order-flow imbalance settings
+#[derive(Debug, Clone, Copy)]
+pub struct OrderFlowConfig {
+    /// Rolling window the imbalance is measured over
+    pub window_minutes: u64,
+    /// Below this much external volume in the window the imbalance is reported as 0
+    pub min_volume_sol: f64,
+}
+
+impl OrderFlowConfig {
+    /// Read ORDER_FLOW_WINDOW_MINUTES and ORDER_FLOW_MIN_VOLUME_SOL
+    pub fn from_env() -> Self {
+        let get = |name: &str| std::env::var(name).ok();
+        Self {
+            window_minutes: get("ORDER_FLOW_WINDOW_MINUTES").and_then(|v| v.parse().ok()).unwrap_or(15),
+            min_volume_sol: get("ORDER_FLOW_MIN_VOLUME_SOL").and_then(|v| v.parse().ok()).unwrap_or(1.0),
+        }
+    }
+}
+
+/// Point-in-time view of external order flow
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OrderFlowSnapshot {
+    pub buy_volume_sol: f64,
+    pub sell_volume_sol: f64,
+    pub trades: usize,
+    /// (buy - sell) / (buy + sell), in [-1, 1]; 0 below the minimum volume
+    pub imbalance: f64,
+}
+
+/// Rolling buy / sell volume of external (non-bot) trades
+pub struct OrderFlowTracker {
+    config: OrderFlowConfig,
+    trades: VecDeque<(Instant, bool, f64)>,
+}
+
+impl OrderFlowTracker {
+    pub fn new(config: OrderFlowConfig) -> Self {
+        Self {
+            config,
+            trades: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, is_buy: bool, volume_sol: f64) {
+        if volume_sol <= 0.0 {
+            return;
+        }
+        self.trades.push_back((Instant::now(), is_buy, volume_sol));
+        self.prune();
+    }
+
+    fn prune(&mut self) {
+        let window = Duration::from_secs(self.config.window_minutes.max(1) * 60);
+        while self.trades.front().map(|(at, _, _)| at.elapsed() > window).unwrap_or(false) {
+            self.trades.pop_front();
+        }
+    }
+
+    pub fn snapshot(&mut self) -> OrderFlowSnapshot {
+        self.prune();
+        let (buy_volume_sol, sell_volume_sol) = self.trades.iter().fold((0.0, 0.0), |(buy, sell), (_, is_buy, volume)| {
+            if *is_buy { (buy + volume, sell) } else { (buy, sell + volume) }
+        });
+        let total = buy_volume_sol + sell_volume_sol;
+        OrderFlowSnapshot {
+            buy_volume_sol,
+            sell_volume_sol,
+            trades: self.trades.len(),
+            imbalance: if total >= self.config.min_volume_sol && total > 0.0 {
+                (buy_volume_sol - sell_volume_sol) / total
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+/// Global order-flow tracker instance
+pub type GlobalOrderFlowTracker = Arc<Mutex<OrderFlowTracker>>;
+
+/// Create a global order-flow tracker
+pub fn create_global_order_flow_tracker(config: OrderFlowConfig) -> GlobalOrderFlowTracker {
+    Arc::new(Mutex::new(OrderFlowTracker::new(config)))
+}
@@ -0,0 +1,88 @@
+use std::fs;
+use std::path::PathBuf;
+use anyhow::{anyhow, Result};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Directory used for persisted runtime state (STATE_DIR, defaults to ./state, or ./state/<profile>
+/// when a PROFILE is active so devnet and mainnet runs never share state)
+pub fn state_dir() -> PathBuf {
+    if let Some(dir) = std::env::var("STATE_DIR").ok().filter(|v| !v.trim().is_empty()) {
+        return PathBuf::from(dir);
+    }
+    match std::env::var("PROFILE").ok().filter(|v| !v.trim().is_empty()) {
+        Some(profile) => PathBuf::from("state").join(profile.trim()),
+        None => PathBuf::from("state"),
+    }
+}
+
+/// Current unix timestamp in seconds, used to stamp persisted records
+pub fn unix_now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+/// Small JSON file store for state that must survive restarts
+#[derive(Debug, Clone)]
+pub struct StateStore {
+    dir: PathBuf,
+}
+
+impl StateStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Create a store rooted at the configured state directory
+    pub fn from_env() -> Self {
+        Self::new(state_dir())
+    }
+
+    fn path_for(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// Write a value atomically (temp file + rename) so a crash never leaves a half-written file
+    pub fn save<T: Serialize>(&self, name: &str, value: &T) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| anyhow!("Failed to create state dir {}: {}", self.dir.display(), e))?;
+
+        let path = self.path_for(name);
+        let tmp_path = self.dir.join(format!("{}.json.tmp", name));
+        let data = serde_json::to_vec_pretty(value)
+            .map_err(|e| anyhow!("Failed to serialize state '{}': {}", name, e))?;
+
+        fs::write(&tmp_path, data)
+            .map_err(|e| anyhow!("Failed to write {}: {}", tmp_path.display(), e))?;
+        fs::rename(&tmp_path, &path)
+            .map_err(|e| anyhow!("Failed to move {} into place: {}", path.display(), e))?;
+
+        Ok(())
+    }
+
+    /// Load a value, returning `None` when nothing has been persisted yet
+    pub fn load<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>> {
+        let path = self.path_for(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let data = fs::read(&path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let value = serde_json::from_slice(&data)
+            .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+
+        Ok(Some(value))
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        self.path_for(name).exists()
+    }
+
+    pub fn remove(&self, name: &str) -> Result<()> {
+        let path = self.path_for(name);
+        if path.exists() {
+            fs::remove_file(&path)
+                .map_err(|e| anyhow!("Failed to remove {}: {}", path.display(), e))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use lazy_static::lazy_static;
+use crate::common::disk_cache::DISK_CACHE;
+use crate::common::persistence::unix_now;
+use crate::engine::monitor::PoolInfo;
+
+/// Live reserve settings (POOL_STATE_STREAM, on by default)
+#[derive(Debug, Clone, Copy)]
+pub struct PoolStateConfig {
+    pub enabled: bool,
+    /// Live reserves older than this are ignored and quoting falls back to RPC
+    pub max_age: Duration,
+}
+
+impl PoolStateConfig {
+    /// Read POOL_STATE_STREAM and POOL_STATE_MAX_AGE_SECONDS
+    pub fn from_env() -> Self {
+        let get = |name: &str| std::env::var(name).ok();
+        Self {
+            enabled: get("POOL_STATE_STREAM").map(|v| v.trim().to_lowercase() != "false").unwrap_or(true),
+            max_age: Duration::from_secs(get("POOL_STATE_MAX_AGE_SECONDS").and_then(|v| v.parse().ok()).unwrap_or(30)),
+        }
+    }
+}
+
+/// Pool reserves kept current from gRPC account updates, keyed by pool id (the CPMM pool
+/// or the pump.fun bonding curve). Quoting reads these instead of fetching accounts per trade.
+pub struct PoolStateCache {
+    pools: RwLock<HashMap<Pubkey, (PoolInfo, Instant)>>,
+    max_age: RwLock<Duration>,
+}
+
+impl PoolStateCache {
+    fn new() -> Self {
+        Self {
+            pools: RwLock::new(HashMap::new()),
+            max_age: RwLock::new(PoolStateConfig::from_env().max_age),
+        }
+    }
+
+    pub fn set_max_age(&self, max_age: Duration) {
+        *self.max_age.write().unwrap_or_else(|e| e.into_inner()) = max_age;
+    }
+
+    /// Register a pool so partial updates (one vault at a time) have something to apply to
+    pub fn track(&self, info: PoolInfo) {
+        let mut pools = self.pools.write().unwrap_or_else(|e| e.into_inner());
+        if !pools.contains_key(&info.pool_id) {
+            DISK_CACHE.put_pool(&info);
+            pools.insert(info.pool_id, (info, Instant::now()));
+        }
+    }
+
+    /// Replace the whole pool, e.g. from a bonding-curve account update that carries
+    /// both reserves and the creator
+    pub fn set_pool(&self, info: PoolInfo) {
+        let mut pools = self.pools.write().unwrap_or_else(|e| e.into_inner());
+        if !pools.contains_key(&info.pool_id) {
+            DISK_CACHE.put_pool(&info);
+        }
+        pools.insert(info.pool_id, (info, Instant::now()));
+    }
+
+    /// Re-load a pool saved at `saved_at` (unix seconds) by the disk cache. Its reserves keep
+    /// their age, so quoting ignores them until the stream refreshes the pool.
+    pub fn restore(&self, info: PoolInfo, saved_at: i64) {
+        let age = Duration::from_secs((unix_now() - saved_at).max(0) as u64);
+        let at = Instant::now().checked_sub(age).unwrap_or_else(Instant::now);
+        self.pools.write().unwrap_or_else(|e| e.into_inner()).entry(info.pool_id).or_insert((info, at));
+    }
+
+    pub fn set_base_reserve(&self, pool_id: &Pubkey, base_reserve: u64) {
+        self.update(pool_id, |info| info.base_reserve = base_reserve);
+    }
+
+    pub fn set_quote_reserve(&self, pool_id: &Pubkey, quote_reserve: u64) {
+        self.update(pool_id, |info| info.quote_reserve = quote_reserve);
+    }
+
+    fn update(&self, pool_id: &Pubkey, apply: impl FnOnce(&mut PoolInfo)) {
+        if let Some((info, at)) = self.pools.write().unwrap_or_else(|e| e.into_inner()).get_mut(pool_id) {
+            apply(info);
+            *at = Instant::now();
+        }
+    }
+
+    /// Current reserves when both sides are known and fresher than POOL_STATE_MAX_AGE_SECONDS
+    pub fn get(&self, pool_id: &Pubkey) -> Option<PoolInfo> {
+        let max_age = *self.max_age.read().unwrap_or_else(|e| e.into_inner());
+        self.pools
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(pool_id)
+            .filter(|(info, at)| at.elapsed() <= max_age && info.base_reserve > 0 && info.quote_reserve > 0)
+            .map(|(info, _)| info.clone())
+    }
+}
+
+lazy_static! {
+    pub static ref POOL_STATE: PoolStateCache = PoolStateCache::new();
+}
+
+/// SPL token account amount (offset 64), for vault updates
+pub fn token_account_amount(data: &[u8]) -> Option<u64> {
+    data.get(64..72).and_then(|b| b.try_into().ok()).map(u64::from_le_bytes)
+}
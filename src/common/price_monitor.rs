@@ -3,7 +3,12 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::time::{Duration, Instant};
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
 use crate::common::logger::Logger;
+use crate::common::persistence::unix_now;
+
+/// State store key for the persisted price and candle history
+pub const PRICE_HISTORY_STATE_KEY: &str = "price_history";
 
 /// Price data point for tracking price history
 #[derive(Debug, Clone)]
@@ -22,4 +27,604 @@ pub struct PriceMonitor {
     throttle_duration: Duration,
     last_throttle_time: Option<Instant>,
     is_throttling: bool,
+    candles: CandleAggregator,
+    vwap: SessionVwap,
+}
+
+/// Candle bucket sizes kept by the price monitor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CandleInterval {
+    OneMinute,
+    FiveMinutes,
+    OneHour,
+}
+
+impl CandleInterval {
+    pub const ALL: [CandleInterval; 3] = [CandleInterval::OneMinute, CandleInterval::FiveMinutes, CandleInterval::OneHour];
+
+    pub fn seconds(&self) -> i64 {
+        match self {
+            CandleInterval::OneMinute => 60,
+            CandleInterval::FiveMinutes => 5 * 60,
+            CandleInterval::OneHour => 60 * 60,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CandleInterval::OneMinute => "1m",
+            CandleInterval::FiveMinutes => "5m",
+            CandleInterval::OneHour => "1h",
+        }
+    }
+
+    /// Parse `1m`, `5m` or `1h`
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|i| i.label() == value.trim().to_lowercase())
+    }
+}
+
+/// One OHLCV candle; `open_time` is the bucket start in unix seconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candle {
+    pub open_time: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume_sol: f64,
+    pub buy_volume_sol: f64,
+    pub sell_volume_sol: f64,
+    pub trades: u32,
+}
+
+impl Candle {
+    fn flat(open_time: i64, price: f64) -> Self {
+        Self {
+            open_time,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume_sol: 0.0,
+            buy_volume_sol: 0.0,
+            sell_volume_sol: 0.0,
+            trades: 0,
+        }
+    }
+}
+
+/// Number of candles kept per interval (CANDLE_HISTORY_SIZE)
+pub fn get_candle_history_size() -> usize {
+    std::env::var("CANDLE_HISTORY_SIZE")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(500)
+        .max(1)
+}
+
+/// Ring buffer of candles for one interval. Buckets without trades are filled with flat
+/// candles at the previous close so indicators see an evenly spaced series.
+#[derive(Debug, Clone)]
+pub struct CandleSeries {
+    interval: CandleInterval,
+    capacity: usize,
+    candles: VecDeque<Candle>,
+}
+
+impl CandleSeries {
+    pub fn new(interval: CandleInterval, capacity: usize) -> Self {
+        Self {
+            interval,
+            capacity,
+            candles: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Add a trade at unix time `at`; trades older than the current candle are ignored
+    pub fn record(&mut self, at: i64, price: f64, volume_sol: f64, is_buy: bool) {
+        if price <= 0.0 {
+            return;
+        }
+        let secs = self.interval.seconds();
+        let bucket = at - at.rem_euclid(secs);
+
+        match self.candles.back() {
+            Some(last) if bucket < last.open_time => return,
+            Some(last) if bucket > last.open_time => {
+                let close = last.close;
+                // Cap the gap fill at the buffer size so a long outage does not loop needlessly
+                let missing = ((bucket - last.open_time) / secs - 1).min(self.capacity as i64);
+                for i in (1..=missing).rev() {
+                    self.push(Candle::flat(bucket - i * secs, close));
+                }
+                self.push(Candle::flat(bucket, close));
+            },
+            Some(_) => {},
+            None => self.push(Candle::flat(bucket, price)),
+        }
+
+        // A new candle opens at the previous close
+        let candle = self.candles.back_mut().expect("candle pushed above");
+        candle.high = candle.high.max(price);
+        candle.low = candle.low.min(price);
+        candle.close = price;
+        candle.volume_sol += volume_sol;
+        if is_buy {
+            candle.buy_volume_sol += volume_sol;
+        } else {
+            candle.sell_volume_sol += volume_sol;
+        }
+        candle.trades += 1;
+    }
+
+    fn push(&mut self, candle: Candle) {
+        if self.candles.len() >= self.capacity {
+            self.candles.pop_front();
+        }
+        self.candles.push_back(candle);
+    }
+
+    /// Last `limit` candles, oldest first (the newest one may still be forming)
+    pub fn recent(&self, limit: usize) -> Vec<Candle> {
+        let skip = self.candles.len().saturating_sub(limit);
+        self.candles.iter().skip(skip).cloned().collect()
+    }
+
+    /// Closes of the last `limit` candles, oldest first
+    pub fn closes(&self, limit: usize) -> Vec<f64> {
+        let skip = self.candles.len().saturating_sub(limit);
+        self.candles.iter().skip(skip).map(|c| c.close).collect()
+    }
+
+    pub fn latest(&self) -> Option<&Candle> {
+        self.candles.back()
+    }
+
+    /// Replace the series with persisted candles, keeping the newest `capacity`
+    pub fn restore(&mut self, candles: &[Candle]) {
+        let skip = candles.len().saturating_sub(self.capacity);
+        self.candles = candles.iter().skip(skip).cloned().collect();
+    }
+
+    pub fn len(&self) -> usize {
+        self.candles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candles.is_empty()
+    }
+}
+
+/// 1m / 5m / 1h candle series fed from parsed trades
+#[derive(Debug, Clone)]
+pub struct CandleAggregator {
+    series: Vec<CandleSeries>,
+}
+
+impl CandleAggregator {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            series: CandleInterval::ALL.into_iter().map(|i| CandleSeries::new(i, capacity)).collect(),
+        }
+    }
+
+    pub fn record(&mut self, at: i64, price: f64, volume_sol: f64, is_buy: bool) {
+        for series in &mut self.series {
+            series.record(at, price, volume_sol, is_buy);
+        }
+    }
+
+    fn series_mut(&mut self, interval: CandleInterval) -> &mut CandleSeries {
+        self.series
+            .iter_mut()
+            .find(|s| s.interval == interval)
+            .expect("a series exists for every interval")
+    }
+
+    pub fn series(&self, interval: CandleInterval) -> &CandleSeries {
+        self.series
+            .iter()
+            .find(|s| s.interval == interval)
+            .expect("a series exists for every interval")
+    }
+}
+
+impl PriceMonitor {
+    /// Aggregate a parsed trade into the candle series
+    pub fn record_trade_candle(&mut self, at: i64, price: f64, volume_sol: f64, is_buy: bool) {
+        self.candles.record(at, price, volume_sol, is_buy);
+    }
+
+    /// Last `limit` candles for `interval`, oldest first
+    pub fn get_candles(&self, interval: CandleInterval, limit: usize) -> Vec<Candle> {
+        self.candles.series(interval).recent(limit)
+    }
+
+    /// Candle series for `interval`, for indicators that read closes directly
+    pub fn candle_series(&self, interval: CandleInterval) -> &CandleSeries {
+        self.candles.series(interval)
+    }
+}
+
+/// Simple moving average of the last `period` values
+pub fn sma(values: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || values.len() < period {
+        return None;
+    }
+    Some(values[values.len() - period..].iter().sum::<f64>() / period as f64)
+}
+
+/// Exponential moving average over `values`, seeded with the SMA of the first `period`
+pub fn ema(values: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || values.len() < period {
+        return None;
+    }
+    let k = 2.0 / (period as f64 + 1.0);
+    let seed = values[..period].iter().sum::<f64>() / period as f64;
+    Some(values[period..].iter().fold(seed, |ema, value| value * k + ema * (1.0 - k)))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovingAverageKind {
+    Sma,
+    Ema,
+}
+
+/// Fast / slow moving-average crossover settings
+#[derive(Debug, Clone, Copy)]
+pub struct MovingAverageConfig {
+    pub kind: MovingAverageKind,
+    pub fast_period: usize,
+    pub slow_period: usize,
+    pub interval: CandleInterval,
+}
+
+impl MovingAverageConfig {
+    /// Read MA_TYPE (ema / sma), MA_FAST_PERIOD, MA_SLOW_PERIOD and MA_INTERVAL (1m / 5m / 1h)
+    pub fn from_env() -> Self {
+        let get = |name: &str| std::env::var(name).ok();
+        Self {
+            kind: match get("MA_TYPE").unwrap_or_default().trim().to_lowercase().as_str() {
+                "sma" => MovingAverageKind::Sma,
+                _ => MovingAverageKind::Ema,
+            },
+            fast_period: get("MA_FAST_PERIOD").and_then(|v| v.parse().ok()).unwrap_or(9),
+            slow_period: get("MA_SLOW_PERIOD").and_then(|v| v.parse().ok()).unwrap_or(21),
+            interval: get("MA_INTERVAL").and_then(|v| CandleInterval::parse(&v)).unwrap_or(CandleInterval::FiveMinutes),
+        }
+    }
+
+    fn average(&self, values: &[f64], period: usize) -> Option<f64> {
+        match self.kind {
+            MovingAverageKind::Sma => sma(values, period),
+            MovingAverageKind::Ema => ema(values, period),
+        }
+    }
+}
+
+/// Fast crossing above (bullish) or below (bearish) the slow average
+#[derive(Debug, Clone, Copy)]
+pub struct CrossoverSignal {
+    pub bullish: bool,
+    pub fast: f64,
+    pub slow: f64,
+    pub interval: CandleInterval,
+    /// Candle open time the crossover was confirmed on
+    pub open_time: i64,
+}
+
+impl CrossoverSignal {
+    /// Gap between the averages relative to the slow one
+    pub fn spread(&self) -> f64 {
+        if self.slow > 0.0 { (self.fast - self.slow) / self.slow } else { 0.0 }
+    }
+}
+
+/// Tracks which side of the slow average the fast one is on, using closed candles only
+pub struct CrossoverDetector {
+    config: MovingAverageConfig,
+    fast_above: Option<bool>,
+    last_checked: Option<i64>,
+}
+
+impl CrossoverDetector {
+    pub fn new(config: MovingAverageConfig) -> Self {
+        Self {
+            config,
+            fast_above: None,
+            last_checked: None,
+        }
+    }
+
+    /// Fast and slow averages over closed candles of `series`
+    pub fn averages(&self, series: &CandleSeries) -> Option<(f64, f64)> {
+        let mut closes = series.closes(self.config.slow_period.max(self.config.fast_period) * 4 + 1);
+        // The newest candle is still forming
+        closes.pop();
+        Some((
+            self.config.average(&closes, self.config.fast_period)?,
+            self.config.average(&closes, self.config.slow_period)?,
+        ))
+    }
+
+    /// Check once per closed candle; returns a signal when the fast average changes side
+    pub fn update(&mut self, series: &CandleSeries) -> Option<CrossoverSignal> {
+        let open_time = series.latest()?.open_time;
+        if self.last_checked == Some(open_time) {
+            return None;
+        }
+        let (fast, slow) = self.averages(series)?;
+        self.last_checked = Some(open_time);
+
+        let above = fast > slow;
+        let previous = self.fast_above.replace(above);
+        match previous {
+            Some(was_above) if was_above != above => Some(CrossoverSignal {
+                bullish: above,
+                fast,
+                slow,
+                interval: self.config.interval,
+                open_time,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// VWAP deviation alert settings
+#[derive(Debug, Clone, Copy)]
+pub struct VwapConfig {
+    /// Alert when price is this many percent above or below session VWAP (0 = off)
+    pub alert_pct: f64,
+    /// Minimum time between repeated alerts in the same direction
+    pub alert_cooldown_minutes: u64,
+}
+
+impl VwapConfig {
+    /// Read VWAP_ALERT_PCT and VWAP_ALERT_COOLDOWN_MINUTES
+    pub fn from_env() -> Self {
+        let get = |name: &str| std::env::var(name).ok();
+        Self {
+            alert_pct: get("VWAP_ALERT_PCT").and_then(|v| v.parse().ok()).unwrap_or(0.0),
+            alert_cooldown_minutes: get("VWAP_ALERT_COOLDOWN_MINUTES").and_then(|v| v.parse().ok()).unwrap_or(15),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.alert_pct > 0.0
+    }
+}
+
+/// Volume-weighted average price since the bot started
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SessionVwap {
+    price_volume: f64,
+    volume_sol: f64,
+    trades: u64,
+}
+
+impl SessionVwap {
+    pub fn record(&mut self, price: f64, volume_sol: f64) {
+        if price <= 0.0 || volume_sol <= 0.0 {
+            return;
+        }
+        self.price_volume += price * volume_sol;
+        self.volume_sol += volume_sol;
+        self.trades += 1;
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        (self.volume_sol > 0.0).then(|| self.price_volume / self.volume_sol)
+    }
+
+    pub fn volume_sol(&self) -> f64 {
+        self.volume_sol
+    }
+
+    pub fn trades(&self) -> u64 {
+        self.trades
+    }
+}
+
+/// Percent deviation of `price` from `vwap` (positive = above)
+pub fn vwap_deviation_pct(price: f64, vwap: f64) -> f64 {
+    if vwap <= 0.0 {
+        return 0.0;
+    }
+    (price - vwap) / vwap * 100.0
+}
+
+impl PriceMonitor {
+    /// Add a parsed trade to the session VWAP
+    pub fn record_vwap(&mut self, price: f64, volume_sol: f64) {
+        self.vwap.record(price, volume_sol);
+    }
+
+    pub fn session_vwap(&self) -> SessionVwap {
+        self.vwap
+    }
+}
+
+/// Price point stored with its unix timestamp
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedPricePoint {
+    pub price: f64,
+    pub volume_sol: f64,
+    pub at: i64,
+}
+
+/// Persisted price monitor history and candles
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PriceHistorySnapshot {
+    pub points: Vec<PersistedPricePoint>,
+    /// (interval label, candles oldest first)
+    pub candles: Vec<(String, Vec<Candle>)>,
+    /// Guardian mode's own 30-minute price window
+    #[serde(default)]
+    pub guardian_points: Vec<PersistedPricePoint>,
+    pub saved_at: i64,
+}
+
+/// Instant for a past unix timestamp, or now if the clock cannot go back that far
+pub fn instant_from_unix(at: i64) -> Instant {
+    let age = Duration::from_secs((unix_now() - at).max(0) as u64);
+    Instant::now().checked_sub(age).unwrap_or_else(Instant::now)
+}
+
+/// Unix timestamp of an Instant in the past
+pub fn unix_from_instant(at: Instant) -> i64 {
+    unix_now() - at.elapsed().as_secs() as i64
+}
+
+impl PriceMonitor {
+    /// Price history and candles for the state store
+    pub fn history_snapshot(&self) -> PriceHistorySnapshot {
+        PriceHistorySnapshot {
+            points: self.price_history
+                .iter()
+                .map(|p| PersistedPricePoint { price: p.price, volume_sol: p.volume_sol, at: unix_from_instant(p.timestamp) })
+                .collect(),
+            candles: CandleInterval::ALL
+                .into_iter()
+                .map(|i| (i.label().to_string(), self.candles.series(i).recent(usize::MAX)))
+                .collect(),
+            guardian_points: Vec::new(),
+            saved_at: unix_now(),
+        }
+    }
+
+    /// Reload history saved by a previous run; the gap while offline is filled on the next trade
+    pub fn restore_history(&mut self, snapshot: &PriceHistorySnapshot) {
+        let skip = snapshot.points.len().saturating_sub(self.max_history_size.max(2));
+        self.price_history = snapshot.points
+            .iter()
+            .skip(skip)
+            .map(|p| PricePoint { price: p.price, timestamp: instant_from_unix(p.at), volume_sol: p.volume_sol })
+            .collect();
+        for (label, candles) in &snapshot.candles {
+            if let Some(interval) = CandleInterval::parse(label) {
+                self.candles.series_mut(interval).restore(candles);
+            }
+        }
+        self.logger.log(format!(
+            "♻️ Restored {} price points and {} 1m candles ({}s old)",
+            self.price_history.len(),
+            self.candles.series(CandleInterval::OneMinute).len(),
+            (unix_now() - snapshot.saved_at).max(0)
+        ).green().to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close_to(actual: f64, expected: f64) -> bool {
+        (actual - expected).abs() < 1e-9
+    }
+
+    fn one_minute_series(closes: &[f64]) -> CandleSeries {
+        let mut series = CandleSeries::new(CandleInterval::OneMinute, 100);
+        for (i, price) in closes.iter().enumerate() {
+            series.record(i as i64 * 60, *price, 1.0, true);
+        }
+        series
+    }
+
+    #[test]
+    fn moving_averages_match_known_values() {
+        assert!(close_to(sma(&[1.0, 2.0, 3.0, 4.0], 2).unwrap(), 3.5));
+        // Seeded with SMA(1, 2, 3) = 2, then k = 0.5: 3, then 4
+        assert!(close_to(ema(&[1.0, 2.0, 3.0, 4.0, 5.0], 3).unwrap(), 4.0));
+        assert!(close_to(ema(&[1.0, 2.0, 3.0, 10.0], 3).unwrap(), 6.0));
+        assert!(sma(&[1.0], 2).is_none());
+        assert!(ema(&[1.0, 2.0], 0).is_none());
+    }
+
+    #[test]
+    fn bollinger_bands_use_the_population_deviation() {
+        // Mean 5, population standard deviation 2
+        let bands = bollinger_bands(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], 8, 2.0).unwrap();
+        assert!(close_to(bands.middle, 5.0));
+        assert!(close_to(bands.upper, 9.0));
+        assert!(close_to(bands.lower, 1.0));
+        assert!(bollinger_bands(&[1.0, 2.0], 3, 2.0).is_none());
+    }
+
+    #[test]
+    fn gaps_are_filled_with_flat_candles_at_the_previous_close() {
+        let mut series = CandleSeries::new(CandleInterval::OneMinute, 100);
+        series.record(10, 1.0, 0.5, true);
+        series.record(250, 2.0, 0.25, false);
+        // Older than the current candle
+        series.record(30, 5.0, 1.0, true);
+
+        let candles = series.recent(10);
+        assert_eq!(candles.iter().map(|c| c.open_time).collect::<Vec<_>>(), vec![0, 60, 120, 180, 240]);
+        for flat in &candles[1..4] {
+            assert_eq!(flat.trades, 0);
+            assert!(close_to(flat.open, 1.0) && close_to(flat.close, 1.0));
+        }
+        let last = &candles[4];
+        assert!(close_to(last.open, 1.0));
+        assert!(close_to(last.low, 1.0));
+        assert!(close_to(last.high, 2.0));
+        assert!(close_to(last.close, 2.0));
+        assert!(close_to(last.sell_volume_sol, 0.25));
+        assert_eq!(last.trades, 1);
+        assert!(close_to(candles[0].buy_volume_sol, 0.5));
+    }
+
+    #[test]
+    fn gap_fill_is_capped_at_the_capacity() {
+        let mut series = CandleSeries::new(CandleInterval::OneMinute, 3);
+        series.record(0, 1.0, 1.0, true);
+        series.record(60 * 1_000, 2.0, 1.0, true);
+        assert_eq!(series.len(), 3);
+        assert_eq!(series.latest().unwrap().open_time, 60 * 1_000);
+    }
+
+    #[test]
+    fn crossovers_fire_only_on_closed_candles() {
+        let mut detector = CrossoverDetector::new(MovingAverageConfig {
+            kind: MovingAverageKind::Sma,
+            fast_period: 2,
+            slow_period: 3,
+            interval: CandleInterval::OneMinute,
+        });
+        // Falling closes then a spike in the forming candle
+        let mut series = one_minute_series(&[10.0, 9.0, 8.0, 20.0]);
+        // Closed candles [10, 9, 8]: fast 8.5 below slow 9, first reading sets the side
+        assert!(detector.update(&series).is_none());
+
+        series.record(200, 21.0, 1.0, true);
+        assert!(detector.update(&series).is_none(), "same candle is checked once");
+
+        series.record(240, 21.0, 1.0, true);
+        // Closed [10, 9, 8, 21]: fast 14.5 above slow 12.67
+        let signal = detector.update(&series).unwrap();
+        assert!(signal.bullish);
+        assert_eq!(signal.open_time, 240);
+        assert!(close_to(signal.fast, 14.5));
+        assert!(close_to(signal.slow, 38.0 / 3.0));
+        assert!(signal.spread() > 0.0);
+    }
+
+    #[test]
+    fn session_vwap_weights_by_volume() {
+        let mut vwap = SessionVwap::default();
+        assert!(vwap.value().is_none());
+        vwap.record(1.0, 2.0);
+        vwap.record(2.0, 1.0);
+        vwap.record(3.0, 0.0);
+        vwap.record(0.0, 5.0);
+        assert!(close_to(vwap.value().unwrap(), 4.0 / 3.0));
+        assert_eq!(vwap.trades(), 2);
+        assert!(close_to(vwap.volume_sol(), 3.0));
+
+        assert!(close_to(vwap_deviation_pct(1.1, 1.0), 10.0));
+        assert!(close_to(vwap_deviation_pct(0.9, 1.0), -10.0));
+        assert_eq!(vwap_deviation_pct(1.0, 0.0), 0.0);
+    }
 }
@@ -0,0 +1,229 @@
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+use std::collections::VecDeque;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use crate::common::guardian_mode::InterventionStrength;
+use crate::common::logger::Logger;
+use crate::common::persistence::unix_now;
+use crate::common::price_monitor::{instant_from_unix, unix_from_instant};
+
+/// State store key for the pump guardian
+pub const PUMP_GUARDIAN_STATE_KEY: &str = "pump_guardian";
+
+/// Pump guardian activation and cooldown persisted across restarts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PumpGuardianState {
+    pub active_since: Option<i64>,
+    pub last_activation: Option<i64>,
+    pub strength: InterventionStrength,
+    pub saved_at: i64,
+}
+
+/// Sell-side guardian settings (PUMP_GUARDIAN_ENABLED, off by default)
+#[derive(Debug, Clone, Copy)]
+pub struct PumpGuardianConfig {
+    pub enabled: bool,
+    /// Rise over 5 minutes that starts a distribution window (0.15 = 15%)
+    pub rise_threshold: f64,
+    pub duration_minutes: u64,
+    pub cooldown_hours: u64,
+    /// Buy ratio reduction at strong intensity
+    pub sell_bias: f64,
+    /// Sell size multiplier at strong intensity
+    pub sell_size_multiplier: f64,
+}
+
+impl PumpGuardianConfig {
+    /// Read PUMP_GUARDIAN_ENABLED, PUMP_GUARDIAN_RISE_THRESHOLD, PUMP_GUARDIAN_DURATION_MINUTES,
+    /// PUMP_GUARDIAN_COOLDOWN_HOURS, PUMP_GUARDIAN_SELL_BIAS and PUMP_GUARDIAN_SIZE_MULTIPLIER
+    pub fn from_env() -> Self {
+        let get = |name: &str| std::env::var(name).ok();
+        Self {
+            enabled: get("PUMP_GUARDIAN_ENABLED").map(|v| v.trim().to_lowercase() == "true").unwrap_or(false),
+            rise_threshold: get("PUMP_GUARDIAN_RISE_THRESHOLD").and_then(|v| v.parse().ok()).unwrap_or(0.15),
+            duration_minutes: get("PUMP_GUARDIAN_DURATION_MINUTES").and_then(|v| v.parse().ok()).unwrap_or(20),
+            cooldown_hours: get("PUMP_GUARDIAN_COOLDOWN_HOURS").and_then(|v| v.parse().ok()).unwrap_or(2),
+            sell_bias: get("PUMP_GUARDIAN_SELL_BIAS").and_then(|v| v.parse().ok()).unwrap_or(0.2),
+            sell_size_multiplier: get("PUMP_GUARDIAN_SIZE_MULTIPLIER").and_then(|v| v.parse().ok()).unwrap_or(1.5),
+        }
+    }
+}
+
+/// Mirror of guardian mode for abnormal pumps: distributes inventory into strength by leaning
+/// the buy ratio towards selling and enlarging sells for a limited window
+pub struct PumpGuardian {
+    config: PumpGuardianConfig,
+    price_history: VecDeque<(f64, Instant)>,
+    active_since: Option<Instant>,
+    last_activation: Option<Instant>,
+    strength: InterventionStrength,
+    logger: Logger,
+}
+
+impl PumpGuardian {
+    pub fn new(config: PumpGuardianConfig) -> Self {
+        let logger = Logger::new("[PUMP-GUARDIAN] => ".magenta().bold().to_string());
+        if config.enabled {
+            logger.log(format!(
+                "📈 Pump guardian initialized (Rise threshold: {:.1}%, {} min active, {}h cooldown)",
+                config.rise_threshold * 100.0, config.duration_minutes, config.cooldown_hours
+            ).green().to_string());
+        }
+        Self {
+            config,
+            price_history: VecDeque::with_capacity(50),
+            active_since: None,
+            last_activation: None,
+            strength: InterventionStrength::None,
+            logger,
+        }
+    }
+
+    /// Add a trade price and check for a pump
+    pub fn add_price_point(&mut self, price: f64) {
+        if !self.config.enabled || price <= 0.0 {
+            return;
+        }
+        let now = Instant::now();
+        self.price_history.push_back((price, now));
+        while self.price_history.front().map(|(_, at)| now.duration_since(*at) > Duration::from_secs(15 * 60)).unwrap_or(false) {
+            self.price_history.pop_front();
+        }
+
+        self.update_status();
+        if self.active_since.is_none() && !self.in_cooldown() && self.price_history.len() >= 5 {
+            self.check_activation_trigger();
+        }
+    }
+
+    fn in_cooldown(&self) -> bool {
+        self.last_activation
+            .map(|at| at.elapsed() < Duration::from_secs(self.config.cooldown_hours * 60 * 60))
+            .unwrap_or(false)
+    }
+
+    /// Rise over `duration`, positive when the price went up
+    fn calculate_price_rise(&self, duration: Duration) -> f64 {
+        let cutoff = Instant::now() - duration;
+        let earliest = self.price_history.iter().find(|(_, at)| *at >= cutoff).map(|(p, _)| *p);
+        let latest = self.price_history.back().map(|(p, _)| *p);
+        match (earliest, latest) {
+            (Some(early), Some(late)) if early > 0.0 => (late - early) / early,
+            _ => 0.0,
+        }
+    }
+
+    fn check_activation_trigger(&mut self) {
+        let threshold = self.config.rise_threshold;
+        let five_min = self.calculate_price_rise(Duration::from_secs(5 * 60));
+        let ten_min = self.calculate_price_rise(Duration::from_secs(10 * 60));
+        let fifteen_min = self.calculate_price_rise(Duration::from_secs(15 * 60));
+        if five_min <= threshold && ten_min <= threshold * 0.8 && fifteen_min <= threshold * 0.7 {
+            return;
+        }
+
+        self.strength = if five_min > threshold * 1.5 {
+            InterventionStrength::Strong
+        } else if five_min > threshold * 1.2 {
+            InterventionStrength::Medium
+        } else {
+            InterventionStrength::Light
+        };
+        self.active_since = Some(Instant::now());
+        self.last_activation = Some(Instant::now());
+        self.logger.log(format!(
+            "🚀 PUMP GUARDIAN ACTIVATED! Strength: {:?} | Rise 5min: {:.1}%, 10min: {:.1}%, 15min: {:.1}%",
+            self.strength, five_min * 100.0, ten_min * 100.0, fifteen_min * 100.0
+        ).magenta().bold().to_string());
+    }
+
+    fn update_status(&mut self) {
+        let expired = self.active_since
+            .map(|at| at.elapsed() >= Duration::from_secs(self.config.duration_minutes * 60))
+            .unwrap_or(false);
+        if expired {
+            self.active_since = None;
+            self.strength = InterventionStrength::None;
+            self.logger.log("✅ Pump guardian deactivated".green().to_string());
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active_since.is_some()
+    }
+
+    /// Share of the configured effect for the current strength
+    fn intensity(&self) -> f64 {
+        if !self.is_active() {
+            return 0.0;
+        }
+        match self.strength {
+            InterventionStrength::None => 0.0,
+            InterventionStrength::Light => 0.5,
+            InterventionStrength::Medium => 0.75,
+            InterventionStrength::Strong => 1.0,
+        }
+    }
+
+    /// Amount subtracted from the buy ratio while distributing
+    pub fn get_sell_bias(&self) -> f64 {
+        self.config.sell_bias * self.intensity()
+    }
+
+    /// Sell size multiplier while distributing (1.0 when inactive)
+    pub fn get_sell_size_multiplier(&self) -> f64 {
+        1.0 + (self.config.sell_size_multiplier - 1.0).max(0.0) * self.intensity()
+    }
+
+    /// Activation window and cooldown for the state store
+    pub fn state(&self) -> PumpGuardianState {
+        PumpGuardianState {
+            active_since: self.active_since.map(unix_from_instant),
+            last_activation: self.last_activation.map(unix_from_instant),
+            strength: self.strength,
+            saved_at: unix_now(),
+        }
+    }
+
+    /// Reload state from a previous run, dropping a window that expired while offline
+    pub fn restore_state(&mut self, state: &PumpGuardianState) {
+        self.active_since = state.active_since.map(instant_from_unix);
+        self.last_activation = state.last_activation.map(instant_from_unix);
+        self.strength = state.strength;
+        self.update_status();
+    }
+
+    pub fn get_status(&self) -> PumpGuardianStatus {
+        let duration = Duration::from_secs(self.config.duration_minutes * 60);
+        let cooldown = Duration::from_secs(self.config.cooldown_hours * 60 * 60);
+        PumpGuardianStatus {
+            enabled: self.config.enabled,
+            active: self.is_active(),
+            strength: if self.is_active() { self.strength } else { InterventionStrength::None },
+            time_remaining: self.active_since.map(|at| duration.saturating_sub(at.elapsed())).unwrap_or_default(),
+            cooldown_remaining: self.last_activation.map(|at| cooldown.saturating_sub(at.elapsed())).unwrap_or_default(),
+            recent_price_rise: self.calculate_price_rise(Duration::from_secs(5 * 60)),
+        }
+    }
+}
+
+/// Pump guardian status information
+#[derive(Debug, Clone)]
+pub struct PumpGuardianStatus {
+    pub enabled: bool,
+    pub active: bool,
+    pub strength: InterventionStrength,
+    pub time_remaining: Duration,
+    pub cooldown_remaining: Duration,
+    pub recent_price_rise: f64,
+}
+
+/// Global pump guardian instance
+pub type GlobalPumpGuardian = Arc<Mutex<PumpGuardian>>;
+
+/// Create a global pump guardian instance
+pub fn create_global_pump_guardian(config: PumpGuardianConfig) -> GlobalPumpGuardian {
+    Arc::new(Mutex::new(PumpGuardian::new(config)))
+}
@@ -0,0 +1,196 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+use colored::Colorize;
+use crate::common::logger::Logger;
+
+const HOUR: Duration = Duration::from_secs(60 * 60);
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Hard trade rate limits (0 = unlimited)
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitConfig {
+    pub max_trades_per_hour: usize,
+    pub max_trades_per_wallet_per_day: usize,
+}
+
+impl RateLimitConfig {
+    /// Read MAX_TRADES_PER_HOUR and MAX_TRADES_PER_WALLET_PER_DAY
+    pub fn from_env() -> Self {
+        let get = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<usize>().ok()).unwrap_or(0);
+        Self {
+            max_trades_per_hour: get("MAX_TRADES_PER_HOUR"),
+            max_trades_per_wallet_per_day: get("MAX_TRADES_PER_WALLET_PER_DAY"),
+        }
+    }
+}
+
+/// Which limit rejected a trade, and when a slot frees up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateLimited {
+    Hourly { retry_after: Duration },
+    WalletDaily { retry_after: Duration },
+}
+
+/// Sliding-window limiter applied on top of the randomized intervals
+pub struct TradeRateLimiter {
+    config: RateLimitConfig,
+    recent: VecDeque<Instant>,
+    per_wallet: HashMap<String, VecDeque<Instant>>,
+    logger: Logger,
+}
+
+fn prune(window: &mut VecDeque<Instant>, length: Duration, now: Instant) {
+    while window.front().map(|t| now.saturating_duration_since(*t) >= length).unwrap_or(false) {
+        window.pop_front();
+    }
+}
+
+/// Time until the oldest entry leaves the window
+fn retry_after(window: &VecDeque<Instant>, length: Duration, now: Instant) -> Duration {
+    window.front().map(|t| length.saturating_sub(now.saturating_duration_since(*t))).unwrap_or_default()
+}
+
+impl TradeRateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        let logger = Logger::new("[RATE-LIMIT] => ".yellow().bold().to_string());
+        if config.max_trades_per_hour > 0 || config.max_trades_per_wallet_per_day > 0 {
+            logger.log(format!(
+                "⏱️ Trade limits: {} per hour, {} per wallet per day (0 = unlimited)",
+                config.max_trades_per_hour, config.max_trades_per_wallet_per_day
+            ).yellow().to_string());
+        }
+        Self {
+            config,
+            recent: VecDeque::new(),
+            per_wallet: HashMap::new(),
+            logger,
+        }
+    }
+
+    pub fn config(&self) -> RateLimitConfig {
+        self.config
+    }
+
+    /// Change the limits while running; trades already in the windows count against the new ones
+    pub fn set_config(&mut self, config: RateLimitConfig) {
+        self.config = config;
+    }
+
+    /// Reserve a trade slot for `wallet`, or report which limit is hit
+    pub fn try_acquire(&mut self, wallet: &str) -> Result<(), RateLimited> {
+        self.try_acquire_at(wallet, Instant::now())
+    }
+
+    fn try_acquire_at(&mut self, wallet: &str, now: Instant) -> Result<(), RateLimited> {
+        prune(&mut self.recent, HOUR, now);
+        // Wallets without a trade in the last day are dropped, so the map only holds active ones
+        self.per_wallet.retain(|_, window| {
+            prune(window, DAY, now);
+            !window.is_empty()
+        });
+
+        if self.config.max_trades_per_hour > 0 && self.recent.len() >= self.config.max_trades_per_hour {
+            let limited = RateLimited::Hourly { retry_after: retry_after(&self.recent, HOUR, now) };
+            self.logger.log(format!("⏱️ Hourly trade limit ({}) reached, skipping trade", self.config.max_trades_per_hour).yellow().to_string());
+            return Err(limited);
+        }
+        let wallet_trades = self.per_wallet.get(wallet).map(|w| w.len()).unwrap_or(0);
+        if self.config.max_trades_per_wallet_per_day > 0 && wallet_trades >= self.config.max_trades_per_wallet_per_day {
+            let limited = RateLimited::WalletDaily { retry_after: self.per_wallet.get(wallet).map(|w| retry_after(w, DAY, now)).unwrap_or_default() };
+            self.logger.log(format!(
+                "⏱️ Daily limit ({}) reached for wallet {}, pick another wallet",
+                self.config.max_trades_per_wallet_per_day, wallet
+            ).yellow().to_string());
+            return Err(limited);
+        }
+
+        self.record_at(wallet, now);
+        Ok(())
+    }
+
+    /// Count a trade that may not be refused (a protective exit) against both windows
+    pub fn record(&mut self, wallet: &str) {
+        self.record_at(wallet, Instant::now());
+    }
+
+    fn record_at(&mut self, wallet: &str, now: Instant) {
+        self.recent.push_back(now);
+        self.per_wallet.entry(wallet.to_string()).or_default().push_back(now);
+    }
+
+    /// Trades in the last hour
+    pub fn trades_last_hour(&mut self) -> usize {
+        prune(&mut self.recent, HOUR, Instant::now());
+        self.recent.len()
+    }
+}
+
+/// Global trade rate limiter instance
+pub type GlobalTradeRateLimiter = Arc<Mutex<TradeRateLimiter>>;
+
+/// Create a global trade rate limiter
+pub fn create_global_trade_rate_limiter(config: RateLimitConfig) -> GlobalTradeRateLimiter {
+    Arc::new(Mutex::new(TradeRateLimiter::new(config)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(max_trades_per_hour: usize, max_trades_per_wallet_per_day: usize) -> TradeRateLimiter {
+        TradeRateLimiter::new(RateLimitConfig { max_trades_per_hour, max_trades_per_wallet_per_day })
+    }
+
+    #[test]
+    fn hourly_limit_frees_a_slot_once_the_oldest_trade_is_an_hour_old() {
+        let mut limiter = limiter(2, 0);
+        let start = Instant::now();
+        assert!(limiter.try_acquire_at("a", start).is_ok());
+        assert!(limiter.try_acquire_at("b", start + Duration::from_secs(60)).is_ok());
+
+        let at = start + Duration::from_secs(120);
+        assert_eq!(
+            limiter.try_acquire_at("c", at),
+            Err(RateLimited::Hourly { retry_after: HOUR - Duration::from_secs(120) })
+        );
+        assert!(limiter.try_acquire_at("c", start + HOUR).is_ok());
+    }
+
+    #[test]
+    fn wallet_limit_only_blocks_that_wallet() {
+        let mut limiter = limiter(0, 1);
+        let start = Instant::now();
+        assert!(limiter.try_acquire_at("a", start).is_ok());
+        assert_eq!(
+            limiter.try_acquire_at("a", start + HOUR),
+            Err(RateLimited::WalletDaily { retry_after: DAY - HOUR })
+        );
+        assert!(limiter.try_acquire_at("b", start + HOUR).is_ok());
+        assert!(limiter.try_acquire_at("a", start + DAY).is_ok());
+    }
+
+    #[test]
+    fn rejected_trades_use_no_slot_and_idle_wallets_are_pruned() {
+        let mut limiter = limiter(1, 0);
+        let start = Instant::now();
+        assert!(limiter.try_acquire_at("a", start).is_ok());
+        assert!(limiter.try_acquire_at("b", start).is_err());
+        assert!(!limiter.per_wallet.contains_key("b"));
+
+        assert!(limiter.try_acquire_at("b", start + DAY).is_ok());
+        assert!(!limiter.per_wallet.contains_key("a"));
+        assert_eq!(limiter.per_wallet.len(), 1);
+    }
+
+    #[test]
+    fn recorded_trades_count_against_the_limits() {
+        let mut limiter = limiter(1, 0);
+        let start = Instant::now();
+        limiter.record_at("a", start);
+        limiter.record_at("a", start);
+        assert_eq!(limiter.recent.len(), 2);
+        assert!(limiter.try_acquire_at("b", start).is_err());
+    }
+}
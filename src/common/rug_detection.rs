@@ -0,0 +1,188 @@
+use std::fmt;
+use std::str::FromStr;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use colored::Colorize;
+use solana_program_pack::Pack;
+use spl_token::state::{Account, AccountState, Mint};
+use crate::common::logger::Logger;
+
+/// Rug detection settings
+#[derive(Debug, Clone, Copy)]
+pub struct RugDetectionConfig {
+    pub enabled: bool,
+    /// LP mint of the pool; supply drops mean LP burns / withdrawals
+    pub lp_mint: Option<Pubkey>,
+    /// LP supply drop from its peak that counts as a rug
+    pub lp_drop_percent: f64,
+    /// Sell everything and collect funds when a rug is detected
+    pub auto_exit: bool,
+}
+
+impl RugDetectionConfig {
+    /// Read RUG_DETECTION_ENABLED, RUG_LP_MINT, RUG_LP_DROP_PERCENT and RUG_AUTO_EXIT
+    pub fn from_env() -> Self {
+        let flag = |name: &str, default: bool| {
+            std::env::var(name).ok().map(|v| v.trim().to_lowercase() == "true").unwrap_or(default)
+        };
+        Self {
+            enabled: flag("RUG_DETECTION_ENABLED", false),
+            lp_mint: std::env::var("RUG_LP_MINT").ok().and_then(|v| Pubkey::from_str(v.trim()).ok()),
+            lp_drop_percent: std::env::var("RUG_LP_DROP_PERCENT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(30.0),
+            auto_exit: flag("RUG_AUTO_EXIT", true),
+        }
+    }
+}
+
+/// A detected rug signature
+#[derive(Debug, Clone, PartialEq)]
+pub enum RugSignal {
+    MintAuthorityEnabled { authority: Pubkey },
+    VaultFrozen { vault: Pubkey },
+    LpSupplyDropped { drop_percent: f64 },
+}
+
+impl fmt::Display for RugSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RugSignal::MintAuthorityEnabled { authority } => write!(f, "mint authority re-enabled ({})", authority),
+            RugSignal::VaultFrozen { vault } => write!(f, "pool token account {} frozen", vault),
+            RugSignal::LpSupplyDropped { drop_percent } => write!(f, "LP supply dropped {:.1}% (burn / withdrawal)", drop_percent),
+        }
+    }
+}
+
+/// Checks raw account updates for the target mint, pool vaults and LP mint
+pub struct RugDetector {
+    config: RugDetectionConfig,
+    // Mint authority as first seen; None means it was renounced
+    initial_mint_authority: Option<Option<Pubkey>>,
+    lp_supply_peak: u64,
+    logger: Logger,
+}
+
+impl RugDetector {
+    pub fn new(config: RugDetectionConfig) -> Self {
+        Self {
+            config,
+            initial_mint_authority: None,
+            lp_supply_peak: 0,
+            logger: Logger::new("[RUG-DETECTOR] => ".red().bold().to_string()),
+        }
+    }
+
+    /// Target mint update: flags a mint authority that appears after being renounced
+    pub fn check_mint(&mut self, data: &[u8]) -> Option<RugSignal> {
+        let mint = Mint::unpack_from_slice(data.get(..Mint::LEN)?).ok()?;
+        let authority: Option<Pubkey> = mint.mint_authority.into();
+
+        match self.initial_mint_authority {
+            None => {
+                self.initial_mint_authority = Some(authority);
+                None
+            },
+            Some(None) => authority.map(|authority| self.signal(RugSignal::MintAuthorityEnabled { authority })),
+            Some(Some(_)) => None,
+        }
+    }
+
+    /// Pool vault update: flags a frozen token account
+    pub fn check_vault(&mut self, vault: &Pubkey, data: &[u8]) -> Option<RugSignal> {
+        let account = Account::unpack_from_slice(data.get(..Account::LEN)?).ok()?;
+        (account.state == AccountState::Frozen).then(|| self.signal(RugSignal::VaultFrozen { vault: *vault }))
+    }
+
+    /// LP mint update: flags a supply drop beyond RUG_LP_DROP_PERCENT from its peak
+    pub fn check_lp_mint(&mut self, data: &[u8]) -> Option<RugSignal> {
+        let mint = Mint::unpack_from_slice(data.get(..Mint::LEN)?).ok()?;
+        if mint.supply >= self.lp_supply_peak {
+            self.lp_supply_peak = mint.supply;
+            return None;
+        }
+
+        let drop_percent = (self.lp_supply_peak - mint.supply) as f64 / self.lp_supply_peak as f64 * 100.0;
+        if drop_percent < self.config.lp_drop_percent {
+            return None;
+        }
+        // Re-baseline so one withdrawal is reported once
+        self.lp_supply_peak = mint.supply;
+        Some(self.signal(RugSignal::LpSupplyDropped { drop_percent }))
+    }
+
+    fn signal(&self, signal: RugSignal) -> RugSignal {
+        self.logger.log(format!("🚨 Rug signature detected: {}", signal).red().bold().to_string());
+        signal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spl_token::solana_program::program_option::COption;
+
+    fn detector(lp_drop_percent: f64) -> RugDetector {
+        RugDetector::new(RugDetectionConfig { enabled: true, lp_mint: None, lp_drop_percent, auto_exit: true })
+    }
+
+    fn mint_data(mint_authority: Option<Pubkey>, supply: u64) -> Vec<u8> {
+        let mint = Mint {
+            mint_authority: mint_authority.map(COption::Some).unwrap_or(COption::None),
+            supply,
+            decimals: 6,
+            is_initialized: true,
+            ..Default::default()
+        };
+        let mut data = vec![0u8; Mint::LEN];
+        mint.pack_into_slice(&mut data);
+        data
+    }
+
+    #[test]
+    fn flags_a_mint_authority_that_returns_after_being_renounced() {
+        let authority = Pubkey::new_unique();
+        let mut renounced = detector(30.0);
+        assert!(renounced.check_mint(&mint_data(None, 1_000)).is_none());
+        assert!(renounced.check_mint(&mint_data(None, 1_000)).is_none());
+        assert_eq!(
+            renounced.check_mint(&mint_data(Some(authority), 1_000)),
+            Some(RugSignal::MintAuthorityEnabled { authority })
+        );
+
+        // An authority that was there from the start isn't a change
+        let mut held = detector(30.0);
+        assert!(held.check_mint(&mint_data(Some(authority), 1_000)).is_none());
+        assert!(held.check_mint(&mint_data(Some(Pubkey::new_unique()), 1_000)).is_none());
+        assert!(held.check_mint(&[0u8; 10]).is_none());
+    }
+
+    #[test]
+    fn flags_a_frozen_vault() {
+        let vault = Pubkey::new_unique();
+        let pack = |state: AccountState| {
+            let account = Account { mint: Pubkey::new_unique(), owner: Pubkey::new_unique(), amount: 5, state, ..Default::default() };
+            let mut data = vec![0u8; Account::LEN];
+            account.pack_into_slice(&mut data);
+            data
+        };
+        let mut detector = detector(30.0);
+        assert!(detector.check_vault(&vault, &pack(AccountState::Initialized)).is_none());
+        assert_eq!(detector.check_vault(&vault, &pack(AccountState::Frozen)), Some(RugSignal::VaultFrozen { vault }));
+    }
+
+    #[test]
+    fn lp_supply_drops_are_measured_from_the_peak_and_reported_once() {
+        let mut detector = detector(30.0);
+        assert!(detector.check_lp_mint(&mint_data(None, 1_000)).is_none());
+        assert!(detector.check_lp_mint(&mint_data(None, 2_000)).is_none());
+        // 20% below the 2000 peak
+        assert!(detector.check_lp_mint(&mint_data(None, 1_600)).is_none());
+        assert_eq!(
+            detector.check_lp_mint(&mint_data(None, 1_000)),
+            Some(RugSignal::LpSupplyDropped { drop_percent: 50.0 })
+        );
+        // Re-baselined at 1000
+        assert!(detector.check_lp_mint(&mint_data(None, 900)).is_none());
+    }
+}
@@ -0,0 +1,213 @@
+use std::env;
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use tokio::process::Command;
+use crate::common::logger::Logger;
+
+/// Where the main wallet key and gRPC token are loaded from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretsBackend {
+    /// Plaintext environment / .env (default)
+    Env,
+    /// AWS Secrets Manager (via the `aws` CLI and its credential chain)
+    Aws,
+    /// AWS KMS-encrypted ciphertext, decrypted with the `aws` CLI
+    AwsKms,
+    /// GCP Secret Manager (via the `gcloud` CLI and its credentials)
+    Gcp,
+    /// HashiCorp Vault KV (via the HTTP API, VAULT_ADDR + VAULT_TOKEN)
+    Vault,
+}
+
+impl SecretsBackend {
+    pub fn from_env() -> Result<Self> {
+        let value = env::var("SECRETS_BACKEND").unwrap_or_default();
+        match value.trim().to_lowercase().as_str() {
+            "" | "env" => Ok(Self::Env),
+            "aws" | "aws-secrets-manager" => Ok(Self::Aws),
+            "aws-kms" => Ok(Self::AwsKms),
+            "gcp" | "gcp-secret-manager" => Ok(Self::Gcp),
+            "vault" | "hashicorp-vault" => Ok(Self::Vault),
+            other => Err(anyhow!("Unknown SECRETS_BACKEND '{}' (expected env, aws-secrets-manager, aws-kms, gcp or vault)", other)),
+        }
+    }
+}
+
+/// Secrets that can be loaded from a backend: (env var to populate, env var holding the secret id)
+const MANAGED_SECRETS: &[(&str, &str)] = &[
+    ("PRIVATE_KEY", "SECRETS_PRIVATE_KEY_ID"),
+    ("YELLOWSTONE_GRPC_TOKEN", "SECRETS_GRPC_TOKEN_ID"),
+];
+
+/// Split `secret-id#field` into the id and an optional JSON field
+fn split_secret_ref(reference: &str) -> (&str, Option<&str>) {
+    match reference.split_once('#') {
+        Some((id, field)) if !field.is_empty() => (id, Some(field)),
+        _ => (reference, None),
+    }
+}
+
+/// Pick a field out of a JSON secret payload, or return the payload as-is
+fn extract_field(payload: &str, field: Option<&str>) -> Result<String> {
+    let payload = payload.trim();
+    match field {
+        None => Ok(payload.to_string()),
+        Some(field) => {
+            let json: serde_json::Value = serde_json::from_str(payload)
+                .map_err(|_| anyhow!("Secret payload is not JSON, cannot extract field '{}'", field))?;
+            json.get(field)
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_string())
+                .ok_or_else(|| anyhow!("Secret payload has no string field '{}'", field))
+        }
+    }
+}
+
+async fn run_cli(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| anyhow!("Failed to run `{}` (is it installed and on PATH?): {}", program, e))?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "`{}` exited with {}: {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+async fn fetch_aws(reference: &str) -> Result<String> {
+    let (id, field) = split_secret_ref(reference);
+    let mut args = vec!["secretsmanager", "get-secret-value", "--secret-id", id, "--query", "SecretString", "--output", "text"];
+    let region = env::var("AWS_REGION").ok();
+    if let Some(region) = region.as_deref() {
+        args.extend(["--region", region]);
+    }
+    let payload = run_cli("aws", &args).await?;
+    extract_field(&payload, field)
+}
+
+/// Decrypt a KMS ciphertext. `reference` is the base64 ciphertext or `fileb://path` to the raw
+/// ciphertext; the key is named in the ciphertext itself.
+async fn fetch_aws_kms(reference: &str) -> Result<String> {
+    let mut args = vec!["kms", "decrypt", "--ciphertext-blob", reference.trim(), "--query", "Plaintext", "--output", "text"];
+    let region = env::var("AWS_REGION").ok();
+    if let Some(region) = region.as_deref() {
+        args.extend(["--region", region]);
+    }
+    let plaintext = run_cli("aws", &args).await?;
+    let decoded = base64::decode(plaintext.trim())
+        .map_err(|e| anyhow!("KMS returned a plaintext that is not base64: {}", e))?;
+    String::from_utf8(decoded)
+        .map(|value| value.trim().to_string())
+        .map_err(|_| anyhow!("Decrypted KMS plaintext is not UTF-8"))
+}
+
+async fn fetch_gcp(reference: &str) -> Result<String> {
+    let (id, field) = split_secret_ref(reference);
+    let secret_arg = format!("--secret={}", id);
+    let mut args = vec!["secrets", "versions", "access", "latest", secret_arg.as_str()];
+    let project_arg = env::var("GCP_PROJECT").ok().map(|p| format!("--project={}", p));
+    if let Some(project_arg) = project_arg.as_deref() {
+        args.push(project_arg);
+    }
+    let payload = run_cli("gcloud", &args).await?;
+    extract_field(&payload, field)
+}
+
+/// Read a key from Vault. `reference` is `path#field`, e.g. `secret/data/mm#private_key` (KV v2).
+async fn fetch_vault(reference: &str) -> Result<String> {
+    let addr = env::var("VAULT_ADDR").map_err(|_| anyhow!("VAULT_ADDR is required for SECRETS_BACKEND=vault"))?;
+    let token = env::var("VAULT_TOKEN").map_err(|_| anyhow!("VAULT_TOKEN is required for SECRETS_BACKEND=vault"))?;
+    let (path, field) = split_secret_ref(reference);
+    let field = field.ok_or_else(|| anyhow!("Vault secret reference '{}' must include a #field", reference))?;
+
+    let url = format!("{}/v1/{}", addr.trim_end_matches('/'), path.trim_start_matches('/'));
+    let response = reqwest::Client::new()
+        .get(&url)
+        .header("X-Vault-Token", token)
+        .send()
+        .await
+        .map_err(|e| anyhow!("Vault request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(anyhow!("Vault returned {} for {}", response.status(), path));
+    }
+
+    let body: serde_json::Value = response.json().await
+        .map_err(|e| anyhow!("Invalid Vault response: {}", e))?;
+
+    // KV v2 nests the payload under data.data, KV v1 under data
+    let data = body.pointer("/data/data").or_else(|| body.get("data"))
+        .ok_or_else(|| anyhow!("Vault response for {} has no data", path))?;
+
+    data.get(field)
+        .and_then(|v| v.as_str())
+        .map(|v| v.to_string())
+        .ok_or_else(|| anyhow!("Vault secret {} has no string field '{}'", path, field))
+}
+
+/// Fetch every configured secret from `backend` as (env var, value) pairs
+async fn fetch_secrets(backend: SecretsBackend) -> Result<Vec<(&'static str, String)>> {
+    let mut secrets = Vec::new();
+    for (target_var, id_var) in MANAGED_SECRETS {
+        let reference = match env::var(id_var) {
+            Ok(reference) if !reference.trim().is_empty() => reference,
+            _ => continue,
+        };
+
+        let value = match backend {
+            SecretsBackend::Aws => fetch_aws(&reference).await,
+            SecretsBackend::AwsKms => fetch_aws_kms(&reference).await,
+            SecretsBackend::Gcp => fetch_gcp(&reference).await,
+            SecretsBackend::Vault => fetch_vault(&reference).await,
+            SecretsBackend::Env => unreachable!(),
+        }
+        .map_err(|e| anyhow!("Failed to load {} from {:?}: {}", target_var, backend, e))?;
+
+        if value.is_empty() {
+            return Err(anyhow!("{} loaded from {:?} is empty", target_var, backend));
+        }
+        secrets.push((*target_var, value));
+    }
+    Ok(secrets)
+}
+
+/// Load the main wallet key and gRPC token from the configured backend into the environment,
+/// so `Config::new` reads them the same way as plaintext values. Secret values are never logged.
+///
+/// Call this before the tokio runtime is built: the secrets are fetched on a short-lived
+/// single-threaded runtime and written to the environment once it has shut down, while no
+/// other thread can be reading the environment.
+pub fn load_secrets() -> Result<SecretsBackend> {
+    let backend = SecretsBackend::from_env()?;
+    if backend == SecretsBackend::Env {
+        return Ok(backend);
+    }
+
+    let logger = Logger::new("[SECRETS] => ".yellow().bold().to_string());
+
+    let secrets = {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| anyhow!("Failed to start the secrets runtime: {}", e))?;
+        runtime.block_on(fetch_secrets(backend))?
+    };
+
+    for (target_var, value) in secrets {
+        if env::var(target_var).is_ok() {
+            logger.log(format!("⚠️ {} is also set in the environment; using the {:?} value", target_var, backend).yellow().to_string());
+        }
+        env::set_var(target_var, value);
+        logger.log(format!("🔐 Loaded {} from {:?}", target_var, backend).green().to_string());
+    }
+
+    Ok(backend)
+}
@@ -0,0 +1,248 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use crate::common::logger::Logger;
+use crate::common::persistence::unix_now;
+
+/// State store key for the rolling spend window
+pub const SPEND_WINDOW_STATE_KEY: &str = "spend_window";
+
+/// Rolling window length for the spend cap
+const WINDOW_SECONDS: i64 = 24 * 60 * 60;
+
+/// Net SOL deployed cap per rolling 24h (DAILY_SOL_SPEND_CAP, 0 = disabled)
+pub fn get_daily_spend_cap() -> f64 {
+    std::env::var("DAILY_SOL_SPEND_CAP")
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .unwrap_or(0.0)
+}
+
+/// One buy (+) or sell (-) inside the window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendEntry {
+    pub at: i64,
+    pub sol: f64,
+}
+
+/// Outcome of a buy check
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpendDecision {
+    Allowed,
+    /// Allowed, and the breaker just reset after having tripped
+    Resumed,
+    Blocked,
+    /// Blocked, and this is the check that tripped the breaker
+    Tripped,
+}
+
+/// Hard stop for new buys once net SOL deployed in the last 24h reaches the cap
+pub struct SpendCircuitBreaker {
+    cap_sol: f64,
+    entries: VecDeque<SpendEntry>,
+    /// SOL reserved by buys in flight, counted against the cap until committed or released
+    reserved: f64,
+    tripped: bool,
+    /// An entry from before the trip has left the window since the breaker tripped
+    rolled_since_trip: bool,
+    logger: Logger,
+}
+
+impl SpendCircuitBreaker {
+    pub fn new(cap_sol: f64) -> Self {
+        let logger = Logger::new("[SPEND-LIMIT] => ".red().bold().to_string());
+        if cap_sol > 0.0 {
+            logger.log(format!("🧯 Daily spend cap: {} SOL net per rolling 24h", cap_sol).yellow().to_string());
+        }
+        Self {
+            cap_sol,
+            entries: VecDeque::new(),
+            reserved: 0.0,
+            tripped: false,
+            rolled_since_trip: false,
+            logger,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.cap_sol > 0.0
+    }
+
+    pub fn cap(&self) -> f64 {
+        self.cap_sol
+    }
+
+    /// Change the cap while running. A tripped breaker checks the next buy against the new cap
+    /// instead of waiting for the window to roll; fills made while the cap was off aren't counted.
+    pub fn set_cap(&mut self, cap_sol: f64) {
+        self.cap_sol = cap_sol;
+        if self.tripped {
+            self.rolled_since_trip = true;
+        }
+    }
+
+    fn prune(&mut self) {
+        let cutoff = unix_now() - WINDOW_SECONDS;
+        while self.entries.front().map(|e| e.at < cutoff).unwrap_or(false) {
+            self.entries.pop_front();
+            self.rolled_since_trip = true;
+        }
+    }
+
+    /// Net SOL deployed in the current window (buys minus sells)
+    pub fn net_deployed(&mut self) -> f64 {
+        self.prune();
+        self.entries.iter().map(|e| e.sol).sum()
+    }
+
+    /// Check whether a buy of `sol` fits under the cap, counting buys still in flight, and
+    /// reserve it until `commit` or `release`. Once tripped, buys stay halted until the window
+    /// rolls: an entry from before the trip has to age out, a sell alone doesn't resume them.
+    pub fn reserve_buy(&mut self, sol: f64) -> SpendDecision {
+        if !self.is_enabled() {
+            return SpendDecision::Allowed;
+        }
+
+        let net = self.net_deployed();
+        if self.tripped && !self.rolled_since_trip {
+            return SpendDecision::Blocked;
+        }
+        if net + sol > self.cap_sol {
+            if self.tripped {
+                // Still over after the roll, wait for the next one
+                self.rolled_since_trip = false;
+                return SpendDecision::Blocked;
+            }
+            self.tripped = true;
+            self.rolled_since_trip = false;
+            self.logger.log(format!(
+                "🧯 Daily spend cap reached: {:.4} SOL deployed, buy of {:.4} SOL would exceed {} SOL. New buys halted.",
+                net, sol, self.cap_sol
+            ).red().bold().to_string());
+            return SpendDecision::Tripped;
+        }
+        // The rest of the cap is held by buys in flight; they may still fail, so no trip
+        if net + self.reserved + sol > self.cap_sol {
+            return SpendDecision::Blocked;
+        }
+
+        self.reserved += sol;
+        if self.tripped {
+            self.tripped = false;
+            self.logger.log(format!(
+                "✅ Spend window rolled ({:.4} / {} SOL), buys resumed", net, self.cap_sol
+            ).green().bold().to_string());
+            return SpendDecision::Resumed;
+        }
+        SpendDecision::Allowed
+    }
+
+    /// Drop a reservation whose buy failed or was skipped
+    pub fn release(&mut self, reserved_sol: f64) {
+        self.reserved = (self.reserved - reserved_sol).max(0.0);
+    }
+
+    /// Replace a reservation with the SOL the confirmed buy actually spent
+    pub fn commit(&mut self, reserved_sol: f64, spent_sol: f64) {
+        self.release(reserved_sol);
+        self.record(true, spent_sol);
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.tripped
+    }
+
+    /// Record a confirmed fill
+    pub fn record(&mut self, is_buy: bool, sol: f64) {
+        if !self.is_enabled() {
+            return;
+        }
+        self.entries.push_back(SpendEntry {
+            at: unix_now(),
+            sol: if is_buy { sol } else { -sol },
+        });
+        self.prune();
+    }
+
+    pub fn entries(&self) -> Vec<SpendEntry> {
+        self.entries.iter().cloned().collect()
+    }
+
+    /// Reload the window so a restart doesn't reset the cap
+    pub fn restore(&mut self, entries: &[SpendEntry]) {
+        self.entries = entries.iter().cloned().collect();
+        self.prune();
+    }
+}
+
+/// Global spend circuit breaker instance
+pub type GlobalSpendCircuitBreaker = Arc<Mutex<SpendCircuitBreaker>>;
+
+/// Create a global spend circuit breaker
+pub fn create_global_spend_circuit_breaker(cap_sol: f64) -> GlobalSpendCircuitBreaker {
+    Arc::new(Mutex::new(SpendCircuitBreaker::new(cap_sol)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_breaker_allows_everything() {
+        let mut breaker = SpendCircuitBreaker::new(0.0);
+        assert_eq!(breaker.reserve_buy(1_000.0), SpendDecision::Allowed);
+        breaker.record(true, 1_000.0);
+        assert_eq!(breaker.net_deployed(), 0.0);
+    }
+
+    #[test]
+    fn trips_once_the_cap_would_be_exceeded() {
+        let mut breaker = SpendCircuitBreaker::new(1.0);
+        assert_eq!(breaker.reserve_buy(0.6), SpendDecision::Allowed);
+        breaker.commit(0.6, 0.6);
+        assert_eq!(breaker.reserve_buy(0.5), SpendDecision::Tripped);
+        assert!(breaker.is_tripped());
+        assert_eq!(breaker.reserve_buy(0.5), SpendDecision::Blocked);
+    }
+
+    #[test]
+    fn buys_in_flight_count_against_the_cap_without_tripping() {
+        let mut breaker = SpendCircuitBreaker::new(1.0);
+        assert_eq!(breaker.reserve_buy(0.7), SpendDecision::Allowed);
+        assert_eq!(breaker.reserve_buy(0.5), SpendDecision::Blocked);
+        assert!(!breaker.is_tripped());
+
+        breaker.release(0.7);
+        assert_eq!(breaker.reserve_buy(0.5), SpendDecision::Allowed);
+    }
+
+    #[test]
+    fn stays_tripped_until_the_window_rolls() {
+        let mut breaker = SpendCircuitBreaker::new(1.0);
+        breaker.record(true, 0.9);
+        assert_eq!(breaker.reserve_buy(0.2), SpendDecision::Tripped);
+
+        // Sells lower the net, but only the window rolling resumes buys
+        breaker.record(false, 0.5);
+        assert_eq!(breaker.reserve_buy(0.2), SpendDecision::Blocked);
+
+        // The buy that tripped it ages out of the window
+        breaker.entries.front_mut().unwrap().at = unix_now() - WINDOW_SECONDS - 1;
+        assert_eq!(breaker.reserve_buy(0.2), SpendDecision::Resumed);
+        assert!(!breaker.is_tripped());
+    }
+
+    #[test]
+    fn raising_the_cap_resumes_a_tripped_breaker() {
+        let mut breaker = SpendCircuitBreaker::new(1.0);
+        breaker.record(true, 0.9);
+        assert_eq!(breaker.reserve_buy(0.2), SpendDecision::Tripped);
+
+        breaker.set_cap(1.05);
+        assert_eq!(breaker.reserve_buy(0.2), SpendDecision::Blocked);
+        breaker.set_cap(2.0);
+        assert_eq!(breaker.reserve_buy(0.2), SpendDecision::Resumed);
+    }
+}
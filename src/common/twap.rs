@@ -0,0 +1,278 @@
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use crate::common::logger::Logger;
+use crate::common::persistence::unix_now;
+
+/// TWAP accumulation settings
+#[derive(Debug, Clone)]
+pub struct TwapConfig {
+    pub enabled: bool,
+    /// Net token position to build up (UI amount)
+    pub target_tokens: f64,
+    /// Window over which the position is accumulated
+    pub duration_hours: f64,
+}
+
+impl Default for TwapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_tokens: 0.0,
+            duration_hours: 24.0,
+        }
+    }
+}
+
+impl TwapConfig {
+    /// Read TWAP_ENABLED, TWAP_TARGET_TOKENS and TWAP_DURATION_HOURS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let get = |name: &str| std::env::var(name).ok().and_then(|v| v.trim().parse::<f64>().ok());
+        Self {
+            enabled: std::env::var("TWAP_ENABLED").map(|v| v.trim().to_lowercase() == "true").unwrap_or(defaults.enabled),
+            target_tokens: get("TWAP_TARGET_TOKENS").unwrap_or(defaults.target_tokens),
+            duration_hours: get("TWAP_DURATION_HOURS").unwrap_or(defaults.duration_hours),
+        }
+    }
+}
+
+/// Persisted accumulation progress, so a restart continues the same schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwapProgress {
+    pub started_at: i64,
+    pub target_tokens: f64,
+    pub duration_hours: f64,
+    pub bought_tokens: f64,
+    pub sold_tokens: f64,
+}
+
+/// Accumulates a net long position along a linear TWAP schedule while still trading both sides
+pub struct TwapAccumulator {
+    config: TwapConfig,
+    started_at: i64,
+    bought_tokens: f64,
+    sold_tokens: f64,
+    completed: bool,
+    logger: Logger,
+}
+
+impl TwapAccumulator {
+    /// Create a new accumulator whose schedule starts now
+    pub fn new(config: TwapConfig) -> Self {
+        let logger = Logger::new("[TWAP] => ".green().bold().to_string());
+        if config.enabled {
+            logger.log(format!(
+                "📈 TWAP accumulation: {} tokens over {:.1} hours",
+                config.target_tokens, config.duration_hours
+            ).green().to_string());
+        }
+
+        Self {
+            config,
+            started_at: unix_now(),
+            bought_tokens: 0.0,
+            sold_tokens: 0.0,
+            completed: false,
+            logger,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.config.enabled && self.config.target_tokens > 0.0 && !self.completed
+    }
+
+    /// Net tokens accumulated so far
+    pub fn net_position(&self) -> f64 {
+        self.bought_tokens - self.sold_tokens
+    }
+
+    /// Fraction of the schedule that has elapsed (0.0 - 1.0)
+    pub fn schedule_progress(&self) -> f64 {
+        let duration_secs = self.config.duration_hours * 3600.0;
+        if duration_secs <= 0.0 {
+            return 1.0;
+        }
+        let elapsed = (unix_now() - self.started_at).max(0) as f64;
+        (elapsed / duration_secs).min(1.0)
+    }
+
+    /// Net position the schedule expects by now
+    pub fn scheduled_position(&self) -> f64 {
+        self.config.target_tokens * self.schedule_progress()
+    }
+
+    /// Tokens behind schedule (negative when ahead)
+    pub fn deficit(&self) -> f64 {
+        self.scheduled_position() - self.net_position()
+    }
+
+    /// Buy size multiplier: up to 2x when behind schedule, down to 0.5x when ahead
+    pub fn buy_amount_multiplier(&self) -> f64 {
+        if !self.is_active() {
+            return 1.0;
+        }
+        // A 10% slice of the target behind schedule applies the full multiplier
+        let slice = (self.config.target_tokens * 0.1).max(f64::EPSILON);
+        let pressure = (self.deficit() / slice).max(-1.0).min(1.0);
+        if pressure >= 0.0 {
+            1.0 + pressure
+        } else {
+            1.0 + pressure * 0.5
+        }
+    }
+
+    /// Largest sell that keeps the net position on schedule
+    pub fn max_sell_tokens(&self) -> f64 {
+        if !self.is_active() {
+            return f64::MAX;
+        }
+        (self.net_position() - self.scheduled_position()).max(0.0)
+    }
+
+    /// Record a confirmed fill (token UI amount)
+    pub fn record_fill(&mut self, is_buy: bool, tokens: f64) {
+        if is_buy {
+            self.bought_tokens += tokens;
+        } else {
+            self.sold_tokens += tokens;
+        }
+
+        if self.is_active() && self.net_position() >= self.config.target_tokens {
+            self.completed = true;
+            self.logger.log(format!(
+                "🎯 TWAP target reached: net {:.2} tokens ({:.2} bought / {:.2} sold)",
+                self.net_position(), self.bought_tokens, self.sold_tokens
+            ).green().bold().to_string());
+        }
+    }
+
+    pub fn get_status(&self) -> TwapStatus {
+        TwapStatus {
+            active: self.is_active(),
+            target_tokens: self.config.target_tokens,
+            net_position: self.net_position(),
+            scheduled_position: self.scheduled_position(),
+            schedule_progress: self.schedule_progress(),
+            completed: self.completed,
+        }
+    }
+
+    pub fn progress(&self) -> TwapProgress {
+        TwapProgress {
+            started_at: self.started_at,
+            target_tokens: self.config.target_tokens,
+            duration_hours: self.config.duration_hours,
+            bought_tokens: self.bought_tokens,
+            sold_tokens: self.sold_tokens,
+        }
+    }
+
+    /// Continue a persisted schedule. Ignored if the target or window changed since it was saved.
+    pub fn restore(&mut self, progress: &TwapProgress) -> bool {
+        if progress.target_tokens != self.config.target_tokens || progress.duration_hours != self.config.duration_hours {
+            self.logger.log("TWAP settings changed since last run, starting a new schedule".yellow().to_string());
+            return false;
+        }
+        self.started_at = progress.started_at;
+        self.bought_tokens = progress.bought_tokens;
+        self.sold_tokens = progress.sold_tokens;
+        self.completed = self.net_position() >= self.config.target_tokens;
+        true
+    }
+}
+
+/// TWAP status information
+#[derive(Debug, Clone)]
+pub struct TwapStatus {
+    pub active: bool,
+    pub target_tokens: f64,
+    pub net_position: f64,
+    pub scheduled_position: f64,
+    pub schedule_progress: f64,
+    pub completed: bool,
+}
+
+/// Global TWAP accumulator instance
+pub type GlobalTwapAccumulator = Arc<Mutex<TwapAccumulator>>;
+
+/// Create a global TWAP accumulator
+pub fn create_global_twap_accumulator(config: TwapConfig) -> GlobalTwapAccumulator {
+    Arc::new(Mutex::new(TwapAccumulator::new(config)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close_to(actual: f64, expected: f64) -> bool {
+        (actual - expected).abs() < 1e-3
+    }
+
+    /// Accumulator for 1000 tokens halfway through a long window (the schedule barely moves
+    /// while the test runs), with `bought` tokens filled
+    fn halfway(bought: f64) -> TwapAccumulator {
+        let mut twap = TwapAccumulator::new(TwapConfig {
+            enabled: true,
+            target_tokens: 1000.0,
+            duration_hours: 2000.0,
+            ..TwapConfig::default()
+        });
+        assert!(twap.restore(&TwapProgress {
+            started_at: unix_now() - 1000 * 3600,
+            target_tokens: 1000.0,
+            duration_hours: 2000.0,
+            bought_tokens: bought,
+            sold_tokens: 0.0,
+        }));
+        twap
+    }
+
+    #[test]
+    fn behind_schedule_buys_more_and_blocks_sells() {
+        let twap = halfway(0.0);
+        assert!(close_to(twap.scheduled_position(), 500.0));
+        // 500 behind is more than the 100-token slice: full pressure
+        assert!(close_to(twap.buy_amount_multiplier(), 2.0));
+        assert_eq!(twap.max_sell_tokens(), 0.0);
+
+        // Half a slice behind
+        let twap = halfway(450.0);
+        assert!(close_to(twap.buy_amount_multiplier(), 1.5));
+    }
+
+    #[test]
+    fn ahead_of_schedule_buys_less_and_sells_the_surplus() {
+        let twap = halfway(550.0);
+        assert!(close_to(twap.deficit(), -50.0));
+        assert!(close_to(twap.buy_amount_multiplier(), 0.75));
+        assert!(close_to(twap.max_sell_tokens(), 50.0));
+
+        let twap = halfway(700.0);
+        assert!(close_to(twap.buy_amount_multiplier(), 0.5));
+        assert!(close_to(twap.max_sell_tokens(), 200.0));
+    }
+
+    #[test]
+    fn reaching_the_target_switches_the_schedule_off() {
+        let mut twap = halfway(900.0);
+        twap.record_fill(false, 100.0);
+        assert!(twap.is_active());
+        twap.record_fill(true, 300.0);
+        assert!(!twap.is_active());
+        assert!(twap.get_status().completed);
+        assert_eq!(twap.buy_amount_multiplier(), 1.0);
+        assert_eq!(twap.max_sell_tokens(), f64::MAX);
+    }
+
+    #[test]
+    fn restore_ignores_progress_for_other_settings() {
+        let mut twap = TwapAccumulator::new(TwapConfig { enabled: true, target_tokens: 1000.0, ..TwapConfig::default() });
+        let mut progress = twap.progress();
+        progress.bought_tokens = 400.0;
+        progress.target_tokens = 2000.0;
+        assert!(!twap.restore(&progress));
+        assert_eq!(twap.net_position(), 0.0);
+    }
+}
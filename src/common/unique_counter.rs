@@ -0,0 +1,163 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+
+/// Register index bits: 2^12 registers (4 KiB), about 1.6% standard error
+const PRECISION: u32 = 12;
+const REGISTERS: usize = 1 << PRECISION;
+
+/// Fixed-size cardinality estimate of the distinct values inserted
+#[derive(Debug, Clone)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self { registers: vec![0; REGISTERS] }
+    }
+}
+
+impl HyperLogLog {
+    pub fn insert<T: Hash + ?Sized>(&mut self, value: &T) {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+        let index = (hash >> (64 - PRECISION)) as usize;
+        // Guard bit keeps the rank bounded when the remaining bits are all zero
+        let rest = (hash << PRECISION) | (1 << (PRECISION - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    /// Fold `other` in, giving the estimate of the union
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        for (mine, theirs) in self.registers.iter_mut().zip(&other.registers) {
+            *mine = (*mine).max(*theirs);
+        }
+    }
+
+    pub fn estimate(&self) -> u64 {
+        let m = REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|r| 2f64.powi(-(*r as i32))).sum();
+        let raw = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|r| **r == 0).count();
+        // Linear counting is more accurate while many registers are still empty
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u64
+    }
+}
+
+/// Distinct values over a sliding window, kept as one HyperLogLog per time bucket so memory
+/// stays at `retention / bucket` sketches no matter how many values are inserted
+#[derive(Debug)]
+pub struct WindowedUniqueCounter {
+    bucket_secs: i64,
+    retention_secs: i64,
+    // (bucket start, sketch), oldest first
+    buckets: VecDeque<(i64, HyperLogLog)>,
+}
+
+impl WindowedUniqueCounter {
+    pub fn new(bucket_secs: u64, retention_secs: u64) -> Self {
+        Self {
+            bucket_secs: bucket_secs.max(1) as i64,
+            retention_secs: retention_secs as i64,
+            buckets: VecDeque::new(),
+        }
+    }
+
+    pub fn insert<T: Hash + ?Sized>(&mut self, value: &T, now: i64) {
+        let start = now - now.rem_euclid(self.bucket_secs);
+        if self.buckets.back().map(|(at, _)| *at != start).unwrap_or(true) {
+            self.buckets.push_back((start, HyperLogLog::default()));
+        }
+        if let Some((_, sketch)) = self.buckets.back_mut() {
+            sketch.insert(value);
+        }
+        while self.buckets.front().map(|(at, _)| now - at > self.retention_secs + self.bucket_secs).unwrap_or(false) {
+            self.buckets.pop_front();
+        }
+    }
+
+    /// Estimated distinct values in the last `period_secs`, at bucket granularity (a bucket
+    /// counts when any part of it falls inside the period)
+    pub fn estimate(&self, period_secs: u64, now: i64) -> u64 {
+        let cutoff = now - period_secs as i64;
+        let mut union = HyperLogLog::default();
+        for (start, sketch) in &self.buckets {
+            if start + self.bucket_secs > cutoff {
+                union.merge(sketch);
+            }
+        }
+        union.estimate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn within(estimate: u64, actual: u64, percent: f64) -> bool {
+        (estimate as f64 - actual as f64).abs() <= actual as f64 * percent / 100.0
+    }
+
+    #[test]
+    fn empty_counter_estimates_zero() {
+        assert_eq!(HyperLogLog::default().estimate(), 0);
+        assert_eq!(WindowedUniqueCounter::new(60, 3600).estimate(3600, 1_000), 0);
+    }
+
+    #[test]
+    fn estimate_stays_within_the_error_bound() {
+        let mut sketch = HyperLogLog::default();
+        for i in 0..50_000u64 {
+            sketch.insert(&i);
+            // Repeats don't count again
+            sketch.insert(&(i / 2));
+        }
+        // About 3x the 1.6% standard error
+        assert!(within(sketch.estimate(), 50_000, 5.0), "estimate {}", sketch.estimate());
+
+        let mut small = HyperLogLog::default();
+        for i in 0..100u64 {
+            small.insert(&format!("wallet-{}", i));
+        }
+        assert!(within(small.estimate(), 100, 5.0), "estimate {}", small.estimate());
+    }
+
+    #[test]
+    fn merge_estimates_the_union() {
+        let mut a = HyperLogLog::default();
+        let mut b = HyperLogLog::default();
+        for i in 0..6_000u64 {
+            a.insert(&i);
+        }
+        for i in 4_000..10_000u64 {
+            b.insert(&i);
+        }
+        a.merge(&b);
+        assert!(within(a.estimate(), 10_000, 5.0), "estimate {}", a.estimate());
+    }
+
+    #[test]
+    fn window_only_counts_buckets_inside_the_period() {
+        let mut counter = WindowedUniqueCounter::new(60, 3600);
+        for i in 0..50u64 {
+            counter.insert(&i, 0);
+        }
+        for i in 50..80u64 {
+            counter.insert(&i, 600);
+        }
+        assert!(within(counter.estimate(120, 600), 30, 5.0));
+        assert!(within(counter.estimate(3600, 600), 80, 5.0));
+        // The first bucket falls out of retention
+        counter.insert(&0u64, 3_700);
+        assert!(within(counter.estimate(3600, 3_700), 31, 5.0));
+    }
+}
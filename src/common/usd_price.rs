@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::env;
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use lazy_static::lazy_static;
+use tokio::sync::RwLock;
+use crate::common::logger::Logger;
+
+const JUPITER_PRICE_URL: &str = "https://lite-api.jup.ag/price/v3";
+const PYTH_HERMES_URL: &str = "https://hermes.pyth.network/v2/updates/price/latest";
+/// Pyth SOL/USD price feed
+const PYTH_SOL_USD_FEED: &str = "ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// Where USD prices come from (USD_PRICE_SOURCE)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsdPriceSource {
+    Off,
+    Jupiter,
+    Pyth,
+}
+
+impl UsdPriceSource {
+    pub fn from_env() -> Self {
+        match env::var("USD_PRICE_SOURCE").unwrap_or_default().trim().to_lowercase().as_str() {
+            "jupiter" | "jup" => Self::Jupiter,
+            "pyth" => Self::Pyth,
+            _ => Self::Off,
+        }
+    }
+}
+
+/// SOL/USD and token/USD prices, cached for USD_PRICE_REFRESH_SECONDS. Failed lookups
+/// return the last cached value (or None) so reports never block on the oracle.
+pub struct UsdPriceOracle {
+    source: UsdPriceSource,
+    refresh: Duration,
+    // Pyth feed id for the target token (PYTH_TOKEN_FEED_ID); Jupiter prices any mint
+    pyth_token_feed: Option<String>,
+    cache: RwLock<HashMap<String, (f64, Instant)>>,
+    client: reqwest::Client,
+    logger: Logger,
+}
+
+impl UsdPriceOracle {
+    pub fn from_env() -> Self {
+        Self {
+            source: UsdPriceSource::from_env(),
+            refresh: Duration::from_secs(
+                env::var("USD_PRICE_REFRESH_SECONDS").ok().and_then(|v| v.parse().ok()).unwrap_or(60),
+            ),
+            pyth_token_feed: env::var("PYTH_TOKEN_FEED_ID")
+                .ok()
+                .map(|v| v.trim().trim_start_matches("0x").to_lowercase())
+                .filter(|v| !v.is_empty()),
+            cache: RwLock::new(HashMap::new()),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .unwrap_or_default(),
+            logger: Logger::new("[USD-PRICE] => ".green().bold().to_string()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.source != UsdPriceSource::Off
+    }
+
+    /// Current SOL price in USD
+    pub async fn sol_usd(&self) -> Option<f64> {
+        self.cached_or_fetch(WSOL_MINT).await
+    }
+
+    /// Current USD price of one whole `mint` token. With Pyth this needs PYTH_TOKEN_FEED_ID;
+    /// callers can fall back to the SOL price of the token times `sol_usd`.
+    pub async fn token_usd(&self, mint: &str) -> Option<f64> {
+        if self.source == UsdPriceSource::Pyth && self.pyth_token_feed.is_none() {
+            return None;
+        }
+        self.cached_or_fetch(mint).await
+    }
+
+    async fn cached_or_fetch(&self, mint: &str) -> Option<f64> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let cached = self.cache.read().await.get(mint).copied();
+        if let Some((price, at)) = cached {
+            if at.elapsed() < self.refresh {
+                return Some(price);
+            }
+        }
+
+        match self.fetch(mint).await {
+            Ok(price) => {
+                self.cache.write().await.insert(mint.to_string(), (price, Instant::now()));
+                Some(price)
+            },
+            Err(e) => {
+                self.logger.log(format!("USD price lookup for {} failed: {}", mint, e).yellow().to_string());
+                cached.map(|(price, _)| price)
+            },
+        }
+    }
+
+    async fn fetch(&self, mint: &str) -> Result<f64> {
+        match self.source {
+            UsdPriceSource::Off => Err(anyhow!("USD pricing is disabled")),
+            UsdPriceSource::Jupiter => {
+                let body: serde_json::Value = self.client
+                    .get(JUPITER_PRICE_URL)
+                    .query(&[("ids", mint)])
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                body.get(mint)
+                    .and_then(|p| p.get("usdPrice"))
+                    .and_then(|p| p.as_f64())
+                    .ok_or_else(|| anyhow!("Jupiter returned no price"))
+            },
+            UsdPriceSource::Pyth => {
+                let feed = if mint == WSOL_MINT {
+                    PYTH_SOL_USD_FEED
+                } else {
+                    self.pyth_token_feed.as_deref().ok_or_else(|| anyhow!("PYTH_TOKEN_FEED_ID is not set"))?
+                };
+                let body: serde_json::Value = self.client
+                    .get(PYTH_HERMES_URL)
+                    .query(&[("ids[]", feed), ("parsed", "true")])
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                let price = body.pointer("/parsed/0/price").ok_or_else(|| anyhow!("Pyth returned no price"))?;
+                let mantissa: f64 = price["price"].as_str().and_then(|p| p.parse().ok()).ok_or_else(|| anyhow!("Invalid Pyth price"))?;
+                let expo = price["expo"].as_i64().ok_or_else(|| anyhow!("Invalid Pyth exponent"))?;
+                Ok(mantissa * 10f64.powi(expo as i32))
+            },
+        }
+    }
+}
+
+lazy_static! {
+    pub static ref USD_PRICES: UsdPriceOracle = UsdPriceOracle::from_env();
+}
+
+/// ` ($12.34)` for a SOL amount when a SOL/USD price is known, otherwise empty
+pub fn usd_suffix(sol: f64, sol_usd: Option<f64>) -> String {
+    match sol_usd {
+        Some(usd) if sol * usd < 0.0 => format!(" (-${:.2})", -sol * usd),
+        Some(usd) => format!(" (${:.2})", sol * usd),
+        None => String::new(),
+    }
+}
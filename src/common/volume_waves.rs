@@ -5,6 +5,31 @@ use rand::Rng;
 use colored::Colorize;
 use crate::common::logger::Logger;
 
+/// Phase lengths and transition odds for the wave cycle
+#[derive(Debug, Clone, Default)]
+pub struct WaveConfig {
+    pub burst_minutes: Option<u64>,
+    pub dormant_minutes: Option<u64>,
+    /// Chance that Active is followed by Burst instead of Slow
+    pub burst_probability: Option<f64>,
+    /// Chance that Slow is followed by Dormant instead of Active
+    pub dormant_probability: Option<f64>,
+}
+
+impl WaveConfig {
+    /// Read VOLUME_WAVE_BURST_MINUTES, VOLUME_WAVE_DORMANT_MINUTES, VOLUME_WAVE_BURST_PROBABILITY
+    /// and VOLUME_WAVE_DORMANT_PROBABILITY; anything unset keeps the built-in behaviour
+    pub fn from_env() -> Self {
+        let get = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        Self {
+            burst_minutes: get("VOLUME_WAVE_BURST_MINUTES").and_then(|v| v.parse().ok()),
+            dormant_minutes: get("VOLUME_WAVE_DORMANT_MINUTES").and_then(|v| v.parse().ok()),
+            burst_probability: get("VOLUME_WAVE_BURST_PROBABILITY").and_then(|v| v.parse().ok()),
+            dormant_probability: get("VOLUME_WAVE_DORMANT_PROBABILITY").and_then(|v| v.parse().ok()),
+        }
+    }
+}
+
 /// Volume wave manager that creates realistic trading patterns
 pub struct VolumeWaveManager {
     current_phase: TradingPhase,
@@ -13,6 +38,11 @@ pub struct VolumeWaveManager {
     slow_duration: Duration,
     logger: Logger,
     activity_multipliers: PhaseMultipliers,
+    enabled: bool,
+    burst_duration: Duration,
+    dormant_duration: Duration,
+    burst_probability: f64,
+    dormant_probability: f64,
 }
 
 impl VolumeWaveManager {
@@ -37,22 +67,45 @@ impl VolumeWaveManager {
             slow_duration: Duration::from_secs(slow_hours * 3600),
             logger,
             activity_multipliers: PhaseMultipliers::default(),
+            enabled: true,
+            burst_duration: Duration::from_secs(15 * 60), // Burst lasts 15 minutes
+            dormant_duration: Duration::from_secs(60 * 60), // Dormant lasts 1 hour
+            burst_probability: 0.15,
+            dormant_probability: 0.1,
+        }
+    }
+    
+    /// Apply configured phase lengths and transition odds
+    pub fn configure(&mut self, config: &WaveConfig) {
+        if let Some(minutes) = config.burst_minutes {
+            self.burst_duration = Duration::from_secs(minutes.max(1) * 60);
+        }
+        if let Some(minutes) = config.dormant_minutes {
+            self.dormant_duration = Duration::from_secs(minutes.max(1) * 60);
+        }
+        if let Some(p) = config.burst_probability {
+            self.burst_probability = p.max(0.0).min(1.0);
         }
+        if let Some(p) = config.dormant_probability {
+            self.dormant_probability = p.max(0.0).min(1.0);
+        }
+        self.logger.log(format!(
+            "⚙️ Wave settings: burst {} min ({:.0}% after active), dormant {} min ({:.0}% after slow)",
+            self.burst_duration.as_secs() / 60, self.burst_probability * 100.0,
+            self.dormant_duration.as_secs() / 60, self.dormant_probability * 100.0
+        ).blue().to_string());
     }
     
     /// Get the current trading phase, updating if necessary
     pub fn get_current_phase(&mut self) -> TradingPhase {
+        if !self.enabled {
+            return self.current_phase;
+        }
+        
         let now = Instant::now();
         let elapsed = now.duration_since(self.phase_start_time);
         
-        let should_switch = match self.current_phase {
-            TradingPhase::Active => elapsed >= self.active_duration,
-            TradingPhase::Slow => elapsed >= self.slow_duration,
-            TradingPhase::Burst => elapsed >= Duration::from_secs(15 * 60), // Burst lasts 15 minutes
-            TradingPhase::Dormant => elapsed >= Duration::from_secs(60 * 60),  // Dormant lasts 1 hour
-        };
-        
-        if should_switch {
+        if elapsed >= self.phase_duration() {
             self.switch_phase();
         }
         
@@ -67,7 +120,7 @@ impl VolumeWaveManager {
         self.current_phase = match self.current_phase {
             TradingPhase::Active => {
                 // After active, go to slow with occasional burst
-                if rng.gen_bool(0.15) { // 15% chance of burst
+                if rng.gen_bool(self.burst_probability) {
                     TradingPhase::Burst
                 } else {
                     TradingPhase::Slow
@@ -75,7 +128,7 @@ impl VolumeWaveManager {
             },
             TradingPhase::Slow => {
                 // After slow, go to active with occasional dormant
-                if rng.gen_bool(0.1) { // 10% chance of dormant
+                if rng.gen_bool(self.dormant_probability) {
                     TradingPhase::Dormant
                 } else {
                     TradingPhase::Active
@@ -96,8 +149,8 @@ impl VolumeWaveManager {
         let duration_text = match self.current_phase {
             TradingPhase::Active => format!("{:.1} hours", self.active_duration.as_secs_f64() / 3600.0),
             TradingPhase::Slow => format!("{:.1} hours", self.slow_duration.as_secs_f64() / 3600.0),
-            TradingPhase::Burst => "15 minutes".to_string(),
-            TradingPhase::Dormant => "1 hour".to_string(),
+            TradingPhase::Burst => format!("{} minutes", self.burst_duration.as_secs() / 60),
+            TradingPhase::Dormant => format!("{} minutes", self.dormant_duration.as_secs() / 60),
         };
         
         self.logger.log(format!(
@@ -106,8 +159,48 @@ impl VolumeWaveManager {
         ).blue().bold().to_string());
     }
     
+    /// Length of the current phase
+    fn phase_duration(&self) -> Duration {
+        match self.current_phase {
+            TradingPhase::Active => self.active_duration,
+            TradingPhase::Slow => self.slow_duration,
+            TradingPhase::Burst => self.burst_duration,
+            TradingPhase::Dormant => self.dormant_duration,
+        }
+    }
+    
+    /// Enable or disable volume waves. While disabled the phase never changes and all multipliers are 1.0.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if enabled {
+            self.phase_start_time = Instant::now();
+        }
+        self.logger.log(format!("🌊 Volume waves {}", if enabled { "enabled" } else { "disabled" }).blue().to_string());
+    }
+    
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    
+    /// Change the active / slow phase lengths; the current phase keeps its start time
+    pub fn set_phase_durations(&mut self, active_hours: u64, slow_hours: u64) {
+        self.active_duration = Duration::from_secs(active_hours * 3600);
+        self.slow_duration = Duration::from_secs(slow_hours * 3600);
+        self.logger.log(format!(
+            "⚙️ Wave durations updated: active {}h / slow {}h", active_hours, slow_hours
+        ).blue().to_string());
+    }
+    
+    /// Active and slow phase lengths in hours
+    pub fn get_phase_durations(&self) -> (u64, u64) {
+        (self.active_duration.as_secs() / 3600, self.slow_duration.as_secs() / 3600)
+    }
+    
     /// Get the frequency multiplier for the current phase
     pub fn get_frequency_multiplier(&self) -> f64 {
+        if !self.enabled {
+            return 1.0;
+        }
         match self.current_phase {
             TradingPhase::Active => self.activity_multipliers.active_frequency,
             TradingPhase::Slow => self.activity_multipliers.slow_frequency,
@@ -118,6 +211,9 @@ impl VolumeWaveManager {
     
     /// Get the amount multiplier for the current phase
     pub fn get_amount_multiplier(&self) -> f64 {
+        if !self.enabled {
+            return 1.0;
+        }
         match self.current_phase {
             TradingPhase::Active => self.activity_multipliers.active_amount,
             TradingPhase::Slow => self.activity_multipliers.slow_amount,
@@ -129,12 +225,7 @@ impl VolumeWaveManager {
     /// Get comprehensive wave information
     pub fn get_wave_info(&self) -> VolumeWaveInfo {
         let elapsed = Instant::now().duration_since(self.phase_start_time);
-        let remaining = match self.current_phase {
-            TradingPhase::Active => self.active_duration.saturating_sub(elapsed),
-            TradingPhase::Slow => self.slow_duration.saturating_sub(elapsed),
-            TradingPhase::Burst => Duration::from_secs(15 * 60).saturating_sub(elapsed),
-            TradingPhase::Dormant => Duration::from_secs(60 * 60).saturating_sub(elapsed),
-        };
+        let remaining = self.phase_duration().saturating_sub(elapsed);
         
         VolumeWaveInfo {
             current_phase: self.current_phase,
@@ -7,10 +7,12 @@ use anchor_client::solana_sdk::signer::Signer;
 use colored::Colorize;
 use rand::seq::SliceRandom;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use crate::common::logger::Logger;
+use crate::common::persistence::{StateStore, unix_now};
 
 /// Wallet profile types that determine trading behavior
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum WalletProfile {
     FrequentSeller,   // Sells often, shorter hold times
     LongTermHolder,   // Holds for long periods, rarely sells
@@ -119,6 +121,25 @@ impl WalletInfo {
     }
 }
 
+/// Persisted per-wallet statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletStateSnapshot {
+    pub pubkey: String,
+    pub profile: WalletProfile,
+    pub usage_count: u32,
+    pub total_buys: u32,
+    pub total_sells: u32,
+}
+
+/// Persisted wallet pool state, written on shutdown
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletPoolSnapshot {
+    pub wallets: Vec<WalletStateSnapshot>,
+    pub saved_at: i64,
+}
+
+/// State store key for the wallet pool snapshot
+pub const WALLET_POOL_STATE_KEY: &str = "wallet_pool";
 
 impl WalletPool {
     
@@ -191,6 +212,56 @@ impl WalletPool {
             .collect()
     }
     
+    /// Capture per-wallet statistics for persistence
+    pub fn snapshot(&self) -> WalletPoolSnapshot {
+        WalletPoolSnapshot {
+            wallets: self.wallets.iter()
+                .map(|w| WalletStateSnapshot {
+                    pubkey: w.keypair.pubkey().to_string(),
+                    profile: w.profile,
+                    usage_count: w.usage_count,
+                    total_buys: w.total_buys,
+                    total_sells: w.total_sells,
+                })
+                .collect(),
+            saved_at: unix_now(),
+        }
+    }
+    
+    /// Re-apply persisted statistics to wallets that are still part of the pool
+    pub fn restore_snapshot(&mut self, snapshot: &WalletPoolSnapshot) {
+        let saved: HashMap<&str, &WalletStateSnapshot> = snapshot.wallets.iter()
+            .map(|w| (w.pubkey.as_str(), w))
+            .collect();
+        
+        let mut restored = 0;
+        for wallet in &mut self.wallets {
+            if let Some(state) = saved.get(wallet.keypair.pubkey().to_string().as_str()) {
+                wallet.profile = state.profile;
+                wallet.usage_count = state.usage_count;
+                wallet.total_buys = state.total_buys;
+                wallet.total_sells = state.total_sells;
+                restored += 1;
+            }
+        }
+        
+        self.logger.log(format!("♻️ Restored statistics for {}/{} wallets", restored, self.wallets.len()).green().to_string());
+    }
+    
+    /// Find a wallet in the pool by its base58 pubkey
+    pub fn find_wallet(&self, pubkey: &str) -> Option<Arc<Keypair>> {
+        self.wallets.iter()
+            .find(|w| w.keypair.pubkey().to_string() == pubkey)
+            .map(|w| w.keypair.clone())
+    }
+    
+    /// Flush wallet statistics to the state store
+    pub fn save_state(&self, store: &StateStore) -> anyhow::Result<()> {
+        store.save(WALLET_POOL_STATE_KEY, &self.snapshot())?;
+        self.logger.log(format!("💾 Wallet pool state saved ({} wallets)", self.wallets.len()).green().to_string());
+        Ok(())
+    }
+    
 }
 
 /// Trade type for tracking recent trades
@@ -211,6 +282,10 @@ pub struct RandomizationConfig {
     pub wallet_rotation_frequency: u32, // Change wallet every N trades
     pub enable_realistic_pauses: bool,
     pub max_consecutive_same_wallet: u32,
+    pub min_wsol_buy_ratio: f64, // Fraction of wrapped WSOL spent per buy (lower bound)
+    pub max_wsol_buy_ratio: f64, // Fraction of wrapped WSOL spent per buy (upper bound)
+    pub min_trade_interval_ms: u64,
+    pub max_trade_interval_ms: u64,
 }
 
 impl Default for RandomizationConfig {
@@ -224,6 +299,38 @@ impl Default for RandomizationConfig {
             wallet_rotation_frequency: 3, // Change wallet every 3 trades
             enable_realistic_pauses: true,
             max_consecutive_same_wallet: 5,
+            min_wsol_buy_ratio: 0.5,
+            max_wsol_buy_ratio: 0.9,
+            min_trade_interval_ms: 600_000,   // 10 minutes
+            max_trade_interval_ms: 7_200_000, // 2 hours
+        }
+    }
+}
+
+impl RandomizationConfig {
+    /// Stealth preset: 50-90% of WSOL per buy, 70/30 buy/sell, rotate every 2 trades, 10min-2h intervals
+    pub fn stealth_mode() -> Self {
+        Self {
+            buy_sell_ratio: 0.7,
+            wallet_rotation_frequency: 2,
+            min_wsol_buy_ratio: 0.5,
+            max_wsol_buy_ratio: 0.9,
+            min_trade_interval_ms: 600_000,
+            max_trade_interval_ms: 7_200_000,
+            ..Self::default()
+        }
+    }
+    
+    /// Conservative preset: smaller buys, slower cadence
+    pub fn conservative_mode() -> Self {
+        Self {
+            buy_sell_ratio: 0.6,
+            wallet_rotation_frequency: 3,
+            min_wsol_buy_ratio: 0.3,
+            max_wsol_buy_ratio: 0.6,
+            min_trade_interval_ms: 1_800_000,  // 30 minutes
+            max_trade_interval_ms: 14_400_000, // 4 hours
+            ..Self::default()
         }
     }
 }
@@ -12,7 +12,7 @@ use std::sync::Arc;
 use anyhow::{Result, anyhow};
 use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
 
-use crate::common::cache::{TOKEN_ACCOUNT_CACHE, TOKEN_MINT_CACHE};
+use crate::common::cache::{MISSING_ACCOUNT_CACHE, TOKEN_ACCOUNT_CACHE, TOKEN_MINT_CACHE};
 
 pub fn get_token_address(
     client: Arc<anchor_client::solana_client::nonblocking::rpc_client::RpcClient>,
@@ -42,6 +42,10 @@ pub async fn get_account_info(
     if let Some(cached_account) = TOKEN_ACCOUNT_CACHE.get(&account) {
         return Ok(cached_account);
     }
+    // Known not to exist a moment ago (created accounts are cleared from this cache)
+    if MISSING_ACCOUNT_CACHE.get(&account).is_some() {
+        return Err(TokenError::AccountNotFound);
+    }
 
     // If not in cache, fetch from RPC
     let program_client = Arc::new(ProgramRpcClient::new(
@@ -53,6 +57,7 @@ pub async fn get_account_info(
         .await
         .map_err(TokenError::Client)?
         .ok_or(TokenError::AccountNotFound)
+        .inspect_err(|_| MISSING_ACCOUNT_CACHE.insert(account, (), None))
         .inspect_err(|_err| {
             // logger.log(format!(
             //     "get_account_info: {} {}: mint {}",
@@ -16,12 +16,9 @@ use spl_associated_token_account::{
 };
 use spl_token::{ui_amount_to_amount, state::{Account, Mint}};
 use solana_program_pack::Pack;
-use tokio::sync::OnceCell;
-use lru::LruCache;
-use std::num::NonZeroUsize;
 
 use crate::{
-    common::{config::SwapConfig, logger::Logger, cache::WALLET_TOKEN_ACCOUNTS},
+    common::{config::SwapConfig, logger::Logger, cache::WALLET_TOKEN_ACCOUNTS, pool_state::POOL_STATE},
     core::token,
     engine::swap::{SwapDirection, SwapInType},
 };
@@ -34,18 +31,6 @@ pub struct BondingCurveInfo {
     pub new_virtual_sol_reserve: u64,
 }
 
-// Constants for cache
-const CACHE_SIZE: usize = 1000;
-
-// Thread-safe cache with LRU eviction policy
-static TOKEN_ACCOUNT_CACHE: OnceCell<LruCache<Pubkey, bool>> = OnceCell::const_new();
-
-async fn init_caches() {
-    TOKEN_ACCOUNT_CACHE.get_or_init(|| async {
-        LruCache::new(NonZeroUsize::new(CACHE_SIZE).unwrap())
-    }).await;
-}
-
 pub const TEN_THOUSAND: u64 = 10000;
 pub const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
 pub const RENT_PROGRAM: &str = "SysvarRent111111111111111111111111111111111";
@@ -66,9 +51,6 @@ impl Pump {
         rpc_client: Arc<anchor_client::solana_client::rpc_client::RpcClient>,
         keypair: Arc<Keypair>,
     ) -> Self {
-        // Initialize caches on first use
-        tokio::spawn(init_caches());
-        
         Self {
             rpc_nonblocking_client,
             keypair,
@@ -132,27 +114,34 @@ impl Pump {
         let is_buy = input_mint == native_mint_str;
         let token_mint = if is_buy { output_mint } else { input_mint };
         
-        // Get bonding curve info
-        let pump_program = Pubkey::from_str(PUMP_FUN_PROGRAM)?;
-        let (_, _, bonding_curve_reserves) = get_bonding_curve_account(
-            self.rpc_client.clone().unwrap(),
-            Pubkey::from_str(token_mint)?,
-            pump_program,
-        ).await?;
+        // Prefer the streamed bonding curve; fetch it only when the live copy is missing or stale
+        let mint = Pubkey::from_str(token_mint)?;
+        let (virtual_sol_reserves, virtual_token_reserves) = match POOL_STATE.get(&bonding_curve_address(&mint)?) {
+            Some(pool) => (pool.quote_reserve, pool.base_reserve),
+            None => {
+                let pump_program = Pubkey::from_str(PUMP_FUN_PROGRAM)?;
+                let (_, _, bonding_curve_reserves) = get_bonding_curve_account(
+                    self.rpc_client.clone().unwrap(),
+                    mint,
+                    pump_program,
+                ).await?;
+                (bonding_curve_reserves.virtual_sol_reserves, bonding_curve_reserves.virtual_token_reserves)
+            }
+        };
         
         if is_buy {
             // Calculate how many tokens we get for the given SOL amount
             Ok(Self::calculate_buy_token_amount(
                 amount,
-                bonding_curve_reserves.virtual_sol_reserves,
-                bonding_curve_reserves.virtual_token_reserves,
+                virtual_sol_reserves,
+                virtual_token_reserves,
             ))
         } else {
             // Calculate how much SOL we get for the given token amount
             Ok(Self::calculate_sell_sol_amount(
                 amount,
-                bonding_curve_reserves.virtual_sol_reserves,
-                bonding_curve_reserves.virtual_token_reserves,
+                virtual_sol_reserves,
+                virtual_token_reserves,
             ))
         }
     }
@@ -221,4 +210,14 @@ pub fn get_pda(mint: &Pubkey, program_id: &Pubkey ) -> Result<Pubkey> {
     let seeds = [b"bonding-curve".as_ref(), mint.as_ref()];
     let (bonding_curve, _bump) = Pubkey::find_program_address(&seeds, program_id);
     Ok(bonding_curve)
+}
+
+/// Bonding curve PDA of `mint` under the pump.fun program
+pub fn bonding_curve_address(mint: &Pubkey) -> Result<Pubkey> {
+    get_pda(mint, &Pubkey::from_str(PUMP_FUN_PROGRAM)?)
+}
+
+/// Decode a bonding curve account update; trailing bytes past the known fields are ignored
+pub fn decode_bonding_curve(data: &[u8]) -> Option<BondingCurveAccount> {
+    borsh::BorshDeserialize::deserialize(&mut &data[..]).ok()
 }
\ No newline at end of file
@@ -21,12 +21,9 @@ use spl_associated_token_account::{
     instruction::create_associated_token_account_idempotent
 };
 use spl_token::ui_amount_to_amount;
-use tokio::sync::OnceCell;
-use lru::LruCache;
-use std::num::NonZeroUsize;
 
 use crate::{
-    common::{config::SwapConfig, logger::Logger, cache::WALLET_TOKEN_ACCOUNTS},
+    common::{config::SwapConfig, logger::Logger, cache::WALLET_TOKEN_ACCOUNTS, pool_state::POOL_STATE},
     core::token,
     engine::swap::{SwapDirection, SwapInType},
 };
@@ -41,14 +38,24 @@ lazy_static::lazy_static! {
     static ref RAYDIUM_CPMM_PROGRAM_ID: Pubkey = Pubkey::from_str("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C").unwrap();
 }
 
-// Thread-safe cache with LRU eviction policy
-static TOKEN_ACCOUNT_CACHE: OnceCell<LruCache<Pubkey, bool>> = OnceCell::const_new();
-
 const TEN_THOUSAND: u64 = 10000;
-const CACHE_SIZE: usize = 1000;
 
-async fn init_caches() {
-    TOKEN_ACCOUNT_CACHE.get_or_init(|| async {
-        LruCache::new(NonZeroUsize::new(CACHE_SIZE).unwrap())
-    }).await;
+/// Standard CPMM trade fee (0.25%), in millionths
+const CPMM_TRADE_FEE_RATE: u128 = 2_500;
+
+/// Constant-product output for `amount_in` against the streamed reserves of `pool_id`
+/// (base = token, quote = WSOL). None when the live copy is missing or stale, in which
+/// case the caller reads the vaults over RPC.
+pub fn quote_from_live_reserves(pool_id: &Pubkey, is_buy: bool, amount_in: u64) -> Option<u64> {
+    let pool = POOL_STATE.get(pool_id)?;
+    let (reserve_in, reserve_out) = if is_buy {
+        (pool.quote_reserve as u128, pool.base_reserve as u128)
+    } else {
+        (pool.base_reserve as u128, pool.quote_reserve as u128)
+    };
+    let amount_in = (amount_in as u128) * (1_000_000 - CPMM_TRADE_FEE_RATE) / 1_000_000;
+    reserve_out
+        .checked_mul(amount_in)?
+        .checked_div(reserve_in.checked_add(amount_in)?)
+        .map(|out| out as u64)
 }
@@ -0,0 +1,186 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use crate::common::persistence::{state_dir, unix_now};
+use crate::common::usd_price::usd_suffix;
+use crate::engine::market_maker::TokenActivityReport;
+
+/// State store key for the running day's activity totals and the previous day's rollup
+pub const ACTIVITY_ROLLUP_STATE_KEY: &str = "activity_rollup";
+
+/// Minutes between activity reports (ACTIVITY_REPORT_MINUTES, default 30, 0 = off)
+pub fn get_activity_report_minutes() -> u64 {
+    std::env::var("ACTIVITY_REPORT_MINUTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(30)
+}
+
+/// Trade counts and volume summed over one or more activity reports
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActivityTotals {
+    pub organic_trades: u32,
+    pub buy_trades: u32,
+    pub sell_trades: u32,
+    pub organic_volume_sol: f64,
+    pub buy_volume_sol: f64,
+    pub sell_volume_sol: f64,
+    pub bot_trades: u32,
+    pub bot_volume_sol: f64,
+    /// Estimated distinct organic makers
+    pub unique_traders: u32,
+    pub reports: u32,
+}
+
+impl ActivityTotals {
+    fn from_report(report: &TokenActivityReport) -> Self {
+        let mut totals = Self::default();
+        totals.add(report);
+        totals
+    }
+
+    fn add(&mut self, report: &TokenActivityReport) {
+        self.organic_trades += report.total_trades;
+        self.buy_trades += report.buy_trades;
+        self.sell_trades += report.sell_trades;
+        self.organic_volume_sol += report.total_volume_sol;
+        self.buy_volume_sol += report.buy_volume_sol;
+        self.sell_volume_sol += report.sell_volume_sol;
+        self.bot_trades += report.bot_trades;
+        self.bot_volume_sol += report.bot_volume_sol;
+        self.reports += 1;
+    }
+
+    pub fn bot_volume_share(&self) -> f64 {
+        let total = self.organic_volume_sol + self.bot_volume_sol;
+        if total > 0.0 { self.bot_volume_sol / total } else { 0.0 }
+    }
+}
+
+/// One UTC day of activity reports
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DailyActivity {
+    /// YYYY-MM-DD (UTC)
+    pub date: String,
+    pub totals: ActivityTotals,
+}
+
+impl DailyActivity {
+    /// End-of-day summary, with the change against `previous` when it is known
+    pub fn to_report(&self, previous: Option<&DailyActivity>, sol_usd: Option<f64>) -> String {
+        let t = &self.totals;
+        let mut report = format!(
+            "📅 Daily rollup {}: {} organic trades ({} buys / {} sells), {:.4} SOL{} organic volume, {} unique makers\n\
+             🤖 Bot {} trades / {:.4} SOL{} ({:.1}% of volume), {} reports",
+            self.date, t.organic_trades, t.buy_trades, t.sell_trades, t.organic_volume_sol,
+            usd_suffix(t.organic_volume_sol, sol_usd), t.unique_traders,
+            t.bot_trades, t.bot_volume_sol, usd_suffix(t.bot_volume_sol, sol_usd), t.bot_volume_share() * 100.0, t.reports
+        );
+        if let Some(previous) = previous {
+            let p = &previous.totals;
+            report.push_str(&format!(
+                "\n↕️ vs {}: trades {}, organic volume {}, unique makers {}, bot share {:+.1} pts",
+                previous.date,
+                percent_change(t.organic_trades as f64, p.organic_trades as f64),
+                percent_change(t.organic_volume_sol, p.organic_volume_sol),
+                percent_change(t.unique_traders as f64, p.unique_traders as f64),
+                (t.bot_volume_share() - p.bot_volume_share()) * 100.0
+            ));
+        }
+        report
+    }
+}
+
+fn percent_change(current: f64, previous: f64) -> String {
+    if previous > 0.0 {
+        format!("{:+.1}%", (current - previous) / previous * 100.0)
+    } else {
+        "n/a".to_string()
+    }
+}
+
+/// Running day totals plus the last finished day, persisted so the rollup survives restarts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActivityRollupState {
+    pub current: Option<DailyActivity>,
+    pub previous: Option<DailyActivity>,
+}
+
+impl ActivityRollupState {
+    /// Add a report to the totals for `today`. `day_unique_traders` is the trailing 24h
+    /// unique-maker estimate, kept as the day's figure. Returns the finished day when the UTC
+    /// date rolled over since the last report, together with the day before it for comparison.
+    pub fn record(&mut self, report: &TokenActivityReport, today: &str, day_unique_traders: u32) -> Option<(DailyActivity, Option<DailyActivity>)> {
+        let finished = match &self.current {
+            Some(day) if day.date != today => self.current.take(),
+            _ => None,
+        };
+        let rollup = finished.map(|day| {
+            let before = self.previous.replace(day.clone());
+            (day, before)
+        });
+        let day = self.current.get_or_insert_with(|| DailyActivity { date: today.to_string(), ..Default::default() });
+        day.totals.add(report);
+        day.totals.unique_traders = day_unique_traders;
+        rollup
+    }
+}
+
+/// Entry in `activity_reports.jsonl`, the activity side of the trade journal next to `fills.jsonl`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ActivityJournalEntry {
+    Report {
+        at: i64,
+        period_minutes: u64,
+        totals: ActivityTotals,
+        average_price: f64,
+        min_price: f64,
+        max_price: f64,
+        vwap: f64,
+        whales: usize,
+    },
+    Daily {
+        at: i64,
+        day: DailyActivity,
+        previous: Option<DailyActivity>,
+    },
+}
+
+impl ActivityJournalEntry {
+    pub fn report(report: &TokenActivityReport) -> Self {
+        let mut totals = ActivityTotals::from_report(report);
+        totals.unique_traders = report.unique_traders;
+        Self::Report {
+            at: unix_now(),
+            period_minutes: report.report_period_minutes,
+            totals,
+            average_price: report.average_price,
+            min_price: report.min_price,
+            max_price: report.max_price,
+            vwap: report.vwap,
+            whales: report.whales.len(),
+        }
+    }
+
+    pub fn daily(day: &DailyActivity, previous: Option<&DailyActivity>) -> Self {
+        Self::Daily {
+            at: unix_now(),
+            day: day.clone(),
+            previous: previous.cloned(),
+        }
+    }
+}
+
+/// Append an activity report or daily rollup to the on-disk journal
+pub fn append_activity_entry(entry: &ActivityJournalEntry) -> Result<()> {
+    let dir = state_dir();
+    std::fs::create_dir_all(&dir)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join("activity_reports.jsonl"))?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
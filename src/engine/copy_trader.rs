@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use colored::Colorize;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use yellowstone_grpc_proto::geyser::SubscribeUpdateTransactionInfo;
+use crate::common::cache::TARGET_WALLET_TOKENS;
+use crate::common::logger::Logger;
+use crate::engine::transaction_parser::resolve_account_keys;
+
+/// Copy-trading settings (COPY_TARGET_WALLETS, empty = disabled). Only trades in the target
+/// mint are mirrored: every execution path (trade queue, sizing and gates, swap) is bound to it.
+#[derive(Debug, Clone)]
+pub struct CopyTradeConfig {
+    pub targets: Vec<Pubkey>,
+    /// The one mint mirrored, TARGET_TOKEN_MINT
+    pub mint: String,
+    /// Our buy size as a fraction of the target's
+    pub size_scale: f64,
+    /// Upper bound for a single copied buy
+    pub max_buy_sol: f64,
+    pub min_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// Mirror sells as well as buys
+    pub copy_sells: bool,
+}
+
+impl CopyTradeConfig {
+    /// Read COPY_TARGET_WALLETS, COPY_SIZE_SCALE, COPY_MAX_BUY_SOL, COPY_MIN_DELAY_MS,
+    /// COPY_MAX_DELAY_MS and COPY_SELLS; the mint is TARGET_TOKEN_MINT
+    pub fn from_env() -> Self {
+        let get = |name: &str| std::env::var(name).ok();
+        Self {
+            targets: get("COPY_TARGET_WALLETS")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|w| Pubkey::from_str(w.trim()).ok())
+                .collect(),
+            mint: get("TARGET_TOKEN_MINT").unwrap_or_default().trim().to_string(),
+            size_scale: get("COPY_SIZE_SCALE").and_then(|v| v.parse().ok()).unwrap_or(0.5),
+            max_buy_sol: get("COPY_MAX_BUY_SOL").and_then(|v| v.parse().ok()).unwrap_or(1.0),
+            min_delay_ms: get("COPY_MIN_DELAY_MS").and_then(|v| v.parse().ok()).unwrap_or(500),
+            max_delay_ms: get("COPY_MAX_DELAY_MS").and_then(|v| v.parse().ok()).unwrap_or(3000),
+            copy_sells: get("COPY_SELLS").map(|v| v.trim().to_lowercase() != "false").unwrap_or(true),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.targets.is_empty()
+    }
+}
+
+/// A buy or sell by a target wallet, as seen in the stream
+#[derive(Debug, Clone)]
+pub struct TargetTrade {
+    pub target: String,
+    pub mint: String,
+    pub is_buy: bool,
+    pub sol_amount: f64,
+    /// Share of the target's holding that was sold (sells only)
+    pub sell_fraction: f64,
+}
+
+/// Mirrored trade, queued for execution once `delay` has elapsed
+#[derive(Debug, Clone)]
+pub struct CopyOrder {
+    pub target: String,
+    pub mint: String,
+    pub is_buy: bool,
+    pub wallet: String,
+    /// SOL to spend (buys)
+    pub sol_amount: f64,
+    /// Share of the wallet's copied position to sell (sells)
+    pub sell_fraction: f64,
+    pub delay: Duration,
+}
+
+/// Work out what a target wallet traded in this transaction from its balance changes
+pub fn parse_target_trade(info: &SubscribeUpdateTransactionInfo, target: &Pubkey) -> Option<TargetTrade> {
+    let meta = info.meta.as_ref()?;
+    let owner = target.to_string();
+    let native_mint = spl_token::native_mint::ID.to_string();
+
+    let mut deltas: HashMap<&str, (f64, f64)> = HashMap::new();
+    for balance in meta.pre_token_balances.iter().filter(|b| b.owner == owner && b.mint != native_mint) {
+        let amount = balance.ui_token_amount.as_ref().map(|a| a.ui_amount).unwrap_or(0.0);
+        deltas.entry(balance.mint.as_str()).or_default().0 += amount;
+    }
+    for balance in meta.post_token_balances.iter().filter(|b| b.owner == owner && b.mint != native_mint) {
+        let amount = balance.ui_token_amount.as_ref().map(|a| a.ui_amount).unwrap_or(0.0);
+        deltas.entry(balance.mint.as_str()).or_default().1 += amount;
+    }
+    let (mint, (before, after)) = deltas
+        .into_iter()
+        .filter(|(_, (before, after))| before != after)
+        .max_by(|a, b| (a.1.1 - a.1.0).abs().partial_cmp(&(b.1.1 - b.1.0).abs()).unwrap_or(std::cmp::Ordering::Equal))?;
+
+    let index = resolve_account_keys(info).iter().position(|key| key == target)?;
+    let pre = *meta.pre_balances.get(index)? as f64;
+    let post = *meta.post_balances.get(index)? as f64;
+    let is_buy = after > before;
+
+    Some(TargetTrade {
+        target: owner,
+        mint: mint.to_string(),
+        is_buy,
+        sol_amount: (pre - post).abs() / 1_000_000_000.0,
+        sell_fraction: if is_buy || before <= 0.0 { 0.0 } else { ((before - after) / before).clamp(0.0, 1.0) },
+    })
+}
+
+/// Mirrors target wallet trades with scaled size and randomized delay across the wallet pool
+pub struct CopyTrader {
+    config: CopyTradeConfig,
+    // mint -> pool wallets holding a copied position
+    copied: HashMap<String, Vec<String>>,
+    logger: Logger,
+}
+
+impl CopyTrader {
+    pub fn new(config: CopyTradeConfig) -> Self {
+        let logger = Logger::new("[COPY-TRADER] => ".cyan().bold().to_string());
+        if config.is_enabled() {
+            logger.log(format!(
+                "👥 Copying target-mint trades of {} wallet(s) at {}x size (max {} SOL), {}-{} ms delay",
+                config.targets.len(), config.size_scale, config.max_buy_sol, config.min_delay_ms, config.max_delay_ms
+            ).cyan().to_string());
+        }
+        Self {
+            config,
+            copied: HashMap::new(),
+            logger,
+        }
+    }
+
+    fn random_delay(&self) -> Duration {
+        let min = self.config.min_delay_ms.min(self.config.max_delay_ms);
+        let max = self.config.max_delay_ms.max(min);
+        Duration::from_millis(rand::thread_rng().gen_range(min..=max))
+    }
+
+    /// Turn a target trade into copy orders. Buys go to a random pool wallet; sells are
+    /// mirrored proportionally by every wallet holding a copied position in that mint.
+    /// Trades in any mint other than the target mint yield no orders.
+    pub fn on_target_trade(&mut self, trade: &TargetTrade, pool_wallets: &[String]) -> Vec<CopyOrder> {
+        if trade.mint != self.config.mint {
+            self.logger.debug(format!("👥 {} traded {}, not the target mint, not copied", trade.target, trade.mint));
+            return Vec::new();
+        }
+        if trade.is_buy {
+            TARGET_WALLET_TOKENS.insert(trade.mint.clone());
+            let sol_amount = (trade.sol_amount * self.config.size_scale).min(self.config.max_buy_sol);
+            let Some(wallet) = pool_wallets.choose(&mut rand::thread_rng()).cloned() else { return Vec::new() };
+            if sol_amount <= 0.0 {
+                return Vec::new();
+            }
+            self.copied.entry(trade.mint.clone()).or_default().push(wallet.clone());
+            self.logger.log(format!(
+                "👥 {} bought {} for {:.4} SOL, copying {:.4} SOL from {}",
+                trade.target, trade.mint, trade.sol_amount, sol_amount, wallet
+            ).cyan().to_string());
+            return vec![CopyOrder {
+                target: trade.target.clone(),
+                mint: trade.mint.clone(),
+                is_buy: true,
+                wallet,
+                sol_amount,
+                sell_fraction: 0.0,
+                delay: self.random_delay(),
+            }];
+        }
+
+        if trade.sell_fraction >= 0.999 {
+            TARGET_WALLET_TOKENS.remove(&trade.mint);
+        }
+        if !self.config.copy_sells {
+            return Vec::new();
+        }
+        let wallets = match self.copied.get(&trade.mint) {
+            Some(wallets) => wallets.clone(),
+            None => return Vec::new(),
+        };
+        if trade.sell_fraction >= 0.999 {
+            self.copied.remove(&trade.mint);
+        }
+        self.logger.log(format!(
+            "👥 {} sold {:.0}% of {}, mirroring on {} wallet(s)",
+            trade.target, trade.sell_fraction * 100.0, trade.mint, wallets.len()
+        ).cyan().to_string());
+        wallets.into_iter()
+            .map(|wallet| CopyOrder {
+                target: trade.target.clone(),
+                mint: trade.mint.clone(),
+                is_buy: false,
+                wallet,
+                sol_amount: 0.0,
+                sell_fraction: trade.sell_fraction,
+                delay: self.random_delay(),
+            })
+            .collect()
+    }
+}
@@ -1,44 +1,81 @@
 use std::sync::Arc;
 use std::time::Duration;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use tokio::time::Instant;
 use anyhow::Result;
 use anchor_client::solana_sdk::signature::Signature;
 use anchor_client::solana_sdk::signer::Signer;
 use anchor_client::solana_sdk::pubkey::Pubkey;
-use anchor_client::solana_sdk::signature::Keypair;
 use anchor_client::solana_sdk::system_instruction;
 use anchor_client::solana_sdk::transaction::Transaction;
 use colored::Colorize;
 use solana_transaction_status;
 use tokio::time;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex, OwnedMutexGuard};
+use tracing::Instrument;
+use once_cell::sync::OnceCell;
+use uuid::Uuid;
 use futures_util::stream::StreamExt;
 use futures_util::{SinkExt, Sink};
 use yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcClient};
 use yellowstone_grpc_proto::geyser::{
     subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest, SubscribeRequestPing,
-    SubscribeRequestFilterTransactions, SubscribeUpdate,
+    SubscribeRequestFilterTransactions, SubscribeRequestFilterAccounts, SubscribeUpdate,
+    SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo,
 };
 use crate::engine::transaction_parser;
 use crate::common::{
-    config::{AppState, SwapConfig, JUPITER_PROGRAM, OKX_DEX_PROGRAM},
+    config::{AppState, Config, SwapConfig, JUPITER_PROGRAM, OKX_DEX_PROGRAM},
     logger::Logger,
-    wallet_pool::{WalletPool, RandomizationConfig, TradeType},
-    price_monitor::{GlobalPriceMonitor, create_global_price_monitor},
-    dynamic_ratios::{GlobalDynamicRatioManager, create_global_dynamic_ratio_manager},
-    volume_waves::{GlobalVolumeWaveManager, create_global_volume_wave_manager},
-    guardian_mode::{GlobalGuardianMode, create_global_guardian_mode},
+    wallet_pool::{WalletPool, RandomizationConfig, TradeType, WalletPoolSnapshot, WALLET_POOL_STATE_KEY},
+    price_monitor::{GlobalPriceMonitor, create_global_price_monitor, Candle, CandleInterval, CrossoverDetector, MovingAverageConfig, BollingerConfig, PriceThrottleConfig, ThrottleMode,
+        PriceHistorySnapshot, PRICE_HISTORY_STATE_KEY, VwapConfig, vwap_deviation_pct},
+    dynamic_ratios::{GlobalDynamicRatioManager, DynamicRatioState, DYNAMIC_RATIO_STATE_KEY, create_global_dynamic_ratio_manager},
+    volume_waves::{GlobalVolumeWaveManager, WaveConfig, create_global_volume_wave_manager},
+    guardian_mode::{GlobalGuardianMode, GuardianConfig, GuardianState, create_global_guardian_mode, GUARDIAN_STATE_KEY},
+    pump_guardian::{GlobalPumpGuardian, PumpGuardianConfig, PumpGuardianState, create_global_pump_guardian, PUMP_GUARDIAN_STATE_KEY},
+    twap::{GlobalTwapAccumulator, TwapConfig, TwapProgress, create_global_twap_accumulator},
+    spend_limit::{GlobalSpendCircuitBreaker, SpendDecision, SpendEntry, SPEND_WINDOW_STATE_KEY, create_global_spend_circuit_breaker, get_daily_spend_cap},
+    rate_limit::{GlobalTradeRateLimiter, RateLimitConfig, RateLimited, create_global_trade_rate_limiter},
+    drawdown::{DrawdownConfig, DrawdownMonitor},
+    liquidity::{GlobalLiquidityGuard, LiquidityConfig, LiquidityEvent, create_global_liquidity_guard},
+    inventory::{GlobalInventoryGuard, InventoryConfig, create_global_inventory_guard},
+    rug_detection::{RugDetectionConfig, RugDetector},
+    creator_monitor::{CreatorMonitorConfig, token_outflow},
 };
-use crate::dex::{raydium_cpmm::RaydiumCPMM, dex_manager::DexManager};
+use crate::dex::{raydium_cpmm::RaydiumCPMM, dex_manager::DexManager, pump_fun};
 use crate::engine::swap::{SwapDirection, SwapInType};
 use crate::core::token;
 use spl_token::instruction::sync_native;
-use spl_associated_token_account::{get_associated_token_address, instruction::create_associated_token_account_idempotent};
+use spl_associated_token_account::{get_associated_token_address_with_program_id, instruction::create_associated_token_account_idempotent};
+use spl_token_2022::extension::{StateWithExtensions, StateWithExtensionsOwned};
 use solana_program_pack::Pack;
 use std::str::FromStr;
 use rand::Rng;
-use crate::engine::transaction_parser::{parse_target_token_transaction, TradeInfoFromToken, DexType};
+use serde::{Deserialize, Serialize};
+use crate::engine::transaction_parser::{parse_target_token_transaction, TradeInfoFromToken, TransactionAnalysis, DexType, ParseStatsSnapshot, PARSE_STATS};
+use crate::common::cache::{TOKEN_MINT_CACHE, invalidate_after_swap};
+use crate::common::persistence::{StateStore, unix_now};
+use crate::common::usd_price::{usd_suffix, USD_PRICES};
+use crate::common::order_flow::{GlobalOrderFlowTracker, OrderFlowConfig, OrderFlowSnapshot, create_global_order_flow_tracker};
+use crate::common::unique_counter::WindowedUniqueCounter;
+use crate::common::pool_state::{PoolStateConfig, POOL_STATE, token_account_amount};
+use crate::services::shutdown::{SHUTDOWN, get_shutdown_timeout};
+use crate::services::telegram;
+use crate::services::control::CONTROL;
+use crate::services::kill_switch::KILL_SWITCH;
+use crate::services::approval::{APPROVALS, get_approval_threshold};
+use crate::services::config_reload::spawn_config_watcher;
+use crate::services::holdings::save_wallet_token_accounts;
+use crate::engine::runtime_params::{RuntimeParams, RuntimeParamsSnapshot, get_runtime_params, register_runtime_params};
+use crate::engine::sandwich::{GlobalSandwichDetector, SandwichConfig, SlotTrade, create_global_sandwich_detector};
+use crate::engine::copy_trader::{CopyTradeConfig, CopyTrader, CopyOrder};
+use crate::engine::monitor::{InstructionType, PoolInfo, TargetWalletEvent, TargetWalletMonitor, WalletMonitorConfig};
+use crate::engine::activity_journal::{ActivityJournalEntry, ActivityRollupState, append_activity_entry, get_activity_report_minutes, ACTIVITY_ROLLUP_STATE_KEY};
+use crate::engine::trade_queue::{QueuedTrade, TradeFill, TradeQueue, WalletState, spawn_trade_workers};
+use crate::engine::positions::{
+    GlobalPositionEngine, PositionExit, PositionRules, PnlSummary, Fill, PositionLedgerSnapshot, append_fill, create_global_position_engine, POSITION_LEDGER_STATE_KEY,
+};
 
 // Activity tracking structures for token analysis
 #[derive(Debug, Clone)]
@@ -61,8 +98,209 @@ pub struct TokenActivityReport {
     pub average_price: f64,
     pub min_price: f64,
     pub max_price: f64,
+    /// Estimated distinct organic traders (HyperLogLog, within ~2%)
     pub unique_traders: u32,
     pub report_period_minutes: u64,
+    /// Volume-weighted average price over the report period
+    pub vwap: f64,
+    /// Volume-weighted average price since the bot started
+    pub session_vwap: f64,
+    /// Most recent trade price in the period
+    pub last_price: f64,
+    /// External trades at or above WHALE_ALERT_SOL, largest first
+    pub whales: Vec<TokenActivity>,
+    /// Trades by the bot's own wallets, excluded from the counts and volume above
+    pub bot_trades: u32,
+    pub bot_volume_sol: f64,
+    /// Stream parser counters since start
+    pub parse_stats: ParseStatsSnapshot,
+}
+
+impl TokenActivityReport {
+    /// Share of all parsed volume in the period that came from the bot's own wallets
+    pub fn bot_volume_share(&self) -> f64 {
+        let total = self.total_volume_sol + self.bot_volume_sol;
+        if total > 0.0 { self.bot_volume_sol / total } else { 0.0 }
+    }
+
+    /// Share of all parsed trades in the period made by the bot's own wallets
+    pub fn bot_trade_share(&self) -> f64 {
+        let total = self.total_trades + self.bot_trades;
+        if total > 0 { self.bot_trades as f64 / total as f64 } else { 0.0 }
+    }
+
+    /// Deviation of the period's newest price from the session VWAP, in percent
+    pub fn last_price_deviation_pct(&self) -> f64 {
+        vwap_deviation_pct(self.last_price, self.session_vwap)
+    }
+
+    /// Plain-text report for the log and Telegram; USD values are added when `sol_usd` is known
+    pub fn to_report(&self, sol_usd: Option<f64>) -> String {
+        let mut report = format!(
+            "📊 Activity (last {} min): {} trades ({} buys / {} sells), {:.4} SOL{} volume ({:.4} buy / {:.4} sell), \
+             price avg {:.10}{} min {:.10} max {:.10}\n\
+             🤖 Organic {} trades / {:.4} SOL from {} unique makers vs bot {} trades / {:.4} SOL{} \
+             (bot share {:.1}% of trades, {:.1}% of volume)",
+            self.report_period_minutes, self.total_trades, self.buy_trades, self.sell_trades,
+            self.total_volume_sol, usd_suffix(self.total_volume_sol, sol_usd), self.buy_volume_sol, self.sell_volume_sol,
+            self.average_price, sol_usd.map(|usd| format!(" (${:.8})", self.average_price * usd)).unwrap_or_default(),
+            self.min_price, self.max_price,
+            self.total_trades, self.total_volume_sol, self.unique_traders, self.bot_trades, self.bot_volume_sol,
+            usd_suffix(self.bot_volume_sol, sol_usd), self.bot_trade_share() * 100.0, self.bot_volume_share() * 100.0
+        );
+        if self.vwap > 0.0 {
+            report.push_str(&format!(
+                "\n📏 VWAP {:.10} (session {:.10}, last price {:+.2}% vs session)",
+                self.vwap, self.session_vwap, self.last_price_deviation_pct()
+            ));
+        }
+        if self.parse_stats.total_misses() > 0 {
+            report.push_str(&format!("\n🧩 Parser: {}", self.parse_stats.to_report()));
+        }
+        if !self.whales.is_empty() {
+            report.push_str(&format!("\n🐋 Whales ({}):", self.whales.len()));
+            for whale in &self.whales {
+                report.push_str(&format!(
+                    "\n  {} {:.4} SOL{} by {} ({} min ago)",
+                    if whale.is_buy { "BUY " } else { "SELL" }, whale.volume_sol, usd_suffix(whale.volume_sol, sol_usd),
+                    whale.user, whale.timestamp.elapsed().as_secs() / 60
+                ));
+            }
+        }
+        report
+    }
+}
+
+/// External trades at or above this size trigger a whale alert (WHALE_ALERT_SOL, 0 = disabled).
+/// Read once; a value that isn't a SOL amount turns alerts off with a warning.
+pub fn get_whale_alert_threshold() -> f64 {
+    static THRESHOLD: OnceCell<f64> = OnceCell::new();
+    *THRESHOLD.get_or_init(|| {
+        let value = match std::env::var("WHALE_ALERT_SOL") {
+            Ok(value) if !value.trim().is_empty() => value,
+            _ => return 0.0,
+        };
+        match value.trim().parse::<f64>() {
+            Ok(sol) if sol.is_finite() && sol >= 0.0 => sol,
+            _ => {
+                tracing::warn!("WHALE_ALERT_SOL={} is not a SOL amount; whale alerts are off", value);
+                0.0
+            },
+        }
+    })
+}
+
+/// Base swap slippage from SLIPPAGE (bps), when set to a valid value
+fn get_slippage_override() -> Option<u64> {
+    std::env::var("SLIPPAGE").ok().and_then(|v| v.trim().parse::<u64>().ok())
+}
+
+/// Activities older than this are dropped from the tracking window
+const ACTIVITY_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+/// Hard cap on tracked activities, so busy pools can't grow the window without bound
+const MAX_TRACKED_ACTIVITIES: usize = 100_000;
+/// Time bucket of the unique-trader sketches
+const UNIQUE_TRADER_BUCKET: Duration = Duration::from_secs(5 * 60);
+
+/// Parsed trades buffered per trade-event subscriber before it starts lagging
+pub const TRADE_EVENT_CAPACITY: usize = 1024;
+
+/// Own signatures remembered for matching against the stream
+const OWN_SIGNATURE_HISTORY: usize = 4096;
+
+/// How often position rules are checked for exits while the engine runs
+const POSITION_EXIT_CHECK: Duration = Duration::from_secs(5);
+
+/// Hours a drawn buy ratio holds before the dynamic ratio manager draws the next one
+const BUY_RATIO_CHANGE_HOURS: u64 = 24;
+
+/// Signatures the bot submitted and the wallets that signed them, so stream trades from
+/// wallets no longer in the pool are still recognised as our own
+#[derive(Debug, Default)]
+pub struct OwnTradeTracker {
+    signatures: HashSet<String>,
+    order: VecDeque<String>,
+    wallets: HashSet<String>,
+}
+
+impl OwnTradeTracker {
+    pub fn record(&mut self, signature: &str, wallet: &str) {
+        self.wallets.insert(wallet.to_string());
+        if self.signatures.insert(signature.to_string()) {
+            self.order.push_back(signature.to_string());
+        }
+        while self.order.len() > OWN_SIGNATURE_HISTORY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.signatures.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn is_own_signature(&self, signature: &str) -> bool {
+        self.signatures.contains(signature)
+    }
+
+    pub fn wallets(&self) -> &HashSet<String> {
+        &self.wallets
+    }
+}
+
+/// State store key for the market maker session state
+pub const MARKET_MAKER_STATE_KEY: &str = "market_maker";
+/// State store key for TWAP accumulation progress
+pub const TWAP_STATE_KEY: &str = "twap";
+
+/// Session marker persisted across restarts: whether the last run stopped cleanly, and when
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MarketMakerState {
+    pub clean_shutdown: bool,
+    pub saved_at: i64,
+}
+
+/// `true` / `false` from the environment; anything else keeps `default`, with a warning if set
+fn env_flag(name: &str, default: bool) -> bool {
+    match std::env::var(name).map(|v| v.trim().to_lowercase()) {
+        Ok(v) if v == "true" => true,
+        Ok(v) if v == "false" => false,
+        Ok(v) if !v.is_empty() => {
+            tracing::warn!("{}={} is not true or false; using {}", name, v, default);
+            default
+        },
+        _ => default,
+    }
+}
+
+/// Optional subsystems that can be switched off individually (all enabled by default)
+#[derive(Debug, Clone, Copy)]
+pub struct SubsystemToggles {
+    pub volume_waves: bool,
+    pub dynamic_ratios: bool,
+    pub guardian_mode: bool,
+    pub price_monitor: bool,
+}
+
+impl Default for SubsystemToggles {
+    fn default() -> Self {
+        Self {
+            volume_waves: true,
+            dynamic_ratios: true,
+            guardian_mode: true,
+            price_monitor: true,
+        }
+    }
+}
+
+impl SubsystemToggles {
+    /// Read ENABLE_VOLUME_WAVES, ENABLE_DYNAMIC_RATIOS, GUARDIAN_MODE_ENABLED and ENABLE_PRICE_MONITOR
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            volume_waves: env_flag("ENABLE_VOLUME_WAVES", defaults.volume_waves),
+            dynamic_ratios: env_flag("ENABLE_DYNAMIC_RATIOS", defaults.dynamic_ratios),
+            guardian_mode: env_flag("GUARDIAN_MODE_ENABLED", defaults.guardian_mode),
+            price_monitor: env_flag("ENABLE_PRICE_MONITOR", defaults.price_monitor),
+        }
+    }
 }
 
 /// Configuration for market maker bot with advanced multi-wallet support
@@ -78,6 +316,26 @@ pub struct MarketMakerConfig {
     pub enable_multi_wallet: bool,
     pub max_concurrent_trades: usize,
     pub enable_telegram_notifications: bool,
+    pub subsystems: SubsystemToggles,
+    pub twap: TwapConfig,
+    pub inventory: InventoryConfig,
+    pub rate_limits: RateLimitConfig,
+    pub drawdown: DrawdownConfig,
+    pub liquidity: LiquidityConfig,
+    pub rug_detection: RugDetectionConfig,
+    pub creator_monitor: CreatorMonitorConfig,
+    pub sandwich: SandwichConfig,
+    pub copy_trading: CopyTradeConfig,
+    pub moving_averages: MovingAverageConfig,
+    pub bollinger: BollingerConfig,
+    pub order_flow: OrderFlowConfig,
+    pub price_throttle: PriceThrottleConfig,
+    pub vwap: VwapConfig,
+    pub guardian: GuardianConfig,
+    pub pump_guardian: PumpGuardianConfig,
+    pub volume_waves: WaveConfig,
+    pub wallet_monitor: WalletMonitorConfig,
+    pub pool_state: PoolStateConfig,
     pub dex_type: DexType,
     // Pool configuration for Raydium CPMM
     pub pool_id: String,
@@ -108,7 +366,27 @@ impl MarketMakerConfig {
             randomization_config: RandomizationConfig::stealth_mode(),
             enable_multi_wallet: true,
             max_concurrent_trades: 3,
-            enable_telegram_notifications: true,
+            enable_telegram_notifications: telegram::is_enabled(),
+            subsystems: SubsystemToggles::from_env(),
+            twap: TwapConfig::from_env(),
+            inventory: InventoryConfig::from_env(),
+            rate_limits: RateLimitConfig::from_env(),
+            drawdown: DrawdownConfig::from_env(),
+            liquidity: LiquidityConfig::from_env(),
+            rug_detection: RugDetectionConfig::from_env(),
+            creator_monitor: CreatorMonitorConfig::from_env(),
+            sandwich: SandwichConfig::from_env(),
+            copy_trading: CopyTradeConfig::from_env(),
+            moving_averages: MovingAverageConfig::from_env(),
+            bollinger: BollingerConfig::from_env(),
+            order_flow: OrderFlowConfig::from_env(),
+            price_throttle: PriceThrottleConfig::from_env(),
+            vwap: VwapConfig::from_env(),
+            guardian: GuardianConfig::from_env(),
+            pump_guardian: PumpGuardianConfig::from_env(),
+            volume_waves: WaveConfig::from_env(),
+            wallet_monitor: WalletMonitorConfig::from_env(),
+            pool_state: PoolStateConfig::from_env(),
             dex_type,
             // Pool configuration from parameters
             pool_id,
@@ -139,7 +417,27 @@ impl MarketMakerConfig {
             randomization_config: RandomizationConfig::conservative_mode(),
             enable_multi_wallet: true,
             max_concurrent_trades: 2,
-            enable_telegram_notifications: true,
+            enable_telegram_notifications: telegram::is_enabled(),
+            subsystems: SubsystemToggles::from_env(),
+            twap: TwapConfig::from_env(),
+            inventory: InventoryConfig::from_env(),
+            rate_limits: RateLimitConfig::from_env(),
+            drawdown: DrawdownConfig::from_env(),
+            liquidity: LiquidityConfig::from_env(),
+            rug_detection: RugDetectionConfig::from_env(),
+            creator_monitor: CreatorMonitorConfig::from_env(),
+            sandwich: SandwichConfig::from_env(),
+            copy_trading: CopyTradeConfig::from_env(),
+            moving_averages: MovingAverageConfig::from_env(),
+            bollinger: BollingerConfig::from_env(),
+            order_flow: OrderFlowConfig::from_env(),
+            price_throttle: PriceThrottleConfig::from_env(),
+            vwap: VwapConfig::from_env(),
+            guardian: GuardianConfig::from_env(),
+            pump_guardian: PumpGuardianConfig::from_env(),
+            volume_waves: WaveConfig::from_env(),
+            wallet_monitor: WalletMonitorConfig::from_env(),
+            pool_state: PoolStateConfig::from_env(),
             dex_type,
             // Pool configuration from parameters
             pool_id,
@@ -170,7 +468,27 @@ impl MarketMakerConfig {
             randomization_config: RandomizationConfig::default(),
             enable_multi_wallet: true,
             max_concurrent_trades: 2,
-            enable_telegram_notifications: true,
+            enable_telegram_notifications: telegram::is_enabled(),
+            subsystems: SubsystemToggles::from_env(),
+            twap: TwapConfig::from_env(),
+            inventory: InventoryConfig::from_env(),
+            rate_limits: RateLimitConfig::from_env(),
+            drawdown: DrawdownConfig::from_env(),
+            liquidity: LiquidityConfig::from_env(),
+            rug_detection: RugDetectionConfig::from_env(),
+            creator_monitor: CreatorMonitorConfig::from_env(),
+            sandwich: SandwichConfig::from_env(),
+            copy_trading: CopyTradeConfig::from_env(),
+            moving_averages: MovingAverageConfig::from_env(),
+            bollinger: BollingerConfig::from_env(),
+            order_flow: OrderFlowConfig::from_env(),
+            price_throttle: PriceThrottleConfig::from_env(),
+            vwap: VwapConfig::from_env(),
+            guardian: GuardianConfig::from_env(),
+            pump_guardian: PumpGuardianConfig::from_env(),
+            volume_waves: WaveConfig::from_env(),
+            wallet_monitor: WalletMonitorConfig::from_env(),
+            pool_state: PoolStateConfig::from_env(),
             dex_type,
             // Pool configuration from parameters
             pool_id,
@@ -187,9 +505,6 @@ pub struct MarketMaker {
     logger: Logger,
     is_running: Arc<tokio::sync::RwLock<bool>>,
     recent_trades: Arc<Mutex<VecDeque<TradeType>>>,
-    trade_counter: Arc<Mutex<u32>>,
-    current_wallet: Arc<Mutex<Option<Arc<anchor_client::solana_sdk::signature::Keypair>>>>,
-    wallet_change_counter: Arc<Mutex<u32>>,
     token_activities: Arc<Mutex<VecDeque<TokenActivity>>>,
     last_activity_report: Arc<Mutex<Instant>>,
     price_monitor: GlobalPriceMonitor,
@@ -197,4 +512,2269 @@ pub struct MarketMaker {
     volume_wave_manager: GlobalVolumeWaveManager,
     guardian_mode: GlobalGuardianMode,
     dex_manager: Arc<Mutex<Option<DexManager>>>,
+    // Base swap slippage (bps); SLIPPAGE when set, else the mode's value, and can be hot-reloaded
+    live_slippage: Arc<tokio::sync::RwLock<u64>>,
+    // Net position schedule for TWAP accumulation mode (inert unless config.twap.enabled)
+    twap_accumulator: GlobalTwapAccumulator,
+    // Per-wallet positions and exit rules (take-profit ladder, stop-loss)
+    position_engine: GlobalPositionEngine,
+    // Rolling 24h net SOL cap for new buys
+    spend_breaker: GlobalSpendCircuitBreaker,
+    // Hard trades-per-hour / per-wallet-per-day limits, independent of the randomized intervals
+    rate_limiter: GlobalTradeRateLimiter,
+    // Quote-side pool liquidity guard for new buys
+    liquidity_guard: GlobalLiquidityGuard,
+    inventory_guard: GlobalInventoryGuard,
+    // Buys are paused until this instant after the coin creator moves tokens out
+    creator_buy_pause: Arc<Mutex<Option<Instant>>>,
+    // Same-slot sandwich matching for our confirmed trades, with slippage adaptation
+    sandwich_detector: GlobalSandwichDetector,
+    // Rolling buy / sell volume of external trades
+    order_flow: GlobalOrderFlowTracker,
+    // Sell-side guardian that distributes into abnormal pumps
+    pump_guardian: GlobalPumpGuardian,
+    // Every parsed stream trade, for subscribers outside the geyser loop (TRADE_EVENT_CAPACITY)
+    trade_events: broadcast::Sender<TransactionAnalysis>,
+    // Our submitted signatures and their signers, for telling bot volume from organic volume
+    own_trades: Arc<Mutex<OwnTradeTracker>>,
+    // Distinct organic traders per UNIQUE_TRADER_BUCKET over ACTIVITY_RETENTION, fixed memory
+    unique_traders: Arc<Mutex<WindowedUniqueCounter>>,
+    // Exit sells queued but not finished by trade id, so later checks don't sell the same
+    // tokens again and each exit's rule markers are settled when its trade ends
+    queued_exits: Arc<Mutex<HashMap<Uuid, PositionExit>>>,
+    // Owner of the target mint (SPL Token or Token-2022), read once
+    target_token_program: OnceCell<Pubkey>,
+}
+
+/// Shared handles to the session state that gets persisted
+#[derive(Clone)]
+struct SessionHandles {
+    wallet_pool: Arc<Mutex<WalletPool>>,
+    twap_accumulator: GlobalTwapAccumulator,
+    position_engine: GlobalPositionEngine,
+    token_activities: Arc<Mutex<VecDeque<TokenActivity>>>,
+    spend_breaker: GlobalSpendCircuitBreaker,
+    price_monitor: GlobalPriceMonitor,
+    guardian_mode: GlobalGuardianMode,
+    pump_guardian: GlobalPumpGuardian,
+    dynamic_ratio_manager: GlobalDynamicRatioManager,
+}
+
+impl SessionHandles {
+    async fn collect_state(&self, clean_shutdown: bool) -> MarketMakerState {
+        MarketMakerState {
+            clean_shutdown,
+            saved_at: unix_now(),
+        }
+    }
+
+    /// Flush wallet pool, positions and the session marker to the state store
+    async fn persist(&self, store: &StateStore, clean_shutdown: bool, logger: &Logger) -> MarketMakerState {
+        if let Err(e) = self.wallet_pool.lock().await.save_state(store) {
+            logger.log(format!("❌ Failed to save wallet pool state: {}", e).red().to_string());
+        }
+
+        if let Err(e) = save_wallet_token_accounts(store) {
+            logger.log(format!("❌ Failed to save wallet token accounts: {}", e).red().to_string());
+        }
+
+        let twap = self.twap_accumulator.lock().await;
+        if twap.is_active() || twap.get_status().completed {
+            if let Err(e) = store.save(TWAP_STATE_KEY, &twap.progress()) {
+                logger.log(format!("❌ Failed to save TWAP progress: {}", e).red().to_string());
+            }
+        }
+        drop(twap);
+
+        let spend_window = self.spend_breaker.lock().await.entries();
+        if let Err(e) = store.save(SPEND_WINDOW_STATE_KEY, &spend_window) {
+            logger.log(format!("❌ Failed to save spend window: {}", e).red().to_string());
+        }
+
+        let ledger = self.position_engine.lock().await.snapshot();
+        if let Err(e) = store.save(POSITION_LEDGER_STATE_KEY, &ledger) {
+            logger.log(format!("❌ Failed to save position ledger: {}", e).red().to_string());
+        }
+
+        let mut price_history = self.price_monitor.lock().await.history_snapshot();
+        price_history.guardian_points = self.guardian_mode.lock().await.price_points();
+        if let Err(e) = store.save(PRICE_HISTORY_STATE_KEY, &price_history) {
+            logger.log(format!("❌ Failed to save price history: {}", e).red().to_string());
+        }
+
+        if let Err(e) = store.save(GUARDIAN_STATE_KEY, &self.guardian_mode.lock().await.state()) {
+            logger.log(format!("❌ Failed to save guardian state: {}", e).red().to_string());
+        }
+        if let Err(e) = store.save(PUMP_GUARDIAN_STATE_KEY, &self.pump_guardian.lock().await.state()) {
+            logger.log(format!("❌ Failed to save pump guardian state: {}", e).red().to_string());
+        }
+        if let Err(e) = store.save(DYNAMIC_RATIO_STATE_KEY, &self.dynamic_ratio_manager.lock().await.state()) {
+            logger.log(format!("❌ Failed to save buy ratio state: {}", e).red().to_string());
+        }
+
+        let state = self.collect_state(clean_shutdown).await;
+        if let Err(e) = store.save(MARKET_MAKER_STATE_KEY, &state) {
+            logger.log(format!("❌ Failed to save market maker state: {}", e).red().to_string());
+        }
+        state
+    }
+}
+
+/// Result of reloading persisted state on startup
+#[derive(Debug, Clone)]
+pub struct RecoveryReport {
+    pub unclean_shutdown: bool,
+    /// Open positions reloaded into the position engine
+    pub restored_positions: usize,
+    /// Tokens held across those positions
+    pub restored_tokens: f64,
+}
+
+/// How often session state is checkpointed while running
+fn get_checkpoint_interval() -> Duration {
+    let secs = std::env::var("STATE_CHECKPOINT_SECONDS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(30);
+    Duration::from_secs(secs.max(1))
+}
+
+impl MarketMaker {
+    /// Build the engine from its config and the app settings (price change threshold, buy ratio
+    /// bounds and wave phase hours). Its runtime params are registered here, so the control socket
+    /// and webhooks can read and set them from the start; nothing else runs until `start_engine`.
+    pub fn new(config: MarketMakerConfig, settings: &Config) -> Result<Self> {
+        // Ratio, wave and trade limit settings get the same checks as a live update
+        RuntimeParamsSnapshot {
+            min_buy_ratio: settings.min_buy_ratio,
+            max_buy_ratio: settings.max_buy_ratio,
+            current_buy_ratio: settings.min_buy_ratio,
+            wave_active_hours: settings.volume_wave_active_hours,
+            wave_slow_hours: settings.volume_wave_slow_hours,
+            slippage_bps: get_slippage_override().unwrap_or(config.slippage),
+            daily_spend_cap_sol: get_daily_spend_cap(),
+            rate_limits: config.rate_limits,
+        }
+        .validate()
+        .map_err(|e| anyhow::anyhow!("Invalid market maker settings: {}", e))?;
+        let wallet_pool = WalletPool::new()
+            .map_err(|e| anyhow::anyhow!("Failed to load wallet pool: {}", e))?;
+        let live_slippage = Arc::new(tokio::sync::RwLock::new(get_slippage_override().unwrap_or(config.slippage)));
+
+        let engine = Self {
+            wallet_pool: Arc::new(Mutex::new(wallet_pool)),
+            logger: Logger::new("[MARKET-MAKER] => ".green().bold().to_string()),
+            is_running: Arc::new(tokio::sync::RwLock::new(false)),
+            recent_trades: Arc::new(Mutex::new(VecDeque::new())),
+            token_activities: Arc::new(Mutex::new(VecDeque::new())),
+            last_activity_report: Arc::new(Mutex::new(Instant::now())),
+            price_monitor: create_global_price_monitor(settings.price_change_threshold),
+            dynamic_ratio_manager: create_global_dynamic_ratio_manager(settings.min_buy_ratio, settings.max_buy_ratio, BUY_RATIO_CHANGE_HOURS),
+            volume_wave_manager: create_global_volume_wave_manager(settings.volume_wave_active_hours, settings.volume_wave_slow_hours),
+            guardian_mode: create_global_guardian_mode(config.guardian.clone()),
+            dex_manager: Arc::new(Mutex::new(None)),
+            live_slippage,
+            twap_accumulator: create_global_twap_accumulator(config.twap.clone()),
+            position_engine: create_global_position_engine(PositionRules::from_env()),
+            spend_breaker: create_global_spend_circuit_breaker(get_daily_spend_cap()),
+            rate_limiter: create_global_trade_rate_limiter(config.rate_limits),
+            liquidity_guard: create_global_liquidity_guard(config.liquidity),
+            inventory_guard: create_global_inventory_guard(config.inventory),
+            creator_buy_pause: Arc::new(Mutex::new(None)),
+            sandwich_detector: create_global_sandwich_detector(config.sandwich),
+            order_flow: create_global_order_flow_tracker(config.order_flow),
+            pump_guardian: create_global_pump_guardian(config.pump_guardian),
+            trade_events: broadcast::channel(TRADE_EVENT_CAPACITY).0,
+            own_trades: Arc::new(Mutex::new(OwnTradeTracker::default())),
+            unique_traders: Arc::new(Mutex::new(WindowedUniqueCounter::new(UNIQUE_TRADER_BUCKET.as_secs(), ACTIVITY_RETENTION.as_secs()))),
+            queued_exits: Arc::new(Mutex::new(HashMap::new())),
+            target_token_program: OnceCell::new(),
+            config,
+        };
+        engine.runtime_params();
+        Ok(engine)
+    }
+
+    fn session_handles(&self) -> SessionHandles {
+        SessionHandles {
+            wallet_pool: self.wallet_pool.clone(),
+            twap_accumulator: self.twap_accumulator.clone(),
+            position_engine: self.position_engine.clone(),
+            token_activities: self.token_activities.clone(),
+            spend_breaker: self.spend_breaker.clone(),
+            price_monitor: self.price_monitor.clone(),
+            guardian_mode: self.guardian_mode.clone(),
+            pump_guardian: self.pump_guardian.clone(),
+            dynamic_ratio_manager: self.dynamic_ratio_manager.clone(),
+        }
+    }
+
+    /// Switch off the subsystems disabled in `config.subsystems`. Disabled managers stay
+    /// in place but are inert: neutral multipliers, a fixed buy ratio and no interventions.
+    /// Enabled volume waves get their configured phase settings.
+    pub async fn apply_subsystem_toggles(&self) {
+        let toggles = self.config.subsystems;
+
+        if toggles.volume_waves {
+            self.volume_wave_manager.lock().await.configure(&self.config.volume_waves);
+        } else {
+            self.volume_wave_manager.lock().await.set_enabled(false);
+        }
+        if !toggles.dynamic_ratios {
+            let fixed_ratio = self.config.randomization_config.buy_sell_ratio;
+            self.dynamic_ratio_manager.lock().await.set_enabled(false, fixed_ratio);
+        }
+        if !toggles.guardian_mode {
+            self.guardian_mode.lock().await.set_enabled(false);
+        }
+        if !toggles.price_monitor {
+            self.logger.log("📉 Price monitor disabled, price-based throttling is off".yellow().to_string());
+        }
+        if !self.config.enable_telegram_notifications {
+            self.logger.log("📵 Telegram notifications disabled".yellow().to_string());
+        }
+    }
+
+    /// False when ENABLE_PRICE_MONITOR=false; `record_token_activity` then stops feeding the
+    /// monitor and `prepare_trade` no longer throttles on price moves
+    pub fn is_price_monitor_enabled(&self) -> bool {
+        self.config.subsystems.price_monitor
+    }
+
+    /// Size for the next trade under price throttling: the amount unchanged when the monitor is
+    /// off or calm, a reduced amount in shrink mode, or None when the trade should be skipped
+    pub async fn apply_price_throttle(&self, trade_type: &TradeType, sol_amount: f64) -> Option<f64> {
+        if !self.is_price_monitor_enabled() {
+            return Some(sol_amount);
+        }
+        let remaining = {
+            let mut monitor = self.price_monitor.lock().await;
+            if !monitor.throttle_active() {
+                return Some(sol_amount);
+            }
+            monitor.throttle_remaining()
+        };
+
+        let side = match trade_type {
+            TradeType::Buy => "BUY",
+            TradeType::Sell => "SELL",
+        };
+        let throttle = self.config.price_throttle;
+        match throttle.mode {
+            ThrottleMode::Skip => {
+                self.logger.log(format!(
+                    "⏳ Price throttle active ({}s left), skipping {} of {:.4} SOL",
+                    remaining.as_secs(), side, sol_amount
+                ).yellow().to_string());
+                None
+            },
+            ThrottleMode::Shrink => {
+                let shrunk = sol_amount * throttle.size_factor;
+                self.logger.log(format!(
+                    "⏳ Price throttle active ({}s left), shrinking {} from {:.4} to {:.4} SOL",
+                    remaining.as_secs(), side, sol_amount, shrunk
+                ).yellow().to_string());
+                (shrunk > 0.0).then_some(shrunk)
+            },
+        }
+    }
+
+    /// Sell size multiplier from the pump guardian (1.0 unless distributing into a pump)
+    pub async fn pump_guardian_sell_multiplier(&self) -> f64 {
+        self.pump_guardian.lock().await.get_sell_size_multiplier()
+    }
+
+    /// Size factor from an active guardian intervention, or None to hold a regular sell back
+    pub async fn guardian_trade_factor(&self, is_buy: bool) -> Option<f64> {
+        if !self.config.subsystems.guardian_mode {
+            return Some(1.0);
+        }
+        let roll = rand::thread_rng().gen::<f64>();
+        self.guardian_mode.lock().await.trade_factor(is_buy, roll)
+    }
+
+    /// Buy size multiplier from the TWAP schedule (1.0 when TWAP mode is off)
+    pub async fn twap_buy_amount_multiplier(&self) -> f64 {
+        self.twap_accumulator.lock().await.buy_amount_multiplier()
+    }
+
+    /// Cap a sell so the accumulated net position stays on the TWAP schedule
+    pub async fn cap_sell_for_twap(&self, tokens: f64) -> f64 {
+        tokens.min(self.twap_accumulator.lock().await.max_sell_tokens())
+    }
+
+    /// Record a confirmed fill against the TWAP schedule
+    pub async fn record_twap_fill(&self, trade_type: &TradeType, tokens: f64) {
+        self.twap_accumulator.lock().await.record_fill(matches!(trade_type, TradeType::Buy), tokens);
+    }
+
+    /// Public keys of every wallet in the pool
+    async fn wallet_pubkeys(&self) -> Vec<Pubkey> {
+        self.wallet_pool.lock().await
+            .snapshot()
+            .wallets
+            .iter()
+            .filter_map(|w| Pubkey::from_str(&w.pubkey).ok())
+            .collect()
+    }
+
+    /// Total target-token balance (UI amount) held across the wallet pool. The mint's owner
+    /// decides the token program, so Token-2022 mints are read at their own ATAs and their
+    /// accounts, which carry extensions, are unpacked with them.
+    pub async fn aggregate_token_inventory(&self) -> Result<f64> {
+        let mint = Pubkey::from_str(&self.config.target_token_mint)?;
+        let rpc = self.config.app_state.rpc_nonblocking_client.clone();
+
+        let token_program = self.target_token_program().await?;
+        let decimals = self.target_mint_decimals().await
+            .ok_or_else(|| anyhow::anyhow!("Failed to read the decimals of {}", mint))?;
+
+        let token_accounts: Vec<Pubkey> = self.wallet_pubkeys().await
+            .iter()
+            .map(|owner| get_associated_token_address_with_program_id(owner, &mint, &token_program))
+            .collect();
+
+        let mut raw_total: u64 = 0;
+        for chunk in token_accounts.chunks(100) {
+            for account in rpc.get_multiple_accounts(chunk).await?.into_iter().flatten() {
+                if let Ok(token_account) = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data) {
+                    raw_total = raw_total.saturating_add(token_account.base.amount);
+                }
+            }
+        }
+
+        Ok(raw_total as f64 / 10f64.powi(decimals as i32))
+    }
+
+    /// Portfolio value in SOL: native and wrapped (WSOL, under either token program) balances
+    /// across the pool plus tokens marked at the latest price
+    pub async fn portfolio_value(&self) -> Result<f64> {
+        let rpc = self.config.app_state.rpc_nonblocking_client.clone();
+        let wallets = self.wallet_pubkeys().await;
+
+        let mut lamports: u64 = 0;
+        for chunk in wallets.chunks(100) {
+            for account in rpc.get_multiple_accounts(chunk).await?.into_iter().flatten() {
+                lamports = lamports.saturating_add(account.lamports);
+            }
+        }
+
+        // Wrapped amounts only; the WSOL accounts' rent isn't spendable
+        let wsol_accounts: Vec<Pubkey> = wallets
+            .iter()
+            .flat_map(|owner| [
+                get_associated_token_address_with_program_id(owner, &spl_token::native_mint::ID, &spl_token::ID),
+                get_associated_token_address_with_program_id(owner, &spl_token_2022::native_mint::ID, &spl_token_2022::ID),
+            ])
+            .collect();
+        for chunk in wsol_accounts.chunks(100) {
+            for account in rpc.get_multiple_accounts(chunk).await?.into_iter().flatten() {
+                if let Some(amount) = token_account_amount(&account.data) {
+                    lamports = lamports.saturating_add(amount);
+                }
+            }
+        }
+
+        let tokens = self.aggregate_token_inventory().await?;
+        let price = self.latest_price().await.unwrap_or(0.0);
+        Ok(lamports as f64 / 1_000_000_000.0 + tokens * price)
+    }
+
+    /// Halt trading and alert when the portfolio falls MAX_DRAWDOWN_PERCENT below its session high.
+    /// Trading stays halted until an explicit `reset-drawdown` (a `resume` doesn't lift it); the
+    /// guard then re-arms from the current value.
+    pub fn spawn_drawdown_monitor(self: &Arc<Self>) {
+        let config = self.config.drawdown;
+        if !config.is_enabled() {
+            return;
+        }
+
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let mut monitor = DrawdownMonitor::new(config);
+            let mut ticker = time::interval(Duration::from_secs(config.check_seconds.max(10)));
+            loop {
+                ticker.tick().await;
+                if SHUTDOWN.is_requested() || !*engine.is_running.read().await {
+                    break;
+                }
+                // Prices are only meaningful once the stream has seen a trade
+                if engine.latest_price().await.is_none() {
+                    continue;
+                }
+                let value = match engine.portfolio_value().await {
+                    Ok(value) => value,
+                    Err(e) => {
+                        engine.logger.log(format!("Failed to value portfolio: {}", e).red().to_string());
+                        continue;
+                    }
+                };
+
+                if monitor.is_tripped() {
+                    if !CONTROL.is_drawdown_halted() {
+                        monitor.rearm(value);
+                    }
+                    continue;
+                }
+
+                if let Some(drawdown) = monitor.update(value) {
+                    CONTROL.halt_for_drawdown(&format!("drawdown {:.2}% from session high", drawdown));
+                    let alert = format!(
+                        "📉 Drawdown {:.2}% from session high ({:.4} -> {:.4} SOL), trading halted. Send `reset-drawdown` to continue.",
+                        drawdown, monitor.session_high(), value
+                    );
+                    if engine.config.enable_telegram_notifications {
+                        if let Err(e) = telegram::send_notification(&alert).await {
+                            engine.logger.log(format!("Failed to send drawdown alert: {}", e).red().to_string());
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Token reserve of the pool (UI amount), read from POOL_BASE_ACCOUNT when configured
+    pub async fn pool_token_reserve(&self) -> Option<f64> {
+        let vault = Pubkey::from_str(&self.config.pool_base_account).ok()?;
+        match self.config.app_state.rpc_nonblocking_client.get_token_account_balance(&vault).await {
+            Ok(balance) => balance.ui_amount,
+            Err(e) => {
+                self.logger.log(format!("Failed to read pool reserve for inventory cap: {}", e).red().to_string());
+                None
+            }
+        }
+    }
+
+    /// False while the pool's SOL reserve is below MIN_POOL_LIQUIDITY_SOL; queued buys are
+    /// skipped (sells still run) until liquidity recovers
+    pub async fn allows_buy_for_liquidity(&self) -> bool {
+        self.liquidity_guard.lock().await.allows_buys()
+    }
+
+    /// False while aggregate token inventory is over its cap; queued buys are skipped
+    /// (sells still run) until the inventory monitor sees it back under
+    pub async fn allows_buy_for_inventory(&self) -> bool {
+        self.inventory_guard.lock().await.allows_buys()
+    }
+
+    /// Feed a quote reserve reading into the liquidity guard and alert on pause / recovery
+    async fn update_pool_liquidity(&self, reserve_sol: f64) {
+        let event = self.liquidity_guard.lock().await.update(reserve_sol);
+        let alert = match event {
+            Some(LiquidityEvent::Dropped { reserve_sol }) => format!(
+                "💧 Pool liquidity dropped to {:.4} SOL (min {} SOL), buys paused",
+                reserve_sol, self.config.liquidity.min_quote_sol
+            ),
+            Some(LiquidityEvent::Recovered { reserve_sol }) => format!(
+                "💧 Pool liquidity recovered to {:.4} SOL, buys resumed", reserve_sol
+            ),
+            None => return,
+        };
+        if self.config.enable_telegram_notifications {
+            if let Err(e) = telegram::send_notification(&alert).await {
+                self.logger.log(format!("Failed to send liquidity alert: {}", e).red().to_string());
+            }
+        }
+    }
+
+    /// Stream Yellowstone updates for `accounts`, calling `on_update(pubkey, data)` for each,
+    /// until the subscription ends or shutdown is requested
+    async fn stream_accounts<F, Fut>(&self, label: &str, accounts: &[Pubkey], mut on_update: F) -> Result<()>
+    where
+        F: FnMut(Pubkey, Vec<u8>) -> Fut,
+        Fut: std::future::Future<Output = ()>,
+    {
+        let mut client = GeyserGrpcClient::build_from_shared(self.config.yellowstone_grpc_http.clone())?
+            .x_token(Some(self.config.yellowstone_grpc_token.clone()))?
+            .tls_config(ClientTlsConfig::new().with_native_roots())?
+            .connect()
+            .await?;
+
+        let mut filters = HashMap::new();
+        filters.insert(label.to_string(), SubscribeRequestFilterAccounts {
+            account: accounts.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        });
+        let (mut subscribe_tx, mut stream) = client.subscribe_with_request(Some(SubscribeRequest {
+            accounts: filters,
+            commitment: Some(CommitmentLevel::Processed as i32),
+            ..Default::default()
+        })).await?;
+
+        while let Some(message) = stream.next().await {
+            match message?.update_oneof {
+                Some(UpdateOneof::Account(update)) => {
+                    let Some(account) = update.account else { continue };
+                    if let Ok(pubkey) = Pubkey::try_from(account.pubkey.as_slice()) {
+                        on_update(pubkey, account.data).await;
+                    }
+                },
+                Some(UpdateOneof::Ping(_)) => {
+                    subscribe_tx.send(SubscribeRequest {
+                        ping: Some(SubscribeRequestPing { id: 1 }),
+                        ..Default::default()
+                    }).await?;
+                },
+                _ => {},
+            }
+            if SHUTDOWN.is_requested() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Track the pool's SOL reserve through a Yellowstone account subscription and pause buying
+    /// while it is below MIN_POOL_LIQUIDITY_SOL
+    pub fn spawn_liquidity_monitor(self: &Arc<Self>) {
+        if !self.config.liquidity.is_enabled() {
+            return;
+        }
+        let vault = match Pubkey::from_str(&self.config.pool_quote_account) {
+            Ok(vault) => vault,
+            Err(_) => {
+                self.logger.log("💧 MIN_POOL_LIQUIDITY_SOL needs POOL_QUOTE_ACCOUNT, liquidity monitor disabled".yellow().to_string());
+                return;
+            }
+        };
+
+        let engine = self.clone();
+        tokio::spawn(async move {
+            // Seed from RPC so the guard is armed before the first account update
+            match engine.config.app_state.rpc_nonblocking_client.get_token_account_balance(&vault).await {
+                Ok(balance) => {
+                    if let Some(reserve) = balance.ui_amount {
+                        engine.update_pool_liquidity(reserve).await;
+                    }
+                },
+                Err(e) => {
+                    engine.logger.log(format!("Failed to read pool liquidity: {}", e).red().to_string());
+                },
+            }
+
+            while !SHUTDOWN.is_requested() && *engine.is_running.read().await {
+                let result = engine.stream_accounts("pool_quote", &[vault], |_, data| {
+                    let engine = engine.clone();
+                    async move {
+                        if let Ok(token_account) = spl_token::state::Account::unpack(&data) {
+                            engine.update_pool_liquidity(token_account.amount as f64 / 1_000_000_000.0).await;
+                        }
+                    }
+                }).await;
+                if let Err(e) = result {
+                    engine.logger.log(format!("Pool liquidity stream error: {}, reconnecting", e).red().to_string());
+                }
+                time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+    }
+
+    /// Keep the shared pool state (reserves and creator) current from account updates so
+    /// quoting reads memory instead of fetching the pool per trade (POOL_STATE_STREAM).
+    /// CPMM streams the two vaults under POOL_ID; pump.fun streams the bonding curve.
+    pub fn spawn_pool_state_stream(self: &Arc<Self>) {
+        if !self.config.pool_state.enabled {
+            return;
+        }
+        POOL_STATE.set_max_age(self.config.pool_state.max_age);
+        let Ok(mint) = Pubkey::from_str(&self.config.target_token_mint) else { return };
+        let coin_creator = Pubkey::from_str(&self.config.coin_creator).unwrap_or_default();
+
+        let (pool_id, accounts) = match self.config.dex_type {
+            DexType::PumpFun => match pump_fun::bonding_curve_address(&mint) {
+                Ok(curve) => (curve, vec![curve]),
+                Err(e) => {
+                    self.logger.log(format!("Failed to derive bonding curve, pool state stream disabled: {}", e).red().to_string());
+                    return;
+                }
+            },
+            _ => {
+                let ids = [&self.config.pool_id, &self.config.pool_base_account, &self.config.pool_quote_account]
+                    .map(|v| Pubkey::from_str(v).ok());
+                let [Some(pool_id), Some(base_vault), Some(quote_vault)] = ids else {
+                    self.logger.log("POOL_STATE_STREAM needs POOL_ID, POOL_BASE_ACCOUNT and POOL_QUOTE_ACCOUNT, quoting stays on RPC".yellow().to_string());
+                    return;
+                };
+                (pool_id, vec![base_vault, quote_vault])
+            },
+        };
+        POOL_STATE.track(PoolInfo {
+            pool_id,
+            base_mint: mint,
+            quote_mint: spl_token::native_mint::ID,
+            base_reserve: 0,
+            quote_reserve: 0,
+            coin_creator,
+        });
+
+        let engine = self.clone();
+        tokio::spawn(async move {
+            // Seed from RPC so the first quotes don't wait for an account update
+            let rpc = engine.config.app_state.rpc_nonblocking_client.clone();
+            if engine.config.dex_type == DexType::PumpFun {
+                if let Ok(data) = rpc.get_account_data(&pool_id).await {
+                    apply_bonding_curve_update(pool_id, mint, &data);
+                }
+            } else {
+                for (index, vault) in accounts.iter().enumerate() {
+                    let reserve = rpc.get_token_account_balance(vault).await.ok().and_then(|b| b.amount.parse().ok());
+                    match (index, reserve) {
+                        (0, Some(reserve)) => POOL_STATE.set_base_reserve(&pool_id, reserve),
+                        (_, Some(reserve)) => POOL_STATE.set_quote_reserve(&pool_id, reserve),
+                        (_, None) => {},
+                    }
+                }
+            }
+            engine.logger.log(format!("🏊 Streaming pool state for {}", pool_id).cyan().to_string());
+
+            while !SHUTDOWN.is_requested() && *engine.is_running.read().await {
+                let result = engine.stream_accounts("pool_state", &accounts, |pubkey, data| {
+                    let base_vault = accounts[0];
+                    async move {
+                        if pubkey == pool_id {
+                            apply_bonding_curve_update(pool_id, mint, &data);
+                        } else if let Some(amount) = token_account_amount(&data) {
+                            if pubkey == base_vault {
+                                POOL_STATE.set_base_reserve(&pool_id, amount);
+                            } else {
+                                POOL_STATE.set_quote_reserve(&pool_id, amount);
+                            }
+                        }
+                    }
+                }).await;
+                if let Err(e) = result {
+                    engine.logger.log(format!("Pool state stream error: {}, reconnecting", e).red().to_string());
+                }
+                time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+    }
+
+    /// False while buys are paused after a coin creator transfer / sell (CREATOR_AUTO_PAUSE_BUYS)
+    pub async fn allows_buy_for_creator(&self) -> bool {
+        let mut pause = self.creator_buy_pause.lock().await;
+        match *pause {
+            Some(until) if Instant::now() < until => false,
+            Some(_) => {
+                *pause = None;
+                self.logger.log("👤 Creator buy pause expired, buys resumed".green().to_string());
+                true
+            },
+            None => true,
+        }
+    }
+
+    /// Alert (and optionally pause buys) on a coin creator outflow
+    async fn handle_creator_outflow(&self, tokens: f64, signature: &str) {
+        let config = self.config.creator_monitor;
+        let mut alert = format!(
+            "👤 Coin creator {} moved {:.2} tokens out (tx {})",
+            self.config.coin_creator, tokens, signature
+        );
+        if config.auto_pause_buys {
+            *self.creator_buy_pause.lock().await = Some(Instant::now() + Duration::from_secs(config.pause_minutes * 60));
+            alert.push_str(&format!(", buys paused for {} min", config.pause_minutes));
+        }
+        self.logger.log(alert.clone().red().bold().to_string());
+        if self.config.enable_telegram_notifications {
+            if let Err(e) = telegram::send_notification(&alert).await {
+                self.logger.log(format!("Failed to send creator alert: {}", e).red().to_string());
+            }
+        }
+    }
+
+    /// Stream non-vote transactions that include any of `accounts`, calling `on_transaction(slot, info)`
+    /// for each, until the subscription ends or shutdown is requested
+    async fn stream_transactions<F, Fut>(&self, label: &str, accounts: &[Pubkey], mut on_transaction: F) -> Result<()>
+    where
+        F: FnMut(u64, SubscribeUpdateTransactionInfo) -> Fut,
+        Fut: std::future::Future<Output = ()>,
+    {
+        let mut client = GeyserGrpcClient::build_from_shared(self.config.yellowstone_grpc_http.clone())?
+            .x_token(Some(self.config.yellowstone_grpc_token.clone()))?
+            .tls_config(ClientTlsConfig::new().with_native_roots())?
+            .connect()
+            .await?;
+
+        let mut transactions = HashMap::new();
+        transactions.insert(label.to_string(), SubscribeRequestFilterTransactions {
+            vote: Some(false),
+            failed: Some(false),
+            account_include: accounts.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        });
+        let (mut subscribe_tx, mut stream) = client.subscribe_with_request(Some(SubscribeRequest {
+            transactions,
+            commitment: Some(CommitmentLevel::Processed as i32),
+            ..Default::default()
+        })).await?;
+
+        while let Some(message) = stream.next().await {
+            match message?.update_oneof {
+                Some(UpdateOneof::Transaction(update)) => {
+                    if let Some(info) = update.transaction {
+                        on_transaction(update.slot, info).await;
+                    }
+                },
+                Some(UpdateOneof::Ping(_)) => {
+                    subscribe_tx.send(SubscribeRequest {
+                        ping: Some(SubscribeRequestPing { id: 1 }),
+                        ..Default::default()
+                    }).await?;
+                },
+                _ => {},
+            }
+            if SHUTDOWN.is_requested() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Subscribe to the `coin_creator` address and alert when it transfers or sells the token
+    pub fn spawn_creator_monitor(self: &Arc<Self>) {
+        if !self.config.creator_monitor.enabled {
+            return;
+        }
+        let Ok(creator) = Pubkey::from_str(&self.config.coin_creator) else {
+            self.logger.log("👤 COIN_CREATOR not set, creator monitor disabled".yellow().to_string());
+            return;
+        };
+
+        self.logger.log(format!("👤 Watching coin creator {}", creator).cyan().to_string());
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let creator_address = creator.to_string();
+            while !SHUTDOWN.is_requested() && *engine.is_running.read().await {
+                let result = engine.stream_transactions("coin_creator", &[creator], |_, info| {
+                    let engine = engine.clone();
+                    let creator_address = creator_address.clone();
+                    async move {
+                        let Some(meta) = info.meta.as_ref() else { return };
+                        let tokens = token_outflow(meta, &creator_address, &engine.config.target_token_mint);
+                        if tokens > 0.0 && tokens >= engine.config.creator_monitor.min_tokens {
+                            let signature = Signature::try_from(info.signature.as_slice())
+                                .map(|s| s.to_string())
+                                .unwrap_or_default();
+                            engine.handle_creator_outflow(tokens, &signature).await;
+                        }
+                    }
+                }).await;
+                if let Err(e) = result {
+                    engine.logger.log(format!("Creator monitor stream error: {}, reconnecting", e).red().to_string());
+                }
+                time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+    }
+
+    /// Stream transactions of MONITOR_WALLETS and COPY_TARGET_WALLETS and publish each classified
+    /// trade or mint. Monitored-wallet events above MONITOR_ALERT_MIN_SOL (and mints, with
+    /// MONITOR_ALERT_MINTS) go to Telegram; the copy trader subscribes to the returned sender.
+    pub fn spawn_wallet_monitor(self: &Arc<Self>) -> Option<broadcast::Sender<TargetWalletEvent>> {
+        let config = self.config.wallet_monitor.clone();
+        let mut wallets = config.wallets.clone();
+        for target in &self.config.copy_trading.targets {
+            if !wallets.contains(target) {
+                wallets.push(*target);
+            }
+        }
+        if wallets.is_empty() {
+            return None;
+        }
+
+        let (sender, _) = broadcast::channel(256);
+        if !config.wallets.is_empty() && (config.alert_min_sol > 0.0 || config.alert_mints) {
+            self.spawn_wallet_monitor_alerts(config.clone(), sender.subscribe());
+        }
+
+        let monitor = Arc::new(TargetWalletMonitor::new(wallets));
+        let engine = self.clone();
+        let events = sender.clone();
+        tokio::spawn(async move {
+            while !SHUTDOWN.is_requested() && *engine.is_running.read().await {
+                let result = engine.stream_transactions("target_wallets", monitor.wallets(), |slot, info| {
+                    let monitor = monitor.clone();
+                    let events = events.clone();
+                    async move {
+                        for event in monitor.on_transaction(slot, &info) {
+                            let _ = events.send(event);
+                        }
+                    }
+                }).await;
+                if let Err(e) = result {
+                    engine.logger.log(format!("Wallet monitor stream error: {}, reconnecting", e).red().to_string());
+                }
+                time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+        Some(sender)
+    }
+
+    /// Telegram alerts for monitored-wallet events (not copy-trade-only targets)
+    fn spawn_wallet_monitor_alerts(self: &Arc<Self>, config: WalletMonitorConfig, mut events: broadcast::Receiver<TargetWalletEvent>) {
+        let watched: Vec<String> = config.wallets.iter().map(|w| w.to_string()).collect();
+        let engine = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if !watched.contains(&event.wallet) {
+                    continue;
+                }
+                let alert = match (&event.trade, event.instruction_type) {
+                    (Some(trade), kind) if config.alert_min_sol > 0.0 && trade.sol_amount >= config.alert_min_sol => format!(
+                        "👀 Watched wallet {} {} {:.4} SOL{} of {}{} ({})",
+                        event.wallet, if trade.is_buy { "BOUGHT" } else { "SOLD" }, trade.sol_amount,
+                        usd_suffix(trade.sol_amount, USD_PRICES.sol_usd().await), trade.mint,
+                        kind.map(|k| format!(" via {:?}", k)).unwrap_or_default(), event.signature
+                    ),
+                    (None, Some(InstructionType::PumpMint)) if config.alert_mints => format!(
+                        "👀 Watched wallet {} created a pump.fun token ({})", event.wallet, event.signature
+                    ),
+                    _ => continue,
+                };
+                engine.logger.log(alert.clone().blue().bold().to_string());
+                if engine.config.enable_telegram_notifications {
+                    if let Err(e) = telegram::send_notification(&alert).await {
+                        engine.logger.log(format!("Failed to send wallet monitor alert: {}", e).red().to_string());
+                    }
+                }
+            }
+        });
+    }
+
+    /// Mirror buys and sells of COPY_TARGET_WALLETS from the wallet monitor's events. Orders are
+    /// delivered on the returned channel after their randomized delay, for `spawn_copy_orders`
+    /// to queue with the named wallet.
+    pub fn spawn_copy_trader(self: &Arc<Self>, mut events: broadcast::Receiver<TargetWalletEvent>) -> Option<tokio::sync::mpsc::UnboundedReceiver<CopyOrder>> {
+        let config = self.config.copy_trading.clone();
+        if !config.is_enabled() {
+            return None;
+        }
+
+        let targets: Vec<String> = config.targets.iter().map(|t| t.to_string()).collect();
+        let (orders, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let mut copier = CopyTrader::new(config);
+        let engine = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if SHUTDOWN.is_requested() || !*engine.is_running.read().await {
+                    break;
+                }
+                let Some(trade) = event.trade.filter(|_| targets.contains(&event.wallet)) else { continue };
+                let pool_wallets: Vec<String> = engine.wallet_pubkeys().await.iter().map(|w| w.to_string()).collect();
+                for order in copier.on_target_trade(&trade, &pool_wallets) {
+                    let orders = orders.clone();
+                    tokio::spawn(async move {
+                        time::sleep(order.delay).await;
+                        let _ = orders.send(order);
+                    });
+                }
+            }
+        });
+        Some(receiver)
+    }
+
+    /// Queue copy orders from the wallet named in each. The copy trader only emits orders on
+    /// the target mint, the one mint the trade queue trades.
+    fn spawn_copy_orders(self: &Arc<Self>, mut orders: tokio::sync::mpsc::UnboundedReceiver<CopyOrder>, queue: TradeQueue) {
+        let engine = self.clone();
+        tokio::spawn(async move {
+            while let Some(order) = orders.recv().await {
+                if SHUTDOWN.is_requested() || !*engine.is_running.read().await {
+                    break;
+                }
+                let side = if order.is_buy { "buy" } else { "sell" };
+                if order.mint != engine.config.target_token_mint {
+                    engine.logger.error(format!("👥 Copy {} of {} refused, only the target mint is traded", side, order.mint));
+                    continue;
+                }
+                let Some(wallet) = engine.wallet_pool.lock().await.find_wallet(&order.wallet) else {
+                    engine.logger.log(format!("No keypair for {} in the wallet pool, copy {} skipped", order.wallet, side).yellow().to_string());
+                    continue;
+                };
+                let trade = if order.is_buy {
+                    QueuedTrade::new(wallet, TradeType::Buy, order.sol_amount)
+                } else {
+                    let held = engine.position_engine.lock().await.get_position(&order.wallet).map(|p| p.amount).unwrap_or(0.0);
+                    let tokens = held * order.sell_fraction;
+                    if tokens <= 0.0 {
+                        continue;
+                    }
+                    QueuedTrade::new(wallet, TradeType::Sell, 0.0).with_tokens(tokens)
+                };
+                if queue.try_enqueue(trade).is_err() {
+                    engine.logger.log(format!("🚦 Trade queue full, copy {} from {} dropped", side, order.wallet).yellow().to_string());
+                }
+            }
+        });
+    }
+
+    /// Watch the target mint, pool vaults and LP mint for rug signatures (mint authority
+    /// re-enabled, frozen pool token account, large LP burn / withdrawal). A detection alerts
+    /// and, with RUG_AUTO_EXIT, engages the kill switch with a full sell-and-collect.
+    pub fn spawn_rug_detector(self: &Arc<Self>) {
+        let config = self.config.rug_detection;
+        if !config.enabled {
+            return;
+        }
+        let Ok(mint) = Pubkey::from_str(&self.config.target_token_mint) else { return };
+        let vaults: Vec<Pubkey> = [&self.config.pool_base_account, &self.config.pool_quote_account]
+            .iter()
+            .filter_map(|v| Pubkey::from_str(v).ok())
+            .collect();
+        let mut accounts = vec![mint];
+        accounts.extend(vaults.iter().copied());
+        accounts.extend(config.lp_mint);
+
+        self.logger.log(format!("🛡️ Rug detection watching {} account(s)", accounts.len()).cyan().to_string());
+        let detector = Arc::new(Mutex::new(RugDetector::new(config)));
+        let engine = self.clone();
+        tokio::spawn(async move {
+            while !SHUTDOWN.is_requested() && !KILL_SWITCH.is_engaged() && *engine.is_running.read().await {
+                let result = engine.stream_accounts("rug_detection", &accounts, |pubkey, data| {
+                    let engine = engine.clone();
+                    let detector = detector.clone();
+                    let is_vault = vaults.contains(&pubkey);
+                    async move {
+                        let signal = {
+                            let mut detector = detector.lock().await;
+                            if pubkey == mint {
+                                detector.check_mint(&data)
+                            } else if is_vault {
+                                detector.check_vault(&pubkey, &data)
+                            } else if Some(pubkey) == config.lp_mint {
+                                detector.check_lp_mint(&data)
+                            } else {
+                                None
+                            }
+                        };
+                        if let Some(signal) = signal {
+                            engine.handle_rug_signal(&signal.to_string(), config.auto_exit).await;
+                        }
+                    }
+                }).await;
+                if let Err(e) = result {
+                    engine.logger.log(format!("Rug detection stream error: {}, reconnecting", e).red().to_string());
+                }
+                time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+    }
+
+    async fn handle_rug_signal(&self, signal: &str, auto_exit: bool) {
+        let alert = if auto_exit {
+            format!("🚨 RUG DETECTED: {}. Emergency exit: selling all inventory and collecting funds.", signal)
+        } else {
+            format!("🚨 RUG DETECTED: {}. Trading halted (RUG_AUTO_EXIT=false, inventory kept).", signal)
+        };
+        if self.config.enable_telegram_notifications {
+            if let Err(e) = telegram::send_notification(&alert).await {
+                self.logger.log(format!("Failed to send rug alert: {}", e).red().to_string());
+            }
+        }
+
+        let reason = format!("rug detected: {}", signal);
+        if auto_exit {
+            KILL_SWITCH.engage_with_liquidation(&reason);
+        } else {
+            KILL_SWITCH.engage(&reason);
+        }
+    }
+
+    /// Periodically compare aggregate inventory with MAX_INVENTORY_TOKENS / MAX_INVENTORY_POOL_PERCENT;
+    /// queued buys are skipped (sells still run) while it is over the cap
+    pub fn spawn_inventory_monitor(self: &Arc<Self>) {
+        let inventory = self.config.inventory;
+        if !inventory.is_enabled() {
+            return;
+        }
+
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = time::interval(Duration::from_secs(inventory.refresh_seconds.max(10)));
+            loop {
+                ticker.tick().await;
+                if SHUTDOWN.is_requested() || !*engine.is_running.read().await {
+                    break;
+                }
+                let current = match engine.aggregate_token_inventory().await {
+                    Ok(current) => current,
+                    Err(e) => {
+                        engine.logger.log(format!("Failed to read token inventory: {}", e).red().to_string());
+                        continue;
+                    }
+                };
+
+                let reserve = if inventory.max_pool_percent > 0.0 {
+                    engine.pool_token_reserve().await
+                } else {
+                    None
+                };
+                engine.inventory_guard.lock().await.update(current, reserve);
+            }
+        });
+    }
+
+    /// Record a confirmed fill in the position engine and the fill ledger (`price` in SOL per token)
+    pub async fn record_position_fill(&self, wallet: &Pubkey, trade_type: &TradeType, tokens: f64, price: f64, signature: &Signature) {
+        if let Ok(mint) = Pubkey::from_str(&self.config.target_token_mint) {
+            invalidate_after_swap(wallet, &mint);
+        }
+        let wallet = wallet.to_string();
+        let is_buy = matches!(trade_type, TradeType::Buy);
+        {
+            let mut engine = self.position_engine.lock().await;
+            if is_buy {
+                engine.record_buy(&wallet, tokens, price);
+            } else {
+                engine.record_sell(&wallet, tokens, price);
+            }
+        }
+
+        let fill = Fill {
+            at: unix_now(),
+            wallet,
+            is_buy,
+            tokens,
+            price,
+            sol: tokens * price,
+            signature: signature.to_string(),
+        };
+        if let Err(e) = append_fill(&fill) {
+            self.logger.log(format!("Failed to append fill to ledger: {}", e).red().to_string());
+        }
+    }
+
+    /// Start the execution workers for `max_concurrent_trades` parallel trades. Trades come from
+    /// `spawn_position_exits` (exit sells) and `spawn_copy_orders` (copy trader orders), which
+    /// enqueue them instead of awaiting them. Every dequeued trade is sized and gated
+    /// by `admit_trade` (pause and approval waits included) before it takes an execution slot,
+    /// then `execute` builds, signs, sends and confirms it in its own task with that wallet's
+    /// `WalletState` (balance, token accounts, blockhash), and the returned fill is booked by
+    /// `record_trade_fill`. Trades dequeued after shutdown was requested are dropped.
+    pub fn spawn_trade_queue<F, Fut>(self: &Arc<Self>, execute: F) -> TradeQueue
+    where
+        F: Fn(Arc<MarketMaker>, QueuedTrade, OwnedMutexGuard<WalletState>) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<TradeFill>> + Send + 'static,
+    {
+        let max_concurrent = if self.config.enable_multi_wallet { self.config.max_concurrent_trades } else { 1 };
+        self.logger.log(format!("🚦 Trade queue running up to {} concurrent trade(s)", max_concurrent.max(1)).cyan().to_string());
+        let admit_engine = self.clone();
+        let engine = self.clone();
+        let execute = Arc::new(execute);
+        spawn_trade_workers(
+            max_concurrent,
+            move |trade| {
+                let engine = admit_engine.clone();
+                let span = trade_span(&trade);
+                async move {
+                    let id = trade.id;
+                    let admitted = engine.admit_trade(trade).await;
+                    if admitted.is_none() {
+                        engine.finish_exit(id, false).await;
+                    }
+                    admitted
+                }.instrument(span)
+            },
+            move |trade, state| {
+                let engine = engine.clone();
+                let execute = execute.clone();
+                let span = trade_span(&trade);
+                async move {
+                    let id = trade.id;
+                    let filled = engine.run_queued_trade(trade, state, execute.as_ref()).await;
+                    engine.finish_exit(id, filled).await;
+                }.instrument(span)
+            },
+        )
+    }
+
+    /// Gate a dequeued trade before it takes an execution slot; None drops it. Waits for the
+    /// operator or drawdown pause to lift, then sizes and gates it with `prepare_trade`.
+    async fn admit_trade(&self, trade: QueuedTrade) -> Option<QueuedTrade> {
+        if KILL_SWITCH.is_engaged() {
+            return None;
+        }
+        // Paused by the operator or the drawdown guard: hold the trade until resume
+        if !self.wait_while_paused().await {
+            return None;
+        }
+        self.prepare_trade(trade).await
+    }
+
+    /// Run an admitted trade in its execution slot; true once its fill is booked
+    async fn run_queued_trade<F, Fut>(self: &Arc<Self>, trade: QueuedTrade, state: OwnedMutexGuard<WalletState>, execute: &F) -> bool
+    where
+        F: Fn(Arc<MarketMaker>, QueuedTrade, OwnedMutexGuard<WalletState>) -> Fut,
+        Fut: std::future::Future<Output = Result<TradeFill>>,
+    {
+        let is_buy = matches!(trade.trade_type, TradeType::Buy);
+        // Counted as in flight until `execute` returns, so the shutdown handler waits for it
+        let in_flight = if KILL_SWITCH.is_engaged() { None } else { SHUTDOWN.begin_trade() };
+        let Some(_in_flight) = in_flight else {
+            if is_buy {
+                self.release_spend(trade.sol_amount).await;
+            }
+            return false;
+        };
+        let waited = trade.queued_at.elapsed();
+        if waited > Duration::from_secs(30) {
+            self.logger.log(format!("🚦 {:?} for {} waited {}s in the trade queue", trade.trade_type, trade.wallet.pubkey(), waited.as_secs()).yellow().to_string());
+        }
+        match execute(self.clone(), trade.clone(), state).await {
+            Ok(fill) => {
+                self.record_trade_fill(&trade, &fill).await;
+                true
+            },
+            Err(e) => {
+                if is_buy {
+                    self.release_spend(trade.sol_amount).await;
+                }
+                self.logger.log(format!("❌ {:?} for {} failed: {}", trade.trade_type, trade.wallet.pubkey(), e).red().to_string());
+                false
+            },
+        }
+    }
+
+    /// Settle the exit behind a finished trade: a confirmed sell completes its position rules,
+    /// a skipped or failed one lets them fire again
+    async fn finish_exit(&self, id: Uuid, filled: bool) {
+        let Some(exit) = self.queued_exits.lock().await.remove(&id) else { return };
+        let mut engine = self.position_engine.lock().await;
+        if filled {
+            engine.confirm_exit(&exit);
+        } else {
+            engine.release_exit(&exit);
+        }
+    }
+
+    /// Size and gate a dequeued trade right before it executes; None skips it.
+    /// Buys are scaled by the TWAP schedule; sells are scaled by the pump guardian and capped to
+    /// keep the TWAP schedule. An active guardian intervention sizes up regular buys and holds
+    /// back some regular sells. The price throttle may then shrink or skip the trade, and trades
+    /// above APPROVAL_THRESHOLD_SOL wait for the operator.
+    /// A buy that passes holds a spend cap reservation and every trade that passes uses a
+    /// rate-limit slot, so those two come last. Protective exits (stop-losses) skip approval
+    /// and are counted by the rate limiter without being refused, so a falling price can't
+    /// leave them waiting.
+    async fn prepare_trade(&self, mut trade: QueuedTrade) -> Option<QueuedTrade> {
+        let is_buy = matches!(trade.trade_type, TradeType::Buy);
+        match trade.trade_type {
+            TradeType::Buy => {
+                trade.sol_amount *= self.twap_buy_amount_multiplier().await;
+            },
+            TradeType::Sell => {
+                // The pump guardian resizes regular sells while distributing; exits keep their size.
+                // A full-balance sell is resolved to the held amount first so it shrinks too; it
+                // can't grow past what the wallet holds.
+                if trade.reason.is_none() {
+                    let multiplier = self.pump_guardian_sell_multiplier().await;
+                    if multiplier != 1.0 {
+                        let tokens = self.sell_tokens(&trade).await;
+                        let scaled = tokens * multiplier;
+                        if tokens > 0.0 {
+                            trade.tokens = Some(if trade.tokens.is_none() { scaled.min(tokens) } else { scaled });
+                        }
+                    }
+                }
+                let cap = self.cap_sell_for_twap(trade.tokens.unwrap_or(f64::MAX)).await;
+                if cap <= 0.0 {
+                    self.logger.log("⏭️ Skipping sell, the TWAP schedule has no tokens to spare".yellow().to_string());
+                    return None;
+                }
+                trade.tokens = (cap < f64::MAX).then_some(cap);
+            },
+        }
+
+        // A guardian intervention sizes up regular buys and holds back some regular sells
+        if trade.reason.is_none() {
+            let Some(factor) = self.guardian_trade_factor(is_buy).await else {
+                self.logger.log("⏭️ Skipping sell, guardian mode is supporting the price".yellow().to_string());
+                return None;
+            };
+            if is_buy {
+                trade.sol_amount *= factor;
+            }
+        }
+
+        // Sharp price moves shrink or skip regular trades; position exits are never held back
+        if trade.reason.is_none() {
+            let value = self.trade_sol_value(&trade).await;
+            let sized = self.apply_price_throttle(&trade.trade_type, value).await?;
+            if is_buy {
+                trade.sol_amount = sized;
+            } else if value > 0.0 && sized < value {
+                trade.tokens = Some(self.sell_tokens(&trade).await * sized / value);
+            }
+        }
+
+        if is_buy && !self.allows_buy_for_liquidity().await {
+            self.logger.log("⏭️ Skipping buy, pool liquidity is below MIN_POOL_LIQUIDITY_SOL".yellow().to_string());
+            return None;
+        }
+        if is_buy && !self.allows_buy_for_inventory().await {
+            self.logger.log("⏭️ Skipping buy, token inventory is over MAX_INVENTORY_TOKENS / MAX_INVENTORY_POOL_PERCENT".yellow().to_string());
+            return None;
+        }
+        if is_buy && !self.allows_buy_for_creator().await {
+            self.logger.log("⏭️ Skipping buy, buys are paused after a coin creator outflow".yellow().to_string());
+            return None;
+        }
+
+        let sol_value = self.trade_sol_value(&trade).await;
+        if !trade.protective && !self.approve_large_trade(&trade.trade_type, &trade.wallet.pubkey(), sol_value).await {
+            return None;
+        }
+        // The operator may have taken a while; don't submit into a kill or shutdown
+        if KILL_SWITCH.is_engaged() || SHUTDOWN.is_requested() {
+            return None;
+        }
+
+        trade.slippage_bps = Some(self.current_slippage().await);
+
+        if is_buy && !self.check_spend_limit(trade.sol_amount).await {
+            self.logger.log(format!("⏭️ Skipping buy of {:.4} SOL, the daily spend cap is reached", trade.sol_amount).yellow().to_string());
+            return None;
+        }
+        if trade.protective {
+            self.rate_limiter.lock().await.record(&trade.wallet.pubkey().to_string());
+        } else if self.acquire_trade_slot(&trade.wallet.pubkey()).await.is_err() {
+            if is_buy {
+                self.release_spend(trade.sol_amount).await;
+            }
+            return None;
+        }
+        Some(trade)
+    }
+
+    /// Tokens a queued sell moves: its own amount, or the wallet's position when unset
+    async fn sell_tokens(&self, trade: &QueuedTrade) -> f64 {
+        match trade.tokens {
+            Some(tokens) => tokens,
+            None => self.position_engine.lock().await
+                .get_position(&trade.wallet.pubkey().to_string())
+                .map(|p| p.amount)
+                .unwrap_or(0.0),
+        }
+    }
+
+    /// SOL a queued trade moves: the buy amount, or a sell's tokens at the latest price
+    async fn trade_sol_value(&self, trade: &QueuedTrade) -> f64 {
+        match trade.trade_type {
+            TradeType::Buy => trade.sol_amount,
+            TradeType::Sell => self.sell_tokens(trade).await * self.latest_price().await.unwrap_or(0.0),
+        }
+    }
+
+    /// Book a confirmed trade
+    async fn record_trade_fill(&self, trade: &QueuedTrade, fill: &TradeFill) {
+        self.record_own_trade(&fill.signature.to_string(), &trade.wallet.pubkey().to_string()).await;
+        let sol = fill.lamports as f64 / 1_000_000_000.0;
+        {
+            let mut spend = self.spend_breaker.lock().await;
+            match trade.trade_type {
+                TradeType::Buy => spend.commit(trade.sol_amount, sol),
+                TradeType::Sell => spend.record(false, sol),
+            }
+        }
+        self.record_position_fill(&trade.wallet.pubkey(), &trade.trade_type, fill.tokens, fill.price, &fill.signature).await;
+        self.record_twap_fill(&trade.trade_type, fill.tokens).await;
+    }
+
+    /// Execute callback for `start_engine`: swap a queued trade through the configured Raydium
+    /// CPMM pool from its own wallet, with that wallet's blockhash, and confirm it. The fill is
+    /// read from the wallet's token and WSOL account balances before and after the swap.
+    pub async fn execute_trade(self: Arc<Self>, trade: QueuedTrade, mut state: OwnedMutexGuard<WalletState>) -> Result<TradeFill> {
+        if self.config.dex_type != DexType::RaydiumCPMM {
+            return Err(anyhow::anyhow!("queued trades execute through Raydium CPMM only; set DEX=0 and the POOL_* accounts"));
+        }
+        let rpc = self.config.app_state.rpc_nonblocking_client.clone();
+        let owner = trade.wallet.pubkey();
+        let mint = Pubkey::from_str(&self.config.target_token_mint)?;
+        let decimals = self.target_mint_decimals().await
+            .ok_or_else(|| anyhow::anyhow!("Failed to read the decimals of {}", mint))?;
+        let token_program = self.target_token_program().await?;
+        let token_account = get_associated_token_address_with_program_id(&owner, &mint, &token_program);
+        let wsol_account = get_associated_token_address_with_program_id(&owner, &spl_token::native_mint::ID, &spl_token::ID);
+        let (tokens_before, wsol_before) = swap_account_balances(&rpc, &token_account, &wsol_account).await?;
+
+        let (swap_direction, in_type, amount_in) = match (trade.trade_type, trade.tokens) {
+            (TradeType::Buy, _) => (SwapDirection::Buy, SwapInType::Qty, trade.sol_amount),
+            (TradeType::Sell, Some(tokens)) => (SwapDirection::Sell, SwapInType::Qty, tokens),
+            (TradeType::Sell, None) => (SwapDirection::Sell, SwapInType::Pct, 1.0),
+        };
+        let raydium = RaydiumCPMM::new(
+            trade.wallet.clone(),
+            Some(self.config.app_state.rpc_client.clone()),
+            Some(rpc.clone()),
+            self.config.pool_id.clone(),
+            self.config.pool_base_account.clone(),
+            self.config.pool_quote_account.clone(),
+        ).map_err(|e| anyhow::anyhow!("Failed to create RaydiumCPMM instance: {}", e))?;
+        let (keypair, instructions, _) = raydium.build_swap_from_default_info(SwapConfig {
+            mint: self.config.target_token_mint.clone(),
+            swap_direction,
+            in_type,
+            amount_in,
+            slippage: trade.slippage_bps.unwrap_or(self.config.slippage),
+            max_buy_amount: trade.sol_amount,
+        }).await?;
+
+        let blockhash = state.blockhash(&rpc).await?;
+        let transaction = Transaction::new_signed_with_payer(&instructions, Some(&keypair.pubkey()), &[keypair.as_ref()], blockhash);
+        let signature = rpc.send_and_confirm_transaction(&transaction).await?;
+        state.record_sent(signature, &[token_account]);
+
+        let (tokens_after, wsol_after) = swap_account_balances(&rpc, &token_account, &wsol_account).await?;
+        let tokens = tokens_after.abs_diff(tokens_before) as f64 / 10f64.powi(decimals as i32);
+        let lamports = wsol_after.abs_diff(wsol_before);
+        let price = if tokens > 0.0 { lamports as f64 / 1_000_000_000.0 / tokens } else { 0.0 };
+        self.logger.log(format!(
+            "✅ {:?} confirmed: {:.4} tokens for {:.6} SOL ({})",
+            trade.trade_type, tokens, lamports as f64 / 1_000_000_000.0, signature
+        ).green().to_string());
+
+        Ok(TradeFill { signature, tokens, price, lamports })
+    }
+
+    /// Queue the sells position rules call for, every POSITION_EXIT_CHECK, from the wallet that
+    /// holds each position. Nothing is checked while trading is paused; exits the queue had no
+    /// room for are retried first. An exit's rungs stay pending until its trade finishes.
+    fn spawn_position_exits(self: &Arc<Self>, queue: TradeQueue) {
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let mut retry: Vec<QueuedTrade> = Vec::new();
+            let mut ticker = time::interval(POSITION_EXIT_CHECK);
+            loop {
+                ticker.tick().await;
+                if SHUTDOWN.is_requested() || !*engine.is_running.read().await {
+                    break;
+                }
+                if CONTROL.is_paused() || KILL_SWITCH.is_engaged() {
+                    continue;
+                }
+
+                let mut trades = std::mem::take(&mut retry);
+                for exit in engine.pending_position_exits().await {
+                    let Some(wallet) = engine.wallet_pool.lock().await.find_wallet(&exit.wallet) else {
+                        engine.logger.log(format!("No keypair for {} in the wallet pool, {} exit skipped", exit.wallet, exit.reason).yellow().to_string());
+                        engine.position_engine.lock().await.release_exit(&exit);
+                        continue;
+                    };
+                    let mut trade = QueuedTrade::exit(wallet, exit.tokens, &exit.reason);
+                    trade.protective = exit.protective;
+                    engine.queued_exits.lock().await.insert(trade.id, exit);
+                    trades.push(trade);
+                }
+                for trade in trades {
+                    if let Err(trade) = queue.try_enqueue(trade) {
+                        retry.push(trade);
+                    }
+                }
+                if !retry.is_empty() {
+                    engine.logger.log(format!("🚦 Trade queue full, {} exit sell(s) retried on the next check", retry.len()).yellow().to_string());
+                }
+            }
+        });
+    }
+
+    /// Start the engine's background tasks and its trade queue. Called once by the binary after
+    /// `restore_state`; trades are then submitted on the returned queue and `execute`
+    /// (`execute_trade` in the binary) builds, signs, sends and confirms each one.
+    pub async fn start_engine<F, Fut>(self: &Arc<Self>, execute: F) -> TradeQueue
+    where
+        F: Fn(Arc<MarketMaker>, QueuedTrade, OwnedMutexGuard<WalletState>) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<TradeFill>> + Send + 'static,
+    {
+        // Background tasks run until the shutdown handler clears this
+        *self.is_running.write().await = true;
+        // Disabled subsystems go inert before anything reads them
+        self.apply_subsystem_toggles().await;
+        self.spawn_shutdown_handler();
+        self.spawn_state_checkpoint();
+        self.spawn_config_watcher();
+        self.spawn_inventory_monitor();
+        self.spawn_drawdown_monitor();
+        self.spawn_liquidity_monitor();
+        self.spawn_rug_detector();
+        self.spawn_creator_monitor();
+        self.spawn_trade_stream();
+        self.spawn_pool_state_stream();
+        self.spawn_order_flow_monitor();
+        self.spawn_crossover_monitor();
+        self.spawn_vwap_monitor();
+        self.spawn_bollinger_guard();
+        self.spawn_guardian_alerts();
+        self.spawn_activity_report();
+        self.spawn_pnl_report();
+        let queue = self.spawn_trade_queue(execute);
+        self.spawn_position_exits(queue.clone());
+        if let Some(events) = self.spawn_wallet_monitor() {
+            if let Some(orders) = self.spawn_copy_trader(events.subscribe()) {
+                self.spawn_copy_orders(orders, queue.clone());
+            }
+        }
+        queue
+    }
+
+    /// Reserve a trade slot for `wallet` under the hard rate limits, right before the trade is
+    /// submitted. On `Err` the queued trade is skipped; the limiter logs which limit was hit.
+    pub async fn acquire_trade_slot(&self, wallet: &Pubkey) -> std::result::Result<(), RateLimited> {
+        self.rate_limiter.lock().await.try_acquire(&wallet.to_string())
+    }
+
+    /// Trades above APPROVAL_THRESHOLD_SOL wait for an operator decision (/approve or /reject in
+    /// Telegram, or `solana-mm approve|reject`); returns false if the trade was rejected or
+    /// timed out and should be skipped
+    pub async fn approve_large_trade(&self, trade_type: &TradeType, wallet: &Pubkey, sol_amount: f64) -> bool {
+        let threshold = get_approval_threshold();
+        if threshold <= 0.0 || sol_amount <= threshold {
+            return true;
+        }
+        let side = match trade_type {
+            TradeType::Buy => "BUY",
+            TradeType::Sell => "SELL",
+        };
+        APPROVALS.request(&format!(
+            "{} {:.4} SOL{} of {} from wallet {}",
+            side, sol_amount, usd_suffix(sol_amount, USD_PRICES.sol_usd().await), self.config.target_token_mint, wallet
+        )).await
+    }
+
+    /// Whether a buy of `sol_amount` may start under the daily spend cap. An allowed buy holds
+    /// a reservation until its fill is booked or `release_spend` drops it.
+    /// Sends a Telegram alert when the breaker trips and when it resets.
+    pub async fn check_spend_limit(&self, sol_amount: f64) -> bool {
+        let decision = self.spend_breaker.lock().await.reserve_buy(sol_amount);
+        let alert = match decision {
+            SpendDecision::Tripped => Some(format!(
+                "🧯 Daily SOL spend cap reached, new buys halted until the 24h window rolls (blocked buy: {:.4} SOL)",
+                sol_amount
+            )),
+            SpendDecision::Resumed => Some("✅ Daily SOL spend window rolled, buys resumed".to_string()),
+            _ => None,
+        };
+
+        if let Some(alert) = alert {
+            if self.config.enable_telegram_notifications {
+                if let Err(e) = telegram::send_notification(&alert).await {
+                    self.logger.log(format!("Failed to send spend cap alert: {}", e).red().to_string());
+                }
+            }
+        }
+
+        matches!(decision, SpendDecision::Allowed | SpendDecision::Resumed)
+    }
+
+    /// Drop the spend reservation of a buy that didn't go through
+    pub async fn release_spend(&self, sol_amount: f64) {
+        self.spend_breaker.lock().await.release(sol_amount);
+    }
+
+    /// Cost basis and PnL across all wallets at the latest price
+    pub async fn pnl_summary(&self) -> PnlSummary {
+        let price = self.latest_price().await.unwrap_or(0.0);
+        self.position_engine.lock().await.pnl_summary(price)
+    }
+
+    /// Send the PnL report to Telegram every PNL_REPORT_MINUTES (default 60, 0 = off)
+    pub fn spawn_pnl_report(self: &Arc<Self>) {
+        let minutes = std::env::var("PNL_REPORT_MINUTES").ok().and_then(|v| v.parse::<u64>().ok()).unwrap_or(60);
+        if minutes == 0 || !self.config.enable_telegram_notifications {
+            return;
+        }
+
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = time::interval(Duration::from_secs(minutes * 60));
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if SHUTDOWN.is_requested() || !*engine.is_running.read().await {
+                    break;
+                }
+                let report = format!(
+                    "{}\n{}",
+                    engine.pnl_summary().await.to_report(USD_PRICES.sol_usd().await),
+                    engine.dynamic_ratio_manager.lock().await.history_report(5)
+                );
+                engine.logger.log(report.clone().cyan().to_string());
+                if let Err(e) = telegram::send_notification(&report).await {
+                    engine.logger.log(format!("Failed to send PnL report: {}", e).red().to_string());
+                }
+            }
+        });
+    }
+
+    /// Log the activity summary, with the bot / organic split, every ACTIVITY_REPORT_MINUTES
+    /// (default 30, 0 = off) and send it to Telegram when notifications are on. Each report is
+    /// appended to the activity journal, and the first report of a new UTC day also sends the
+    /// rollup of the day before, compared with the day before that.
+    pub fn spawn_activity_report(self: &Arc<Self>) {
+        let minutes = get_activity_report_minutes();
+        if minutes == 0 {
+            return;
+        }
+
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let store = StateStore::from_env();
+            let mut rollup = match store.load::<ActivityRollupState>(ACTIVITY_ROLLUP_STATE_KEY) {
+                Ok(state) => state.unwrap_or_default(),
+                Err(e) => {
+                    engine.logger.log(format!("Failed to load activity rollup, starting a new day: {}", e).yellow().to_string());
+                    ActivityRollupState::default()
+                }
+            };
+            let mut ticker = time::interval(Duration::from_secs(minutes * 60));
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if SHUTDOWN.is_requested() || !*engine.is_running.read().await {
+                    break;
+                }
+                let sol_usd = USD_PRICES.sol_usd().await;
+                let activity = engine.activity_report(minutes).await;
+                *engine.last_activity_report.lock().await = Instant::now();
+                if let Err(e) = append_activity_entry(&ActivityJournalEntry::report(&activity)) {
+                    engine.logger.log(format!("Failed to journal activity report: {}", e).red().to_string());
+                }
+
+                let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+                let day_unique = engine.unique_traders.lock().await.estimate(ACTIVITY_RETENTION.as_secs(), unix_now()) as u32;
+                let mut reports = vec![activity.to_report(sol_usd)];
+                if let Some((day, before)) = rollup.record(&activity, &today, day_unique) {
+                    if let Err(e) = append_activity_entry(&ActivityJournalEntry::daily(&day, before.as_ref())) {
+                        engine.logger.log(format!("Failed to journal daily rollup: {}", e).red().to_string());
+                    }
+                    reports.push(day.to_report(before.as_ref(), sol_usd));
+                }
+                if let Err(e) = store.save(ACTIVITY_ROLLUP_STATE_KEY, &rollup) {
+                    engine.logger.log(format!("Failed to save activity rollup: {}", e).red().to_string());
+                }
+
+                for report in reports {
+                    engine.logger.log(report.clone().cyan().to_string());
+                    if engine.config.enable_telegram_notifications {
+                        if let Err(e) = telegram::send_notification(&report).await {
+                            engine.logger.log(format!("Failed to send activity report: {}", e).red().to_string());
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Track a parsed trade from the stream. External trades at or above WHALE_ALERT_SOL are
+    /// logged and pushed to Telegram immediately.
+    pub async fn record_token_activity(&self, activity: TokenActivity) {
+        let own_wallet = self.own_wallets().await.contains(&activity.user);
+        if !own_wallet {
+            self.order_flow.lock().await.record(activity.is_buy, activity.volume_sol);
+            self.unique_traders.lock().await.insert(activity.user.as_str(), unix_now());
+        }
+
+        let threshold = get_whale_alert_threshold();
+        if !own_wallet && threshold > 0.0 && activity.volume_sol >= threshold {
+            let alert = format!(
+                "🐋 Whale {} {:.4} SOL{} of {} by {} at {:.10} SOL/token",
+                if activity.is_buy { "BUY" } else { "SELL" },
+                activity.volume_sol, usd_suffix(activity.volume_sol, USD_PRICES.sol_usd().await),
+                self.config.target_token_mint, activity.user, activity.price
+            );
+            self.logger.log(alert.clone().magenta().bold().to_string());
+            if self.config.enable_telegram_notifications {
+                if let Err(e) = telegram::send_notification(&alert).await {
+                    self.logger.log(format!("Failed to send whale alert: {}", e).red().to_string());
+                }
+            }
+        }
+
+        {
+            let mut monitor = self.price_monitor.lock().await;
+            monitor.record_trade_candle(unix_now(), activity.price, activity.volume_sol, activity.is_buy);
+            monitor.record_vwap(activity.price, activity.volume_sol);
+            if self.is_price_monitor_enabled() {
+                monitor.observe_trade_price(activity.price, activity.volume_sol);
+            }
+        }
+        if self.config.subsystems.guardian_mode {
+            self.guardian_mode.lock().await.add_price_point(activity.price, activity.volume_sol);
+        }
+        if self.config.pump_guardian.enabled {
+            self.pump_guardian.lock().await.add_price_point(activity.price);
+        }
+
+        let mut activities = self.token_activities.lock().await;
+        activities.push_back(activity);
+        while activities.front().map(|a| a.timestamp.elapsed() > ACTIVITY_RETENTION).unwrap_or(false)
+            || activities.len() > MAX_TRACKED_ACTIVITIES
+        {
+            activities.pop_front();
+        }
+    }
+
+    /// Remember a transaction the bot submitted. Called for each confirmed queued trade.
+    pub async fn record_own_trade(&self, signature: &str, wallet: &str) {
+        self.own_trades.lock().await.record(signature, wallet);
+    }
+
+    /// True when `signature` is one of the bot's own recent transactions
+    pub async fn is_own_signature(&self, signature: &str) -> bool {
+        self.own_trades.lock().await.is_own_signature(signature)
+    }
+
+    /// Main wallet, pool wallets and every wallet that signed one of our recorded trades
+    async fn own_wallets(&self) -> HashSet<String> {
+        let mut wallets: HashSet<String> = self.wallet_pool.lock().await
+            .snapshot()
+            .wallets
+            .into_iter()
+            .map(|w| w.pubkey)
+            .collect();
+        wallets.insert(self.config.app_state.wallet.pubkey().to_string());
+        wallets.extend(self.own_trades.lock().await.wallets().iter().cloned());
+        wallets
+    }
+
+    /// Publish a parsed stream trade to every trade-event subscriber. Called by the trade stream
+    /// for each `TransactionAnalysis`; having no subscribers is not an error.
+    pub fn publish_trade_event(&self, analysis: &TransactionAnalysis) {
+        let _ = self.trade_events.send(analysis.clone());
+    }
+
+    /// Subscribe to parsed stream trades. A subscriber that falls more than
+    /// TRADE_EVENT_CAPACITY trades behind gets `RecvError::Lagged` and skips ahead.
+    pub fn subscribe_trade_events(&self) -> broadcast::Receiver<TransactionAnalysis> {
+        self.trade_events.subscribe()
+    }
+
+    /// External order-flow imbalance over the configured window
+    pub async fn order_flow(&self) -> OrderFlowSnapshot {
+        self.order_flow.lock().await.snapshot()
+    }
+
+    /// Push the order-flow imbalance to guardian mode
+    pub fn spawn_order_flow_monitor(self: &Arc<Self>) {
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = time::interval(Duration::from_secs(30));
+            loop {
+                ticker.tick().await;
+                if SHUTDOWN.is_requested() || !*engine.is_running.read().await {
+                    break;
+                }
+                let flow = engine.order_flow().await;
+                engine.guardian_mode.lock().await.set_order_flow(flow.imbalance);
+            }
+        });
+    }
+
+    /// Watch the configured moving averages and log each crossover
+    pub fn spawn_crossover_monitor(self: &Arc<Self>) {
+        let config = self.config.moving_averages;
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let mut detector = CrossoverDetector::new(config);
+            let mut ticker = time::interval(Duration::from_secs(15));
+            loop {
+                ticker.tick().await;
+                if SHUTDOWN.is_requested() || !*engine.is_running.read().await {
+                    break;
+                }
+                let signal = {
+                    let monitor = engine.price_monitor.lock().await;
+                    detector.update(monitor.candle_series(config.interval))
+                };
+                if let Some(signal) = signal {
+                    engine.logger.log(format!(
+                        "📐 {} {:?} crossover on {} candles: fast {:.10} / slow {:.10}",
+                        if signal.bullish { "Bullish" } else { "Bearish" },
+                        config.kind, config.interval.label(), signal.fast, signal.slow
+                    ).blue().bold().to_string());
+                }
+            }
+        });
+    }
+
+    /// Compare the latest trade price with session VWAP and alert when it deviates by more than
+    /// VWAP_ALERT_PCT
+    pub fn spawn_vwap_monitor(self: &Arc<Self>) {
+        let config = self.config.vwap;
+        if !config.is_enabled() {
+            return;
+        }
+
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let cooldown = Duration::from_secs(config.alert_cooldown_minutes * 60);
+            let mut last_alert: Option<(bool, Instant)> = None;
+            let mut ticker = time::interval(Duration::from_secs(15));
+            loop {
+                ticker.tick().await;
+                if SHUTDOWN.is_requested() || !*engine.is_running.read().await {
+                    break;
+                }
+                let (price, vwap) = {
+                    let monitor = engine.price_monitor.lock().await;
+                    let price = monitor.candle_series(CandleInterval::OneMinute).latest().map(|c| c.close);
+                    (price, monitor.session_vwap().value())
+                };
+                let (Some(price), Some(vwap)) = (price, vwap) else { continue };
+                let deviation = vwap_deviation_pct(price, vwap);
+
+                if deviation.abs() >= config.alert_pct {
+                    let above = deviation > 0.0;
+                    let repeat = last_alert
+                        .map(|(was_above, at)| was_above == above && at.elapsed() < cooldown)
+                        .unwrap_or(false);
+                    if !repeat {
+                        last_alert = Some((above, Instant::now()));
+                        let alert = format!(
+                            "📏 Price {:.10} is {:+.2}% {} session VWAP {:.10} for {}",
+                            price, deviation, if above { "above" } else { "below" }, vwap, engine.config.target_token_mint
+                        );
+                        engine.logger.log(alert.clone().yellow().to_string());
+                        if engine.config.enable_telegram_notifications {
+                            if let Err(e) = telegram::send_notification(&alert).await {
+                                engine.logger.log(format!("Failed to send VWAP alert: {}", e).red().to_string());
+                            }
+                        }
+                    }
+                } else if last_alert.is_some() {
+                    last_alert = None;
+                    engine.logger.log(format!("📏 Price back within {}% of session VWAP", config.alert_pct).green().to_string());
+                }
+            }
+        });
+    }
+
+    /// Send a Telegram alert when guardian mode activates or an activation is refused by
+    /// GUARDIAN_MAX_INTERVENTIONS_PER_DAY, with the interventions used in the last 24h
+    pub fn spawn_guardian_alerts(self: &Arc<Self>) {
+        if !self.config.enable_telegram_notifications || !self.config.subsystems.guardian_mode {
+            return;
+        }
+
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let (mut was_active, mut refused) = {
+                let status = engine.guardian_mode.lock().await.get_status();
+                (status.active, status.refused_activations)
+            };
+            let mut ticker = time::interval(Duration::from_secs(10));
+            loop {
+                ticker.tick().await;
+                if SHUTDOWN.is_requested() || !*engine.is_running.read().await {
+                    break;
+                }
+                let status = engine.guardian_mode.lock().await.get_status();
+                let limit = if status.max_interventions_per_day == 0 {
+                    "unlimited".to_string()
+                } else {
+                    status.max_interventions_per_day.to_string()
+                };
+
+                let mut alert = None;
+                if status.active && !was_active {
+                    alert = Some(format!(
+                        "🛡️ Guardian mode activated for {} ({:?}, {:.1}% drop in 5 min, {} min). Interventions in the last 24h: {}/{}",
+                        engine.config.target_token_mint, status.intervention_strength, status.recent_price_drop * 100.0,
+                        status.time_remaining.as_secs() / 60, status.interventions_last_24h, limit
+                    ));
+                } else if status.refused_activations > refused {
+                    alert = Some(format!(
+                        "🚫 Guardian activation refused for {}: daily limit reached ({}/{} in the last 24h)",
+                        engine.config.target_token_mint, status.interventions_last_24h, limit
+                    ));
+                }
+                was_active = status.active;
+                refused = status.refused_activations;
+
+                if let Some(alert) = alert {
+                    if let Err(e) = telegram::send_notification(&alert).await {
+                        engine.logger.log(format!("Failed to send guardian alert: {}", e).red().to_string());
+                    }
+                }
+            }
+        });
+    }
+
+    /// Check each closed candle against its Bollinger bands and report closes below the lower
+    /// band to guardian mode (BOLLINGER_ENABLED), as a trigger or as drop confirmation. The
+    /// intervention it starts resizes queued trades in `prepare_trade`.
+    pub fn spawn_bollinger_guard(self: &Arc<Self>) {
+        let config = self.config.bollinger;
+        if !config.enabled || !self.config.subsystems.guardian_mode {
+            return;
+        }
+
+        let engine = self.clone();
+        tokio::spawn(async move {
+            engine.guardian_mode.lock().await.set_band_confirmation(config.confirm_drops);
+            let mut last_candle = None;
+            let mut ticker = time::interval(Duration::from_secs(15));
+            loop {
+                ticker.tick().await;
+                if SHUTDOWN.is_requested() || !*engine.is_running.read().await {
+                    break;
+                }
+                let checked = engine.price_monitor.lock().await.bollinger_bands(config.interval, config.period, config.std_dev);
+                let Some((candle, bands)) = checked else { continue };
+                if last_candle == Some(candle.open_time) {
+                    continue;
+                }
+                last_candle = Some(candle.open_time);
+
+                if candle.close < bands.lower {
+                    engine.guardian_mode.lock().await.on_lower_band_breach(candle.close, bands.lower, bands.middle);
+                }
+            }
+        });
+    }
+
+    /// Last `limit` OHLCV candles for `interval`, oldest first (for indicators and reports)
+    pub async fn candles(&self, interval: CandleInterval, limit: usize) -> Vec<Candle> {
+        self.price_monitor.lock().await.get_candles(interval, limit)
+    }
+
+    /// Summarize stream activity over the last `period_minutes`, including a whales section.
+    /// Counts, volume and the unique-trader estimate are organic only; the bot's own trades are reported
+    /// separately, while prices and VWAP use every trade since they all move the pool.
+    pub async fn activity_report(&self, period_minutes: u64) -> TokenActivityReport {
+        let period = Duration::from_secs(period_minutes * 60);
+        let threshold = get_whale_alert_threshold();
+        let own_wallets = self.own_wallets().await;
+
+        let activities = self.token_activities.lock().await;
+        let recent: Vec<&TokenActivity> = activities.iter().filter(|a| a.timestamp.elapsed() <= period).collect();
+
+        let mut report = TokenActivityReport {
+            report_period_minutes: period_minutes,
+            parse_stats: PARSE_STATS.snapshot(),
+            ..Default::default()
+        };
+        let mut price_sum = 0.0;
+        let mut priced = 0u32;
+        let mut price_volume = 0.0;
+        let mut priced_volume = 0.0;
+        for activity in &recent {
+            let own = own_wallets.contains(&activity.user);
+            if own {
+                report.bot_trades += 1;
+                report.bot_volume_sol += activity.volume_sol;
+            } else {
+                report.total_trades += 1;
+                report.total_volume_sol += activity.volume_sol;
+                if activity.is_buy {
+                    report.buy_trades += 1;
+                    report.buy_volume_sol += activity.volume_sol;
+                } else {
+                    report.sell_trades += 1;
+                    report.sell_volume_sol += activity.volume_sol;
+                }
+            }
+            if activity.price > 0.0 {
+                price_sum += activity.price;
+                priced += 1;
+                report.min_price = if report.min_price == 0.0 { activity.price } else { report.min_price.min(activity.price) };
+                report.max_price = report.max_price.max(activity.price);
+                report.last_price = activity.price;
+                price_volume += activity.price * activity.volume_sol;
+                priced_volume += activity.volume_sol;
+            }
+        }
+        report.unique_traders = self.unique_traders.lock().await.estimate(period.as_secs(), unix_now()) as u32;
+        if priced > 0 {
+            report.average_price = price_sum / priced as f64;
+        }
+        if priced_volume > 0.0 {
+            report.vwap = price_volume / priced_volume;
+        }
+        drop(activities);
+        report.session_vwap = self.price_monitor.lock().await.session_vwap().value().unwrap_or(0.0);
+
+        if threshold > 0.0 {
+            report.whales = recent.iter()
+                .filter(|a| a.volume_sol >= threshold && !own_wallets.contains(&a.user))
+                .map(|a| (*a).clone())
+                .collect();
+            report.whales.sort_by(|a, b| b.volume_sol.partial_cmp(&a.volume_sol).unwrap_or(std::cmp::Ordering::Equal));
+        }
+        report
+    }
+
+    /// Token program that owns the target mint; the mint state read on the first call also
+    /// fills the mint cache
+    async fn target_token_program(&self) -> Result<Pubkey> {
+        if let Some(program) = self.target_token_program.get() {
+            return Ok(*program);
+        }
+        let mint = Pubkey::from_str(&self.config.target_token_mint)?;
+        let account = self.config.app_state.rpc_nonblocking_client.get_account(&mint).await?;
+        if let Ok(state) = StateWithExtensionsOwned::<spl_token_2022::state::Mint>::unpack(account.data) {
+            TOKEN_MINT_CACHE.insert(mint, state, None);
+        }
+        Ok(*self.target_token_program.get_or_init(|| account.owner))
+    }
+
+    /// Decimals of the target mint, from the mint cache or RPC
+    async fn target_mint_decimals(&self) -> Option<u8> {
+        let mint = Pubkey::from_str(&self.config.target_token_mint).ok()?;
+        if let Some(state) = TOKEN_MINT_CACHE.get(&mint) {
+            return Some(state.base.decimals);
+        }
+        let account = self.config.app_state.rpc_nonblocking_client.get_account(&mint).await.ok()?;
+        let state = StateWithExtensionsOwned::<spl_token_2022::state::Mint>::unpack(account.data).ok()?;
+        let decimals = state.base.decimals;
+        TOKEN_MINT_CACHE.insert(mint, state, None);
+        Some(decimals)
+    }
+
+    /// Stream the target token's transactions and hand each parsed swap to
+    /// `handle_stream_trade`, reconnecting until shutdown
+    pub fn spawn_trade_stream(self: &Arc<Self>) {
+        let Ok(mint) = Pubkey::from_str(&self.config.target_token_mint) else {
+            self.logger.log("TARGET_TOKEN_MINT is not a valid pubkey, trade stream disabled".red().to_string());
+            return;
+        };
+
+        let engine = self.clone();
+        tokio::spawn(async move {
+            while !SHUTDOWN.is_requested() && *engine.is_running.read().await {
+                let result = engine.stream_transactions("target_token", &[mint], |slot, info| {
+                    let engine = engine.clone();
+                    async move {
+                        engine.handle_stream_trade(slot, info).await;
+                    }
+                }).await;
+                if let Err(e) = result {
+                    engine.logger.log(format!("Trade stream error: {}, reconnecting", e).red().to_string());
+                }
+                time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+    }
+
+    /// One target-token transaction from the stream. A swap is recorded as token activity, which
+    /// drives prices, candles and reports, then goes into sandwich detection with its slot and
+    /// position in the block: ours are tracked, everyone else's matched against them.
+    async fn handle_stream_trade(&self, slot: u64, info: SubscribeUpdateTransactionInfo) {
+        let signature = Signature::try_from(info.signature.as_slice())
+            .map(|s| s.to_string())
+            .unwrap_or_default();
+        let index = info.index;
+        let update = SubscribeUpdateTransaction { transaction: Some(info), slot };
+        let Some(analysis) = transaction_parser::parse_dex_transaction(&update, &self.config.target_token_mint, &self.config.dex_type) else {
+            return;
+        };
+        self.publish_trade_event(&analysis);
+
+        let tokens = if analysis.is_buy { analysis.amount_out } else { analysis.amount_in };
+        let volume_sol = analysis.volume_change.abs();
+        let price = match self.target_mint_decimals().await {
+            Some(decimals) if tokens > 0 => volume_sol / (tokens as f64 / 10f64.powi(decimals as i32)),
+            _ => 0.0,
+        };
+        self.record_token_activity(TokenActivity {
+            timestamp: Instant::now(),
+            is_buy: analysis.is_buy,
+            volume_sol,
+            user: analysis.user.clone(),
+            price,
+        }).await;
+
+        let own = self.is_own_signature(&signature).await || self.own_wallets().await.contains(&analysis.user);
+        let slot_trade = SlotTrade { slot, index, signer: analysis.user.clone(), is_buy: analysis.is_buy, signature };
+        if own {
+            self.track_own_trade_slot(slot_trade).await;
+        } else {
+            self.observe_slot_trade(slot_trade).await;
+        }
+    }
+
+    /// Feed a target-pool swap from the gRPC stream (slot, position in block, signer) into
+    /// sandwich detection; alerts for any of our trades found sandwiched
+    pub async fn observe_slot_trade(&self, trade: SlotTrade) {
+        let hits = self.sandwich_detector.lock().await.observe(trade);
+        if hits.is_empty() || !self.config.enable_telegram_notifications {
+            return;
+        }
+        for hit in hits {
+            let alert = format!("🥪 Our trade {} was sandwiched by {}", hit.signature, hit.attacker);
+            if let Err(e) = telegram::send_notification(&alert).await {
+                self.logger.log(format!("Failed to send sandwich alert: {}", e).red().to_string());
+            }
+        }
+    }
+
+    /// Queue one of our confirmed trades for sandwich checking once its slot completes
+    pub async fn track_own_trade_slot(&self, trade: SlotTrade) {
+        self.sandwich_detector.lock().await.track_own_trade(trade);
+    }
+
+    /// Slippage (bps) for the next swap: the configured value, tightened while we are being
+    /// sandwiched. Queued trades carry it in `slippage_bps` for the swap build.
+    pub async fn current_slippage(&self) -> u64 {
+        let base = *self.live_slippage.read().await;
+        self.sandwich_detector.lock().await.slippage_bps(base)
+    }
+
+    /// True once the sandwich rate crossed its threshold; the submitter then prefers a private relay
+    pub async fn prefer_private_relay(&self) -> bool {
+        self.sandwich_detector.lock().await.prefer_private_relay()
+    }
+
+    /// Latest observed token price from the activity stream
+    pub async fn latest_price(&self) -> Option<f64> {
+        self.token_activities.lock().await.back().map(|a| a.price).filter(|p| *p > 0.0)
+    }
+
+    /// Sells requested by position rules at the latest price, one per wallet, capped at what the
+    /// wallet holds minus exit sells already queued for it
+    pub async fn pending_position_exits(&self) -> Vec<PositionExit> {
+        let Some(price) = self.latest_price().await else { return Vec::new() };
+        let mut engine = self.position_engine.lock().await;
+        let mut exits = engine.check_exits(price);
+        let queued = self.queued_exits.lock().await;
+        for exit in &mut exits {
+            let held = engine.get_position(&exit.wallet).map(|p| p.amount).unwrap_or(0.0);
+            let reserved: f64 = queued.values().filter(|q| q.wallet == exit.wallet).map(|q| q.tokens).sum();
+            exit.tokens = exit.tokens.min((held - reserved).max(0.0));
+        }
+        let (exits, empty): (Vec<_>, Vec<_>) = exits.into_iter().partition(|e| e.tokens > 0.0);
+        for exit in &empty {
+            engine.release_exit(exit);
+        }
+        exits
+    }
+
+    /// Live ratio bounds, wave settings and trade limits, shared by the control socket and
+    /// config reload. Registered by `new`; later calls return the registered instance.
+    pub fn runtime_params(&self) -> Arc<RuntimeParams> {
+        if let Some(params) = get_runtime_params() {
+            return params;
+        }
+        let params = Arc::new(RuntimeParams::new(
+            self.dynamic_ratio_manager.clone(),
+            self.volume_wave_manager.clone(),
+            self.live_slippage.clone(),
+            self.spend_breaker.clone(),
+            self.rate_limiter.clone(),
+        ));
+        register_runtime_params(params.clone());
+        // Another caller may have registered first; theirs is the one in use
+        get_runtime_params().unwrap_or(params)
+    }
+
+    /// Start watching config.toml for the trade-path limits (slippage, spend cap, rate limits)
+    pub fn spawn_config_watcher(&self) {
+        spawn_config_watcher(self.runtime_params());
+    }
+
+    /// Capture the current session marker
+    pub async fn state_snapshot(&self, clean_shutdown: bool) -> MarketMakerState {
+        self.session_handles().collect_state(clean_shutdown).await
+    }
+
+    /// Reload persisted state from a previous run so the schedule resumes instead of starting over.
+    /// Open positions come back from the position ledger, so their exit rules keep running and a
+    /// position that crossed a take-profit or stop-loss while the bot was down exits on the
+    /// first price check.
+    pub async fn restore_state(&self) -> Result<Option<RecoveryReport>> {
+        let store = StateStore::from_env();
+        let state: MarketMakerState = match store.load(MARKET_MAKER_STATE_KEY)? {
+            Some(state) => state,
+            None => return Ok(None),
+        };
+
+        let unclean_shutdown = !state.clean_shutdown;
+        if unclean_shutdown {
+            self.logger.log("⚠️ Previous run did not shut down cleanly, recovering persisted state".yellow().bold().to_string());
+        }
+
+        if let Some(snapshot) = store.load::<WalletPoolSnapshot>(WALLET_POOL_STATE_KEY)? {
+            self.wallet_pool.lock().await.restore_snapshot(&snapshot);
+        }
+
+        if let Some(entries) = store.load::<Vec<SpendEntry>>(SPEND_WINDOW_STATE_KEY)? {
+            self.spend_breaker.lock().await.restore(&entries);
+        }
+
+        let (restored_positions, restored_tokens) = match store.load::<PositionLedgerSnapshot>(POSITION_LEDGER_STATE_KEY)? {
+            Some(ledger) => {
+                self.position_engine.lock().await.restore(&ledger);
+                (ledger.positions.len(), ledger.positions.iter().map(|p| p.amount).sum())
+            },
+            None => (0, 0.0),
+        };
+
+        if let Some(progress) = store.load::<TwapProgress>(TWAP_STATE_KEY)? {
+            self.twap_accumulator.lock().await.restore(&progress);
+        }
+
+        // Indicators and guardian mode pick up where they left off instead of rebuilding history
+        if let Some(history) = store.load::<PriceHistorySnapshot>(PRICE_HISTORY_STATE_KEY)? {
+            self.price_monitor.lock().await.restore_history(&history);
+            self.guardian_mode.lock().await.restore_price_points(&history.guardian_points);
+        }
+        // Restarting must not reset the guardian cooldowns
+        if let Some(guardian) = store.load::<GuardianState>(GUARDIAN_STATE_KEY)? {
+            self.guardian_mode.lock().await.restore_state(&guardian);
+        }
+        if let Some(pump) = store.load::<PumpGuardianState>(PUMP_GUARDIAN_STATE_KEY)? {
+            self.pump_guardian.lock().await.restore_state(&pump);
+        }
+        // Keep the current buy ratio and its rotation schedule instead of drawing a new one
+        if let Some(ratios) = store.load::<DynamicRatioState>(DYNAMIC_RATIO_STATE_KEY)? {
+            self.dynamic_ratio_manager.lock().await.restore_state(&ratios);
+        }
+
+        let report = RecoveryReport {
+            unclean_shutdown,
+            restored_positions,
+            restored_tokens,
+        };
+
+        self.logger.log(format!(
+            "♻️ Resumed session: {} open positions ({:.2} tokens) saved {}s ago",
+            report.restored_positions,
+            report.restored_tokens,
+            (unix_now() - state.saved_at).max(0)
+        ).green().bold().to_string());
+
+        Ok(Some(report))
+    }
+
+    /// Wait while trading is paused from the control socket or the kill switch is engaged. The
+    /// queue workers call it through `admit_trade` before `prepare_trade`, so a paused trade is
+    /// held rather than sized. Returns false if a shutdown was requested; the trade is dropped.
+    pub async fn wait_while_paused(&self) -> bool {
+        let mut announced = false;
+        while CONTROL.is_paused() || KILL_SWITCH.is_engaged() {
+            if SHUTDOWN.is_requested() {
+                return false;
+            }
+            if !announced {
+                self.logger.log(format!(
+                    "⏸️ Trading paused ({}), waiting for resume...",
+                    CONTROL.pause_reason().unwrap_or_default()
+                ).yellow().to_string());
+                announced = true;
+            }
+            time::sleep(Duration::from_secs(1)).await;
+        }
+        !SHUTDOWN.is_requested()
+    }
+
+    /// Periodically checkpoint session state (marked as not cleanly shut down) so a crash
+    /// can be detected and resumed on the next start
+    pub fn spawn_state_checkpoint(&self) {
+        let handles = self.session_handles();
+        let logger = self.logger.clone();
+        let is_running = self.is_running.clone();
+        let interval = get_checkpoint_interval();
+
+        tokio::spawn(async move {
+            let store = StateStore::from_env();
+            let mut ticker = time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if SHUTDOWN.is_requested() || !*is_running.read().await {
+                    break;
+                }
+                handles.persist(&store, false, &logger).await;
+            }
+        });
+    }
+
+    /// Spawn the graceful shutdown sequence. Once a shutdown is requested it stops new trades,
+    /// waits for in-flight transactions, flushes wallet-pool/position state and sends a summary.
+    pub fn spawn_shutdown_handler(&self) {
+        let is_running = self.is_running.clone();
+        let handles = self.session_handles();
+        let logger = self.logger.clone();
+        let notify = self.config.enable_telegram_notifications;
+
+        tokio::spawn(async move {
+            SHUTDOWN.requested().await;
+
+            // Stop scheduling new trades
+            *is_running.write().await = false;
+
+            logger.log(format!("⏳ Waiting for {} in-flight trade(s) to confirm...", SHUTDOWN.in_flight()).yellow().to_string());
+            let drained = SHUTDOWN.wait_for_in_flight(get_shutdown_timeout()).await;
+
+            // Only a fully drained stop counts as clean; otherwise the next start runs recovery
+            let store = StateStore::from_env();
+            handles.persist(&store, drained, &logger).await;
+
+            let wallet_count = handles.wallet_pool.lock().await.snapshot().wallets.len();
+
+            // Fill counts come from the position ledger, which books every confirmed trade
+            let price = handles.token_activities.lock().await.back().map(|a| a.price).unwrap_or(0.0);
+            let pnl = handles.position_engine.lock().await.pnl_summary(price);
+
+            let summary = format!(
+                "🛑 Market maker stopped\nTrades: {} ({} buys / {} sells)\nWallets: {}\nOpen positions: {}\nIn-flight drained: {}\n{}",
+                pnl.totals.buys + pnl.totals.sells, pnl.totals.buys, pnl.totals.sells, wallet_count, pnl.open_positions,
+                if drained { "yes" } else { "no (timed out)" },
+                pnl.to_report(USD_PRICES.sol_usd().await)
+            );
+            logger.log(summary.clone().green().to_string());
+
+            if notify {
+                if let Err(e) = telegram::send_notification(&summary).await {
+                    logger.log(format!("Failed to send shutdown summary: {}", e).red().to_string());
+                }
+            }
+
+            SHUTDOWN.mark_completed();
+        });
+    }
+}
+
+/// Tracing span carried by every log line of a queued trade
+fn trade_span(trade: &QueuedTrade) -> tracing::Span {
+    tracing::info_span!("trade", trade_id = %trade.id, side = ?trade.trade_type, wallet = %trade.wallet.pubkey(), sol = trade.sol_amount)
+}
+
+/// Raw amounts held in a wallet's target-token and WSOL accounts; a missing account holds 0
+async fn swap_account_balances(
+    rpc: &anchor_client::solana_client::nonblocking::rpc_client::RpcClient,
+    token_account: &Pubkey,
+    wsol_account: &Pubkey,
+) -> Result<(u64, u64)> {
+    let accounts = rpc.get_multiple_accounts(&[*token_account, *wsol_account]).await?;
+    let amount = |index: usize| {
+        accounts.get(index)
+            .and_then(|account| account.as_ref())
+            .and_then(|account| token_account_amount(&account.data))
+            .unwrap_or(0)
+    };
+    Ok((amount(0), amount(1)))
+}
+
+/// Store a bonding curve account update as the pool's virtual reserves and creator
+pub fn apply_bonding_curve_update(curve: Pubkey, mint: Pubkey, data: &[u8]) {
+    if let Some(account) = pump_fun::decode_bonding_curve(data) {
+        POOL_STATE.set_pool(PoolInfo {
+            pool_id: curve,
+            base_mint: mint,
+            quote_mint: spl_token::native_mint::ID,
+            base_reserve: account.virtual_token_reserves,
+            quote_reserve: account.virtual_sol_reserves,
+            coin_creator: account.creator,
+        });
+    }
 }
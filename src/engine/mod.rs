@@ -2,4 +2,10 @@ pub mod market_maker;
 pub mod monitor;
 pub mod swap;
 pub mod transaction_parser;
+pub mod activity_journal;
+pub mod trade_queue;
 pub mod random_trader;
+pub mod runtime_params;
+pub mod positions;
+pub mod sandwich;
+pub mod copy_trader;
@@ -1,5 +1,12 @@
 use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::Signature;
+use colored::Colorize;
+use std::str::FromStr;
 use std::{collections::HashSet, time::Instant};
+use yellowstone_grpc_proto::geyser::SubscribeUpdateTransactionInfo;
+use crate::common::logger::Logger;
+use crate::engine::copy_trader::{parse_target_trade, TargetTrade};
+use crate::engine::transaction_parser::resolve_account_keys;
 
 #[derive(Clone, Debug, PartialEq, Eq, Copy)]
 pub enum InstructionType {
@@ -7,7 +14,20 @@ pub enum InstructionType {
     PumpBuy,
     PumpSell,
     PumpSwapBuy,
-    PumpSwapSell
+    PumpSwapSell,
+    /// Raydium CPMM `swap_base_input` / `swap_base_output`; the direction is in the accounts
+    CpmmSwap,
+}
+
+impl InstructionType {
+    /// True for buys, false for sells; None for mints and CPMM swaps
+    pub fn is_buy(&self) -> Option<bool> {
+        match self {
+            InstructionType::PumpBuy | InstructionType::PumpSwapBuy => Some(true),
+            InstructionType::PumpSell | InstructionType::PumpSwapSell => Some(false),
+            InstructionType::PumpMint | InstructionType::CpmmSwap => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -33,4 +53,152 @@ pub struct TokenTrackingInfo {
     pub top_pnl: f64,
     pub last_sell_time: Instant,
     pub completed_intervals: HashSet<String>,
-}
\ No newline at end of file
+}
+const PUMP_FUN_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+const PUMP_SWAP_PROGRAM_ID: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+const RAYDIUM_CPMM_PROGRAM_ID: &str = "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C";
+
+// Anchor instruction discriminators; pump.fun and PumpSwap share `buy` / `sell`
+const CREATE_DISCRIMINATOR: [u8; 8] = [24, 30, 200, 40, 5, 28, 7, 119];
+const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+const SWAP_BASE_INPUT_DISCRIMINATOR: [u8; 8] = [143, 190, 90, 218, 196, 30, 51, 222];
+const SWAP_BASE_OUTPUT_DISCRIMINATOR: [u8; 8] = [55, 217, 98, 86, 163, 74, 180, 173];
+
+/// Target-wallet monitoring (MONITOR_WALLETS, empty = only the copy-trade targets are watched)
+#[derive(Debug, Clone)]
+pub struct WalletMonitorConfig {
+    pub wallets: Vec<Pubkey>,
+    /// Telegram alert for monitored-wallet trades of at least this size (0 = no alerts)
+    pub alert_min_sol: f64,
+    /// Alert on new token mints by monitored wallets
+    pub alert_mints: bool,
+}
+
+impl WalletMonitorConfig {
+    /// Read MONITOR_WALLETS, MONITOR_ALERT_MIN_SOL and MONITOR_ALERT_MINTS
+    pub fn from_env() -> Self {
+        let get = |name: &str| std::env::var(name).ok();
+        Self {
+            wallets: get("MONITOR_WALLETS")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|w| Pubkey::from_str(w.trim()).ok())
+                .collect(),
+            alert_min_sol: get("MONITOR_ALERT_MIN_SOL").and_then(|v| v.parse().ok()).unwrap_or(0.0),
+            alert_mints: get("MONITOR_ALERT_MINTS").map(|v| v.trim().to_lowercase() != "false").unwrap_or(true),
+        }
+    }
+}
+
+/// A classified transaction signed by a watched wallet
+#[derive(Debug, Clone)]
+pub struct TargetWalletEvent {
+    pub wallet: String,
+    pub signature: String,
+    pub slot: u64,
+    /// Pump.fun / PumpSwap instruction when recognised; None for swaps on other venues
+    pub instruction_type: Option<InstructionType>,
+    /// Mint, direction and size from the wallet's balance changes (None for non-trades)
+    pub trade: Option<TargetTrade>,
+}
+
+impl TargetWalletEvent {
+    /// SOL moved by the trade, 0 when the event is not a trade
+    pub fn sol_amount(&self) -> f64 {
+        self.trade.as_ref().map(|t| t.sol_amount).unwrap_or(0.0)
+    }
+}
+
+/// Pump.fun, PumpSwap and Raydium CPMM instructions in a transaction, matched by program id and
+/// the 8-byte discriminator. Top-level instructions come first, each followed by its CPIs, so
+/// routed and aggregator trades are found too.
+pub fn classify_instructions(info: &SubscribeUpdateTransactionInfo) -> Vec<InstructionType> {
+    let Some(message) = info.transaction.as_ref().and_then(|t| t.message.as_ref()) else { return Vec::new() };
+    let keys = resolve_account_keys(info);
+    let classify = |program_id_index: u32, data: &[u8]| -> Option<InstructionType> {
+        let program = keys.get(program_id_index as usize)?.to_string();
+        let discriminator: [u8; 8] = data.get(..8)?.try_into().ok()?;
+        match (program.as_str(), discriminator) {
+            (PUMP_FUN_PROGRAM_ID, CREATE_DISCRIMINATOR) => Some(InstructionType::PumpMint),
+            (PUMP_FUN_PROGRAM_ID, BUY_DISCRIMINATOR) => Some(InstructionType::PumpBuy),
+            (PUMP_FUN_PROGRAM_ID, SELL_DISCRIMINATOR) => Some(InstructionType::PumpSell),
+            (PUMP_SWAP_PROGRAM_ID, BUY_DISCRIMINATOR) => Some(InstructionType::PumpSwapBuy),
+            (PUMP_SWAP_PROGRAM_ID, SELL_DISCRIMINATOR) => Some(InstructionType::PumpSwapSell),
+            (RAYDIUM_CPMM_PROGRAM_ID, SWAP_BASE_INPUT_DISCRIMINATOR | SWAP_BASE_OUTPUT_DISCRIMINATOR) => Some(InstructionType::CpmmSwap),
+            _ => None,
+        }
+    };
+
+    let inner = info.meta.as_ref().map(|meta| meta.inner_instructions.as_slice()).unwrap_or_default();
+    let mut found = Vec::new();
+    for (index, ix) in message.instructions.iter().enumerate() {
+        found.extend(classify(ix.program_id_index, &ix.data));
+        for cpi in inner.iter().filter(|group| group.index as usize == index).flat_map(|group| group.instructions.iter()) {
+            found.extend(classify(cpi.program_id_index, &cpi.data));
+        }
+    }
+    found
+}
+
+/// Classifies watched-wallet transactions from the gRPC stream into `TargetWalletEvent`s
+pub struct TargetWalletMonitor {
+    wallets: Vec<Pubkey>,
+    logger: Logger,
+}
+
+impl TargetWalletMonitor {
+    pub fn new(wallets: Vec<Pubkey>) -> Self {
+        let logger = Logger::new("[WALLET-MONITOR] => ".blue().bold().to_string());
+        if !wallets.is_empty() {
+            logger.log(format!("👀 Monitoring {} target wallet(s)", wallets.len()).blue().to_string());
+        }
+        Self { wallets, logger }
+    }
+
+    pub fn wallets(&self) -> &[Pubkey] {
+        &self.wallets
+    }
+
+    /// One event per watched wallet that signed `info` and traded or minted in it
+    pub fn on_transaction(&self, slot: u64, info: &SubscribeUpdateTransactionInfo) -> Vec<TargetWalletEvent> {
+        let signer_count = info.transaction.as_ref()
+            .and_then(|t| t.message.as_ref())
+            .and_then(|m| m.header.as_ref())
+            .map(|h| h.num_required_signatures as usize)
+            .unwrap_or(1);
+        let signers: Vec<Pubkey> = resolve_account_keys(info).into_iter().take(signer_count).collect();
+        let signature = Signature::try_from(info.signature.as_slice()).map(|s| s.to_string()).unwrap_or_default();
+        let instructions = classify_instructions(info);
+        let mut events = Vec::new();
+        for wallet in self.wallets.iter().filter(|w| signers.contains(w)) {
+            let trade = parse_target_trade(info, wallet);
+            let instruction_type = match &trade {
+                // Prefer the instruction matching the observed direction
+                Some(trade) => instructions.iter().copied().find(|kind| match kind.is_buy() {
+                    Some(is_buy) => is_buy == trade.is_buy,
+                    None => *kind == InstructionType::CpmmSwap,
+                }),
+                None => instructions.iter().copied().find(|kind| *kind == InstructionType::PumpMint),
+            };
+            if trade.is_none() && instruction_type.is_none() {
+                continue;
+            }
+            self.logger.log(format!(
+                "👀 {} {:?} {} at slot {}",
+                wallet,
+                instruction_type,
+                trade.as_ref().map(|t| format!("{} {} {:.4} SOL", if t.is_buy { "BUY" } else { "SELL" }, t.mint, t.sol_amount)).unwrap_or_default(),
+                slot
+            ).blue().to_string());
+            events.push(TargetWalletEvent {
+                wallet: wallet.to_string(),
+                signature: signature.clone(),
+                slot,
+                instruction_type,
+                trade,
+            });
+        }
+        events
+    }
+}
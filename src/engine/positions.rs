@@ -0,0 +1,1090 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::Arc;
+use anyhow::Result;
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use crate::common::logger::Logger;
+use crate::common::persistence::{state_dir, unix_now};
+use crate::common::usd_price::usd_suffix;
+use crate::engine::monitor::{RetracementLevel, TokenTrackingInfo};
+
+/// State store key for the position ledger
+pub const POSITION_LEDGER_STATE_KEY: &str = "position_ledger";
+
+/// One rung of the take-profit ladder, in the same shape as `monitor::RetracementLevel`:
+/// once the position is up `threshold`% from entry, sell `sell_amount`% of the original size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TakeProfitLevel {
+    pub threshold: u64,
+    pub sell_amount: u64,
+}
+
+/// Parse TAKE_PROFIT_LEVELS, e.g. "20:25,50:25,100:50" (gain%:sell%)
+pub fn parse_take_profit_levels(value: &str) -> Result<Vec<TakeProfitLevel>, String> {
+    let mut levels = Vec::new();
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (threshold, sell_amount) = entry
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid take-profit level '{}', expected gain%:sell%", entry))?;
+        let threshold: u64 = threshold.trim().parse()
+            .map_err(|_| format!("Invalid take-profit gain '{}'", threshold))?;
+        let sell_amount: u64 = sell_amount.trim().parse()
+            .map_err(|_| format!("Invalid take-profit sell amount '{}'", sell_amount))?;
+        if threshold == 0 || sell_amount == 0 || sell_amount > 100 {
+            return Err(format!("Take-profit level '{}' must have gain > 0 and 0 < sell% <= 100", entry));
+        }
+        levels.push(TakeProfitLevel { threshold, sell_amount });
+    }
+
+    levels.sort_by_key(|l| l.threshold);
+    let total: u64 = levels.iter().map(|l| l.sell_amount).sum();
+    if total > 100 {
+        return Err(format!("Take-profit levels sell {}% in total, expected at most 100%", total));
+    }
+    Ok(levels)
+}
+
+/// Take-profit ladder from TAKE_PROFIT_LEVELS (empty = disabled)
+pub fn get_take_profit_levels() -> Vec<TakeProfitLevel> {
+    std::env::var("TAKE_PROFIT_LEVELS")
+        .ok()
+        .and_then(|v| parse_take_profit_levels(&v).ok())
+        .unwrap_or_default()
+}
+
+/// Parse RETRACEMENT_LEVELS, e.g. "50:20:30,100:15:50" (peak gain%:retrace%:sell%): once a
+/// position has been up 50%, sell 30% of its size when price falls 20% from the peak
+pub fn parse_retracement_levels(value: &str) -> Result<Vec<RetracementLevel>, String> {
+    let mut levels = Vec::new();
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parts: Vec<&str> = entry.split(':').map(str::trim).collect();
+        let &[threshold, percentage, sell_amount] = parts.as_slice() else {
+            return Err(format!("Invalid retracement level '{}', expected peak gain%:retrace%:sell%", entry));
+        };
+        let parse = |v: &str, what: &str| v.parse::<u64>().map_err(|_| format!("Invalid retracement {} '{}'", what, v));
+        let level = RetracementLevel {
+            threshold: parse(threshold, "peak gain")?,
+            percentage: parse(percentage, "retrace")?,
+            sell_amount: parse(sell_amount, "sell amount")?,
+        };
+        if level.threshold == 0 || level.percentage == 0 || level.percentage >= 100 || level.sell_amount == 0 || level.sell_amount > 100 {
+            return Err(format!("Retracement level '{}' must have gain > 0, 0 < retrace% < 100 and 0 < sell% <= 100", entry));
+        }
+        levels.push(level);
+    }
+
+    levels.sort_by_key(|l| l.threshold);
+    let total: u64 = levels.iter().map(|l| l.sell_amount).sum();
+    if total > 100 {
+        return Err(format!("Retracement levels sell {}% in total, expected at most 100%", total));
+    }
+    Ok(levels)
+}
+
+/// Retracement sells from RETRACEMENT_LEVELS (empty = disabled)
+pub fn get_retracement_levels() -> Vec<RetracementLevel> {
+    std::env::var("RETRACEMENT_LEVELS")
+        .ok()
+        .and_then(|v| parse_retracement_levels(&v).ok())
+        .unwrap_or_default()
+}
+
+/// Minimum spacing between retracement exits across wallets (RETRACEMENT_STAGGER_SECONDS)
+pub fn get_retracement_stagger() -> Duration {
+    Duration::from_secs(
+        std::env::var("RETRACEMENT_STAGGER_SECONDS").ok().and_then(|v| v.parse().ok()).unwrap_or(30),
+    )
+}
+
+/// Profit milestone sells: every `interval_percent` of gain (+X%, +2X%, ...) sells
+/// `sell_percent` of the position's size, once per milestone
+#[derive(Debug, Clone, Copy)]
+pub struct MilestoneConfig {
+    pub interval_percent: f64,
+    pub sell_percent: f64,
+    /// Milestones beyond this many intervals are ignored
+    pub max_intervals: u64,
+}
+
+impl MilestoneConfig {
+    /// Read MILESTONE_INTERVAL_PERCENT (unset or 0 = disabled), MILESTONE_SELL_PERCENT and
+    /// MILESTONE_MAX_INTERVALS
+    pub fn from_env() -> Option<Self> {
+        let get = |name: &str| std::env::var(name).ok();
+        let interval_percent = get("MILESTONE_INTERVAL_PERCENT").and_then(|v| v.parse::<f64>().ok()).unwrap_or(0.0);
+        if interval_percent <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            interval_percent,
+            sell_percent: get("MILESTONE_SELL_PERCENT").and_then(|v| v.parse().ok()).unwrap_or(10.0),
+            max_intervals: get("MILESTONE_MAX_INTERVALS").and_then(|v| v.parse().ok()).unwrap_or(10),
+        })
+    }
+
+    /// Completed-interval key for the `n`th milestone, e.g. "+50%"
+    fn interval_key(&self, n: u64) -> String {
+        format!("+{}%", self.interval_percent * n as f64)
+    }
+}
+
+/// Stop-loss settings shared by all positions
+#[derive(Debug, Clone)]
+pub struct StopLossConfig {
+    /// Loss in percent that triggers the exit
+    pub percent: f64,
+    /// Measure the loss from the highest price seen since entry instead of the entry price
+    pub trailing: bool,
+    /// Minimum spacing between stop-loss exits, so wallets don't all sell at once
+    pub stagger: Duration,
+}
+
+impl StopLossConfig {
+    /// Read STOP_LOSS_PERCENT (unset or 0 = disabled), STOP_LOSS_TRAILING and STOP_LOSS_STAGGER_SECONDS
+    pub fn from_env() -> Option<Self> {
+        let percent = std::env::var("STOP_LOSS_PERCENT").ok().and_then(|v| v.parse::<f64>().ok()).unwrap_or(0.0);
+        if percent <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            percent,
+            trailing: std::env::var("STOP_LOSS_TRAILING").map(|v| v.trim().to_lowercase() == "true").unwrap_or(false),
+            stagger: Duration::from_secs(
+                std::env::var("STOP_LOSS_STAGGER_SECONDS").ok().and_then(|v| v.parse().ok()).unwrap_or(120),
+            ),
+        })
+    }
+}
+
+/// Exit rules applied to every position
+#[derive(Debug, Clone, Default)]
+pub struct PositionRules {
+    pub take_profit_levels: Vec<TakeProfitLevel>,
+    pub stop_loss: Option<StopLossConfig>,
+    pub retracement_levels: Vec<RetracementLevel>,
+    pub retracement_stagger: Duration,
+    pub milestones: Option<MilestoneConfig>,
+}
+
+impl PositionRules {
+    pub fn from_env() -> Self {
+        Self {
+            take_profit_levels: get_take_profit_levels(),
+            stop_loss: StopLossConfig::from_env(),
+            retracement_levels: get_retracement_levels(),
+            retracement_stagger: get_retracement_stagger(),
+            milestones: MilestoneConfig::from_env(),
+        }
+    }
+}
+
+/// Token position held by a single wallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Position {
+    pub wallet: String,
+    pub amount: f64,
+    /// Tokens bought into the position, used to size each take-profit rung
+    pub original_amount: f64,
+    /// Average entry price in SOL per token
+    pub entry_price: f64,
+    /// Highest price seen since entry, for trailing stops
+    pub peak_price: f64,
+    pub completed_levels: HashSet<u64>,
+    /// Peak-gain thresholds of retracement levels already sold
+    #[serde(default)]
+    pub completed_retracements: HashSet<u64>,
+}
+
+impl Position {
+    /// Gain from entry in percent
+    pub fn pnl_percent(&self, price: f64) -> f64 {
+        if self.entry_price <= 0.0 {
+            return 0.0;
+        }
+        (price / self.entry_price - 1.0) * 100.0
+    }
+
+    /// Highest gain from entry reached so far, in percent
+    pub fn peak_pnl_percent(&self) -> f64 {
+        self.pnl_percent(self.peak_price)
+    }
+
+    /// Fall from the peak price in percent (0 at or above the peak)
+    pub fn retracement_percent(&self, price: f64) -> f64 {
+        if self.peak_price <= 0.0 {
+            return 0.0;
+        }
+        ((1.0 - price / self.peak_price) * 100.0).max(0.0)
+    }
+
+    /// Price at which the stop-loss fires
+    pub fn stop_price(&self, stop_loss: &StopLossConfig) -> f64 {
+        let reference = if stop_loss.trailing { self.peak_price.max(self.entry_price) } else { self.entry_price };
+        reference * (1.0 - stop_loss.percent / 100.0)
+    }
+}
+
+/// A confirmed buy or sell, appended to `fills.jsonl` in the state directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    pub at: i64,
+    pub wallet: String,
+    pub is_buy: bool,
+    pub tokens: f64,
+    /// SOL per token
+    pub price: f64,
+    pub sol: f64,
+    pub signature: String,
+}
+
+/// Append a fill to the on-disk trade ledger
+pub fn append_fill(fill: &Fill) -> Result<()> {
+    let dir = state_dir();
+    std::fs::create_dir_all(&dir)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join("fills.jsonl"))?;
+    writeln!(file, "{}", serde_json::to_string(fill)?)?;
+    Ok(())
+}
+
+/// Aggregate cost basis and PnL across every wallet (SOL)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PnlTotals {
+    pub bought_sol: f64,
+    pub sold_sol: f64,
+    pub bought_tokens: f64,
+    pub sold_tokens: f64,
+    pub realized_pnl_sol: f64,
+    /// Confirmed fills by side
+    #[serde(default)]
+    pub buys: u32,
+    #[serde(default)]
+    pub sells: u32,
+}
+
+/// Point-in-time PnL report
+#[derive(Debug, Clone)]
+pub struct PnlSummary {
+    pub totals: PnlTotals,
+    pub open_positions: usize,
+    pub open_tokens: f64,
+    /// SOL paid for the tokens still held
+    pub cost_basis_sol: f64,
+    pub market_value_sol: f64,
+    pub unrealized_pnl_sol: f64,
+}
+
+impl PnlSummary {
+    pub fn total_pnl_sol(&self) -> f64 {
+        self.totals.realized_pnl_sol + self.unrealized_pnl_sol
+    }
+
+    /// Multi-line text used by reports and Telegram; USD values are added when `sol_usd` is known
+    pub fn to_report(&self, sol_usd: Option<f64>) -> String {
+        format!(
+            "💹 PnL: {:+.4} SOL{} (realized {:+.4}, unrealized {:+.4})\nVolume: {:.4} SOL{} bought / {:.4} SOL{} sold\nOpen: {} positions, {:.2} tokens, cost basis {:.4} SOL, value {:.4} SOL{}",
+            self.total_pnl_sol(),
+            usd_suffix(self.total_pnl_sol(), sol_usd),
+            self.totals.realized_pnl_sol,
+            self.unrealized_pnl_sol,
+            self.totals.bought_sol,
+            usd_suffix(self.totals.bought_sol, sol_usd),
+            self.totals.sold_sol,
+            usd_suffix(self.totals.sold_sol, sol_usd),
+            self.open_positions,
+            self.open_tokens,
+            self.cost_basis_sol,
+            self.market_value_sol,
+            usd_suffix(self.market_value_sol, sol_usd),
+        )
+    }
+}
+
+/// Persisted position ledger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionLedgerSnapshot {
+    pub positions: Vec<Position>,
+    pub realized_by_wallet: HashMap<String, f64>,
+    pub totals: PnlTotals,
+    /// Completed profit milestones per wallet, so a restart doesn't sell them again
+    #[serde(default)]
+    pub milestone_intervals: HashMap<String, Vec<String>>,
+    /// Peak PnL (percent) per wallet reached while tracking milestones
+    #[serde(default)]
+    pub milestone_top_pnl: HashMap<String, f64>,
+    pub saved_at: i64,
+}
+
+/// Rule markers an exit completes once its sell is confirmed. Until then they are pending:
+/// the rules don't fire again, but a skipped or failed sell hands them back.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExitMarks {
+    /// Take-profit rung thresholds
+    pub take_profits: Vec<u64>,
+    /// Peak-gain thresholds of retracement levels
+    pub retracements: Vec<u64>,
+    /// Profit milestone interval keys, e.g. "+50%"
+    pub milestones: Vec<String>,
+}
+
+impl ExitMarks {
+    fn extend(&mut self, other: &ExitMarks) {
+        self.take_profits.extend(other.take_profits.iter().copied());
+        self.retracements.extend(other.retracements.iter().copied());
+        self.milestones.extend(other.milestones.iter().cloned());
+    }
+
+    fn is_empty(&self) -> bool {
+        self.take_profits.is_empty() && self.retracements.is_empty() && self.milestones.is_empty()
+    }
+}
+
+/// Sell that a position rule wants executed
+#[derive(Debug, Clone)]
+pub struct PositionExit {
+    pub wallet: String,
+    pub tokens: f64,
+    pub reason: String,
+    pub marks: ExitMarks,
+    /// Set for stop-losses: the sell protects the position and must not be held back
+    pub protective: bool,
+}
+
+/// Tracks per-wallet positions, cost basis and PnL, and decides when position rules call for a sell
+pub struct PositionEngine {
+    positions: HashMap<String, Position>,
+    realized_by_wallet: HashMap<String, f64>,
+    totals: PnlTotals,
+    take_profit_levels: Vec<TakeProfitLevel>,
+    stop_loss: Option<StopLossConfig>,
+    last_stop_exit: Option<Instant>,
+    retracement_levels: Vec<RetracementLevel>,
+    retracement_stagger: Duration,
+    last_retracement_exit: Option<Instant>,
+    milestones: Option<MilestoneConfig>,
+    // Peak PnL and completed milestone intervals per wallet
+    milestone_tracking: HashMap<String, TokenTrackingInfo>,
+    // Markers of exits handed out but not filled yet, by wallet
+    pending_marks: HashMap<String, ExitMarks>,
+    logger: Logger,
+}
+
+impl PositionEngine {
+    pub fn new(rules: PositionRules) -> Self {
+        let logger = Logger::new("[POSITIONS] => ".green().bold().to_string());
+        if !rules.take_profit_levels.is_empty() {
+            let ladder: Vec<String> = rules.take_profit_levels
+                .iter()
+                .map(|l| format!("{}% at +{}%", l.sell_amount, l.threshold))
+                .collect();
+            logger.log(format!("🪜 Take-profit ladder: {}", ladder.join(", ")).green().to_string());
+        }
+        if !rules.retracement_levels.is_empty() {
+            let levels: Vec<String> = rules.retracement_levels
+                .iter()
+                .map(|l| format!("{}% on a {}% pullback after +{}%", l.sell_amount, l.percentage, l.threshold))
+                .collect();
+            logger.log(format!("📉 Retracement sells: {}", levels.join(", ")).green().to_string());
+        }
+        if let Some(milestones) = &rules.milestones {
+            logger.log(format!(
+                "🏁 Milestone sells: {}% every +{}% (up to {} milestones)",
+                milestones.sell_percent, milestones.interval_percent, milestones.max_intervals
+            ).green().to_string());
+        }
+        if let Some(stop_loss) = &rules.stop_loss {
+            logger.log(format!(
+                "🛑 Stop-loss at -{}%{} (one exit every {}s)",
+                stop_loss.percent,
+                if stop_loss.trailing { " trailing" } else { "" },
+                stop_loss.stagger.as_secs()
+            ).green().to_string());
+        }
+
+        Self {
+            positions: HashMap::new(),
+            realized_by_wallet: HashMap::new(),
+            totals: PnlTotals::default(),
+            take_profit_levels: rules.take_profit_levels,
+            stop_loss: rules.stop_loss,
+            last_stop_exit: None,
+            retracement_levels: rules.retracement_levels,
+            retracement_stagger: rules.retracement_stagger,
+            last_retracement_exit: None,
+            milestones: rules.milestones,
+            milestone_tracking: HashMap::new(),
+            pending_marks: HashMap::new(),
+            logger,
+        }
+    }
+
+    /// Record a confirmed buy. Adding to an open position re-averages the entry price and grows
+    /// the ladder's size; rungs, retracements and milestones already sold stay done. Only a
+    /// position opened from nothing starts the ladder over.
+    pub fn record_buy(&mut self, wallet: &str, tokens: f64, price: f64) {
+        if tokens <= 0.0 {
+            return;
+        }
+        self.totals.bought_tokens += tokens;
+        self.totals.bought_sol += tokens * price;
+        self.totals.buys += 1;
+
+        if !self.positions.contains_key(wallet) {
+            self.milestone_tracking.remove(wallet);
+            self.pending_marks.remove(wallet);
+        }
+        let position = self.positions.entry(wallet.to_string()).or_insert_with(|| Position {
+            wallet: wallet.to_string(),
+            amount: 0.0,
+            original_amount: 0.0,
+            entry_price: 0.0,
+            peak_price: 0.0,
+            completed_levels: HashSet::new(),
+            completed_retracements: HashSet::new(),
+        });
+
+        let total = position.amount + tokens;
+        position.entry_price = (position.entry_price * position.amount + price * tokens) / total;
+        // A trailing stop measures from the peak since the current entry
+        position.peak_price = price;
+        position.amount = total;
+        position.original_amount += tokens;
+    }
+
+    /// Record a confirmed sell, realizing PnL against the average cost basis.
+    /// The position is closed when nothing is left.
+    pub fn record_sell(&mut self, wallet: &str, tokens: f64, price: f64) {
+        if tokens <= 0.0 {
+            return;
+        }
+        self.totals.sold_tokens += tokens;
+        self.totals.sold_sol += tokens * price;
+        self.totals.sells += 1;
+
+        let position = match self.positions.get_mut(wallet) {
+            Some(position) => position,
+            None => {
+                // Tokens bought before tracking started have no known cost basis
+                self.logger.log(format!("Sell from {} without a tracked position, PnL not realized", wallet).yellow().to_string());
+                return;
+            }
+        };
+
+        let sold = tokens.min(position.amount);
+        let realized = (price - position.entry_price) * sold;
+        *self.realized_by_wallet.entry(wallet.to_string()).or_insert(0.0) += realized;
+        self.totals.realized_pnl_sol += realized;
+
+        position.amount -= sold;
+        if position.amount <= f64::EPSILON {
+            self.positions.remove(wallet);
+            self.milestone_tracking.remove(wallet);
+            self.pending_marks.remove(wallet);
+        }
+    }
+
+    /// Realized PnL for a single wallet (SOL)
+    pub fn realized_pnl(&self, wallet: &str) -> f64 {
+        self.realized_by_wallet.get(wallet).copied().unwrap_or(0.0)
+    }
+
+    /// Unrealized PnL for a single wallet at `price` (SOL)
+    pub fn unrealized_pnl(&self, wallet: &str, price: f64) -> f64 {
+        self.positions
+            .get(wallet)
+            .map(|p| (price - p.entry_price) * p.amount)
+            .unwrap_or(0.0)
+    }
+
+    /// Totals across every wallet, valuing open positions at `price`
+    pub fn pnl_summary(&self, price: f64) -> PnlSummary {
+        let open_tokens: f64 = self.positions.values().map(|p| p.amount).sum();
+        let cost_basis_sol: f64 = self.positions.values().map(|p| p.entry_price * p.amount).sum();
+        let market_value_sol = open_tokens * price;
+
+        PnlSummary {
+            totals: self.totals.clone(),
+            open_positions: self.positions.len(),
+            open_tokens,
+            cost_basis_sol,
+            market_value_sol,
+            unrealized_pnl_sol: market_value_sol - cost_basis_sol,
+        }
+    }
+
+    pub fn snapshot(&self) -> PositionLedgerSnapshot {
+        PositionLedgerSnapshot {
+            positions: self.positions(),
+            realized_by_wallet: self.realized_by_wallet.clone(),
+            totals: self.totals.clone(),
+            milestone_intervals: self.milestone_tracking
+                .iter()
+                .map(|(wallet, info)| (wallet.clone(), info.completed_intervals.iter().cloned().collect()))
+                .collect(),
+            milestone_top_pnl: self.milestone_tracking
+                .iter()
+                .map(|(wallet, info)| (wallet.clone(), info.top_pnl))
+                .collect(),
+            saved_at: unix_now(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &PositionLedgerSnapshot) {
+        self.positions = snapshot.positions.iter().map(|p| (p.wallet.clone(), p.clone())).collect();
+        self.realized_by_wallet = snapshot.realized_by_wallet.clone();
+        self.totals = snapshot.totals.clone();
+        self.milestone_tracking = snapshot.milestone_intervals
+            .iter()
+            .filter(|(wallet, _)| self.positions.contains_key(*wallet))
+            .map(|(wallet, intervals)| (wallet.clone(), TokenTrackingInfo {
+                top_pnl: snapshot.milestone_top_pnl.get(wallet).copied().unwrap_or(0.0),
+                last_sell_time: std::time::Instant::now(),
+                completed_intervals: intervals.iter().cloned().collect(),
+            }))
+            .collect();
+    }
+
+    pub fn get_position(&self, wallet: &str) -> Option<&Position> {
+        self.positions.get(wallet)
+    }
+
+    pub fn positions(&self) -> Vec<Position> {
+        self.positions.values().cloned().collect()
+    }
+
+    /// Commit the markers of an exit whose sell was confirmed, so its rules don't fire again
+    pub fn confirm_exit(&mut self, exit: &PositionExit) {
+        let marks = self.take_pending(exit);
+        if let Some(position) = self.positions.get_mut(&exit.wallet) {
+            position.completed_levels.extend(marks.take_profits);
+            position.completed_retracements.extend(marks.retracements);
+        }
+        if !marks.milestones.is_empty() {
+            if let Some(info) = self.milestone_tracking.get_mut(&exit.wallet) {
+                info.completed_intervals.extend(marks.milestones);
+                info.last_sell_time = std::time::Instant::now();
+            }
+        }
+    }
+
+    /// Hand back the markers of an exit that was skipped or failed, so its rules can fire again
+    pub fn release_exit(&mut self, exit: &PositionExit) {
+        self.take_pending(exit);
+    }
+
+    /// Remove `exit`'s markers from the pending set, returning those that were still pending
+    /// (a position closed since then has none left)
+    fn take_pending(&mut self, exit: &PositionExit) -> ExitMarks {
+        let Some(pending) = self.pending_marks.get_mut(&exit.wallet) else { return ExitMarks::default() };
+        let mut taken = ExitMarks::default();
+        for threshold in &exit.marks.take_profits {
+            if let Some(i) = pending.take_profits.iter().position(|t| t == threshold) {
+                taken.take_profits.push(pending.take_profits.remove(i));
+            }
+        }
+        for threshold in &exit.marks.retracements {
+            if let Some(i) = pending.retracements.iter().position(|t| t == threshold) {
+                taken.retracements.push(pending.retracements.remove(i));
+            }
+        }
+        for key in &exit.marks.milestones {
+            if let Some(i) = pending.milestones.iter().position(|k| k == key) {
+                taken.milestones.push(pending.milestones.remove(i));
+            }
+        }
+        if pending.is_empty() {
+            self.pending_marks.remove(&exit.wallet);
+        }
+        taken
+    }
+
+    /// Every exit the position rules call for at `price`, at most one per wallet: exits from
+    /// different rules for the same wallet are merged and capped at what the wallet holds.
+    /// Their markers stay pending until `confirm_exit` or `release_exit`.
+    pub fn check_exits(&mut self, price: f64) -> Vec<PositionExit> {
+        let mut triggered = self.check_stop_losses(price);
+        triggered.extend(self.check_retracements(price));
+        triggered.extend(self.check_milestones(price));
+        triggered.extend(self.check_take_profits(price));
+
+        let mut exits: Vec<PositionExit> = Vec::new();
+        for exit in triggered {
+            match exits.iter_mut().find(|e| e.wallet == exit.wallet) {
+                Some(merged) => {
+                    merged.tokens += exit.tokens;
+                    merged.reason = format!("{} + {}", merged.reason, exit.reason);
+                    merged.marks.extend(&exit.marks);
+                    merged.protective |= exit.protective;
+                },
+                None => exits.push(exit),
+            }
+        }
+        for exit in &mut exits {
+            let held = self.positions.get(&exit.wallet).map(|p| p.amount).unwrap_or(0.0);
+            exit.tokens = exit.tokens.min(held);
+        }
+        let (exits, empty): (Vec<_>, Vec<_>) = exits.into_iter().partition(|e| e.tokens > 0.0);
+        for exit in &empty {
+            self.release_exit(exit);
+        }
+        exits
+    }
+
+    /// Take-profit sells triggered at `price`. Each rung fires once per position: it is
+    /// pending while its sell is out and done once the sell is confirmed.
+    pub fn check_take_profits(&mut self, price: f64) -> Vec<PositionExit> {
+        let mut exits = Vec::new();
+        if self.take_profit_levels.is_empty() || price <= 0.0 {
+            return exits;
+        }
+
+        for position in self.positions.values() {
+            let pnl = position.pnl_percent(price);
+            for level in &self.take_profit_levels {
+                let pending = self.pending_marks.get(&position.wallet).map(|m| m.take_profits.contains(&level.threshold)).unwrap_or(false);
+                if pnl < level.threshold as f64 || position.completed_levels.contains(&level.threshold) || pending {
+                    continue;
+                }
+
+                let tokens = (position.original_amount * level.sell_amount as f64 / 100.0).min(position.amount);
+                if tokens <= 0.0 {
+                    continue;
+                }
+                self.pending_marks.entry(position.wallet.clone()).or_default().take_profits.push(level.threshold);
+
+                self.logger.log(format!(
+                    "🎯 Take-profit +{}% hit for {} (PnL {:+.1}%), selling {:.2} tokens",
+                    level.threshold, position.wallet, pnl, tokens
+                ).green().bold().to_string());
+
+                exits.push(PositionExit {
+                    wallet: position.wallet.clone(),
+                    tokens,
+                    reason: format!("take-profit +{}%", level.threshold),
+                    marks: ExitMarks { take_profits: vec![level.threshold], ..ExitMarks::default() },
+                    protective: false,
+                });
+            }
+        }
+
+        exits
+    }
+
+    /// Profit milestone sells triggered at `price`: each +X% interval the position has crossed
+    /// sells its chunk once. Several intervals crossed at once are sold as one exit, and they
+    /// count as completed (and are persisted) only once that sell is confirmed.
+    pub fn check_milestones(&mut self, price: f64) -> Vec<PositionExit> {
+        let Some(config) = self.milestones else { return Vec::new() };
+        if price <= 0.0 {
+            return Vec::new();
+        }
+
+        let mut exits = Vec::new();
+        for position in self.positions.values() {
+            let pnl = position.pnl_percent(price);
+            let info = self.milestone_tracking.entry(position.wallet.clone()).or_insert_with(|| TokenTrackingInfo {
+                top_pnl: 0.0,
+                last_sell_time: std::time::Instant::now(),
+                completed_intervals: HashSet::new(),
+            });
+            info.top_pnl = info.top_pnl.max(pnl);
+
+            let crossed = ((pnl / config.interval_percent).floor().max(0.0) as u64).min(config.max_intervals);
+            let pending = self.pending_marks.get(&position.wallet).map(|m| m.milestones.as_slice()).unwrap_or_default();
+            let new: Vec<String> = (1..=crossed)
+                .map(|n| config.interval_key(n))
+                .filter(|key| !info.completed_intervals.contains(key) && !pending.contains(key))
+                .collect();
+            if new.is_empty() {
+                continue;
+            }
+
+            let tokens = (position.original_amount * config.sell_percent / 100.0 * new.len() as f64).min(position.amount);
+            if tokens <= 0.0 {
+                continue;
+            }
+            self.pending_marks.entry(position.wallet.clone()).or_default().milestones.extend(new.iter().cloned());
+            self.logger.log(format!(
+                "🏁 Milestone {} reached for {} (PnL {:+.1}%), selling {:.2} tokens",
+                new.join(", "), position.wallet, pnl, tokens
+            ).green().bold().to_string());
+            exits.push(PositionExit {
+                wallet: position.wallet.clone(),
+                tokens,
+                reason: format!("milestone {}", new.join(", ")),
+                marks: ExitMarks { milestones: new, ..ExitMarks::default() },
+                protective: false,
+            });
+        }
+        exits
+    }
+
+    /// Track the high-water price of every position for trailing stops
+    pub fn update_peaks(&mut self, price: f64) {
+        for position in self.positions.values_mut() {
+            position.peak_price = position.peak_price.max(price);
+        }
+    }
+
+    /// Retracement sells triggered at `price`: a position whose peak gain reached a level's
+    /// threshold sells that level's share once price falls the level's percentage from the peak.
+    /// Like stop-losses, one exit is released per stagger window across all wallets (deepest
+    /// pullback first); the rest stay triggered and are released on later checks. A level is
+    /// pending while its sell is out and done once the sell is confirmed.
+    pub fn check_retracements(&mut self, price: f64) -> Vec<PositionExit> {
+        if self.retracement_levels.is_empty() || price <= 0.0 {
+            return Vec::new();
+        }
+        self.update_peaks(price);
+
+        if let Some(last) = self.last_retracement_exit {
+            if last.elapsed() < self.retracement_stagger {
+                return Vec::new();
+            }
+        }
+
+        let levels = &self.retracement_levels;
+        let pending_marks = &self.pending_marks;
+        let triggered = |position: &Position| {
+            let pending = pending_marks.get(&position.wallet).map(|m| m.retracements.as_slice()).unwrap_or_default();
+            levels.iter().find(|level| {
+                position.amount > 0.0
+                    && !position.completed_retracements.contains(&level.threshold)
+                    && !pending.contains(&level.threshold)
+                    && position.peak_pnl_percent() >= level.threshold as f64
+                    && position.retracement_percent(price) >= level.percentage as f64
+            }).copied()
+        };
+        let Some((wallet, level)) = self.positions
+            .values()
+            .filter_map(|p| triggered(p).map(|level| (p, level)))
+            .max_by(|a, b| a.0.retracement_percent(price).partial_cmp(&b.0.retracement_percent(price)).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(p, level)| (p.wallet.clone(), level))
+        else {
+            return Vec::new();
+        };
+        let Some(position) = self.positions.get(&wallet) else { return Vec::new() };
+
+        let tokens = (position.original_amount * level.sell_amount as f64 / 100.0).min(position.amount);
+        if tokens <= 0.0 {
+            return Vec::new();
+        }
+        self.pending_marks.entry(wallet.clone()).or_default().retracements.push(level.threshold);
+        self.last_retracement_exit = Some(Instant::now());
+        self.logger.log(format!(
+            "📉 Retracement hit for {} (peak {:+.1}%, now {:.1}% off the peak), selling {:.2} tokens",
+            position.wallet, position.peak_pnl_percent(), position.retracement_percent(price), tokens
+        ).yellow().bold().to_string());
+
+        vec![PositionExit {
+            wallet: position.wallet.clone(),
+            tokens,
+            reason: format!("retracement -{}% after +{}%", level.percentage, level.threshold),
+            marks: ExitMarks { retracements: vec![level.threshold], ..ExitMarks::default() },
+            protective: false,
+        }]
+    }
+
+    /// Stop-loss sells triggered at `price`. At most one exit is released per stagger window
+    /// (worst loss first); the rest stay triggered and are released on later checks.
+    pub fn check_stop_losses(&mut self, price: f64) -> Vec<PositionExit> {
+        let stop_loss = match &self.stop_loss {
+            Some(stop_loss) if price > 0.0 => stop_loss.clone(),
+            _ => return Vec::new(),
+        };
+        self.update_peaks(price);
+
+        if let Some(last) = self.last_stop_exit {
+            if last.elapsed() < stop_loss.stagger {
+                return Vec::new();
+            }
+        }
+
+        let worst = self.positions
+            .values()
+            .filter(|p| p.amount > 0.0 && price <= p.stop_price(&stop_loss))
+            .min_by(|a, b| a.pnl_percent(price).partial_cmp(&b.pnl_percent(price)).unwrap_or(std::cmp::Ordering::Equal))
+            .cloned();
+
+        let position = match worst {
+            Some(position) => position,
+            None => return Vec::new(),
+        };
+
+        self.last_stop_exit = Some(Instant::now());
+        self.logger.log(format!(
+            "🛑 Stop-loss hit for {} (PnL {:+.1}%, stop {:.10}), selling {:.2} tokens",
+            position.wallet, position.pnl_percent(price), position.stop_price(&stop_loss), position.amount
+        ).red().bold().to_string());
+
+        vec![PositionExit {
+            wallet: position.wallet.clone(),
+            tokens: position.amount,
+            reason: format!("stop-loss -{}%{}", stop_loss.percent, if stop_loss.trailing { " trailing" } else { "" }),
+            marks: ExitMarks::default(),
+            protective: true,
+        }]
+    }
+}
+
+/// Global position engine instance
+pub type GlobalPositionEngine = Arc<Mutex<PositionEngine>>;
+
+/// Create a global position engine
+pub fn create_global_position_engine(rules: PositionRules) -> GlobalPositionEngine {
+    Arc::new(Mutex::new(PositionEngine::new(rules)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_take_profit_levels_in_gain_order() {
+        let levels = parse_take_profit_levels("50:25, 20:25,100:50").unwrap();
+        assert_eq!(levels, vec![
+            TakeProfitLevel { threshold: 20, sell_amount: 25 },
+            TakeProfitLevel { threshold: 50, sell_amount: 25 },
+            TakeProfitLevel { threshold: 100, sell_amount: 50 },
+        ]);
+        assert!(parse_take_profit_levels("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_take_profit_levels() {
+        for value in ["20", "20:x", "x:25", "0:25", "20:0", "20:101", "20:60,50:50"] {
+            assert!(parse_take_profit_levels(value).is_err(), "{} should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn parses_retracement_levels_in_gain_order() {
+        let levels = parse_retracement_levels("100:15:50, 50:20:30").unwrap();
+        let fields: Vec<(u64, u64, u64)> = levels.iter().map(|l| (l.threshold, l.percentage, l.sell_amount)).collect();
+        assert_eq!(fields, vec![(50, 20, 30), (100, 15, 50)]);
+        assert!(parse_retracement_levels(" ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_retracement_levels() {
+        for value in ["50:20", "50:20:30:1", "50:x:30", "0:20:30", "50:0:30", "50:100:30", "50:20:0", "50:20:101", "50:20:60,100:15:50"] {
+            assert!(parse_retracement_levels(value).is_err(), "{} should be rejected", value);
+        }
+    }
+
+    fn engine() -> PositionEngine {
+        PositionEngine::new(PositionRules::default())
+    }
+
+    #[test]
+    fn add_buys_keep_ladder_progress_and_reset_the_peak() {
+        let mut engine = engine();
+        engine.record_buy("w", 100.0, 1.0);
+        engine.positions.get_mut("w").unwrap().completed_levels.insert(20);
+        engine.update_peaks(2.0);
+
+        engine.record_buy("w", 100.0, 1.5);
+        let position = engine.get_position("w").unwrap();
+        assert_eq!(position.amount, 200.0);
+        assert_eq!(position.original_amount, 200.0);
+        assert!((position.entry_price - 1.25).abs() < 1e-9);
+        assert_eq!(position.peak_price, 1.5);
+        assert!(position.completed_levels.contains(&20));
+
+        // Closing the position and buying again starts the ladder over
+        engine.record_sell("w", 200.0, 2.0);
+        engine.record_buy("w", 50.0, 1.0);
+        let position = engine.get_position("w").unwrap();
+        assert_eq!(position.original_amount, 50.0);
+        assert!(position.completed_levels.is_empty());
+        let totals = engine.pnl_summary(1.0).totals;
+        assert_eq!((totals.buys, totals.sells), (3, 1));
+    }
+
+    #[test]
+    fn take_profit_rung_fires_again_after_a_dropped_exit() {
+        let mut engine = PositionEngine::new(PositionRules {
+            take_profit_levels: vec![TakeProfitLevel { threshold: 20, sell_amount: 25 }],
+            ..PositionRules::default()
+        });
+        engine.record_buy("w", 100.0, 1.0);
+
+        let exits = engine.check_exits(1.3);
+        assert_eq!(exits.len(), 1);
+        assert_eq!(exits[0].tokens, 25.0);
+        // Pending while its sell is out
+        assert!(engine.check_exits(1.3).is_empty());
+
+        engine.release_exit(&exits[0]);
+        let retried = engine.check_exits(1.3);
+        assert_eq!(retried.len(), 1);
+
+        engine.confirm_exit(&retried[0]);
+        engine.record_sell("w", 25.0, 1.3);
+        assert!(engine.check_exits(1.3).is_empty());
+        assert!(engine.get_position("w").unwrap().completed_levels.contains(&20));
+    }
+
+    fn milestone_engine() -> PositionEngine {
+        PositionEngine::new(PositionRules {
+            milestones: Some(MilestoneConfig { interval_percent: 50.0, sell_percent: 10.0, max_intervals: 3 }),
+            ..PositionRules::default()
+        })
+    }
+
+    #[test]
+    fn milestones_sell_each_crossed_interval_once() {
+        let mut engine = milestone_engine();
+        engine.record_buy("w", 100.0, 1.0);
+        assert!(engine.check_milestones(1.4).is_empty());
+
+        // +110% crosses +50% and +100% at once
+        let exits = engine.check_milestones(2.1);
+        assert_eq!(exits.len(), 1);
+        assert_eq!(exits[0].marks.milestones, vec!["+50%".to_string(), "+100%".to_string()]);
+        assert!((exits[0].tokens - 20.0).abs() < 1e-9);
+        assert!(engine.check_milestones(2.1).is_empty());
+
+        // A dropped sell leaves the intervals open
+        engine.release_exit(&exits[0]);
+        let retried = engine.check_milestones(2.1);
+        assert_eq!(retried.len(), 1);
+        engine.confirm_exit(&retried[0]);
+        assert!(engine.check_milestones(2.1).is_empty());
+
+        let next = engine.check_milestones(2.6);
+        assert_eq!(next[0].marks.milestones, vec!["+150%".to_string()]);
+        engine.confirm_exit(&next[0]);
+        // MILESTONE_MAX_INTERVALS caps the ladder
+        assert!(engine.check_milestones(10.0).is_empty());
+    }
+
+    #[test]
+    fn restore_keeps_confirmed_milestones_and_peak_pnl() {
+        let mut engine = milestone_engine();
+        engine.record_buy("w", 100.0, 1.0);
+        let exits = engine.check_milestones(2.1);
+        engine.confirm_exit(&exits[0]);
+        // Still pending at snapshot time: not persisted, so it fires again after a restart
+        engine.check_milestones(2.6);
+
+        let mut restored = milestone_engine();
+        restored.restore(&engine.snapshot());
+        let info = &restored.milestone_tracking["w"];
+        assert!((info.top_pnl - 160.0).abs() < 1e-6);
+        assert_eq!(info.completed_intervals.len(), 2);
+
+        let exits = restored.check_milestones(2.6);
+        assert_eq!(exits.len(), 1);
+        assert_eq!(exits[0].marks.milestones, vec!["+150%".to_string()]);
+    }
+
+    fn open_position(entry_price: f64, peak_price: f64) -> Position {
+        Position {
+            wallet: "w".to_string(),
+            amount: 100.0,
+            original_amount: 100.0,
+            entry_price,
+            peak_price,
+            completed_levels: HashSet::new(),
+            completed_retracements: HashSet::new(),
+        }
+    }
+
+    #[test]
+    fn retracement_is_measured_from_the_peak() {
+        let position = open_position(1.0, 2.0);
+        assert!((position.peak_pnl_percent() - 100.0).abs() < 1e-9);
+        assert!((position.retracement_percent(1.5) - 25.0).abs() < 1e-9);
+        assert_eq!(position.retracement_percent(2.5), 0.0);
+        assert_eq!(open_position(1.0, 0.0).retracement_percent(0.5), 0.0);
+    }
+
+    #[test]
+    fn retracement_fires_after_the_peak_gain_and_pullback() {
+        let mut engine = PositionEngine::new(PositionRules {
+            retracement_levels: parse_retracement_levels("50:20:30").unwrap(),
+            ..PositionRules::default()
+        });
+        engine.record_buy("w", 100.0, 1.0);
+
+        // Pullbacks before the peak gain reaches +50% don't count
+        assert!(engine.check_retracements(1.4).is_empty());
+        assert!(engine.check_retracements(1.1).is_empty());
+        assert!(engine.check_retracements(1.6).is_empty());
+        // 18.75% off a +60% peak
+        assert!(engine.check_retracements(1.3).is_empty());
+
+        let exits = engine.check_retracements(1.25);
+        assert_eq!(exits.len(), 1);
+        assert!((exits[0].tokens - 30.0).abs() < 1e-9);
+        assert_eq!(exits[0].marks.retracements, vec![50]);
+        assert!(engine.check_retracements(1.25).is_empty());
+
+        engine.release_exit(&exits[0]);
+        let retried = engine.check_retracements(1.25);
+        assert_eq!(retried.len(), 1);
+        engine.confirm_exit(&retried[0]);
+        assert!(engine.check_retracements(1.2).is_empty());
+        assert!(engine.get_position("w").unwrap().completed_retracements.contains(&50));
+    }
+
+    fn stop_loss(percent: f64, trailing: bool, stagger: Duration) -> StopLossConfig {
+        StopLossConfig { percent, trailing, stagger }
+    }
+
+    #[test]
+    fn stop_price_follows_the_entry_or_the_peak() {
+        let fixed = stop_loss(10.0, false, Duration::ZERO);
+        let trailing = stop_loss(10.0, true, Duration::ZERO);
+        assert!((open_position(1.0, 2.0).stop_price(&fixed) - 0.9).abs() < 1e-9);
+        assert!((open_position(1.0, 2.0).stop_price(&trailing) - 1.8).abs() < 1e-9);
+        // A trailing stop never sits below the fixed one
+        assert!((open_position(1.0, 0.8).stop_price(&trailing) - 0.9).abs() < 1e-9);
+    }
+
+    #[test]
+    fn trailing_stop_sells_the_whole_position_off_the_peak() {
+        let mut engine = PositionEngine::new(PositionRules {
+            stop_loss: Some(stop_loss(10.0, true, Duration::ZERO)),
+            ..PositionRules::default()
+        });
+        engine.record_buy("w", 100.0, 1.0);
+        assert!(engine.check_stop_losses(2.0).is_empty());
+        assert!(engine.check_stop_losses(1.85).is_empty());
+
+        let exits = engine.check_stop_losses(1.79);
+        assert_eq!(exits.len(), 1);
+        assert_eq!(exits[0].tokens, 100.0);
+        assert!(exits[0].protective);
+        assert_eq!(exits[0].reason, "stop-loss -10% trailing");
+    }
+
+    #[test]
+    fn stop_losses_are_staggered_worst_loss_first() {
+        let mut engine = PositionEngine::new(PositionRules {
+            stop_loss: Some(stop_loss(10.0, false, Duration::from_secs(60))),
+            ..PositionRules::default()
+        });
+        engine.record_buy("a", 100.0, 1.0);
+        engine.record_buy("b", 100.0, 1.2);
+
+        let first = engine.check_stop_losses(0.8);
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].wallet, "b");
+        assert!(engine.check_stop_losses(0.8).is_empty());
+
+        // Once the stagger window has passed the next wallet goes
+        engine.last_stop_exit = engine.last_stop_exit.map(|at| at - Duration::from_secs(61));
+        engine.record_sell("b", 100.0, 0.8);
+        let second = engine.check_stop_losses(0.8);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].wallet, "a");
+    }
+}
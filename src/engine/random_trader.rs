@@ -5,7 +5,10 @@ use anyhow::Result;
 use colored::Colorize;
 use anchor_client::solana_sdk::signature::Signature;
 use anchor_client::solana_sdk::signer::Signer;
-use std::sync::atomic::{AtomicU64, Ordering};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tracing::Instrument;
+use uuid::Uuid;
 use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
 use anchor_client::solana_sdk::commitment_config::CommitmentLevel;
 use solana_transaction_status;
@@ -15,6 +18,7 @@ use crate::{
     dex::raydium_cpmm::RaydiumCPMM,
     engine::swap::{SwapDirection, SwapInType},
     common::config::SwapConfig,
+    services::blockhash_processor::BlockhashProcessor,
 };
 
 #[derive(Clone)]
@@ -24,7 +28,7 @@ pub struct RandomTrader {
     target_mint: String,
     logger: Logger,
     is_running: Arc<tokio::sync::RwLock<bool>>,
-    counter: Arc<AtomicU64>, // For deterministic "randomness"
+    rng: Arc<std::sync::Mutex<StdRng>>, // OS entropy, or RANDOM_TRADER_SEED for reproducible runs
 }
 
 #[derive(Debug, Clone)]
@@ -50,6 +54,61 @@ impl Default for RandomTraderConfig {
     }
 }
 
+impl RandomTraderConfig {
+    /// Defaults overridden by RANDOM_TRADER_MIN_BUY_AMOUNT / RANDOM_TRADER_MAX_BUY_AMOUNT (SOL),
+    /// RANDOM_TRADER_MIN_SELL_PERCENT / RANDOM_TRADER_MAX_SELL_PERCENT and
+    /// RANDOM_TRADER_MIN_INTERVAL_SECONDS / RANDOM_TRADER_MAX_INTERVAL_SECONDS
+    pub fn from_env() -> Self {
+        let get = |name: &str| std::env::var(name).ok();
+        let defaults = Self::default();
+        let percent = |name: &str, default: f64| {
+            get(name).and_then(|v| v.parse::<f64>().ok()).map(|p| p / 100.0).unwrap_or(default)
+        };
+        Self {
+            min_buy_amount: get("RANDOM_TRADER_MIN_BUY_AMOUNT").and_then(|v| v.parse().ok()).unwrap_or(defaults.min_buy_amount),
+            max_buy_amount: get("RANDOM_TRADER_MAX_BUY_AMOUNT").and_then(|v| v.parse().ok()).unwrap_or(defaults.max_buy_amount),
+            min_sell_percentage: percent("RANDOM_TRADER_MIN_SELL_PERCENT", defaults.min_sell_percentage),
+            max_sell_percentage: percent("RANDOM_TRADER_MAX_SELL_PERCENT", defaults.max_sell_percentage),
+            min_interval_seconds: get("RANDOM_TRADER_MIN_INTERVAL_SECONDS").and_then(|v| v.parse().ok()).unwrap_or(defaults.min_interval_seconds),
+            max_interval_seconds: get("RANDOM_TRADER_MAX_INTERVAL_SECONDS").and_then(|v| v.parse().ok()).unwrap_or(defaults.max_interval_seconds),
+        }
+    }
+}
+
+/// Engine run by the binary (MODE: market_maker, the default, or random_trader)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunMode {
+    MarketMaker,
+    RandomTrader,
+}
+
+impl RunMode {
+    pub fn from_env() -> Self {
+        match std::env::var("MODE").unwrap_or_default().trim().to_lowercase().as_str() {
+            "random_trader" | "random-trader" => Self::RandomTrader,
+            _ => Self::MarketMaker,
+        }
+    }
+}
+
+/// Base seed for reproducible simulation (RANDOM_TRADER_SEED, unset = OS entropy)
+pub fn get_random_trader_seed() -> Option<u64> {
+    std::env::var("RANDOM_TRADER_SEED").ok().and_then(|v| v.trim().parse().ok())
+}
+
+/// RNG for one wallet: seeded from `seed` mixed with the wallet's public key, so every
+/// wallet gets its own reproducible sequence, or from OS entropy when no seed is set
+pub fn wallet_rng(seed: Option<u64>, wallet: &anchor_client::solana_sdk::pubkey::Pubkey) -> StdRng {
+    match seed {
+        Some(seed) => {
+            let mut key = [0u8; 8];
+            key.copy_from_slice(&wallet.to_bytes()[..8]);
+            StdRng::seed_from_u64(seed ^ u64::from_le_bytes(key))
+        },
+        None => StdRng::from_entropy(),
+    }
+}
+
 impl RandomTrader {
     pub fn new(app_state: Arc<AppState>, target_mint: String, pool_id: String, pool_base_account: String, pool_quote_account: String) -> Result<Self> {
         let raydium_cpmm = RaydiumCPMM::new(
@@ -61,40 +120,31 @@ impl RandomTrader {
             pool_quote_account,
         ).map_err(|e| anyhow::anyhow!("Failed to create RaydiumCPMM instance: {}", e))?;
         
+        let rng = wallet_rng(get_random_trader_seed(), &app_state.wallet.pubkey());
         Ok(Self {
             app_state,
             raydium_cpmm,
             target_mint,
             logger: Logger::new("[RANDOM-TRADER] => ".magenta().to_string()),
             is_running: Arc::new(tokio::sync::RwLock::new(false)),
-            counter: Arc::new(AtomicU64::new(0)),
+            rng: Arc::new(std::sync::Mutex::new(rng)),
         })
     }
     
-    /// Generate pseudo-random number using atomic counter
-    fn next_pseudo_random(&self) -> u64 {
-        let counter = self.counter.fetch_add(1, Ordering::SeqCst);
-        // Simple linear congruential generator
-        (counter.wrapping_mul(1103515245).wrapping_add(12345)) & 0x7fffffff
-    }
-    
-    /// Generate random value in range using pseudo-random
+    /// Generate random value in [min, max]
     fn random_in_range(&self, min: u64, max: u64) -> u64 {
         if min >= max {
             return min;
         }
-        let range = max - min;
-        let random = self.next_pseudo_random();
-        min + (random % range)
+        self.rng.lock().unwrap_or_else(|e| e.into_inner()).gen_range(min..=max)
     }
     
-    /// Generate random float in range
+    /// Generate random float in [min, max)
     fn random_float_in_range(&self, min: f64, max: f64) -> f64 {
         if min >= max {
             return min;
         }
-        let random = self.next_pseudo_random() as f64 / (0x7fffffff as f64);
-        min + (max - min) * random
+        self.rng.lock().unwrap_or_else(|e| e.into_inner()).gen_range(min..max)
     }
     
     /// Start the random trading engine with buy-then-sell pattern using SELLING_TIME_AFTER_BUYING
@@ -132,7 +182,8 @@ impl RandomTrader {
             
             // Step 1: Execute buy
             self.logger.log("💰 STEP 1: Executing BUY...".green().bold().to_string());
-            match self.execute_random_buy(&config).await {
+            let wallet = self.app_state.wallet.pubkey();
+            match self.execute_random_buy(&config).instrument(tracing::info_span!("trade", trade_id = %Uuid::new_v4(), side = "buy", %wallet)).await {
                 Ok(()) => {
                     self.logger.log("✅ Buy successful, waiting before selling...".green().to_string());
                     
@@ -146,7 +197,7 @@ impl RandomTrader {
                     
                     // Step 3: Execute sell (100% of tokens)
                     self.logger.log("💸 STEP 3: Executing SELL ALL...".blue().bold().to_string());
-                    if let Err(e) = self.execute_sell_all().await {
+                    if let Err(e) = self.execute_sell_all().instrument(tracing::info_span!("trade", trade_id = %Uuid::new_v4(), side = "sell", %wallet)).await {
                         self.logger.log(format!("❌ Sell failed: {}", e).red().to_string());
                         // Continue to next cycle even if sell fails
                     }
@@ -274,9 +325,7 @@ impl RandomTrader {
         use anchor_client::solana_sdk::transaction::Transaction;
         
         // Get recent blockhash
-        let recent_blockhash = self.app_state.rpc_client
-            .get_latest_blockhash()
-            .map_err(|e| anyhow::anyhow!("Failed to get recent blockhash: {}", e))?;
+        let recent_blockhash = BlockhashProcessor::blockhash_or_fetch(&self.app_state.rpc_nonblocking_client).await?;
         
         // Create and sign transaction
         let transaction = Transaction::new_signed_with_payer(
@@ -0,0 +1,276 @@
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::Arc;
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use tokio::sync::{Mutex, RwLock};
+use crate::common::{
+    dynamic_ratios::GlobalDynamicRatioManager,
+    logger::Logger,
+    persistence::{state_dir, unix_now},
+    rate_limit::{GlobalTradeRateLimiter, RateLimitConfig},
+    spend_limit::GlobalSpendCircuitBreaker,
+    volume_waves::GlobalVolumeWaveManager,
+};
+
+/// Number of audit entries kept in memory
+const AUDIT_HISTORY_SIZE: usize = 200;
+
+/// Highest base slippage a swap may be built with (same bound as SLIPPAGE)
+const MAX_SLIPPAGE_BPS: u64 = 25_000;
+
+/// Parameters that can be read and changed on a running market maker
+pub const PARAM_KEYS: &[&str] = &[
+    "min_buy_ratio",
+    "max_buy_ratio",
+    "wave_active_hours",
+    "wave_slow_hours",
+    "slippage_bps",
+    "daily_spend_cap_sol",
+    "max_trades_per_hour",
+    "max_trades_per_wallet_per_day",
+];
+
+/// A single recorded parameter change
+#[derive(Debug, Clone, Serialize)]
+pub struct ParamChange {
+    pub at: i64,
+    pub source: String,
+    pub key: String,
+    pub old_value: String,
+    pub new_value: String,
+}
+
+/// Point-in-time view of every runtime parameter
+#[derive(Debug, Clone)]
+pub struct RuntimeParamsSnapshot {
+    pub min_buy_ratio: f64,
+    pub max_buy_ratio: f64,
+    pub current_buy_ratio: f64,
+    pub wave_active_hours: u64,
+    pub wave_slow_hours: u64,
+    /// Base swap slippage before sandwich adaptation
+    pub slippage_bps: u64,
+    pub daily_spend_cap_sol: f64,
+    pub rate_limits: RateLimitConfig,
+}
+
+fn parse_param<T: std::str::FromStr>(key: &str, value: &str) -> Result<T> {
+    value
+        .trim()
+        .parse::<T>()
+        .map_err(|_| anyhow!("Invalid value '{}' for {}", value, key))
+}
+
+impl RuntimeParamsSnapshot {
+    /// Current value of `key` rendered as a string
+    pub fn get(&self, key: &str) -> Option<String> {
+        let value = match key {
+            "min_buy_ratio" => self.min_buy_ratio.to_string(),
+            "max_buy_ratio" => self.max_buy_ratio.to_string(),
+            "wave_active_hours" => self.wave_active_hours.to_string(),
+            "wave_slow_hours" => self.wave_slow_hours.to_string(),
+            "slippage_bps" => self.slippage_bps.to_string(),
+            "daily_spend_cap_sol" => self.daily_spend_cap_sol.to_string(),
+            "max_trades_per_hour" => self.rate_limits.max_trades_per_hour.to_string(),
+            "max_trades_per_wallet_per_day" => self.rate_limits.max_trades_per_wallet_per_day.to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "min_buy_ratio" => self.min_buy_ratio = parse_param(key, value)?,
+            "max_buy_ratio" => self.max_buy_ratio = parse_param(key, value)?,
+            "wave_active_hours" => self.wave_active_hours = parse_param(key, value)?,
+            "wave_slow_hours" => self.wave_slow_hours = parse_param(key, value)?,
+            "slippage_bps" => self.slippage_bps = parse_param(key, value)?,
+            "daily_spend_cap_sol" => self.daily_spend_cap_sol = parse_param(key, value)?,
+            "max_trades_per_hour" => self.rate_limits.max_trades_per_hour = parse_param(key, value)?,
+            "max_trades_per_wallet_per_day" => self.rate_limits.max_trades_per_wallet_per_day = parse_param(key, value)?,
+            other => return Err(anyhow!("Unknown parameter '{}' (expected one of: {})", other, PARAM_KEYS.join(", "))),
+        }
+        Ok(())
+    }
+
+    /// Check the ranges an update must keep; also run on the startup settings by `MarketMaker::new`
+    pub fn validate(&self) -> Result<()> {
+        let unit = 0.0..=1.0;
+        if !unit.contains(&self.min_buy_ratio) || !unit.contains(&self.max_buy_ratio) || self.min_buy_ratio > self.max_buy_ratio {
+            return Err(anyhow!("Buy ratio bounds must satisfy 0 <= min_buy_ratio <= max_buy_ratio <= 1"));
+        }
+        if self.wave_active_hours == 0 || self.wave_slow_hours == 0 {
+            return Err(anyhow!("Wave durations must be at least 1 hour"));
+        }
+        if self.slippage_bps > MAX_SLIPPAGE_BPS {
+            return Err(anyhow!("slippage_bps must be at most {}", MAX_SLIPPAGE_BPS));
+        }
+        if !self.daily_spend_cap_sol.is_finite() || self.daily_spend_cap_sol < 0.0 {
+            return Err(anyhow!("daily_spend_cap_sol must be 0 (off) or a positive SOL amount"));
+        }
+        Ok(())
+    }
+
+    /// Render as `key=value` pairs separated by spaces
+    pub fn to_line(&self) -> String {
+        PARAM_KEYS
+            .iter()
+            .filter_map(|key| self.get(key).map(|value| format!("{}={}", key, value)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Read / update access to the live parameters of a running `MarketMaker`.
+/// Shared by the control socket and other operator interfaces; every change is audited.
+pub struct RuntimeParams {
+    dynamic_ratio_manager: GlobalDynamicRatioManager,
+    volume_wave_manager: GlobalVolumeWaveManager,
+    slippage: Arc<RwLock<u64>>,
+    spend_breaker: GlobalSpendCircuitBreaker,
+    rate_limiter: GlobalTradeRateLimiter,
+    // Serializes updates so concurrent callers never interleave partial changes
+    update_lock: Mutex<()>,
+    audit: std::sync::Mutex<VecDeque<ParamChange>>,
+    logger: Logger,
+}
+
+impl RuntimeParams {
+    pub fn new(
+        dynamic_ratio_manager: GlobalDynamicRatioManager,
+        volume_wave_manager: GlobalVolumeWaveManager,
+        slippage: Arc<RwLock<u64>>,
+        spend_breaker: GlobalSpendCircuitBreaker,
+        rate_limiter: GlobalTradeRateLimiter,
+    ) -> Self {
+        Self {
+            dynamic_ratio_manager,
+            volume_wave_manager,
+            slippage,
+            spend_breaker,
+            rate_limiter,
+            update_lock: Mutex::new(()),
+            audit: std::sync::Mutex::new(VecDeque::with_capacity(AUDIT_HISTORY_SIZE)),
+            logger: Logger::new("[RUNTIME-PARAMS] => ".cyan().bold().to_string()),
+        }
+    }
+
+    /// Read every parameter
+    pub async fn snapshot(&self) -> RuntimeParamsSnapshot {
+        let stats = self.dynamic_ratio_manager.lock().await.get_ratio_stats();
+        let (wave_active_hours, wave_slow_hours) = self.volume_wave_manager.lock().await.get_phase_durations();
+
+        RuntimeParamsSnapshot {
+            min_buy_ratio: stats.min_buy_ratio,
+            max_buy_ratio: stats.max_buy_ratio,
+            current_buy_ratio: stats.current_buy_ratio,
+            wave_active_hours,
+            wave_slow_hours,
+            slippage_bps: *self.slippage.read().await,
+            daily_spend_cap_sol: self.spend_breaker.lock().await.cap(),
+            rate_limits: self.rate_limiter.lock().await.config(),
+        }
+    }
+
+    /// Apply a set of `(key, value)` changes atomically: all are validated together and
+    /// either every change takes effect or none does. Returns the recorded changes.
+    pub async fn update(&self, changes: &[(&str, &str)], source: &str) -> Result<Vec<ParamChange>> {
+        if changes.is_empty() {
+            return Err(anyhow!("No parameters given"));
+        }
+
+        let _guard = self.update_lock.lock().await;
+        let before = self.snapshot().await;
+        let mut after = before.clone();
+        for (key, value) in changes {
+            after.set(key, value)?;
+        }
+        after.validate()?;
+
+        if before.min_buy_ratio != after.min_buy_ratio || before.max_buy_ratio != after.max_buy_ratio {
+            self.dynamic_ratio_manager.lock().await.set_ratio_bounds(after.min_buy_ratio, after.max_buy_ratio);
+        }
+        if before.wave_active_hours != after.wave_active_hours || before.wave_slow_hours != after.wave_slow_hours {
+            self.volume_wave_manager.lock().await.set_phase_durations(after.wave_active_hours, after.wave_slow_hours);
+        }
+        *self.slippage.write().await = after.slippage_bps;
+        if before.daily_spend_cap_sol != after.daily_spend_cap_sol {
+            self.spend_breaker.lock().await.set_cap(after.daily_spend_cap_sol);
+        }
+        self.rate_limiter.lock().await.set_config(after.rate_limits);
+
+        let now = unix_now();
+        let recorded: Vec<ParamChange> = PARAM_KEYS
+            .iter()
+            .filter_map(|key| {
+                let old_value = before.get(key)?;
+                let new_value = after.get(key)?;
+                (old_value != new_value).then(|| ParamChange {
+                    at: now,
+                    source: source.to_string(),
+                    key: key.to_string(),
+                    old_value,
+                    new_value,
+                })
+            })
+            .collect();
+
+        for change in &recorded {
+            self.record(change.clone());
+        }
+
+        Ok(recorded)
+    }
+
+    /// Most recent changes, newest last
+    pub fn audit_log(&self, limit: usize) -> Vec<ParamChange> {
+        let audit = self.audit.lock().unwrap();
+        let skip = audit.len().saturating_sub(limit);
+        audit.iter().skip(skip).cloned().collect()
+    }
+
+    fn record(&self, change: ParamChange) {
+        self.logger.log(format!(
+            "🔧 {} changed {}: {} -> {}",
+            change.source, change.key, change.old_value, change.new_value
+        ).cyan().bold().to_string());
+
+        // The on-disk audit trail survives restarts; failing to write it never blocks a change
+        if let Err(e) = append_audit_file(&change) {
+            self.logger.log(format!("Failed to write parameter audit log: {}", e).red().to_string());
+        }
+
+        let mut audit = self.audit.lock().unwrap();
+        if audit.len() >= AUDIT_HISTORY_SIZE {
+            audit.pop_front();
+        }
+        audit.push_back(change);
+    }
+}
+
+fn append_audit_file(change: &ParamChange) -> Result<()> {
+    let dir = state_dir();
+    std::fs::create_dir_all(&dir)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join("param_audit.jsonl"))?;
+    writeln!(file, "{}", serde_json::to_string(change)?)?;
+    Ok(())
+}
+
+static RUNTIME_PARAMS: OnceCell<Arc<RuntimeParams>> = OnceCell::new();
+
+/// Make the running market maker's parameters reachable from operator interfaces
+pub fn register_runtime_params(params: Arc<RuntimeParams>) {
+    let _ = RUNTIME_PARAMS.set(params);
+}
+
+/// Parameters of the running market maker, if one has been registered
+pub fn get_runtime_params() -> Option<Arc<RuntimeParams>> {
+    RUNTIME_PARAMS.get().cloned()
+}
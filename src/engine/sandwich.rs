@@ -0,0 +1,317 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use colored::Colorize;
+use crate::common::logger::Logger;
+
+/// Stream transactions are kept for this many slots behind the newest one
+const SLOT_RETENTION: u64 = 4;
+
+/// Sandwich detection and adaptation settings
+#[derive(Debug, Clone, Copy)]
+pub struct SandwichConfig {
+    pub enabled: bool,
+    /// Number of our recent trades the sandwich rate is measured over
+    pub sample_size: usize,
+    /// Sandwich rate (percent) above which slippage is tightened
+    pub rate_threshold_percent: f64,
+    /// Slippage is cut by this many bps per adaptation step
+    pub slippage_step_bps: u64,
+    /// Slippage is never tightened below this
+    pub min_slippage_bps: u64,
+}
+
+impl SandwichConfig {
+    /// Read SANDWICH_DETECTION_ENABLED, SANDWICH_SAMPLE_SIZE, SANDWICH_RATE_THRESHOLD,
+    /// SANDWICH_SLIPPAGE_STEP_BPS and SANDWICH_MIN_SLIPPAGE_BPS
+    pub fn from_env() -> Self {
+        Self {
+            enabled: std::env::var("SANDWICH_DETECTION_ENABLED")
+                .ok()
+                .map(|v| v.trim().to_lowercase() != "false")
+                .unwrap_or(true),
+            sample_size: std::env::var("SANDWICH_SAMPLE_SIZE").ok().and_then(|v| v.parse().ok()).unwrap_or(20),
+            rate_threshold_percent: std::env::var("SANDWICH_RATE_THRESHOLD").ok().and_then(|v| v.parse().ok()).unwrap_or(20.0),
+            slippage_step_bps: std::env::var("SANDWICH_SLIPPAGE_STEP_BPS").ok().and_then(|v| v.parse().ok()).unwrap_or(200),
+            min_slippage_bps: std::env::var("SANDWICH_MIN_SLIPPAGE_BPS").ok().and_then(|v| v.parse().ok()).unwrap_or(100),
+        }
+    }
+}
+
+/// A swap on the target pool seen in the stream, with its position in the block
+#[derive(Debug, Clone)]
+pub struct SlotTrade {
+    pub slot: u64,
+    pub index: u64,
+    pub signer: String,
+    pub is_buy: bool,
+    pub signature: String,
+}
+
+/// One of our trades that was sandwiched
+#[derive(Debug, Clone)]
+pub struct SandwichHit {
+    pub signature: String,
+    pub attacker: String,
+    pub front_run: String,
+    pub back_run: String,
+}
+
+/// Summary for the status report
+#[derive(Debug, Clone)]
+pub struct SandwichStats {
+    pub checked: usize,
+    pub sandwiched: usize,
+    pub rate_percent: f64,
+    pub slippage_reduction_bps: u64,
+    pub prefer_private_relay: bool,
+}
+
+/// Matches our confirmed trades against same-slot stream transactions: a front-run by some
+/// signer in our direction just before us and a back-run by the same signer the other way after us
+pub struct SandwichDetector {
+    config: SandwichConfig,
+    recent: VecDeque<SlotTrade>,
+    pending: Vec<SlotTrade>,
+    outcomes: VecDeque<bool>,
+    slippage_reduction_bps: u64,
+    prefer_private_relay: bool,
+    logger: Logger,
+}
+
+impl SandwichDetector {
+    pub fn new(config: SandwichConfig) -> Self {
+        Self {
+            config,
+            recent: VecDeque::new(),
+            pending: Vec::new(),
+            outcomes: VecDeque::new(),
+            slippage_reduction_bps: 0,
+            prefer_private_relay: false,
+            logger: Logger::new("[SANDWICH] => ".red().bold().to_string()),
+        }
+    }
+
+    /// Record a pool swap from the stream. Our trades older than this slot are evaluated and
+    /// any sandwiches found are returned.
+    pub fn observe(&mut self, trade: SlotTrade) -> Vec<SandwichHit> {
+        if !self.config.enabled {
+            return Vec::new();
+        }
+        let slot = trade.slot;
+        self.recent.push_back(trade);
+        while self.recent.front().map(|t| t.slot + SLOT_RETENTION < slot).unwrap_or(false) {
+            self.recent.pop_front();
+        }
+        self.evaluate(slot)
+    }
+
+    /// Queue one of our confirmed trades; it is checked once its slot is complete in the stream
+    pub fn track_own_trade(&mut self, trade: SlotTrade) {
+        if self.config.enabled {
+            self.pending.push(trade);
+        }
+    }
+
+    fn evaluate(&mut self, current_slot: u64) -> Vec<SandwichHit> {
+        let (ready, waiting): (Vec<SlotTrade>, Vec<SlotTrade>) =
+            self.pending.drain(..).partition(|t| t.slot < current_slot);
+        self.pending = waiting;
+
+        let mut hits = Vec::new();
+        for ours in ready {
+            let hit = self.find_sandwich(&ours);
+            self.outcomes.push_back(hit.is_some());
+            while self.outcomes.len() > self.config.sample_size.max(1) {
+                self.outcomes.pop_front();
+            }
+            if let Some(hit) = hit {
+                self.logger.log(format!(
+                    "🥪 Trade {} sandwiched by {} (front {} / back {})",
+                    hit.signature, hit.attacker, hit.front_run, hit.back_run
+                ).red().to_string());
+                hits.push(hit);
+            }
+            self.adapt();
+        }
+        hits
+    }
+
+    fn find_sandwich(&self, ours: &SlotTrade) -> Option<SandwichHit> {
+        let same_slot: Vec<&SlotTrade> = self.recent.iter()
+            .filter(|t| t.slot == ours.slot && t.signature != ours.signature && t.signer != ours.signer)
+            .collect();
+
+        same_slot.iter()
+            .filter(|front| front.index < ours.index && front.is_buy == ours.is_buy)
+            .find_map(|front| {
+                same_slot.iter()
+                    .find(|back| back.index > ours.index && back.signer == front.signer && back.is_buy != ours.is_buy)
+                    .map(|back| SandwichHit {
+                        signature: ours.signature.clone(),
+                        attacker: front.signer.clone(),
+                        front_run: front.signature.clone(),
+                        back_run: back.signature.clone(),
+                    })
+            })
+    }
+
+    /// Tighten slippage one step while the sandwich rate is above the threshold over a full sample
+    fn adapt(&mut self) {
+        if self.outcomes.len() < self.config.sample_size.max(1) {
+            return;
+        }
+        let rate = self.rate_percent();
+        if rate < self.config.rate_threshold_percent {
+            return;
+        }
+        self.slippage_reduction_bps += self.config.slippage_step_bps;
+        self.prefer_private_relay = true;
+        self.logger.log(format!(
+            "🥪 Sandwich rate {:.1}% over last {} trades, tightening slippage by {} bps and preferring private submission",
+            rate, self.outcomes.len(), self.slippage_reduction_bps
+        ).yellow().bold().to_string());
+        // Start a fresh sample so each step is measured against the new settings
+        self.outcomes.clear();
+    }
+
+    fn rate_percent(&self) -> f64 {
+        if self.outcomes.is_empty() {
+            return 0.0;
+        }
+        self.outcomes.iter().filter(|hit| **hit).count() as f64 / self.outcomes.len() as f64 * 100.0
+    }
+
+    /// Slippage to use for the next trade, given the configured base
+    pub fn slippage_bps(&self, base_bps: u64) -> u64 {
+        base_bps
+            .saturating_sub(self.slippage_reduction_bps)
+            .max(self.config.min_slippage_bps.min(base_bps))
+    }
+
+    /// Whether submission should go through a private relay instead of the public RPC
+    pub fn prefer_private_relay(&self) -> bool {
+        self.prefer_private_relay
+    }
+
+    pub fn get_stats(&self) -> SandwichStats {
+        SandwichStats {
+            checked: self.outcomes.len(),
+            sandwiched: self.outcomes.iter().filter(|hit| **hit).count(),
+            rate_percent: self.rate_percent(),
+            slippage_reduction_bps: self.slippage_reduction_bps,
+            prefer_private_relay: self.prefer_private_relay,
+        }
+    }
+}
+
+/// Global sandwich detector instance
+pub type GlobalSandwichDetector = Arc<Mutex<SandwichDetector>>;
+
+/// Create a global sandwich detector
+pub fn create_global_sandwich_detector(config: SandwichConfig) -> GlobalSandwichDetector {
+    Arc::new(Mutex::new(SandwichDetector::new(config)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(sample_size: usize) -> SandwichConfig {
+        SandwichConfig {
+            enabled: true,
+            sample_size,
+            rate_threshold_percent: 50.0,
+            slippage_step_bps: 200,
+            min_slippage_bps: 100,
+        }
+    }
+
+    fn trade(slot: u64, index: u64, signer: &str, is_buy: bool, signature: &str) -> SlotTrade {
+        SlotTrade { slot, index, signer: signer.to_string(), is_buy, signature: signature.to_string() }
+    }
+
+    /// Our buy at index 5 of `slot`, with `attacker` buying before and selling after when given
+    fn play_slot(detector: &mut SandwichDetector, slot: u64, attacker: Option<&str>) -> Vec<SandwichHit> {
+        let ours = trade(slot, 5, "us", true, &format!("ours-{}", slot));
+        detector.track_own_trade(ours.clone());
+        if let Some(attacker) = attacker {
+            detector.observe(trade(slot, 4, attacker, true, &format!("front-{}", slot)));
+        }
+        detector.observe(ours);
+        if let Some(attacker) = attacker {
+            detector.observe(trade(slot, 6, attacker, false, &format!("back-{}", slot)));
+        }
+        // Our trade is checked once the stream moves past its slot
+        detector.observe(trade(slot + 1, 0, "other", false, &format!("next-{}", slot)))
+    }
+
+    #[test]
+    fn finds_a_front_and_back_run_by_the_same_signer() {
+        let mut detector = SandwichDetector::new(config(20));
+        let hits = play_slot(&mut detector, 10, Some("mev"));
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].signature, "ours-10");
+        assert_eq!(hits[0].attacker, "mev");
+        assert_eq!(hits[0].front_run, "front-10");
+        assert_eq!(hits[0].back_run, "back-10");
+        assert_eq!(detector.get_stats().sandwiched, 1);
+    }
+
+    #[test]
+    fn ignores_trades_that_dont_bracket_ours() {
+        let mut detector = SandwichDetector::new(config(20));
+        detector.track_own_trade(trade(10, 5, "us", true, "ours"));
+        // Same direction both sides, and a back-run by a different signer
+        detector.observe(trade(10, 4, "a", true, "a-front"));
+        detector.observe(trade(10, 6, "a", true, "a-after"));
+        detector.observe(trade(10, 7, "b", false, "b-back"));
+        assert!(detector.observe(trade(11, 0, "other", true, "next")).is_empty());
+        assert_eq!(detector.get_stats().checked, 1);
+        assert_eq!(detector.get_stats().sandwiched, 0);
+    }
+
+    #[test]
+    fn waits_for_the_slot_to_complete() {
+        let mut detector = SandwichDetector::new(config(20));
+        detector.track_own_trade(trade(10, 5, "us", true, "ours"));
+        detector.observe(trade(10, 4, "mev", true, "front"));
+        assert!(detector.observe(trade(10, 6, "mev", false, "back")).is_empty());
+        assert_eq!(detector.get_stats().checked, 0);
+        assert_eq!(detector.observe(trade(11, 0, "other", true, "next")).len(), 1);
+    }
+
+    #[test]
+    fn tightens_slippage_once_per_full_sample_above_the_threshold() {
+        let mut detector = SandwichDetector::new(config(4));
+        play_slot(&mut detector, 10, Some("mev"));
+        play_slot(&mut detector, 20, Some("mev"));
+        play_slot(&mut detector, 30, None);
+        assert_eq!(detector.slippage_bps(500), 500, "sample not full yet");
+        assert!(!detector.prefer_private_relay());
+
+        // 2 of 4 sandwiched = 50%
+        play_slot(&mut detector, 40, None);
+        assert_eq!(detector.slippage_bps(500), 300);
+        assert!(detector.prefer_private_relay());
+        assert_eq!(detector.get_stats().checked, 0, "a fresh sample starts after each step");
+
+        for slot in [50, 60, 70, 80] {
+            play_slot(&mut detector, slot, Some("mev"));
+        }
+        assert_eq!(detector.slippage_bps(500), 100);
+        // Never below the floor, nor above a smaller base
+        for slot in [90, 100, 110, 120] {
+            play_slot(&mut detector, slot, Some("mev"));
+        }
+        assert_eq!(detector.slippage_bps(500), 100);
+        assert_eq!(detector.slippage_bps(50), 50);
+    }
+
+    #[test]
+    fn disabled_detector_tracks_nothing() {
+        let mut detector = SandwichDetector::new(SandwichConfig { enabled: false, ..config(1) });
+        assert!(play_slot(&mut detector, 10, Some("mev")).is_empty());
+        assert_eq!(detector.get_stats().checked, 0);
+    }
+}
@@ -0,0 +1,345 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use anyhow::Result;
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::hash::Hash;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::{Keypair, Signature};
+use anchor_client::solana_sdk::signer::Signer;
+use tokio::sync::{mpsc, Mutex, OwnedMutexGuard, Semaphore};
+use tokio::time::{Duration, Instant};
+use uuid::Uuid;
+use crate::common::wallet_pool::TradeType;
+use crate::services::blockhash_processor::BlockhashProcessor;
+
+/// Trades waiting for an execution slot per allowed concurrent trade, before enqueueing fails
+const QUEUE_DEPTH_PER_SLOT: usize = 4;
+
+/// A trade handed from a producer (position exits, copy orders) to the execution workers
+#[derive(Clone)]
+pub struct QueuedTrade {
+    /// Correlation id carried by every log line of this trade, from sizing to confirmation
+    pub id: Uuid,
+    pub wallet: Arc<Keypair>,
+    pub trade_type: TradeType,
+    pub sol_amount: f64,
+    /// Tokens to sell (UI amount); None sells the wallet's balance
+    pub tokens: Option<f64>,
+    /// Position rule behind an exit sell (stop-loss, take-profit, ...)
+    pub reason: Option<String>,
+    /// Protective exit (stop-loss): never held for operator approval or refused by the rate limit
+    pub protective: bool,
+    /// Swap slippage, set from the sandwich-adapted slippage right before execution; None
+    /// until then
+    pub slippage_bps: Option<u64>,
+    pub queued_at: Instant,
+}
+
+impl QueuedTrade {
+    pub fn new(wallet: Arc<Keypair>, trade_type: TradeType, sol_amount: f64) -> Self {
+        Self { id: Uuid::new_v4(), wallet, trade_type, sol_amount, tokens: None, reason: None, protective: false, slippage_bps: None, queued_at: Instant::now() }
+    }
+
+    /// An exit sell of `tokens` requested by a position rule
+    pub fn exit(wallet: Arc<Keypair>, tokens: f64, reason: &str) -> Self {
+        let mut trade = Self::new(wallet, TradeType::Sell, 0.0).with_tokens(tokens);
+        trade.reason = Some(reason.to_string());
+        trade
+    }
+
+    /// Sell exactly `tokens` instead of the whole balance
+    pub fn with_tokens(mut self, tokens: f64) -> Self {
+        self.tokens = Some(tokens);
+        self
+    }
+}
+
+/// What an executed trade did, reported back by the execute callback for bookkeeping
+#[derive(Debug, Clone)]
+pub struct TradeFill {
+    pub signature: Signature,
+    /// Tokens bought or sold (UI amount)
+    pub tokens: f64,
+    /// SOL per token
+    pub price: f64,
+    /// Lamports spent by a buy or received by a sell
+    pub lamports: u64,
+}
+
+/// Balance reads older than this are refreshed before sizing a trade
+const BALANCE_MAX_AGE: Duration = Duration::from_secs(10);
+
+/// Execution state of one wallet. Only the trade holding the wallet's lock can see it, so
+/// parallel trades never share a balance read, token-account set or blockhash.
+#[derive(Debug, Default)]
+pub struct WalletState {
+    /// Token accounts known to exist, so the idempotent create instruction can be left out
+    pub known_token_accounts: HashSet<Pubkey>,
+    sol_balance: Option<(u64, Instant)>,
+    /// Blockhash of the wallet's last transaction
+    pub last_blockhash: Option<Hash>,
+    pub last_signature: Option<Signature>,
+    pub trades: u64,
+}
+
+impl WalletState {
+    /// Lamport balance, read over RPC when the cached value is older than BALANCE_MAX_AGE
+    pub async fn sol_balance(&mut self, rpc: &RpcClient, wallet: &Pubkey) -> Result<u64> {
+        if let Some((lamports, at)) = self.sol_balance {
+            if at.elapsed() < BALANCE_MAX_AGE {
+                return Ok(lamports);
+            }
+        }
+        let lamports = rpc.get_balance(wallet).await?;
+        self.sol_balance = Some((lamports, Instant::now()));
+        Ok(lamports)
+    }
+
+    /// Blockhash for the wallet's next transaction: the processor's cached hash while fresh,
+    /// otherwise one from RPC
+    pub async fn blockhash(&mut self, rpc: &RpcClient) -> Result<Hash> {
+        let hash = BlockhashProcessor::blockhash_or_fetch(rpc).await?;
+        self.last_blockhash = Some(hash);
+        Ok(hash)
+    }
+
+    /// Record a sent transaction; the balance is stale from here on
+    pub fn record_sent(&mut self, signature: Signature, created_token_accounts: &[Pubkey]) {
+        self.last_signature = Some(signature);
+        self.trades += 1;
+        self.sol_balance = None;
+        self.known_token_accounts.extend(created_token_accounts.iter().copied());
+    }
+}
+
+/// One `WalletState` behind an async mutex per wallet, so two trades never build, sign and
+/// send from the same wallet at once (stale balance reads and duplicate ATA creation otherwise race)
+#[derive(Default)]
+pub struct WalletStates {
+    states: std::sync::Mutex<HashMap<Pubkey, Arc<Mutex<WalletState>>>>,
+}
+
+impl WalletStates {
+    pub async fn lock(&self, wallet: &Pubkey) -> OwnedMutexGuard<WalletState> {
+        let state = self.states
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(*wallet)
+            .or_default()
+            .clone();
+        state.lock_owned().await
+    }
+
+    /// True while a trade holds `wallet`
+    pub fn is_busy(&self, wallet: &Pubkey) -> bool {
+        self.states
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(wallet)
+            .map(|state| state.try_lock().is_err())
+            .unwrap_or(false)
+    }
+}
+
+/// Producer side of the bounded trade queue
+#[derive(Clone)]
+pub struct TradeQueue {
+    sender: mpsc::Sender<QueuedTrade>,
+    slots: Arc<Semaphore>,
+    max_concurrent: usize,
+    /// Trades taken off the channel that are waiting for their wallet or a slot
+    waiting: Arc<Semaphore>,
+    max_waiting: usize,
+    wallets: Arc<WalletStates>,
+}
+
+impl TradeQueue {
+    /// Queue a trade without waiting; the trade is handed back when the queue is full or closed
+    pub fn try_enqueue(&self, trade: QueuedTrade) -> Result<(), QueuedTrade> {
+        self.sender.try_send(trade).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(trade) | mpsc::error::TrySendError::Closed(trade) => trade,
+        })
+    }
+
+    /// Trades currently executing
+    pub fn in_flight(&self) -> usize {
+        self.max_concurrent - self.slots.available_permits()
+    }
+
+    /// Trades waiting for a slot
+    pub fn queued(&self) -> usize {
+        (self.sender.max_capacity() - self.sender.capacity()) + (self.max_waiting - self.waiting.available_permits())
+    }
+
+    /// True while a trade holds `wallet`: being admitted (pause or approval waits included),
+    /// waiting for an execution slot or executing.
+    pub fn is_wallet_busy(&self, wallet: &Pubkey) -> bool {
+        self.wallets.is_busy(wallet)
+    }
+}
+
+/// Start the dispatcher: up to `max_concurrent` trades execute at once, each in its own task
+/// with exclusive access to its wallet's `WalletState`, so trades for one wallet run one after
+/// another. A trade first locks its wallet, then `admit` gates it (None drops it); only an
+/// admitted trade takes an execution slot and runs `execute`. A trade waiting on its wallet,
+/// on a pause or on operator approval therefore never holds a slot other wallets could use.
+pub fn spawn_trade_workers<A, AFut, F, Fut>(max_concurrent: usize, admit: A, execute: F) -> TradeQueue
+where
+    A: Fn(QueuedTrade) -> AFut + Send + Sync + 'static,
+    AFut: std::future::Future<Output = Option<QueuedTrade>> + Send + 'static,
+    F: Fn(QueuedTrade, OwnedMutexGuard<WalletState>) -> Fut + Send + Sync + 'static,
+    Fut: std::future::Future<Output = ()> + Send + 'static,
+{
+    let max_concurrent = max_concurrent.max(1);
+    let (sender, mut receiver) = mpsc::channel::<QueuedTrade>(max_concurrent * QUEUE_DEPTH_PER_SLOT);
+    let slots = Arc::new(Semaphore::new(max_concurrent));
+    // Bounds trades parked on a busy wallet or in admission the same way the channel bounds
+    // queued ones
+    let max_waiting = max_concurrent * QUEUE_DEPTH_PER_SLOT;
+    let waiting = Arc::new(Semaphore::new(max_waiting));
+    let wallets = Arc::new(WalletStates::default());
+    let admit = Arc::new(admit);
+    let execute = Arc::new(execute);
+
+    let dispatcher_slots = slots.clone();
+    let dispatcher_waiting = waiting.clone();
+    let dispatcher_wallets = wallets.clone();
+    tokio::spawn(async move {
+        while let Some(trade) = receiver.recv().await {
+            let Ok(waiting) = dispatcher_waiting.clone().acquire_owned().await else { break };
+            let slots = dispatcher_slots.clone();
+            let wallets = dispatcher_wallets.clone();
+            let admit = admit.clone();
+            let execute = execute.clone();
+            tokio::spawn(async move {
+                let state = wallets.lock(&trade.wallet.pubkey()).await;
+                let Some(trade) = admit(trade).await else { return };
+                let Ok(permit) = slots.acquire_owned().await else { return };
+                drop(waiting);
+                execute(trade, state).await;
+                drop(permit);
+            });
+        }
+    });
+
+    TradeQueue { sender, slots, max_concurrent, waiting, max_waiting, wallets }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::Notify;
+
+    fn trade(wallet: &Arc<Keypair>) -> QueuedTrade {
+        QueuedTrade::new(wallet.clone(), TradeType::Buy, 0.1)
+    }
+
+    async fn admit_all(trade: QueuedTrade) -> Option<QueuedTrade> {
+        Some(trade)
+    }
+
+    #[tokio::test]
+    async fn a_busy_wallet_doesnt_hold_a_slot() {
+        let busy = Arc::new(Keypair::new());
+        let idle = Arc::new(Keypair::new());
+        let release = Arc::new(Notify::new());
+        let (done, mut finished) = mpsc::unbounded_channel();
+
+        let busy_key = busy.pubkey();
+        let gate = release.clone();
+        let queue = spawn_trade_workers(2, admit_all, move |trade, _state| {
+            let gate = gate.clone();
+            let done = done.clone();
+            async move {
+                if trade.wallet.pubkey() == busy_key {
+                    gate.notified().await;
+                }
+                let _ = done.send(trade.wallet.pubkey());
+            }
+        });
+
+        // The second trade for `busy` waits on its wallet; `idle` still gets the free slot
+        queue.try_enqueue(trade(&busy)).ok().unwrap();
+        queue.try_enqueue(trade(&busy)).ok().unwrap();
+        queue.try_enqueue(trade(&idle)).ok().unwrap();
+        let first = tokio::time::timeout(Duration::from_secs(2), finished.recv()).await.unwrap();
+        assert_eq!(first, Some(idle.pubkey()));
+        assert!(queue.is_wallet_busy(&busy.pubkey()));
+
+        release.notify_one();
+        assert_eq!(finished.recv().await, Some(busy.pubkey()));
+        release.notify_one();
+        assert_eq!(finished.recv().await, Some(busy.pubkey()));
+    }
+
+    #[tokio::test]
+    async fn admission_waits_dont_hold_a_slot() {
+        let waiting = Arc::new(Keypair::new());
+        let other = Arc::new(Keypair::new());
+        let approve = Arc::new(Notify::new());
+        let (done, mut finished) = mpsc::unbounded_channel();
+
+        let waiting_key = waiting.pubkey();
+        let gate = approve.clone();
+        let queue = spawn_trade_workers(
+            1,
+            move |trade: QueuedTrade| {
+                let gate = gate.clone();
+                async move {
+                    if trade.wallet.pubkey() == waiting_key {
+                        gate.notified().await;
+                    }
+                    Some(trade)
+                }
+            },
+            move |trade, _state| {
+                let done = done.clone();
+                async move {
+                    let _ = done.send(trade.wallet.pubkey());
+                }
+            },
+        );
+
+        queue.try_enqueue(trade(&waiting)).ok().unwrap();
+        queue.try_enqueue(trade(&other)).ok().unwrap();
+        let first = tokio::time::timeout(Duration::from_secs(2), finished.recv()).await.unwrap();
+        assert_eq!(first, Some(other.pubkey()));
+
+        approve.notify_one();
+        assert_eq!(finished.recv().await, Some(waiting.pubkey()));
+    }
+
+    #[tokio::test]
+    async fn trades_for_one_wallet_run_one_at_a_time() {
+        let wallet = Arc::new(Keypair::new());
+        let running = Arc::new(AtomicUsize::new(0));
+        let most = Arc::new(AtomicUsize::new(0));
+        let (done, mut finished) = mpsc::unbounded_channel();
+
+        let (running_in, most_in) = (running.clone(), most.clone());
+        let queue = spawn_trade_workers(4, admit_all, move |_trade, mut state| {
+            let (running, most, done) = (running_in.clone(), most_in.clone(), done.clone());
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                most.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                state.trades += 1;
+                let trades = state.trades;
+                running.fetch_sub(1, Ordering::SeqCst);
+                let _ = done.send(trades);
+            }
+        });
+
+        for _ in 0..4 {
+            queue.try_enqueue(trade(&wallet)).ok().unwrap();
+        }
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            seen.push(tokio::time::timeout(Duration::from_secs(2), finished.recv()).await.unwrap().unwrap());
+        }
+        // Each trade saw the state the one before it left behind
+        assert_eq!(seen, vec![1, 2, 3, 4]);
+        assert_eq!(most.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,10 +1,13 @@
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use anyhow::{anyhow, Result};
 use anchor_client::solana_sdk::pubkey::Pubkey;
 use colored::Colorize;
-use yellowstone_grpc_proto::geyser::SubscribeUpdateTransaction;
+use lazy_static::lazy_static;
+use yellowstone_grpc_proto::geyser::{SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo};
 use yellowstone_grpc_proto::prelude::{TransactionStatusMeta, TokenBalance};
 use crate::common::logger::Logger;
+use crate::engine::monitor::{classify_instructions, InstructionType};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DexType {
@@ -23,6 +26,9 @@ pub struct TransactionAnalysis {
     pub volume_change: f64,
     pub dex_type: DexType,
     pub swap_event: Option<SwapEventData>,
+    /// Instruction behind the trade, from its discriminator. PumpMint marks the creator's
+    /// initial buy inside the create transaction, so mints can be told apart from swaps.
+    pub instruction_type: Option<InstructionType>,
 }
 
 #[derive(Debug, Clone)]
@@ -64,36 +70,648 @@ pub struct TradeInfoFromToken {
     pub amount_out: u64,
 }
 
-/// Parse Raydium CPMM transaction logs and extract trading information
+/// Full account list of a stream transaction, in the order balance and instruction indexes use:
+/// the static message keys, then the writable and readonly addresses loaded from lookup tables.
+/// Legacy messages load nothing, so this is just `account_keys` for them.
+pub fn resolve_account_keys(info: &SubscribeUpdateTransactionInfo) -> Vec<Pubkey> {
+    let Some(message) = info.transaction.as_ref().and_then(|t| t.message.as_ref()) else { return Vec::new() };
+    let loaded = info.meta.as_ref().map(|meta| {
+        meta.loaded_writable_addresses.iter().chain(meta.loaded_readonly_addresses.iter())
+    });
+    message.account_keys
+        .iter()
+        .chain(loaded.into_iter().flatten())
+        // Keep positions stable even if a key were malformed
+        .map(|key| Pubkey::try_from(key.as_slice()).unwrap_or_default())
+        .collect()
+}
+
+/// One SPL Token / Token-2022 transfer executed inside a transaction
+#[derive(Debug, Clone)]
+pub struct TokenTransfer {
+    pub mint: String,
+    pub source_owner: String,
+    pub destination_owner: String,
+    pub amount: u64,
+}
+
+/// Mint and owner of the token account at `index`, from the transaction's token balances
+fn token_account_info(meta: &TransactionStatusMeta, index: usize) -> Option<(String, String)> {
+    meta.post_token_balances
+        .iter()
+        .chain(meta.pre_token_balances.iter())
+        .find(|balance| balance.account_index as usize == index)
+        .map(|balance| (balance.mint.clone(), balance.owner.clone()))
+}
+
+/// Every `Transfer` / `TransferChecked` issued by the token programs as an inner instruction
+pub fn inner_token_transfers(info: &SubscribeUpdateTransactionInfo) -> Vec<TokenTransfer> {
+    let Some(meta) = info.meta.as_ref() else { return Vec::new() };
+    let keys = resolve_account_keys(info);
+    let token_programs = [spl_token::id(), spl_token_2022::id()];
+    let mut transfers = Vec::new();
+    for ix in meta.inner_instructions.iter().flat_map(|inner| inner.instructions.iter()) {
+        if !keys.get(ix.program_id_index as usize).map(|p| token_programs.contains(p)).unwrap_or(false) {
+            continue;
+        }
+        // Transfer: [source, destination, authority]; TransferChecked: [source, mint, destination, authority]
+        let (source, destination) = match ix.data.first() {
+            Some(&3) => (ix.accounts.first(), ix.accounts.get(1)),
+            Some(&12) => (ix.accounts.first(), ix.accounts.get(2)),
+            _ => continue,
+        };
+        let (Some(&source), Some(&destination)) = (source, destination) else { continue };
+        let Some(amount) = ix.data.get(1..9).and_then(|b| b.try_into().ok()).map(u64::from_le_bytes) else { continue };
+        let (Some((mint, source_owner)), Some((_, destination_owner))) = (
+            token_account_info(meta, source as usize),
+            token_account_info(meta, destination as usize),
+        ) else { continue };
+        transfers.push(TokenTransfer { mint, source_owner, destination_owner, amount });
+    }
+    transfers
+}
+
+/// A swap seen from the user's token accounts
+#[derive(Debug, Clone, Copy)]
+pub struct TransferSwapAmounts {
+    pub is_buy: bool,
+    /// WSOL lamports spent (buy) or received (sell)
+    pub sol_amount: u64,
+    /// Target tokens received (buy) or spent (sell)
+    pub token_amount: u64,
+}
+
+/// Net WSOL and `target_mint` flow of `user` across all inner transfers. Hops through other
+/// pools or intermediate mints cancel out, so routed swaps report the user's real in/out.
+pub fn swap_amounts_from_transfers(info: &SubscribeUpdateTransactionInfo, user: &str, target_mint: &str) -> Option<TransferSwapAmounts> {
+    let native_mint = spl_token::native_mint::ID.to_string();
+    let (mut token_net, mut sol_net) = (0i128, 0i128);
+    for transfer in inner_token_transfers(info) {
+        let delta = match (transfer.source_owner == user, transfer.destination_owner == user) {
+            (true, false) => -(transfer.amount as i128),
+            (false, true) => transfer.amount as i128,
+            _ => continue,
+        };
+        if transfer.mint == target_mint {
+            token_net += delta;
+        } else if transfer.mint == native_mint {
+            sol_net += delta;
+        }
+    }
+    if token_net == 0 || sol_net == 0 || token_net.signum() == sol_net.signum() {
+        return None;
+    }
+    Some(TransferSwapAmounts {
+        is_buy: token_net > 0,
+        sol_amount: sol_net.unsigned_abs() as u64,
+        token_amount: token_net.unsigned_abs() as u64,
+    })
+}
+
+/// Why a stream transaction yielded no (or only a partial) analysis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMiss {
+    /// Update without a transaction body
+    NoTransaction,
+    /// Transaction without status meta (no logs, balances or inner instructions)
+    NoMeta,
+    /// No swap event in the Raydium logs
+    NoSwapEvent,
+    /// Balance changes did not show the trade direction
+    NoDirection,
+    /// No PumpFun / PumpSwap trade event for the target mint
+    NoTradeEvent,
+    /// Target mint is not a valid public key
+    InvalidMint,
+}
+
+impl ParseMiss {
+    pub const ALL: [ParseMiss; 6] = [
+        ParseMiss::NoTransaction,
+        ParseMiss::NoMeta,
+        ParseMiss::NoSwapEvent,
+        ParseMiss::NoDirection,
+        ParseMiss::NoTradeEvent,
+        ParseMiss::InvalidMint,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParseMiss::NoTransaction => "no transaction",
+            ParseMiss::NoMeta => "no meta",
+            ParseMiss::NoSwapEvent => "no swap event",
+            ParseMiss::NoDirection => "no direction",
+            ParseMiss::NoTradeEvent => "no trade event",
+            ParseMiss::InvalidMint => "invalid mint",
+        }
+    }
+}
+
+/// Parser outcome counters since start
+pub struct ParseStats {
+    parsed: AtomicU64,
+    partial: AtomicU64,
+    misses: [AtomicU64; ParseMiss::ALL.len()],
+}
+
+impl ParseStats {
+    fn new() -> Self {
+        Self {
+            parsed: AtomicU64::new(0),
+            partial: AtomicU64::new(0),
+            misses: std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+
+    fn record_parsed(&self) {
+        self.parsed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_partial(&self) {
+        self.partial.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_miss(&self, miss: ParseMiss) {
+        if let Some(i) = ParseMiss::ALL.iter().position(|m| *m == miss) {
+            self.misses[i].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn snapshot(&self) -> ParseStatsSnapshot {
+        ParseStatsSnapshot {
+            parsed: self.parsed.load(Ordering::Relaxed),
+            partial: self.partial.load(Ordering::Relaxed),
+            misses: ParseMiss::ALL
+                .iter()
+                .zip(&self.misses)
+                .map(|(miss, count)| (*miss, count.load(Ordering::Relaxed)))
+                .filter(|(_, count)| *count > 0)
+                .collect(),
+        }
+    }
+}
+
+/// Point-in-time parser counters for reports
+#[derive(Debug, Clone, Default)]
+pub struct ParseStatsSnapshot {
+    /// Fully parsed trades
+    pub parsed: u64,
+    /// Trades recovered from inner transfers after a miss
+    pub partial: u64,
+    /// Non-zero miss counters; a partial trade also counts its miss
+    pub misses: Vec<(ParseMiss, u64)>,
+}
+
+impl ParseStatsSnapshot {
+    pub fn total_misses(&self) -> u64 {
+        self.misses.iter().map(|(_, count)| count).sum()
+    }
+
+    /// One-line summary, e.g. `980 parsed, 12 partial, misses: 14 no swap event, 3 no meta`
+    pub fn to_report(&self) -> String {
+        let mut line = format!("{} parsed, {} partial", self.parsed, self.partial);
+        if !self.misses.is_empty() {
+            let misses: Vec<String> = self.misses.iter().map(|(miss, count)| format!("{} {}", count, miss.as_str())).collect();
+            line.push_str(&format!(", misses: {}", misses.join(", ")));
+        }
+        line
+    }
+}
+
+lazy_static! {
+    pub static ref PARSE_STATS: ParseStats = ParseStats::new();
+}
+
+/// Count `miss` and return None
+fn parse_miss<T>(miss: ParseMiss) -> Option<T> {
+    PARSE_STATS.record_miss(miss);
+    None
+}
+
+/// Parse Raydium CPMM transaction logs and extract trading information. When the swap event or
+/// direction is missing, the trade is rebuilt from the fee payer's inner token transfers
+/// (`swap_event: None`) and counted as partial in PARSE_STATS.
 pub fn parse_raydium_cpmm_transaction(
     txn: &SubscribeUpdateTransaction,
     target_mint: &str,
 ) -> Option<TransactionAnalysis> {
     let logger = Logger::new("[TX-PARSER] => ".cyan().to_string());
     
-    let transaction = txn.transaction.as_ref()?;
-    let meta = transaction.meta.as_ref()?;
+    let Some(transaction) = txn.transaction.as_ref() else { return parse_miss(ParseMiss::NoTransaction) };
+    let Some(meta) = transaction.meta.as_ref() else { return parse_miss(ParseMiss::NoMeta) };
     
     // Parse log messages for swap events
-    let swap_event = parse_swap_event_from_logs(&meta.log_messages)?;
+    let Some(swap_event) = parse_swap_event_from_logs(&meta.log_messages) else {
+        PARSE_STATS.record_miss(ParseMiss::NoSwapEvent);
+        return parse_raydium_from_transfers(transaction, target_mint, &logger);
+    };
     
     // Parse balance changes
     let balance_changes = parse_balance_changes(meta, target_mint);
     
     // Determine if this is a buy or sell based on the swap event and balance changes
-    let (is_buy, user, volume_change) = analyze_transaction_direction(&balance_changes, &swap_event, target_mint)?;
+    let Some((is_buy, user, volume_change)): Option<(bool, String, f64)> = analyze_transaction_direction(&balance_changes, &swap_event, target_mint) else {
+        PARSE_STATS.record_miss(ParseMiss::NoDirection);
+        return parse_raydium_from_transfers(transaction, target_mint, &logger);
+    };
+    
+    // Exact amounts from the user's inner token transfers; logs and balance deltas are
+    // ambiguous when the transaction touches several pools
+    let (amount_in, amount_out, volume_change) = match swap_amounts_from_transfers(transaction, &user, target_mint) {
+        Some(exact) if exact.is_buy == is_buy => {
+            let volume = exact.sol_amount as f64 / 1_000_000_000.0;
+            if is_buy { (exact.sol_amount, exact.token_amount, volume) } else { (exact.token_amount, exact.sol_amount, volume) }
+        },
+        _ => (swap_event.amount_in, swap_event.amount_out, volume_change),
+    };
     
     logger.log(format!("Parsed transaction - Mint: {}, Is Buy: {}, Volume: {}", 
         target_mint, is_buy, volume_change).green().to_string());
     
+    PARSE_STATS.record_parsed();
     Some(TransactionAnalysis {
         mint: target_mint.to_string(),
         is_buy,
-        amount_in: swap_event.amount_in,
-        amount_out: swap_event.amount_out,
+        amount_in,
+        amount_out,
         user,
         volume_change,
         dex_type: DexType::RaydiumCPMM,
         swap_event: Some(swap_event),
+        instruction_type: cpmm_instruction(transaction),
+    })
+}
+
+/// Partial CPMM analysis from the fee payer's inner token transfers
+fn parse_raydium_from_transfers(
+    transaction: &SubscribeUpdateTransactionInfo,
+    target_mint: &str,
+    logger: &Logger,
+) -> Option<TransactionAnalysis> {
+    let user = resolve_account_keys(transaction).first()?.to_string();
+    let exact = swap_amounts_from_transfers(transaction, &user, target_mint)?;
+    let (amount_in, amount_out) = if exact.is_buy {
+        (exact.sol_amount, exact.token_amount)
+    } else {
+        (exact.token_amount, exact.sol_amount)
+    };
+    let volume_change = exact.sol_amount as f64 / 1_000_000_000.0;
+    
+    logger.log(format!("Partially parsed transaction from transfers - Mint: {}, Is Buy: {}, Volume: {}", 
+        target_mint, exact.is_buy, volume_change).yellow().to_string());
+    
+    PARSE_STATS.record_partial();
+    Some(TransactionAnalysis {
+        mint: target_mint.to_string(),
+        is_buy: exact.is_buy,
+        amount_in,
+        amount_out,
+        user,
+        volume_change,
+        dex_type: DexType::RaydiumCPMM,
+        swap_event: None,
+        instruction_type: cpmm_instruction(transaction),
+    })
+}
+
+fn cpmm_instruction(transaction: &SubscribeUpdateTransactionInfo) -> Option<InstructionType> {
+    classify_instructions(transaction).into_iter().find(|kind| *kind == InstructionType::CpmmSwap)
+}
+
+/// Anchor discriminator of the PumpFun `TradeEvent`
+const PUMP_FUN_TRADE_EVENT_DISCRIMINATOR: [u8; 8] = [189, 219, 127, 211, 78, 230, 97, 238];
+/// Anchor discriminators of the PumpSwap AMM `BuyEvent` / `SellEvent`
+const PUMP_SWAP_BUY_EVENT_DISCRIMINATOR: [u8; 8] = [103, 244, 82, 31, 44, 245, 119, 119];
+const PUMP_SWAP_SELL_EVENT_DISCRIMINATOR: [u8; 8] = [62, 47, 55, 10, 165, 3, 220, 42];
+/// Prefix of Anchor `emit_cpi!` self-invocations carrying an event
+const ANCHOR_EVENT_CPI_TAG: [u8; 8] = [228, 69, 165, 46, 81, 203, 154, 29];
+
+/// Fields of a PumpFun bonding-curve `TradeEvent`
+#[derive(Debug, Clone)]
+pub struct PumpFunTradeEvent {
+    pub mint: Pubkey,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    pub is_buy: bool,
+    pub user: Pubkey,
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+}
+
+/// Decode a `TradeEvent` (discriminator included); None for any other event
+pub fn decode_pump_fun_trade_event(data: &[u8]) -> Option<PumpFunTradeEvent> {
+    if data.len() < 113 || data[..8] != PUMP_FUN_TRADE_EVENT_DISCRIMINATOR {
+        return None;
+    }
+    let pubkey = |at: usize| Pubkey::try_from(&data[at..at + 32]).ok();
+    let u64_at = |at: usize| data[at..at + 8].try_into().ok().map(u64::from_le_bytes);
+    Some(PumpFunTradeEvent {
+        mint: pubkey(8)?,
+        sol_amount: u64_at(40)?,
+        token_amount: u64_at(48)?,
+        is_buy: data[56] != 0,
+        user: pubkey(57)?,
+        // 89..97 is the event timestamp
+        virtual_sol_reserves: u64_at(97)?,
+        virtual_token_reserves: u64_at(105)?,
     })
 }
+
+/// Fields of a PumpSwap AMM `BuyEvent` or `SellEvent`. The events carry the pool, not the
+/// mints; base is the token and quote is WSOL for pools created by a pump.fun migration.
+#[derive(Debug, Clone)]
+pub struct PumpSwapTradeEvent {
+    /// PumpSwapBuy or PumpSwapSell
+    pub instruction_type: InstructionType,
+    pub base_amount: u64,
+    /// Quote paid by (buy) or paid to (sell) the user, fees included
+    pub quote_amount: u64,
+    pub pool_base_reserves: u64,
+    pub pool_quote_reserves: u64,
+    pub pool: Pubkey,
+    pub user: Pubkey,
+}
+
+impl PumpSwapTradeEvent {
+    pub fn is_buy(&self) -> bool {
+        self.instruction_type == InstructionType::PumpSwapBuy
+    }
+}
+
+/// Decode a PumpSwap `BuyEvent` / `SellEvent` (discriminator included); None for any other event
+pub fn decode_pump_swap_trade_event(data: &[u8]) -> Option<PumpSwapTradeEvent> {
+    if data.len() < 184 {
+        return None;
+    }
+    let instruction_type = if data[..8] == PUMP_SWAP_BUY_EVENT_DISCRIMINATOR {
+        InstructionType::PumpSwapBuy
+    } else if data[..8] == PUMP_SWAP_SELL_EVENT_DISCRIMINATOR {
+        InstructionType::PumpSwapSell
+    } else {
+        return None;
+    };
+    let pubkey = |at: usize| Pubkey::try_from(&data[at..at + 32]).ok();
+    let u64_at = |at: usize| data[at..at + 8].try_into().ok().map(u64::from_le_bytes);
+    // Both events share the layout: timestamp, base amount, quote limit, user / pool reserves,
+    // quote amount, fees, then user_quote_amount (in or out) at 112 and pool / user keys
+    Some(PumpSwapTradeEvent {
+        instruction_type,
+        base_amount: u64_at(16)?,
+        quote_amount: u64_at(112)?,
+        pool_base_reserves: u64_at(48)?,
+        pool_quote_reserves: u64_at(56)?,
+        pool: pubkey(120)?,
+        user: pubkey(152)?,
+    })
+}
+
+/// Raw Anchor event payloads in a transaction: `emit_cpi!` inner instructions first, then
+/// `Program data:` log lines emitted by older program versions
+fn anchor_event_payloads(meta: &TransactionStatusMeta) -> Vec<Vec<u8>> {
+    let from_cpi = meta.inner_instructions
+        .iter()
+        .flat_map(|inner| inner.instructions.iter())
+        .filter_map(|ix| ix.data.strip_prefix(&ANCHOR_EVENT_CPI_TAG[..]))
+        .map(|data| data.to_vec());
+    let from_logs = meta.log_messages
+        .iter()
+        .filter_map(|line| line.strip_prefix("Program data: "))
+        .filter_map(|encoded| base64::decode(encoded.trim()).ok());
+    from_cpi.chain(from_logs).collect()
+}
+
+/// PumpFun trade events in a transaction
+fn pump_fun_trade_events(meta: &TransactionStatusMeta) -> Vec<PumpFunTradeEvent> {
+    anchor_event_payloads(meta).iter().filter_map(|data| decode_pump_fun_trade_event(data)).collect()
+}
+
+/// Parse a PumpFun bonding-curve buy or sell of `target_mint`. `volume_change` is the SOL
+/// side of the trade in SOL; amounts are lamports in and tokens out for buys, the reverse for sells.
+pub fn parse_pump_fun_transaction(
+    txn: &SubscribeUpdateTransaction,
+    target_mint: &str,
+) -> Option<TransactionAnalysis> {
+    let logger = Logger::new("[TX-PARSER] => ".cyan().to_string());
+    
+    let Some(transaction) = txn.transaction.as_ref() else { return parse_miss(ParseMiss::NoTransaction) };
+    let Some(meta) = transaction.meta.as_ref() else { return parse_miss(ParseMiss::NoMeta) };
+    let Ok(mint) = Pubkey::from_str(target_mint) else { return parse_miss(ParseMiss::InvalidMint) };
+    
+    // A routed transaction can hold several trades; the first one for the target mint counts
+    let Some(event) = pump_fun_trade_events(meta).into_iter().find(|event| event.mint == mint) else {
+        return parse_miss(ParseMiss::NoTradeEvent);
+    };
+    let (amount_in, amount_out) = if event.is_buy {
+        (event.sol_amount, event.token_amount)
+    } else {
+        (event.token_amount, event.sol_amount)
+    };
+    let volume_change = event.sol_amount as f64 / 1_000_000_000.0;
+    
+    logger.log(format!("Parsed PumpFun transaction - Mint: {}, Is Buy: {}, Volume: {}", 
+        target_mint, event.is_buy, volume_change).green().to_string());
+    
+    PARSE_STATS.record_parsed();
+    Some(TransactionAnalysis {
+        mint: target_mint.to_string(),
+        is_buy: event.is_buy,
+        amount_in,
+        amount_out,
+        user: event.user.to_string(),
+        volume_change,
+        dex_type: DexType::PumpFun,
+        swap_event: None,
+        instruction_type: Some(if classify_instructions(transaction).contains(&InstructionType::PumpMint) {
+            InstructionType::PumpMint
+        } else if event.is_buy {
+            InstructionType::PumpBuy
+        } else {
+            InstructionType::PumpSell
+        }),
+    })
+}
+
+/// Parse a PumpSwap AMM buy or sell of `target_mint`, for pump.fun tokens after migration. The
+/// trade counts when its pool holds a `target_mint` vault in this transaction. `volume_change`
+/// is the quote (SOL) side in SOL; amounts are lamports in and tokens out for buys, the reverse for sells.
+pub fn parse_pump_swap_transaction(
+    txn: &SubscribeUpdateTransaction,
+    target_mint: &str,
+) -> Option<TransactionAnalysis> {
+    let logger = Logger::new("[TX-PARSER] => ".cyan().to_string());
+    
+    let Some(transaction) = txn.transaction.as_ref() else { return parse_miss(ParseMiss::NoTransaction) };
+    let Some(meta) = transaction.meta.as_ref() else { return parse_miss(ParseMiss::NoMeta) };
+    let target_pools: Vec<&str> = meta.pre_token_balances
+        .iter()
+        .chain(meta.post_token_balances.iter())
+        .filter(|balance| balance.mint == target_mint)
+        .map(|balance| balance.owner.as_str())
+        .collect();
+    
+    let Some(event) = anchor_event_payloads(meta)
+        .iter()
+        .filter_map(|data| decode_pump_swap_trade_event(data))
+        .find(|event| target_pools.contains(&event.pool.to_string().as_str()))
+    else {
+        return parse_miss(ParseMiss::NoTradeEvent);
+    };
+    let is_buy = event.is_buy();
+    let (amount_in, amount_out) = if is_buy {
+        (event.quote_amount, event.base_amount)
+    } else {
+        (event.base_amount, event.quote_amount)
+    };
+    let volume_change = event.quote_amount as f64 / 1_000_000_000.0;
+    
+    logger.log(format!("Parsed PumpSwap transaction - Mint: {}, Is Buy: {}, Volume: {}", 
+        target_mint, is_buy, volume_change).green().to_string());
+    
+    PARSE_STATS.record_parsed();
+    Some(TransactionAnalysis {
+        mint: target_mint.to_string(),
+        is_buy,
+        amount_in,
+        amount_out,
+        user: event.user.to_string(),
+        volume_change,
+        dex_type: DexType::PumpFun,
+        swap_event: None,
+        instruction_type: Some(event.instruction_type),
+    })
+}
+
+/// Parse a stream transaction with the parser for `dex_type`
+pub fn parse_dex_transaction(
+    txn: &SubscribeUpdateTransaction,
+    target_mint: &str,
+    dex_type: &DexType,
+) -> Option<TransactionAnalysis> {
+    match dex_type {
+        DexType::RaydiumCPMM => parse_raydium_cpmm_transaction(txn, target_mint),
+        // Migrated pump.fun tokens trade on PumpSwap; pick the parser by the events present so
+        // only one of them counts a miss
+        DexType::PumpFun => {
+            let on_curve = txn.transaction.as_ref()
+                .and_then(|t| t.meta.as_ref())
+                .map(|meta| !pump_fun_trade_events(meta).is_empty())
+                .unwrap_or(true);
+            if on_curve {
+                parse_pump_fun_transaction(txn, target_mint)
+            } else {
+                parse_pump_swap_transaction(txn, target_mint)
+            }
+        },
+        DexType::RaydiumLaunchpad => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// PumpFun `TradeEvent` blob: discriminator, mint, sol amount, token amount, is_buy, user,
+    /// timestamp, virtual sol reserves, virtual token reserves
+    fn pump_fun_event(mint: &Pubkey, sol_amount: u64, token_amount: u64, is_buy: bool, user: &Pubkey) -> Vec<u8> {
+        let mut data = PUMP_FUN_TRADE_EVENT_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(mint.as_ref());
+        data.extend_from_slice(&sol_amount.to_le_bytes());
+        data.extend_from_slice(&token_amount.to_le_bytes());
+        data.push(is_buy as u8);
+        data.extend_from_slice(user.as_ref());
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        data.extend_from_slice(&30_000_000_000u64.to_le_bytes());
+        data.extend_from_slice(&1_000_000_000_000_000u64.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn decodes_a_pump_fun_trade_event() {
+        let mint = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let data = pump_fun_event(&mint, 250_000_000, 8_123_456_789, true, &user);
+        assert_eq!(data.len(), 113);
+
+        let event = decode_pump_fun_trade_event(&data).unwrap();
+        assert_eq!(event.mint, mint);
+        assert_eq!(event.sol_amount, 250_000_000);
+        assert_eq!(event.token_amount, 8_123_456_789);
+        assert!(event.is_buy);
+        assert_eq!(event.user, user);
+        assert_eq!(event.virtual_sol_reserves, 30_000_000_000);
+        assert_eq!(event.virtual_token_reserves, 1_000_000_000_000_000);
+
+        let sell = decode_pump_fun_trade_event(&pump_fun_event(&mint, 1, 2, false, &user)).unwrap();
+        assert!(!sell.is_buy);
+    }
+
+    #[test]
+    fn pump_fun_decoder_rejects_other_payloads() {
+        let mint = Pubkey::new_unique();
+        let data = pump_fun_event(&mint, 1, 2, true, &mint);
+        assert!(decode_pump_fun_trade_event(&data[..112]).is_none());
+
+        let mut other = data.clone();
+        other[..8].copy_from_slice(&PUMP_SWAP_BUY_EVENT_DISCRIMINATOR);
+        assert!(decode_pump_fun_trade_event(&other).is_none());
+    }
+
+    #[test]
+    fn pump_fun_events_are_read_from_program_data_logs() {
+        let mint = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let meta = TransactionStatusMeta {
+            log_messages: vec![
+                "Program log: Instruction: Buy".to_string(),
+                format!("Program data: {}", base64::encode(pump_fun_event(&mint, 5, 7, true, &user))),
+            ],
+            ..Default::default()
+        };
+        let events = pump_fun_trade_events(&meta);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].mint, mint);
+        assert_eq!(events[0].token_amount, 7);
+    }
+
+    /// PumpSwap `BuyEvent` / `SellEvent` blob of 184 bytes with the fields the decoder reads filled in
+    fn pump_swap_event(discriminator: [u8; 8], base_amount: u64, quote_amount: u64, pool: &Pubkey, user: &Pubkey) -> Vec<u8> {
+        let mut data = vec![0u8; 184];
+        data[..8].copy_from_slice(&discriminator);
+        data[8..16].copy_from_slice(&1_700_000_000i64.to_le_bytes());
+        data[16..24].copy_from_slice(&base_amount.to_le_bytes());
+        data[48..56].copy_from_slice(&900_000_000_000u64.to_le_bytes());
+        data[56..64].copy_from_slice(&85_000_000_000u64.to_le_bytes());
+        data[112..120].copy_from_slice(&quote_amount.to_le_bytes());
+        data[120..152].copy_from_slice(pool.as_ref());
+        data[152..184].copy_from_slice(user.as_ref());
+        data
+    }
+
+    #[test]
+    fn decodes_pump_swap_buy_and_sell_events() {
+        let pool = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+
+        let buy = decode_pump_swap_trade_event(&pump_swap_event(PUMP_SWAP_BUY_EVENT_DISCRIMINATOR, 5_000_000, 120_000_000, &pool, &user)).unwrap();
+        assert!(buy.is_buy());
+        assert_eq!(buy.base_amount, 5_000_000);
+        assert_eq!(buy.quote_amount, 120_000_000);
+        assert_eq!(buy.pool_base_reserves, 900_000_000_000);
+        assert_eq!(buy.pool_quote_reserves, 85_000_000_000);
+        assert_eq!(buy.pool, pool);
+        assert_eq!(buy.user, user);
+
+        let sell = decode_pump_swap_trade_event(&pump_swap_event(PUMP_SWAP_SELL_EVENT_DISCRIMINATOR, 7, 3, &pool, &user)).unwrap();
+        assert_eq!(sell.instruction_type, InstructionType::PumpSwapSell);
+        assert_eq!(sell.base_amount, 7);
+        assert_eq!(sell.quote_amount, 3);
+    }
+
+    #[test]
+    fn pump_swap_decoder_rejects_other_payloads() {
+        let pool = Pubkey::new_unique();
+        let data = pump_swap_event(PUMP_SWAP_BUY_EVENT_DISCRIMINATOR, 1, 1, &pool, &pool);
+        assert!(decode_pump_swap_trade_event(&data[..183]).is_none());
+        assert!(decode_pump_swap_trade_event(&pump_swap_event(PUMP_FUN_TRADE_EVENT_DISCRIMINATOR, 1, 1, &pool, &pool)).is_none());
+    }
+}
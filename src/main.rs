@@ -1,10 +1,13 @@
 use anchor_client::solana_sdk::signature::Signer;
 use solana_vntr_sniper::{
-    common::{config::Config, constants::RUN_MSG, cache::WALLET_TOKEN_ACCOUNTS},
+    common::{config::Config, config_file, logger, config_validation, secrets, constants::RUN_MSG, cache::WALLET_TOKEN_ACCOUNTS, disk_cache::DISK_CACHE},
     engine::{
-        market_maker::{start_market_maker, MarketMakerConfig},
+        activity_journal,
+        market_maker::{MarketMaker, MarketMakerConfig},
+        random_trader::{RandomTrader, RandomTraderConfig, RunMode},
+        transaction_parser::DexType,
     },
-    services::{telegram, cache_maintenance, blockhash_processor::BlockhashProcessor},
+    services::{telegram, cache_maintenance, blockhash_processor::BlockhashProcessor, shutdown::{self, SHUTDOWN}, control, kill_switch::{self, KILL_SWITCH}, holdings, webhook},
     core::token,
 };
 use solana_program_pack::Pack;
@@ -23,28 +26,64 @@ use std::path::Path;
 use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
 use anchor_client::solana_sdk::commitment_config::CommitmentLevel;
 use solana_transaction_status;
+use tracing::{error, info, warn};
 
-#[tokio::main]
-async fn main() {
+fn main() {
     /* Initial Settings */
+    // Everything that writes the process environment runs here, before the tokio runtime and its
+    // worker threads exist
+    // config.toml fills in anything not already set in the environment / .env
+    let config_load = match config_file::load_config_file() {
+        Ok(config_load) => config_load,
+        Err(e) => {
+            eprintln!("Failed to load config file: {}", e);
+            return;
+        }
+    };
+    // LOG_FILTER may come from .env or config.toml, so logging starts once they are loaded
+    logger::init_logging();
+    config_load.log();
+    // Optionally pull PRIVATE_KEY / YELLOWSTONE_GRPC_TOKEN from a secrets backend instead of .env
+    if let Err(e) = secrets::load_secrets() {
+        error!("Failed to load secrets: {}", e);
+        return;
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            error!("Failed to start the tokio runtime: {}", e);
+            return;
+        }
+    };
+    runtime.block_on(run());
+}
+
+/// Validate the settings, then run the requested command or the market maker
+async fn run() {
+    // Refuse to start with missing or malformed settings instead of silently using defaults
+    if let Err(e) = config_validation::validate_env() {
+        error!("{}", e);
+        std::process::exit(1);
+    }
     let config = Config::new().await;
     let config = config.lock().await;
 
     /* Running Bot */
     let run_msg = RUN_MSG;
-    println!("{}", run_msg);
+    info!("{}", run_msg);
     
     // Initialize blockhash processor
     match BlockhashProcessor::new(config.app_state.rpc_client.clone()).await {
         Ok(processor) => {
             if let Err(e) = processor.start().await {
-                eprintln!("Failed to start blockhash processor: {}", e);
+                error!("Failed to start blockhash processor: {}", e);
                 return;
             }
-            println!("Blockhash processor started successfully");
+            info!("Blockhash processor started successfully");
         },
         Err(e) => {
-            eprintln!("Failed to initialize blockhash processor: {}", e);
+            error!("Failed to initialize blockhash processor: {}", e);
             return;
         }
     }
@@ -54,22 +93,22 @@ async fn main() {
     if args.len() > 1 {
         // Check for wallet generation argument
         if args.contains(&"--wallet".to_string()) {
-            println!("Generating wallets...");
+            info!("Generating wallets...");
             
             match generate_wallets().await {
                 Ok(_) => {
-                    println!("✅ Wallet generation completed successfully!");
+                    info!("✅ Wallet generation completed successfully!");
                     return;
                 },
                 Err(e) => {
-                    eprintln!("❌ Failed to generate wallets: {}", e);
+                    error!("❌ Failed to generate wallets: {}", e);
                     return;
                 }
             }
         }
         // Check for command line arguments
         else if args.contains(&"--wrap".to_string()) {
-            println!("Wrapping SOL to WSOL...");
+            info!("Wrapping SOL to WSOL...");
             
             // Get wrap amount from .env
             let wrap_amount = std::env::var("WRAP_AMOUNT")
@@ -79,67 +118,73 @@ async fn main() {
             
             match wrap_sol(&config, wrap_amount).await {
                 Ok(_) => {
-                    println!("Successfully wrapped {} SOL to WSOL", wrap_amount);
+                    info!("Successfully wrapped {} SOL to WSOL", wrap_amount);
                     return;
                 },
                 Err(e) => {
-                    eprintln!("Failed to wrap SOL: {}", e);
+                    error!("Failed to wrap SOL: {}", e);
                     return;
                 }
             }
         } else if args.contains(&"--unwrap".to_string()) {
-            println!("Unwrapping WSOL to SOL...");
+            info!("Unwrapping WSOL to SOL...");
             
             match unwrap_sol(&config).await {
                 Ok(_) => {
-                    println!("Successfully unwrapped WSOL to SOL");
+                    info!("Successfully unwrapped WSOL to SOL");
                     return;
                 },
                 Err(e) => {
-                    eprintln!("Failed to unwrap WSOL: {}", e);
+                    error!("Failed to unwrap WSOL: {}", e);
                     return;
                 }
             }
         } else if args.contains(&"--close".to_string()) {
-            println!("Closing all token accounts...");
+            info!("Closing all token accounts...");
             
             match close_all_token_accounts(&config).await {
                 Ok(_) => {
-                    println!("Successfully closed all token accounts");
+                    info!("Successfully closed all token accounts");
                     return;
                 },
                 Err(e) => {
-                    eprintln!("Failed to close all token accounts: {}", e);
+                    error!("Failed to close all token accounts: {}", e);
                     return;
                 }
             }
         } else if args.contains(&"--check-tokens".to_string()) {
-            println!("Token monitoring feature disabled in this version");
-            return;
+            let sell_dust = args.contains(&"--sell-dust".to_string());
+            match holdings::check_tokens(&config, sell_dust).await {
+                Ok(_) => return,
+                Err(e) => {
+                    error!("❌ Failed to check token holdings: {}", e);
+                    return;
+                }
+            }
         } else if args.contains(&"--distribute".to_string()) {
-            println!("Distributing SOL to all wallets and converting to WSOL...");
+            info!("Distributing SOL to all wallets and converting to WSOL...");
             
             match distribute_sol(&config).await {
                 Ok(_) => {
-                    println!("✅ SOL distribution and WSOL conversion completed successfully!");
+                    info!("✅ SOL distribution and WSOL conversion completed successfully!");
                     return;
                 },
                 Err(e) => {
-                    eprintln!("❌ Failed to distribute SOL: {}", e);
+                    error!("❌ Failed to distribute SOL: {}", e);
                     return;
                 }
             }
         } else if args.contains(&"--collect".to_string()) {
-            println!("🔍 Checking wallet balances and collecting all funds...");
-            println!("📊 This will: sell all tokens, close WSOL accounts, and collect SOL to main wallet");
+            info!("🔍 Checking wallet balances and collecting all funds...");
+            info!("📊 This will: sell all tokens, close WSOL accounts, and collect SOL to main wallet");
             
             match collect_sol(&config).await {
                 Ok(_) => {
-                    println!("✅ Collection completed successfully!");
+                    info!("✅ Collection completed successfully!");
                     return;
                 },
                 Err(e) => {
-                    eprintln!("❌ Failed to complete collection: {}", e);
+                    error!("❌ Failed to complete collection: {}", e);
                     return;
                 }
             }
@@ -148,16 +193,61 @@ async fn main() {
 
     // Initialize Telegram bot
     match telegram::init().await {
-        Ok(_) => println!("Telegram bot initialized successfully"),
-        Err(e) => println!("Failed to initialize Telegram bot: {}. Continuing without notifications.", e),
+        Ok(_) => info!("Telegram bot initialized successfully"),
+        Err(e) => warn!("Failed to initialize Telegram bot: {}. Continuing without notifications.", e),
+    }
+    if telegram::start_command_listener() {
+        info!("Telegram command listener started (/kill, /approve, /reject)");
     }
     
+    // Load mint info, wallet token accounts and pools saved by the disk cache (CACHE_BACKEND=sled)
+    if DISK_CACHE.is_enabled() {
+        DISK_CACHE.warm_up();
+    }
+
     // Initialize token account list
     initialize_token_account_list(&config).await;
+
+    // Find the pool wallets' existing token accounts in batches
+    let mut owners = vec![config.app_state.wallet.pubkey()];
+    if let Ok(wallets) = holdings::load_pool_wallets() {
+        owners.extend(wallets.iter().map(|w| w.pubkey()));
+    }
+    match Pubkey::from_str(&config.target_token_mint) {
+        Ok(mint) => match holdings::discover_wallet_token_accounts(&config.app_state.rpc_nonblocking_client, &owners, &mint).await {
+            Ok(known) => info!("Found {} existing token accounts across {} wallets", known, owners.len()),
+            Err(e) => warn!("Token account discovery failed: {}. Using {} saved accounts.", e, WALLET_TOKEN_ACCOUNTS.size()),
+        },
+        Err(e) => info!("Skipping token account discovery, invalid target mint: {}", e),
+    }
     
-    // Start cache maintenance service (clean up expired cache entries every 60 seconds)
-    cache_maintenance::start_cache_maintenance(60).await;
-    println!("Cache maintenance service started");
+    // Start cache maintenance service (clean up expired cache entries every CACHE_MAINTENANCE_SECONDS)
+    cache_maintenance::start_cache_maintenance(cache_maintenance::get_cache_maintenance_seconds()).await;
+    info!("Cache maintenance service started");
+
+    // Start local control listener (pause / resume / stop via `solana-mm`)
+    match control::start_control_listener().await {
+        Ok(_) => info!("Control listener started on {}", control::get_control_addr()),
+        Err(e) => warn!("Failed to start control listener: {}. Continuing without it.", e),
+    }
+
+    // Start the signed webhook for external signals (only when WEBHOOK_SECRET is set)
+    match webhook::start_webhook_listener().await {
+        Ok(true) => info!("Webhook listener started on {}", webhook::get_webhook_addr()),
+        Ok(false) => {},
+        Err(e) => warn!("Failed to start webhook listener: {}. Continuing without it.", e),
+    }
+
+    // Fetch pool state, vaults, AMM config and mints so the first trades hit warm caches
+    if let Err(e) = cache_maintenance::warm_up_pool_caches(&config).await {
+        warn!("Pool cache warm-up failed: {}. The first trades will fetch over RPC.", e);
+    }
+
+    // MODE=random_trader runs the simpler buy-then-sell engine instead of the market maker
+    if RunMode::from_env() == RunMode::RandomTrader {
+        run_random_trader(&config).await;
+        return;
+    }
 
     // Market maker mode - no need for target addresses
 
@@ -175,22 +265,104 @@ async fn main() {
     );
     
     // Start the advanced stealth market maker bot
-    println!("🚀 Starting Advanced Stealth Market Maker for mint: {}", config.target_token_mint);
-    println!("🎯 Using 100 wallets with sophisticated randomization");
-    println!("💰 Buy amount ratio: 50% - 90% of wrapped WSOL");
-    println!("🎲 70% Buy / 30% Sell ratio");
-    println!("🔄 Wallet rotation every 2 trades");
-    println!("⏰ Randomized intervals: 10 minutes - 2 hours");
-    println!("📊 Activity reports every 30 minutes");
-    println!("🎯 Buy: amount_in = WSOL lamports, minimum_amount_out = 0");
-    println!("🎯 Sell: amount_in = token balance, minimum_amount_out = 0");
+    info!("🚀 Starting Advanced Stealth Market Maker for mint: {}", config.target_token_mint);
+    let wallet_count = std::env::var("WALLET_COUNT")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(100);
+    info!("🎯 Using {} wallets", wallet_count);
+    match activity_journal::get_activity_report_minutes() {
+        0 => info!("📊 Activity reports off"),
+        minutes => info!("📊 Activity reports every {} minutes", minutes),
+    }
+    
+    // Ctrl+C / SIGTERM stop new trades and flush state instead of killing the process mid-trade
+    shutdown::spawn_signal_listener();
     
-    if let Err(e) = start_market_maker(market_maker_config).await {
-        eprintln!("Advanced Market Maker error: {}", e);
+    // Emergency stop: SIGUSR1, the KILL sentinel file, Telegram /kill or `solana-mm kill`
+    kill_switch::spawn_kill_switch_watchers();
+    tokio::spawn(async {
+        KILL_SWITCH.engaged().await;
+        // With liquidation the engine is stopped so inventory can be sold below
+        if KILL_SWITCH.should_liquidate() {
+            SHUTDOWN.request("kill switch");
+        }
+    });
+    
+    let result = run_market_maker(market_maker_config, &config).await;
+    
+    if SHUTDOWN.is_requested() {
+        let timeout = shutdown::get_shutdown_timeout() + std::time::Duration::from_secs(10);
+        if !SHUTDOWN.wait_completed(timeout).await {
+            error!("Shutdown sequence did not complete in time; state may not be fully flushed");
+        }
+    }
+    
+    if KILL_SWITCH.should_liquidate() {
+        info!("🚨 Kill switch liquidation: selling all inventory and collecting funds to the main wallet");
+        match collect_sol(&config).await {
+            Ok(_) => info!("✅ Inventory sold and funds collected"),
+            Err(e) => error!("❌ Kill switch liquidation failed: {}", e),
+        }
+    }
+    
+    if let Err(e) = result {
+        error!("Advanced Market Maker error: {}", e);
         
         // Send error notification via Telegram
         if let Err(te) = telegram::send_error_notification(&format!("Advanced Market Maker bot crashed: {}", e)).await {
-            eprintln!("Failed to send Telegram notification: {}", te);
+            error!("Failed to send Telegram notification: {}", te);
+        }
+    }
+}
+
+/// Build the market maker, reload the previous run's persisted state, and start its background
+/// tasks and trade queue, with queued trades executed by `MarketMaker::execute_trade`, then run
+/// until a shutdown is requested
+async fn run_market_maker(market_maker_config: MarketMakerConfig, config: &Config) -> anyhow::Result<()> {
+    let engine = Arc::new(MarketMaker::new(market_maker_config, config)?);
+    // Before any task starts, so the first checkpoint can't overwrite the state being resumed
+    engine.restore_state().await
+        .map_err(|e| anyhow::anyhow!("Failed to restore persisted state: {}", e))?;
+    engine.start_engine(MarketMaker::execute_trade).await;
+    SHUTDOWN.requested().await;
+    Ok(())
+}
+
+/// Run the random trader on the configured Raydium CPMM pool until Ctrl+C / SIGTERM
+async fn run_random_trader(config: &Config) {
+    if config.dex_type != DexType::RaydiumCPMM {
+        error!("MODE=random_trader trades through Raydium CPMM only; set DEX=0 and the POOL_* accounts");
+        return;
+    }
+    let trader = match RandomTrader::new(
+        Arc::new(config.app_state.clone()),
+        config.target_token_mint.clone(),
+        config.pool_id.clone(),
+        config.pool_base_account.clone(),
+        config.pool_quote_account.clone(),
+    ) {
+        Ok(trader) => Arc::new(trader),
+        Err(e) => {
+            error!("Failed to create random trader: {}", e);
+            return;
+        }
+    };
+
+    shutdown::spawn_signal_listener();
+    let stopper = trader.clone();
+    tokio::spawn(async move {
+        SHUTDOWN.requested().await;
+        stopper.stop().await;
+    });
+
+    let trader_config = RandomTraderConfig::from_env();
+    info!("🎲 Starting random trader for mint: {}", config.target_token_mint);
+    if let Err(e) = trader.start(trader_config).await {
+        error!("Random trader error: {}", e);
+        if let Err(te) = telegram::send_error_notification(&format!("Random trader crashed: {}", e)).await {
+            error!("Failed to send Telegram notification: {}", te);
         }
     }
+    SHUTDOWN.mark_completed();
 }
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::env;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use colored::Colorize;
+use lazy_static::lazy_static;
+use tokio::sync::oneshot;
+use crate::common::logger::Logger;
+use crate::services::telegram;
+
+/// Trades above this size need manual approval (APPROVAL_THRESHOLD_SOL, 0 = disabled)
+pub fn get_approval_threshold() -> f64 {
+    env::var("APPROVAL_THRESHOLD_SOL")
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .unwrap_or(0.0)
+}
+
+/// How long to wait for a decision before skipping the trade (APPROVAL_TIMEOUT_SECONDS)
+pub fn get_approval_timeout() -> Duration {
+    let secs = env::var("APPROVAL_TIMEOUT_SECONDS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(120);
+    Duration::from_secs(secs.max(1))
+}
+
+struct PendingApproval {
+    description: String,
+    decision: oneshot::Sender<bool>,
+}
+
+/// Trades waiting for an operator decision
+pub struct ApprovalQueue {
+    next_id: AtomicU64,
+    pending: Mutex<HashMap<u64, PendingApproval>>,
+    logger: Logger,
+}
+
+impl ApprovalQueue {
+    pub fn new() -> Self {
+        Self {
+            next_id: AtomicU64::new(1),
+            pending: Mutex::new(HashMap::new()),
+            logger: Logger::new("[APPROVAL] => ".yellow().bold().to_string()),
+        }
+    }
+
+    /// Ask the operator to approve a trade. Returns false if rejected or not answered in time.
+    pub async fn request(&self, description: &str) -> bool {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, PendingApproval {
+            description: description.to_string(),
+            decision: sender,
+        });
+
+        let timeout = get_approval_timeout();
+        let prompt = format!(
+            "✋ Approval #{} needed: {}\nReply /approve {} or /reject {} (or `solana-mm approve {}`) within {}s",
+            id, description, id, id, id, timeout.as_secs()
+        );
+        self.logger.log(prompt.clone().yellow().bold().to_string());
+        if let Err(e) = telegram::send_notification(&prompt).await {
+            self.logger.log(format!("Failed to send approval request: {}", e).red().to_string());
+        }
+
+        let approved = match tokio::time::timeout(timeout, receiver).await {
+            Ok(Ok(approved)) => approved,
+            _ => {
+                self.pending.lock().unwrap().remove(&id);
+                self.logger.log(format!("⌛ Approval #{} timed out, skipping trade", id).yellow().to_string());
+                false
+            }
+        };
+
+        if approved {
+            self.logger.log(format!("✅ Approval #{} granted", id).green().to_string());
+        }
+        approved
+    }
+
+    /// Record a decision. Returns false if there is no pending request with that id.
+    pub fn resolve(&self, id: u64, approved: bool) -> bool {
+        let pending = self.pending.lock().unwrap().remove(&id);
+        match pending {
+            Some(pending) => {
+                if !approved {
+                    self.logger.log(format!("❌ Approval #{} rejected ({})", id, pending.description).red().to_string());
+                }
+                pending.decision.send(approved).is_ok()
+            },
+            None => false,
+        }
+    }
+
+    /// Pending requests as (id, description)
+    pub fn list(&self) -> Vec<(u64, String)> {
+        let mut pending: Vec<(u64, String)> = self.pending.lock().unwrap()
+            .iter()
+            .map(|(id, p)| (*id, p.description.clone()))
+            .collect();
+        pending.sort_by_key(|(id, _)| *id);
+        pending
+    }
+}
+
+lazy_static! {
+    pub static ref APPROVALS: ApprovalQueue = ApprovalQueue::new();
+}
@@ -3,6 +3,7 @@ use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use solana_sdk::hash::Hash;
 use solana_client::rpc_client::RpcClient;
+use solana_client::nonblocking::rpc_client::RpcClient as NonblockingRpcClient;
 use anyhow::{Result, anyhow};
 use colored::Colorize;
 use lazy_static::lazy_static;
@@ -107,4 +108,18 @@ impl BlockhashProcessor {
         Self::update_blockhash(new_hash).await;
         Ok(new_hash)
     }
+
+    /// Cached blockhash while it is fresh, otherwise one from `rpc`, which then refreshes the
+    /// cache. Every transaction build goes through this instead of calling `get_latest_blockhash`
+    /// itself, saving an RPC round trip per trade while the processor is running.
+    pub async fn blockhash_or_fetch(rpc: &NonblockingRpcClient) -> Result<Hash> {
+        if let Some(hash) = Self::get_latest_blockhash().await {
+            return Ok(hash);
+        }
+        let new_hash = rpc.get_latest_blockhash()
+            .await
+            .map_err(|e| anyhow!("Failed to get blockhash from RPC: {}", e))?;
+        Self::update_blockhash(new_hash).await;
+        Ok(new_hash)
+    }
 } 
\ No newline at end of file
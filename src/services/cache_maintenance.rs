@@ -1,3 +1,137 @@
-pub async fn start_cache_maintenance(_interval_seconds: u64) {
-    println!("Cache maintenance service started (placeholder)");
-} 
\ No newline at end of file
+use std::str::FromStr;
+use anyhow::{anyhow, Result};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use colored::Colorize;
+use spl_token_2022::extension::StateWithExtensionsOwned;
+use spl_token_2022::state::Mint;
+use tokio::time::{Duration, Instant};
+use crate::common::cache::{cache_stats, clear_expired_caches, CacheStats, POOL_ACCOUNT_CACHE, TOKEN_MINT_CACHE};
+use crate::common::config::Config;
+use crate::common::logger::Logger;
+use crate::common::pool_state::{token_account_amount, POOL_STATE};
+use crate::dex::pump_fun;
+use crate::engine::market_maker::apply_bonding_curve_update;
+use crate::engine::monitor::PoolInfo;
+use crate::engine::transaction_parser::DexType;
+
+/// How often cumulative hit/miss counters are logged
+const STATS_LOG_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Seconds between cache maintenance runs (CACHE_MAINTENANCE_SECONDS, default 60)
+pub fn get_cache_maintenance_seconds() -> u64 {
+    std::env::var("CACHE_MAINTENANCE_SECONDS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60)
+}
+
+/// One cache's size and what it dropped since the previous run
+fn run_summary(stats: &CacheStats, previous: Option<&CacheStats>) -> String {
+    let (expired, evicted) = previous
+        .map(|p| (stats.expired - p.expired, stats.evicted - p.evicted))
+        .unwrap_or((stats.expired, stats.evicted));
+    format!("{} {} (-{} expired, -{} evicted)", stats.name, stats.size, expired, evicted)
+}
+
+/// Run pending cache evictions every `interval_seconds` and log each cache's size and
+/// evictions per run, plus hit/miss counters every five minutes, so TTLs can be tuned from
+/// what the caches actually see
+pub async fn start_cache_maintenance(interval_seconds: u64) {
+    let logger = Logger::new("[CACHE] => ".cyan().bold().to_string());
+    let interval = Duration::from_secs(interval_seconds.max(1));
+    tokio::spawn(async move {
+        let mut last_log = Instant::now();
+        let mut previous: Vec<CacheStats> = Vec::new();
+        loop {
+            tokio::time::sleep(interval).await;
+            clear_expired_caches();
+            let current = cache_stats();
+            let summary: Vec<String> = current
+                .iter()
+                .map(|stats| run_summary(stats, previous.iter().find(|p| p.name == stats.name)))
+                .collect();
+            logger.log(format!("🧹 {}", summary.join(" | ")));
+            if last_log.elapsed() >= STATS_LOG_INTERVAL {
+                last_log = Instant::now();
+                for stats in &current {
+                    logger.log(format!("📊 {}", stats.to_line()));
+                }
+            }
+            previous = current;
+        }
+    });
+}
+
+/// The AMM config a CPMM pool state points at (right after the 8-byte discriminator)
+const CPMM_AMM_CONFIG_OFFSET: usize = 8;
+
+/// AMM configs only change by governance, so they are kept longer than pool state
+const AMM_CONFIG_TTL_SECONDS: u64 = 3_600;
+
+/// Fetch the configured pool's accounts in one batch before trading starts: the CPMM pool
+/// state, its AMM config and vault reserves (or the pump.fun bonding curve), and both mints.
+/// The first trades then read warm caches like every later one. Returns the accounts cached.
+pub async fn warm_up_pool_caches(config: &Config) -> Result<usize> {
+    let logger = Logger::new("[CACHE] => ".cyan().bold().to_string());
+    let rpc = &config.app_state.rpc_nonblocking_client;
+    let mint = Pubkey::from_str(&config.target_token_mint)?;
+    let coin_creator = Pubkey::from_str(&config.coin_creator).unwrap_or_default();
+    let quote_mint = spl_token::native_mint::ID;
+
+    let pool_accounts = match config.dex_type {
+        DexType::PumpFun => vec![pump_fun::bonding_curve_address(&mint)?],
+        _ => [&config.pool_id, &config.pool_base_account, &config.pool_quote_account]
+            .iter()
+            .map(|v| Pubkey::from_str(v).map_err(|_| anyhow!("POOL_ID, POOL_BASE_ACCOUNT and POOL_QUOTE_ACCOUNT must be set")))
+            .collect::<Result<Vec<_>>>()?,
+    };
+    let mut addresses = pool_accounts.clone();
+    addresses.extend([mint, quote_mint]);
+    let accounts = rpc.get_multiple_accounts(&addresses).await?;
+
+    let mut cached = 0;
+    for (address, account) in addresses.iter().zip(&accounts) {
+        let Some(account) = account else { continue };
+        if *address == mint || *address == quote_mint {
+            if let Ok(state) = StateWithExtensionsOwned::<Mint>::unpack(account.data.clone()) {
+                TOKEN_MINT_CACHE.insert(*address, state, None);
+                cached += 1;
+            }
+        }
+    }
+
+    if config.dex_type == DexType::PumpFun {
+        if let Some(Some(curve)) = accounts.first() {
+            apply_bonding_curve_update(pool_accounts[0], mint, &curve.data);
+            cached += 1;
+        }
+    } else {
+        let pool_id = pool_accounts[0];
+        let reserve = |index: usize| accounts[index].as_ref().and_then(|a| token_account_amount(&a.data));
+        let (base_reserve, quote_reserve) = (reserve(1), reserve(2));
+        cached += base_reserve.iter().chain(&quote_reserve).count();
+        POOL_STATE.set_pool(PoolInfo {
+            pool_id,
+            base_mint: mint,
+            quote_mint,
+            base_reserve: base_reserve.unwrap_or_default(),
+            quote_reserve: quote_reserve.unwrap_or_default(),
+            coin_creator,
+        });
+        if let Some(Some(pool)) = accounts.first() {
+            POOL_ACCOUNT_CACHE.insert(pool_id, pool.data.clone(), None);
+            cached += 1;
+            let amm_config = pool.data.get(CPMM_AMM_CONFIG_OFFSET..CPMM_AMM_CONFIG_OFFSET + 32)
+                .and_then(|b| Pubkey::try_from(b).ok());
+            if let Some(amm_config) = amm_config {
+                if let Ok(account) = rpc.get_account(&amm_config).await {
+                    POOL_ACCOUNT_CACHE.insert(amm_config, account.data, Some(AMM_CONFIG_TTL_SECONDS));
+                    cached += 1;
+                }
+            }
+        }
+    }
+
+    logger.log(format!("🔥 Warmed {} pool accounts for {}", cached, mint).green().to_string());
+    Ok(cached)
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use anyhow::Result;
+use colored::Colorize;
+use crate::common::{
+    config_file::{config_file_paths, is_externally_overridden, read_config_values},
+    logger::Logger,
+};
+use crate::engine::runtime_params::RuntimeParams;
+
+/// Environment variables that are reloaded while running, with the runtime parameter each one
+/// sets. Only limits the queued trade path reads on every trade are listed; anything else
+/// (guardian, buy ratio, volume wave settings, ...) takes effect on the next start.
+pub const HOT_RELOADABLE_KEYS: &[(&str, &str)] = &[
+    ("SLIPPAGE", "slippage_bps"),
+    ("DAILY_SOL_SPEND_CAP", "daily_spend_cap_sol"),
+    ("MAX_TRADES_PER_HOUR", "max_trades_per_hour"),
+    ("MAX_TRADES_PER_WALLET_PER_DAY", "max_trades_per_wallet_per_day"),
+];
+
+fn is_hot_reloadable(env_name: &str) -> bool {
+    HOT_RELOADABLE_KEYS.iter().any(|(key, _)| *key == env_name)
+}
+
+fn get_reload_interval() -> Duration {
+    let secs = env::var("CONFIG_RELOAD_INTERVAL_SECONDS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(5);
+    Duration::from_secs(secs.max(1))
+}
+
+fn is_hot_reload_enabled() -> bool {
+    env::var("CONFIG_HOT_RELOAD")
+        .ok()
+        .map(|v| v.trim().to_lowercase() != "false")
+        .unwrap_or(true)
+}
+
+/// Read the hot-reloadable values currently in the config file
+fn read_tunables() -> Result<HashMap<&'static str, String>> {
+    let (values, _) = read_config_values()?;
+    Ok(values
+        .into_iter()
+        .filter(|(env_name, _)| is_hot_reloadable(env_name))
+        .filter(|(env_name, _)| !is_externally_overridden(env_name))
+        .collect())
+}
+
+/// Apply the tunables that changed in the file to the running engine. Keys whose file value
+/// did not change are left alone, so values set at runtime (e.g. `solana-mm set`) survive
+/// an unrelated edit. Changes go through the same `RuntimeParams` the control socket uses,
+/// so they are validated together, applied together or not at all, and audited alike.
+async fn apply_changes(
+    params: &RuntimeParams,
+    previous: &HashMap<&'static str, String>,
+    current: &HashMap<&'static str, String>,
+) -> Result<usize> {
+    let updates: Vec<(&str, &str)> = HOT_RELOADABLE_KEYS
+        .iter()
+        .filter(|(key, _)| previous.get(key) != current.get(key))
+        .filter_map(|(key, param)| current.get(key).map(|value| (*param, value.as_str())))
+        .collect();
+
+    if updates.is_empty() {
+        return Ok(0);
+    }
+    params.update(&updates, "config reload").await?;
+    Ok(updates.len())
+}
+
+fn modified_times(paths: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    paths.iter()
+        .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// Watch the config file(s) and apply the hot-reloadable limits to the running engine
+pub fn spawn_config_watcher(params: Arc<RuntimeParams>) {
+    let paths = config_file_paths();
+    if !is_hot_reload_enabled() || paths.is_empty() {
+        return;
+    }
+
+    let logger = Logger::new("[CONFIG-RELOAD] => ".cyan().bold().to_string());
+    let names: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+    logger.log(format!("👀 Watching {} for changes", names.join(", ")).cyan().to_string());
+
+    tokio::spawn(async move {
+        let mut last_modified = modified_times(&paths);
+        let mut applied = read_tunables().unwrap_or_default();
+        let mut ticker = tokio::time::interval(get_reload_interval());
+
+        loop {
+            ticker.tick().await;
+
+            let modified = modified_times(&paths);
+            if modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+
+            let current = match read_tunables() {
+                Ok(values) => values,
+                Err(e) => {
+                    logger.log(format!("❌ Ignoring config change: {}", e).red().to_string());
+                    continue;
+                }
+            };
+
+            match apply_changes(&params, &applied, &current).await {
+                Ok(0) => {
+                    logger.log("Config file changed, no hot-reloadable values differ".to_string());
+                },
+                Ok(count) => {
+                    logger.log(format!("✅ Applied {} config change(s)", count).green().to_string());
+                    applied = current;
+                },
+                Err(e) => {
+                    logger.log(format!("❌ Rejected config change: {}", e).red().to_string());
+                },
+            }
+        }
+    });
+}
@@ -0,0 +1,401 @@
+use std::env;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use lazy_static::lazy_static;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use crate::common::cache::{cache_stats, flush_caches};
+use crate::common::logger::Logger;
+use crate::common::persistence::state_dir;
+use crate::engine::runtime_params::get_runtime_params;
+use crate::services::approval::APPROVALS;
+use crate::services::kill_switch::KILL_SWITCH;
+use crate::services::shutdown::SHUTDOWN;
+
+/// Address of the local control listener (CONTROL_ADDR, loopback only)
+pub fn get_control_addr() -> String {
+    env::var("CONTROL_ADDR")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| "127.0.0.1:7878".to_string())
+}
+
+/// Bytes read from one control connection before it is dropped
+const MAX_CONNECTION_BYTES: u64 = 64 * 1024;
+/// A client has this long to send its `auth` line
+const AUTH_TIMEOUT: Duration = Duration::from_secs(5);
+/// Pause after a failed accept, so a persistent error (e.g. out of file descriptors) can't spin
+const ACCEPT_RETRY: Duration = Duration::from_secs(1);
+
+/// Token file written on first start when CONTROL_TOKEN is not set
+pub fn control_token_path() -> PathBuf {
+    state_dir().join("control.token")
+}
+
+fn env_control_token() -> Option<String> {
+    env::var("CONTROL_TOKEN").ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+/// Token clients must present (CONTROL_TOKEN, or the token file). Used by the `solana-mm` CLI.
+pub fn load_control_token() -> Result<String> {
+    if let Some(token) = env_control_token() {
+        return Ok(token);
+    }
+    let path = control_token_path();
+    fs::read_to_string(&path)
+        .map(|token| token.trim().to_string())
+        .ok()
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| anyhow!("No CONTROL_TOKEN set and {} not found; start the bot first or set CONTROL_TOKEN", path.display()))
+}
+
+/// Token for the listener: CONTROL_TOKEN or the token file, generated with 0600 permissions if missing
+fn ensure_control_token() -> Result<String> {
+    if let Ok(token) = load_control_token() {
+        return Ok(token);
+    }
+    let token: String = rand::thread_rng().sample_iter(&Alphanumeric).take(48).map(char::from).collect();
+    let path = control_token_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    write_private(&path, &token)
+        .map_err(|e| anyhow!("Failed to write control token to {}: {}", path.display(), e))?;
+    CONTROL.logger.log(format!("🔑 Generated control token in {}", path.display()).magenta().to_string());
+    Ok(token)
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let mut file = fs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?;
+    // `mode` only applies on creation; tighten a file that already existed
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(contents.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    fs::write(path, contents)
+}
+
+/// Compare without returning early, so response timing doesn't leak the token
+fn tokens_match(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected.bytes().zip(given.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// A browser can be made to send a cross-protocol POST to a loopback port; its request line
+/// and headers are never control commands, so such connections are dropped unanswered
+fn looks_like_http(line: &str) -> bool {
+    const METHODS: [&str; 9] = ["GET", "POST", "PUT", "DELETE", "HEAD", "OPTIONS", "PATCH", "CONNECT", "TRACE"];
+    let first = line.split_whitespace().next().unwrap_or("");
+    METHODS.contains(&first) || line.contains(" HTTP/") || first.ends_with(':')
+}
+
+/// Pause state shared by the control listener and the trading engines
+pub struct ControlState {
+    paused: RwLock<Option<String>>,
+    // Risk stop set by the drawdown guard; a resume doesn't lift it, only `reset-drawdown`
+    drawdown_halt: RwLock<Option<String>>,
+    logger: Logger,
+}
+
+impl ControlState {
+    pub fn new() -> Self {
+        Self {
+            paused: RwLock::new(None),
+            drawdown_halt: RwLock::new(None),
+            logger: Logger::new("[CONTROL] => ".magenta().bold().to_string()),
+        }
+    }
+
+    /// Pause new trades. In-flight trades are allowed to finish.
+    pub fn pause(&self, reason: &str) {
+        let mut paused = self.paused.write().unwrap();
+        if paused.is_none() {
+            self.logger.log(format!("⏸️ Trading paused ({})", reason).yellow().bold().to_string());
+        }
+        *paused = Some(reason.to_string());
+    }
+
+    /// Lift an operator pause. Returns false if trading was not paused. A drawdown halt stays.
+    pub fn resume(&self) -> bool {
+        let mut paused = self.paused.write().unwrap();
+        if paused.take().is_some() {
+            self.logger.log("▶️ Trading resumed".green().bold().to_string());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Halt new trades after the drawdown guard tripped, until `reset_drawdown`
+    pub fn halt_for_drawdown(&self, reason: &str) {
+        let mut halt = self.drawdown_halt.write().unwrap();
+        if halt.is_none() {
+            self.logger.log(format!("⏸️ Trading halted by the drawdown guard ({})", reason).red().bold().to_string());
+        }
+        *halt = Some(reason.to_string());
+    }
+
+    /// Lift the drawdown halt. Returns false if the guard had not tripped.
+    pub fn reset_drawdown(&self) -> bool {
+        let mut halt = self.drawdown_halt.write().unwrap();
+        if halt.take().is_some() {
+            self.logger.log("▶️ Drawdown halt reset".green().bold().to_string());
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn is_drawdown_halted(&self) -> bool {
+        self.drawdown_halt.read().unwrap().is_some()
+    }
+
+    /// True while an operator pause or a drawdown halt holds new trades
+    pub fn is_paused(&self) -> bool {
+        self.paused.read().unwrap().is_some() || self.is_drawdown_halted()
+    }
+
+    pub fn pause_reason(&self) -> Option<String> {
+        let paused = self.paused.read().unwrap().clone();
+        let halt = self.drawdown_halt.read().unwrap().clone();
+        match (paused, halt) {
+            (Some(paused), Some(halt)) => Some(format!("{}; {}", halt, paused)),
+            (paused, halt) => halt.or(paused),
+        }
+    }
+}
+
+lazy_static! {
+    pub static ref CONTROL: ControlState = ControlState::new();
+}
+
+/// Execute a single control command and return the response line
+pub async fn handle_command(command: &str) -> String {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    let name = parts.first().map(|p| p.to_lowercase()).unwrap_or_default();
+
+    match name.as_str() {
+        "pause" => {
+            CONTROL.pause("control socket");
+            "ok paused".to_string()
+        },
+        "resume" => {
+            if KILL_SWITCH.is_engaged() {
+                "error kill switch engaged, restart the bot to resume".to_string()
+            } else {
+                let resumed = CONTROL.resume();
+                match (resumed, CONTROL.is_drawdown_halted()) {
+                    (_, true) => "error halted by the drawdown guard, send reset-drawdown to lift it".to_string(),
+                    (true, false) => "ok resumed".to_string(),
+                    (false, false) => "ok not paused".to_string(),
+                }
+            }
+        },
+        "reset-drawdown" => {
+            if KILL_SWITCH.is_engaged() {
+                "error kill switch engaged, restart the bot to resume".to_string()
+            } else if CONTROL.reset_drawdown() {
+                "ok drawdown halt reset".to_string()
+            } else {
+                "ok drawdown guard not tripped".to_string()
+            }
+        },
+        "stop" => {
+            SHUTDOWN.request("control socket");
+            "ok stopping".to_string()
+        },
+        "kill" => {
+            if KILL_SWITCH.engage("control socket") {
+                "ok kill switch engaged".to_string()
+            } else {
+                "ok kill switch already engaged".to_string()
+            }
+        },
+        "approve" | "reject" => {
+            let approved = name == "approve";
+            match parts.get(1).and_then(|v| v.parse::<u64>().ok()) {
+                Some(id) if APPROVALS.resolve(id, approved) => format!("ok #{} {}", id, if approved { "approved" } else { "rejected" }),
+                Some(id) => format!("error no pending approval #{}", id),
+                None => match APPROVALS.list().as_slice() {
+                    [] => "ok no pending approvals".to_string(),
+                    pending => format!(
+                        "ok pending: {}",
+                        pending.iter().map(|(id, d)| format!("#{} {}", id, d)).collect::<Vec<_>>().join(" | ")
+                    ),
+                },
+            }
+        },
+        "status" => {
+            let state = match CONTROL.pause_reason() {
+                Some(reason) => format!("paused ({})", reason),
+                None if SHUTDOWN.is_requested() => "stopping".to_string(),
+                None => "running".to_string(),
+            };
+            format!("ok {} | in-flight: {}", state, SHUTDOWN.in_flight())
+        },
+        "cache" => match parts.get(1).map(|p| p.to_lowercase()).as_deref() {
+            None => format!(
+                "ok {}",
+                cache_stats().iter().map(|stats| stats.to_line()).collect::<Vec<_>>().join(" | ")
+            ),
+            Some("flush") => format!("ok flushed {} cache entries", flush_caches()),
+            Some(other) => format!("error unknown cache command '{}' (expected flush)", other),
+        },
+        "params" => match get_runtime_params() {
+            Some(params) => format!("ok {}", params.snapshot().await.to_line()),
+            None => "error market maker not running".to_string(),
+        },
+        "set" => {
+            let params = match get_runtime_params() {
+                Some(params) => params,
+                None => return "error market maker not running".to_string(),
+            };
+            let args = &parts[1..];
+            if args.is_empty() || args.len() % 2 != 0 {
+                return "error usage: set <key> <value> [<key> <value> ...]".to_string();
+            }
+            let changes: Vec<(&str, &str)> = args.chunks(2).map(|pair| (pair[0], pair[1])).collect();
+            match params.update(&changes, "control socket").await {
+                Ok(applied) if applied.is_empty() => "ok no changes".to_string(),
+                Ok(applied) => format!(
+                    "ok {}",
+                    applied.iter().map(|c| format!("{}={}", c.key, c.new_value)).collect::<Vec<_>>().join(" ")
+                ),
+                Err(e) => format!("error {}", e),
+            }
+        },
+        "audit" => match get_runtime_params() {
+            Some(params) => {
+                let limit = parts.get(1).and_then(|v| v.parse().ok()).unwrap_or(10);
+                let entries: Vec<String> = params.audit_log(limit)
+                    .iter()
+                    .map(|c| format!("{} {} {} {}->{}", c.at, c.source.replace(' ', "_"), c.key, c.old_value, c.new_value))
+                    .collect();
+                format!("ok {} change(s) | {}", entries.len(), entries.join(" | "))
+            },
+            None => "error market maker not running".to_string(),
+        },
+        other => format!("error unknown command '{}' (expected pause, resume, reset-drawdown, stop, kill, approve, reject, status, cache, params, set, audit)", other),
+    }
+}
+
+/// Start the control listener on the loopback interface. Every connection must open with
+/// `auth <token>`; see `load_control_token`.
+pub async fn start_control_listener() -> Result<()> {
+    let addr: SocketAddr = get_control_addr()
+        .parse()
+        .map_err(|e| anyhow!("Invalid CONTROL_ADDR: {}", e))?;
+
+    if !addr.ip().is_loopback() {
+        return Err(anyhow!("CONTROL_ADDR must be a loopback address, got {}", addr));
+    }
+
+    let token = Arc::new(ensure_control_token()?);
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| anyhow!("Failed to bind control listener on {}: {}", addr, e))?;
+
+    CONTROL.logger.log(format!("🎛️ Control listener on {}", addr).magenta().to_string());
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let token = token.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(stream, &token).await {
+                            CONTROL.logger.log(format!("Control connection error: {}", e).red().to_string());
+                        }
+                    });
+                },
+                Err(e) => {
+                    CONTROL.logger.log(format!("Control accept error: {}", e).red().to_string());
+                    tokio::time::sleep(ACCEPT_RETRY).await;
+                },
+            }
+        }
+    });
+
+    Ok(())
+}
+
+async fn handle_connection(stream: TcpStream, token: &str) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader.take(MAX_CONNECTION_BYTES)).lines();
+
+    let first = match tokio::time::timeout(AUTH_TIMEOUT, lines.next_line()).await {
+        Ok(line) => line?.unwrap_or_default(),
+        Err(_) => return Ok(()),
+    };
+    if looks_like_http(&first) {
+        CONTROL.logger.log("Dropped an HTTP request sent to the control listener".yellow().to_string());
+        return Ok(());
+    }
+    let given = first.trim().strip_prefix("auth ").map(str::trim).unwrap_or("");
+    if !tokens_match(token, given) {
+        CONTROL.logger.log("Rejected a control connection with a missing or wrong token".yellow().to_string());
+        writer.write_all(b"error unauthorized\n").await?;
+        return Ok(());
+    }
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if looks_like_http(&line) {
+            return Ok(());
+        }
+        let response = handle_command(&line).await;
+        CONTROL.logger.log(format!("Command '{}' -> {}", line.trim(), response));
+        writer.write_all(format!("{}\n", response).as_bytes()).await?;
+    }
+
+    Ok(())
+}
+
+/// Send a command to a running instance and return its response (used by the `solana-mm` CLI)
+pub async fn send_command(addr: &str, token: &str, command: &str) -> Result<String> {
+    let stream = TcpStream::connect(addr)
+        .await
+        .map_err(|e| anyhow!("Could not connect to running instance at {}: {}", addr, e))?;
+    let (reader, mut writer) = stream.into_split();
+
+    writer.write_all(format!("auth {}\n{}\n", token, command).as_bytes()).await?;
+    writer.shutdown().await?;
+
+    let mut lines = BufReader::new(reader).lines();
+    lines.next_line()
+        .await?
+        .ok_or_else(|| anyhow!("Connection closed without a response"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resume_leaves_a_drawdown_halt_in_place() {
+        let control = ControlState::new();
+        control.halt_for_drawdown("drawdown 12.00% from session high");
+        control.pause("control socket");
+        assert!(control.resume());
+        assert!(control.is_paused());
+        assert_eq!(control.pause_reason().as_deref(), Some("drawdown 12.00% from session high"));
+
+        assert!(control.reset_drawdown());
+        assert!(!control.is_paused());
+        assert!(!control.reset_drawdown());
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use anyhow::{anyhow, Result};
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_request::TokenAccountsFilter;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::{Keypair, Signature};
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::solana_sdk::transaction::Transaction;
+use colored::Colorize;
+use solana_account_decoder::UiAccountData;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use crate::common::cache::WALLET_TOKEN_ACCOUNTS;
+use crate::common::config::{Config, SwapConfig};
+use crate::common::logger::Logger;
+use crate::common::persistence::StateStore;
+use crate::dex::raydium_cpmm::RaydiumCPMM;
+use crate::services::blockhash_processor::BlockhashProcessor;
+use crate::engine::swap::{SwapDirection, SwapInType};
+use crate::engine::transaction_parser::DexType;
+
+/// Directory generated wallets are stored in (one base58 key per file)
+const WALLET_DIR: &str = "wallet";
+
+/// Holdings worth less than this are treated as dust (DUST_THRESHOLD_SOL)
+pub fn get_dust_threshold() -> f64 {
+    std::env::var("DUST_THRESHOLD_SOL")
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .unwrap_or(0.001)
+}
+
+/// One token balance held by a pool wallet
+#[derive(Debug, Clone)]
+pub struct Holding {
+    pub wallet: Pubkey,
+    pub mint: String,
+    pub ui_amount: f64,
+}
+
+/// Load every generated pool wallet from ./wallet
+pub fn load_pool_wallets() -> Result<Vec<Arc<Keypair>>> {
+    let dir = Path::new(WALLET_DIR);
+    if !dir.exists() {
+        return Err(anyhow!("No ./{} directory found; generate wallets with --wallet first", WALLET_DIR));
+    }
+
+    let mut wallets = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let key = fs::read_to_string(&path)?.trim().to_string();
+        if key.len() < 85 {
+            continue;
+        }
+        wallets.push(Arc::new(Keypair::from_base58_string(&key)));
+    }
+    Ok(wallets)
+}
+
+/// State store key for the token accounts known to exist for the pool wallets
+pub const WALLET_TOKEN_ACCOUNTS_STATE_KEY: &str = "wallet_token_accounts";
+
+/// Addresses per getMultipleAccounts call (the RPC limit)
+const ACCOUNTS_PER_BATCH: usize = 100;
+
+/// Save WALLET_TOKEN_ACCOUNTS to the state store
+pub fn save_wallet_token_accounts(store: &StateStore) -> Result<()> {
+    let accounts: Vec<String> = WALLET_TOKEN_ACCOUNTS.get_all().iter().map(|a| a.to_string()).collect();
+    store.save(WALLET_TOKEN_ACCOUNTS_STATE_KEY, &accounts)
+}
+
+/// Record which of the wallets' target-token and WSOL ATAs exist in WALLET_TOKEN_ACCOUNTS, so
+/// swaps can leave out the idempotent create instruction. The saved set is loaded first and
+/// stays in use if the scan fails; otherwise every address is re-checked in batches (accounts
+/// closed since the last run are dropped) and the result is saved. Returns the known accounts.
+pub async fn discover_wallet_token_accounts(rpc: &RpcClient, wallets: &[Pubkey], mint: &Pubkey) -> Result<usize> {
+    let store = StateStore::from_env();
+    if let Some(saved) = store.load::<Vec<String>>(WALLET_TOKEN_ACCOUNTS_STATE_KEY)? {
+        for account in saved.iter().filter_map(|a| Pubkey::from_str(a).ok()) {
+            WALLET_TOKEN_ACCOUNTS.insert(account);
+        }
+    }
+
+    let token_program = rpc.get_account(mint).await?.owner;
+    let candidates: Vec<Pubkey> = wallets
+        .iter()
+        .flat_map(|wallet| [
+            get_associated_token_address_with_program_id(wallet, mint, &token_program),
+            get_associated_token_address_with_program_id(wallet, &spl_token::native_mint::ID, &spl_token::id()),
+        ])
+        .collect();
+    for batch in candidates.chunks(ACCOUNTS_PER_BATCH) {
+        let accounts = rpc.get_multiple_accounts(batch).await?;
+        for (address, account) in batch.iter().zip(accounts) {
+            if account.is_some() {
+                WALLET_TOKEN_ACCOUNTS.insert(*address);
+            } else {
+                WALLET_TOKEN_ACCOUNTS.remove(address);
+            }
+        }
+    }
+
+    save_wallet_token_accounts(&store)?;
+    Ok(WALLET_TOKEN_ACCOUNTS.size())
+}
+
+/// Non-zero SPL and Token-2022 balances for each wallet
+pub async fn scan_holdings(rpc: &RpcClient, wallets: &[Pubkey]) -> Result<Vec<Holding>> {
+    let mut holdings = Vec::new();
+    for wallet in wallets {
+        for program in [spl_token::id(), spl_token_2022::id()] {
+            let accounts = rpc.get_token_accounts_by_owner(wallet, TokenAccountsFilter::ProgramId(program)).await?;
+            for keyed in accounts {
+                let UiAccountData::Json(parsed) = keyed.account.data else { continue };
+                let info = &parsed.parsed["info"];
+                let mint = info["mint"].as_str().unwrap_or_default().to_string();
+                let ui_amount = info["tokenAmount"]["uiAmount"].as_f64().unwrap_or(0.0);
+                if ui_amount > 0.0 && !mint.is_empty() {
+                    holdings.push(Holding { wallet: *wallet, mint, ui_amount });
+                }
+            }
+        }
+    }
+    Ok(holdings)
+}
+
+/// Target token price in SOL from the configured venue: the Raydium pool vaults, or the
+/// PumpFun bonding curve quote
+pub async fn target_token_price(config: &Config) -> Result<f64> {
+    let rpc = config.app_state.rpc_nonblocking_client.clone();
+    match config.dex_type {
+        DexType::PumpFun => {
+            let pump = crate::dex::pump_fun::Pump::new(rpc.clone(), config.app_state.rpc_client.clone(), config.app_state.wallet.clone());
+            let mint = Pubkey::from_str(&config.target_token_mint)?;
+            let decimals = rpc.get_token_supply(&mint).await?.decimals;
+            let lamports = pump.get_quote(&config.target_token_mint, &spl_token::native_mint::ID.to_string(), 10u64.pow(decimals as u32)).await?;
+            Ok(lamports as f64 / 1_000_000_000.0)
+        },
+        DexType::RaydiumCPMM | DexType::RaydiumLaunchpad => {
+            let base = rpc.get_token_account_balance(&Pubkey::from_str(&config.pool_base_account)?).await?;
+            let quote = rpc.get_token_account_balance(&Pubkey::from_str(&config.pool_quote_account)?).await?;
+            let base = base.ui_amount.unwrap_or(0.0);
+            let quote = quote.ui_amount.unwrap_or(0.0);
+            if base <= 0.0 {
+                return Err(anyhow!("Pool base reserve is empty"));
+            }
+            Ok(quote / base)
+        },
+    }
+}
+
+/// Sell a wallet's whole target-token balance through the configured Raydium CPMM pool
+async fn sell_all(config: &Config, wallet: Arc<Keypair>) -> Result<Signature> {
+    let raydium = RaydiumCPMM::new(
+        wallet,
+        Some(config.app_state.rpc_client.clone()),
+        Some(config.app_state.rpc_nonblocking_client.clone()),
+        config.pool_id.clone(),
+        config.pool_base_account.clone(),
+        config.pool_quote_account.clone(),
+    ).map_err(|e| anyhow!("Failed to create RaydiumCPMM instance: {}", e))?;
+
+    let (keypair, instructions, _) = raydium.build_swap_from_default_info(SwapConfig {
+        mint: config.target_token_mint.clone(),
+        swap_direction: SwapDirection::Sell,
+        in_type: SwapInType::Pct,
+        amount_in: 1.0,
+        slippage: 1000,
+        max_buy_amount: 0.0,
+    }).await?;
+
+    let recent_blockhash = BlockhashProcessor::blockhash_or_fetch(&config.app_state.rpc_nonblocking_client).await?;
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&keypair.pubkey()),
+        &[keypair.as_ref()],
+        recent_blockhash,
+    );
+    Ok(config.app_state.rpc_nonblocking_client.send_and_confirm_transaction(&transaction).await?)
+}
+
+/// `--check-tokens`: print every pool wallet's token holdings with SOL value, and with
+/// `sell_dust` sell target-token balances worth less than DUST_THRESHOLD_SOL
+pub async fn check_tokens(config: &Config, sell_dust: bool) -> Result<()> {
+    let logger = Logger::new("[CHECK-TOKENS] => ".cyan().bold().to_string());
+    let wallets = load_pool_wallets()?;
+    let pubkeys: Vec<Pubkey> = wallets.iter().map(|w| w.pubkey()).collect();
+    logger.log(format!("🔍 Scanning {} wallet(s) for token holdings...", pubkeys.len()));
+
+    let holdings = scan_holdings(&config.app_state.rpc_nonblocking_client, &pubkeys).await?;
+    let price = match target_token_price(config).await {
+        Ok(price) => Some(price),
+        Err(e) => {
+            logger.log(format!("Could not price {}: {}", config.target_token_mint, e).yellow().to_string());
+            None
+        }
+    };
+    let value_of = |h: &Holding| (h.mint == config.target_token_mint).then_some(price).flatten().map(|p| p * h.ui_amount);
+
+    println!("{:<46} {:<46} {:>20} {:>14}", "WALLET", "MINT", "AMOUNT", "VALUE (SOL)");
+    for holding in &holdings {
+        let value = value_of(holding).map(|v| format!("{:.6}", v)).unwrap_or_else(|| "-".to_string());
+        println!("{:<46} {:<46} {:>20.4} {:>14}", holding.wallet, holding.mint, holding.ui_amount, value);
+    }
+
+    let mut totals: BTreeMap<&str, (f64, usize)> = BTreeMap::new();
+    for holding in &holdings {
+        let total = totals.entry(holding.mint.as_str()).or_default();
+        total.0 += holding.ui_amount;
+        total.1 += 1;
+    }
+    println!();
+    println!("{:<46} {:>20} {:>8} {:>14}", "MINT", "TOTAL", "WALLETS", "VALUE (SOL)");
+    for (mint, (amount, count)) in &totals {
+        let value = (*mint == config.target_token_mint)
+            .then_some(price)
+            .flatten()
+            .map(|p| format!("{:.6}", p * amount))
+            .unwrap_or_else(|| "-".to_string());
+        println!("{:<46} {:>20.4} {:>8} {:>14}", mint, amount, count, value);
+    }
+
+    if !sell_dust {
+        return Ok(());
+    }
+    if config.dex_type != DexType::RaydiumCPMM {
+        logger.log("Dust selling is only supported on Raydium CPMM; use --collect to sell everything".yellow().to_string());
+        return Ok(());
+    }
+
+    let threshold = get_dust_threshold();
+    let dust: Vec<&Holding> = holdings.iter()
+        .filter(|h| value_of(h).map(|v| v < threshold).unwrap_or(false))
+        .collect();
+    logger.log(format!("🧹 Selling {} dust balance(s) worth less than {} SOL", dust.len(), threshold).cyan().to_string());
+    for holding in dust {
+        let Some(wallet) = wallets.iter().find(|w| w.pubkey() == holding.wallet).cloned() else { continue };
+        match sell_all(config, wallet).await {
+            Ok(signature) => {
+                logger.log(format!("✅ Sold dust from {}: {}", holding.wallet, signature).green().to_string());
+            },
+            Err(e) => {
+                logger.log(format!("❌ Failed to sell dust from {}: {}", holding.wallet, e).red().to_string());
+            },
+        }
+    }
+    Ok(())
+}
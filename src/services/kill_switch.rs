@@ -0,0 +1,126 @@
+use std::env;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use colored::Colorize;
+use lazy_static::lazy_static;
+use tokio_util::sync::CancellationToken;
+use crate::common::logger::Logger;
+use crate::services::control::CONTROL;
+
+/// Sentinel file that engages the kill switch when it appears (KILL_SWITCH_FILE, defaults to ./KILL)
+pub fn get_kill_switch_file() -> PathBuf {
+    env::var("KILL_SWITCH_FILE")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("KILL"))
+}
+
+/// Whether an engaged kill switch also sells all inventory and collects funds (KILL_SWITCH_LIQUIDATE)
+pub fn is_liquidation_enabled() -> bool {
+    env::var("KILL_SWITCH_LIQUIDATE")
+        .ok()
+        .map(|v| v.trim().to_lowercase() == "true")
+        .unwrap_or(false)
+}
+
+/// Emergency stop. Once engaged it stays engaged until the process restarts.
+pub struct KillSwitch {
+    engaged: CancellationToken,
+    reason: RwLock<Option<String>>,
+    // Liquidate even when KILL_SWITCH_LIQUIDATE is off (emergency exits)
+    force_liquidation: AtomicBool,
+    logger: Logger,
+}
+
+impl KillSwitch {
+    pub fn new() -> Self {
+        Self {
+            engaged: CancellationToken::new(),
+            reason: RwLock::new(None),
+            force_liquidation: AtomicBool::new(false),
+            logger: Logger::new("[KILL-SWITCH] => ".red().bold().to_string()),
+        }
+    }
+
+    /// Halt all new trades immediately. Returns false if it was already engaged.
+    pub fn engage(&self, reason: &str) -> bool {
+        let mut current = self.reason.write().unwrap();
+        if current.is_some() {
+            return false;
+        }
+        *current = Some(reason.to_string());
+        drop(current);
+
+        self.logger.log(format!("🚨 Kill switch engaged ({}), halting all new trades", reason).red().bold().to_string());
+        CONTROL.pause(&format!("kill switch: {}", reason));
+        self.engaged.cancel();
+        true
+    }
+
+    /// Engage and always sell all inventory and collect funds, regardless of KILL_SWITCH_LIQUIDATE
+    pub fn engage_with_liquidation(&self, reason: &str) -> bool {
+        self.force_liquidation.store(true, Ordering::SeqCst);
+        self.engage(reason)
+    }
+
+    /// Whether the engaged kill switch should be followed by a full liquidation
+    pub fn should_liquidate(&self) -> bool {
+        self.is_engaged() && (self.force_liquidation.load(Ordering::SeqCst) || is_liquidation_enabled())
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.engaged.is_cancelled()
+    }
+
+    pub fn reason(&self) -> Option<String> {
+        self.reason.read().unwrap().clone()
+    }
+
+    /// Resolves once the kill switch is engaged
+    pub async fn engaged(&self) {
+        self.engaged.cancelled().await
+    }
+}
+
+lazy_static! {
+    pub static ref KILL_SWITCH: KillSwitch = KillSwitch::new();
+}
+
+/// Engage the kill switch on SIGUSR1 or when the sentinel file appears
+pub fn spawn_kill_switch_watchers() {
+    #[cfg(unix)]
+    tokio::spawn(async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::user_defined1()) {
+            Ok(mut sigusr1) => {
+                if sigusr1.recv().await.is_some() {
+                    KILL_SWITCH.engage("SIGUSR1");
+                }
+            },
+            Err(e) => {
+                KILL_SWITCH.logger.log(format!("Failed to listen for SIGUSR1: {}", e).red().to_string());
+            },
+        }
+    });
+
+    let path = get_kill_switch_file();
+    if path.exists() {
+        KILL_SWITCH.logger.log(format!(
+            "⚠️ Kill switch file {} already exists; remove it or the bot will halt immediately",
+            path.display()
+        ).yellow().bold().to_string());
+    }
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(2));
+        while !KILL_SWITCH.is_engaged() {
+            ticker.tick().await;
+            if path.exists() {
+                KILL_SWITCH.engage(&format!("sentinel file {}", path.display()));
+            }
+        }
+    });
+}
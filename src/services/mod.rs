@@ -2,3 +2,10 @@ pub mod blockhash_processor;
 pub mod rpc_client;
 pub mod telegram;
 pub mod cache_maintenance;
+pub mod shutdown;
+pub mod control;
+pub mod config_reload;
+pub mod kill_switch;
+pub mod approval;
+pub mod holdings;
+pub mod webhook;
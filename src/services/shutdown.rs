@@ -0,0 +1,144 @@
+use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use colored::Colorize;
+use lazy_static::lazy_static;
+use tokio::time::{sleep, Instant};
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+use crate::common::logger::Logger;
+
+/// How long to wait for in-flight transactions before flushing state anyway
+pub fn get_shutdown_timeout() -> Duration {
+    let secs = env::var("SHUTDOWN_TIMEOUT_SECONDS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(60);
+    Duration::from_secs(secs)
+}
+
+/// Coordinates a graceful stop: no new trades, drain in-flight ones, then flush state
+pub struct ShutdownController {
+    requested: CancellationToken,
+    completed: CancellationToken,
+    in_flight: Arc<AtomicUsize>,
+    logger: Logger,
+}
+
+/// Held for the lifetime of a trade; dropping it marks the trade as finished
+pub struct InFlightGuard {
+    counter: Arc<AtomicUsize>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl ShutdownController {
+    pub fn new() -> Self {
+        Self {
+            requested: CancellationToken::new(),
+            completed: CancellationToken::new(),
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            logger: Logger::new("[SHUTDOWN] => ".red().bold().to_string()),
+        }
+    }
+
+    /// Request a graceful shutdown (idempotent)
+    pub fn request(&self, reason: &str) {
+        if self.requested.is_cancelled() {
+            return;
+        }
+        self.logger.log(format!("🛑 Shutdown requested ({}). No new trades will be started.", reason).red().bold().to_string());
+        self.requested.cancel();
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.is_cancelled()
+    }
+
+    /// Wait until a shutdown has been requested
+    pub async fn requested(&self) {
+        self.requested.cancelled().await
+    }
+
+    /// Register a trade as in flight. Returns `None` once shutdown has been requested.
+    pub fn begin_trade(&self) -> Option<InFlightGuard> {
+        if self.is_requested() {
+            return None;
+        }
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        Some(InFlightGuard { counter: self.in_flight.clone() })
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Wait for all in-flight trades to finish. Returns false if the timeout elapsed first.
+    pub async fn wait_for_in_flight(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while self.in_flight() > 0 {
+            if Instant::now() >= deadline {
+                self.logger.log(format!("⚠️ Timed out waiting for {} in-flight trade(s)", self.in_flight()).yellow().to_string());
+                return false;
+            }
+            sleep(Duration::from_millis(250)).await;
+        }
+        true
+    }
+
+    /// Mark the shutdown sequence (drain + flush + notify) as finished
+    pub fn mark_completed(&self) {
+        self.completed.cancel();
+    }
+
+    /// Wait for the shutdown sequence to finish. Returns false on timeout.
+    pub async fn wait_completed(&self, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, self.completed.cancelled()).await.is_ok()
+    }
+}
+
+lazy_static! {
+    pub static ref SHUTDOWN: ShutdownController = ShutdownController::new();
+}
+
+/// Listen for SIGINT/SIGTERM and turn the first one into a graceful shutdown request.
+/// A second signal exits immediately.
+pub fn spawn_signal_listener() {
+    tokio::spawn(async {
+        let reason = wait_for_signal().await;
+        SHUTDOWN.request(reason);
+
+        let _ = wait_for_signal().await;
+        warn!("Second signal received, exiting immediately");
+        std::process::exit(130);
+    });
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() -> &'static str {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigterm = match signal(SignalKind::terminate()) {
+        Ok(s) => s,
+        Err(_) => {
+            let _ = tokio::signal::ctrl_c().await;
+            return "SIGINT";
+        }
+    };
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => "SIGINT",
+        _ = sigterm.recv() => "SIGTERM",
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() -> &'static str {
+    let _ = tokio::signal::ctrl_c().await;
+    "SIGINT"
+}
@@ -1,17 +1,149 @@
+use std::time::Duration;
 use anyhow::Result;
+use teloxide::prelude::*;
+use teloxide::types::{AllowedUpdate, UpdateKind};
+use tracing::{info, warn};
+use crate::services::approval::APPROVALS;
+use crate::services::kill_switch::KILL_SWITCH;
+use crate::services::shutdown::SHUTDOWN;
+
+/// Long-poll timeout for bot updates
+const COMMAND_POLL_TIMEOUT_SECS: u32 = 30;
+/// Pause after a failed update poll
+const COMMAND_POLL_RETRY: Duration = Duration::from_secs(5);
+
+/// Telegram notifications can be switched off with ENABLE_TELEGRAM=false
+pub fn is_enabled() -> bool {
+    std::env::var("ENABLE_TELEGRAM")
+        .ok()
+        .map(|v| v.trim().to_lowercase() != "false")
+        .unwrap_or(true)
+}
+
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+/// Bot for TELEGRAM_BOT_TOKEN, None when Telegram is disabled or no token is set
+fn bot() -> Option<Bot> {
+    if !is_enabled() {
+        return None;
+    }
+    env_value("TELEGRAM_BOT_TOKEN").map(Bot::new)
+}
+
+/// The operator chat (TELEGRAM_CHAT_ID)
+fn chat_id() -> Option<ChatId> {
+    env_value("TELEGRAM_CHAT_ID").and_then(|v| v.parse::<i64>().ok()).map(ChatId)
+}
 
 pub async fn init() -> Result<()> {
-    println!("Telegram service initialized (placeholder)");
+    if !is_enabled() {
+        info!("Telegram service disabled (ENABLE_TELEGRAM=false)");
+        return Ok(());
+    }
+    if bot().is_none() || chat_id().is_none() {
+        anyhow::bail!("TELEGRAM_BOT_TOKEN and a numeric TELEGRAM_CHAT_ID are required");
+    }
+    info!("Telegram service initialized");
     Ok(())
 }
 
-pub async fn send_trade_notification<T>(_data: &T, _protocol: &str, _action: &str) -> Result<()> {
-    // Placeholder implementation
+/// Send `text` to the operator chat; a no-op when Telegram isn't configured
+async fn send_to_chat(text: &str) -> Result<()> {
+    let (Some(bot), Some(chat)) = (bot(), chat_id()) else { return Ok(()) };
+    bot.send_message(chat, text).await?;
     Ok(())
 }
 
-pub async fn send_error_notification(_message: &str) -> Result<()> {
+/// Answer bot commands (/kill, /approve, /reject) in the chat they came from, by long-polling
+/// for updates until shutdown. `handle_command` only acts on TELEGRAM_CHAT_ID. Returns false
+/// when Telegram is disabled or has no bot token.
+pub fn start_command_listener() -> bool {
+    let Some(bot) = bot() else { return false };
+    tokio::spawn(async move {
+        let mut offset = 0;
+        while !SHUTDOWN.is_requested() {
+            let updates = match bot
+                .get_updates()
+                .offset(offset)
+                .timeout(COMMAND_POLL_TIMEOUT_SECS)
+                .allowed_updates(vec![AllowedUpdate::Message])
+                .await
+            {
+                Ok(updates) => updates,
+                Err(e) => {
+                    warn!("Telegram update poll failed: {}", e);
+                    tokio::time::sleep(COMMAND_POLL_RETRY).await;
+                    continue;
+                }
+            };
+            for update in updates {
+                offset = update.id + 1;
+                let UpdateKind::Message(message) = update.kind else { continue };
+                let Some(text) = message.text().filter(|t| t.starts_with('/')) else { continue };
+                let reply = match handle_command(&message.chat.id.0.to_string(), text).await {
+                    Ok(reply) => reply,
+                    Err(e) => format!("Command failed: {}", e),
+                };
+                if let Err(e) = bot.send_message(message.chat.id, reply).await {
+                    warn!("Failed to answer Telegram command: {}", e);
+                }
+            }
+        }
+    });
+    true
+}
+
+pub async fn send_trade_notification<T>(_data: &T, _protocol: &str, _action: &str) -> Result<()> {
     // Placeholder implementation
-    println!("Error notification: {}", _message);
     Ok(())
-} 
\ No newline at end of file
+}
+
+pub async fn send_error_notification(message: &str) -> Result<()> {
+    if !is_enabled() {
+        return Ok(());
+    }
+    info!("Error notification: {}", message);
+    send_to_chat(&format!("❌ {}", message)).await
+}
+
+pub async fn send_notification(message: &str) -> Result<()> {
+    if !is_enabled() {
+        return Ok(());
+    }
+    info!("Telegram notification: {}", message);
+    send_to_chat(message).await
+}
+
+/// Handle an incoming bot command. Only messages from TELEGRAM_CHAT_ID are accepted.
+pub async fn handle_command(chat_id: &str, text: &str) -> Result<String> {
+    let allowed = std::env::var("TELEGRAM_CHAT_ID").unwrap_or_default();
+    if allowed.trim().is_empty() || allowed.trim() != chat_id.trim() {
+        return Ok("Unauthorized chat".to_string());
+    }
+
+    let mut parts = text.split_whitespace();
+    // Commands in group chats arrive as `/kill@BotName`
+    let command = parts.next().unwrap_or_default().split('@').next().unwrap_or_default().to_lowercase();
+    let argument = parts.next();
+    let reply = match command.as_str() {
+        "/approve" | "/reject" => {
+            let approved = command == "/approve";
+            return Ok(match argument.and_then(|v| v.trim_start_matches('#').parse::<u64>().ok()) {
+                Some(id) if APPROVALS.resolve(id, approved) => format!("#{} {}", id, if approved { "approved" } else { "rejected" }),
+                Some(id) => format!("No pending approval #{}", id),
+                None => format!("Usage: {} <id>", command),
+            });
+        },
+        "/kill" => {
+            if KILL_SWITCH.engage("Telegram /kill") {
+                "🚨 Kill switch engaged, all new trades halted"
+            } else {
+                "Kill switch already engaged"
+            }
+        },
+        _ => "Unknown command",
+    };
+    Ok(reply.to_string())
+}
@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use hmac::{Hmac, Mac};
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use sha2::Sha256;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use crate::common::logger::Logger;
+use crate::common::persistence::unix_now;
+use crate::services::control::CONTROL;
+use crate::services::kill_switch::KILL_SWITCH;
+
+/// Largest request body accepted
+const MAX_BODY_BYTES: usize = 16 * 1024;
+/// Largest request line plus headers accepted
+const MAX_HEADER_BYTES: usize = 8 * 1024;
+/// A connection has this long to send its whole request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Pause before accepting again after an accept error
+const ACCEPT_RETRY: Duration = Duration::from_secs(1);
+/// Signed requests older or newer than this are rejected as replays
+const MAX_TIMESTAMP_SKEW_SECS: i64 = 300;
+
+/// Shared secret the webhook signatures are checked against (WEBHOOK_SECRET, empty = webhook off)
+pub fn get_webhook_secret() -> Option<String> {
+    env::var("WEBHOOK_SECRET").ok().filter(|v| !v.trim().is_empty())
+}
+
+/// Address the webhook listener binds to (WEBHOOK_ADDR)
+pub fn get_webhook_addr() -> String {
+    env::var("WEBHOOK_ADDR")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| "127.0.0.1:8787".to_string())
+}
+
+lazy_static! {
+    static ref LOGGER: Logger = Logger::new("[WEBHOOK] => ".bright_blue().bold().to_string());
+    static ref SEEN_SIGNATURES: Mutex<ReplayGuard> = Mutex::new(ReplayGuard::default());
+}
+
+/// Signatures accepted within the timestamp window. A signed request is valid for the whole
+/// window, so without this a captured one could be sent again until it goes stale.
+#[derive(Default)]
+struct ReplayGuard {
+    /// Signature -> the timestamp it was signed with
+    seen: HashMap<String, i64>,
+}
+
+impl ReplayGuard {
+    /// Record a verified `signature`; false if it was already used inside the window
+    fn check_and_insert(&mut self, signature: &str, timestamp: i64, now: i64) -> bool {
+        self.seen.retain(|_, signed_at| now - *signed_at <= MAX_TIMESTAMP_SKEW_SECS);
+        let key = signature.trim().trim_start_matches("sha256=").to_lowercase();
+        if self.seen.contains_key(&key) {
+            return false;
+        }
+        self.seen.insert(key, timestamp);
+        true
+    }
+}
+
+/// JSON body of a signal request
+#[derive(Debug, Deserialize)]
+pub struct Signal {
+    /// pause or resume
+    pub action: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if value.len() % 2 != 0 {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Check `signature` (hex, optionally prefixed `sha256=`) against HMAC-SHA256 of
+/// `"{timestamp}.{body}"` with the shared secret
+pub fn verify_signature(secret: &str, timestamp: &str, body: &[u8], signature: &str) -> bool {
+    let signature = signature.trim();
+    let signature = signature.strip_prefix("sha256=").unwrap_or(signature);
+    let Some(expected) = decode_hex(signature) else { return false };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else { return false };
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// True when `timestamp` (unix seconds) is within MAX_TIMESTAMP_SKEW_SECS of `now`
+fn is_fresh(timestamp: &str, now: i64) -> bool {
+    timestamp
+        .trim()
+        .parse::<i64>()
+        .map(|t| (now - t).abs() <= MAX_TIMESTAMP_SKEW_SECS)
+        .unwrap_or(false)
+}
+
+/// Apply a verified signal. Returns the HTTP status and a short message.
+pub async fn handle_signal(signal: &Signal) -> (u16, String) {
+    let source = format!("webhook ({})", signal.reason.as_deref().unwrap_or("no reason"));
+
+    match signal.action.to_lowercase().as_str() {
+        "pause" => {
+            CONTROL.pause(&source);
+            (200, "paused".to_string())
+        },
+        "resume" => {
+            if KILL_SWITCH.is_engaged() {
+                (409, "kill switch engaged, restart the bot to resume".to_string())
+            } else {
+                // Only lifts an operator pause; the drawdown halt is a risk stop for the operator
+                let resumed = CONTROL.resume();
+                match (resumed, CONTROL.is_drawdown_halted()) {
+                    (_, true) => (409, "halted by the drawdown guard, reset-drawdown on the control socket lifts it".to_string()),
+                    (true, false) => (200, "resumed".to_string()),
+                    (false, false) => (200, "not paused".to_string()),
+                }
+            }
+        },
+        other => (400, format!("unknown action '{}' (expected pause, resume)", other)),
+    }
+}
+
+/// Start the webhook listener. Does nothing when WEBHOOK_SECRET is not set.
+pub async fn start_webhook_listener() -> Result<bool> {
+    let Some(secret) = get_webhook_secret() else { return Ok(false) };
+    let addr: SocketAddr = get_webhook_addr()
+        .parse()
+        .map_err(|e| anyhow!("Invalid WEBHOOK_ADDR: {}", e))?;
+
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| anyhow!("Failed to bind webhook listener on {}: {}", addr, e))?;
+
+    LOGGER.log(format!("🪝 Webhook listener on {}", addr).bright_blue().to_string());
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer)) => {
+                    let secret = secret.clone();
+                    tokio::spawn(async move {
+                        match tokio::time::timeout(REQUEST_TIMEOUT, handle_connection(stream, &secret)).await {
+                            Ok(Ok(())) => {},
+                            Ok(Err(e)) => {
+                                LOGGER.log(format!("Webhook request from {} failed: {}", peer, e).red().to_string());
+                            },
+                            Err(_) => {
+                                LOGGER.log(format!("Webhook request from {} timed out after {}s", peer, REQUEST_TIMEOUT.as_secs()).yellow().to_string());
+                            },
+                        }
+                    });
+                },
+                Err(e) => {
+                    LOGGER.log(format!("Webhook accept error: {}", e).red().to_string());
+                    tokio::time::sleep(ACCEPT_RETRY).await;
+                },
+            }
+        }
+    });
+
+    Ok(true)
+}
+
+async fn respond(stream: &mut TcpStream, status: u16, message: &str) -> Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        _ => "Service Unavailable",
+    };
+    let body = serde_json::json!({ "ok": status == 200, "message": message }).to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, reason, body.len(), body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+/// Read one line of the request head, charging it to `budget`. None once the head would
+/// exceed MAX_HEADER_BYTES.
+async fn read_head_line<R: AsyncBufRead + Unpin>(reader: &mut R, budget: &mut usize) -> Result<Option<String>> {
+    let mut line = String::new();
+    let read = (&mut *reader).take(*budget as u64).read_line(&mut line).await?;
+    *budget -= read;
+    if *budget == 0 && !line.ends_with('\n') {
+        return Ok(None);
+    }
+    Ok(Some(line))
+}
+
+/// Minimal HTTP/1.1 handling: one `POST /signal` per connection
+async fn handle_connection(mut stream: TcpStream, secret: &str) -> Result<()> {
+    let mut reader = BufReader::new(&mut stream);
+
+    let mut budget = MAX_HEADER_BYTES;
+    let Some(request_line) = read_head_line(&mut reader, &mut budget).await? else {
+        drop(reader);
+        return respond(&mut stream, 431, "request head too large").await;
+    };
+    let mut headers = Vec::new();
+    loop {
+        let Some(line) = read_head_line(&mut reader, &mut budget).await? else {
+            drop(reader);
+            return respond(&mut stream, 431, "request head too large").await;
+        };
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    let header = |name: &str| headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.clone());
+
+    let content_length: usize = header("content-length").and_then(|v| v.parse().ok()).unwrap_or(0);
+    if content_length > MAX_BODY_BYTES {
+        drop(reader);
+        return respond(&mut stream, 413, "body too large").await;
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).await?;
+    drop(reader);
+
+    let mut parts = request_line.split_whitespace();
+    if parts.next() != Some("POST") || parts.next() != Some("/signal") {
+        return respond(&mut stream, 404, "expected POST /signal").await;
+    }
+
+    let (Some(timestamp), Some(signature)) = (header("x-timestamp"), header("x-signature")) else {
+        return respond(&mut stream, 401, "missing X-Timestamp or X-Signature").await;
+    };
+    let now = unix_now();
+    if !is_fresh(&timestamp, now) || !verify_signature(secret, &timestamp, &body, &signature) {
+        LOGGER.log("Rejected webhook request with a bad or stale signature".yellow().to_string());
+        return respond(&mut stream, 401, "invalid signature").await;
+    }
+    let signed_at = timestamp.trim().parse::<i64>().unwrap_or(now);
+    if !SEEN_SIGNATURES.lock().unwrap().check_and_insert(&signature, signed_at, now) {
+        LOGGER.log("Rejected a replayed webhook request".yellow().to_string());
+        return respond(&mut stream, 401, "replayed request").await;
+    }
+
+    let signal: Signal = match serde_json::from_slice(&body) {
+        Ok(signal) => signal,
+        Err(e) => return respond(&mut stream, 400, &format!("invalid JSON: {}", e)).await,
+    };
+    let (status, message) = handle_signal(&signal).await;
+    LOGGER.log(format!("Signal '{}' -> {} {}", signal.action, status, message).bright_blue().to_string());
+    respond(&mut stream, status, &message).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = br#"{"action":"pause"}"#;
+
+    fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(timestamp.as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn accepts_a_valid_signature_with_or_without_prefix() {
+        let signature = sign("secret", "1700000000", BODY);
+        assert!(verify_signature("secret", "1700000000", BODY, &signature));
+        assert!(verify_signature("secret", "1700000000", BODY, &format!("sha256={}", signature)));
+    }
+
+    #[test]
+    fn rejects_a_bad_signature() {
+        let signature = sign("secret", "1700000000", BODY);
+        assert!(!verify_signature("other", "1700000000", BODY, &signature));
+        assert!(!verify_signature("secret", "1700000001", BODY, &signature));
+        assert!(!verify_signature("secret", "1700000000", br#"{"action":"resume"}"#, &signature));
+        assert!(!verify_signature("secret", "1700000000", BODY, &signature[..62]));
+        assert!(!verify_signature("secret", "1700000000", BODY, "not-hex"));
+    }
+
+    #[test]
+    fn rejects_skewed_timestamps() {
+        let now = 1_700_000_000;
+        assert!(is_fresh("1700000000", now));
+        assert!(is_fresh(&(now - MAX_TIMESTAMP_SKEW_SECS).to_string(), now));
+        assert!(is_fresh(&(now + MAX_TIMESTAMP_SKEW_SECS).to_string(), now));
+        assert!(!is_fresh(&(now - MAX_TIMESTAMP_SKEW_SECS - 1).to_string(), now));
+        assert!(!is_fresh(&(now + MAX_TIMESTAMP_SKEW_SECS + 1).to_string(), now));
+        assert!(!is_fresh("yesterday", now));
+    }
+
+    #[test]
+    fn rejects_replayed_signatures_within_the_window() {
+        let mut guard = ReplayGuard::default();
+        let now = 1_700_000_000;
+        assert!(guard.check_and_insert("abcd", now, now));
+        assert!(!guard.check_and_insert("sha256=ABCD", now, now + 10));
+        assert!(guard.check_and_insert("ef01", now, now + 10));
+        // Dropped once its timestamp would be rejected as stale anyway
+        assert!(guard.check_and_insert("abcd", now, now + MAX_TIMESTAMP_SKEW_SECS + 1));
+    }
+}